@@ -0,0 +1,190 @@
+//! Benchmarks for the rendering hot path on a `HeadlessScreen`.
+//!
+//! Run with `cargo bench` and compare against a saved baseline when optimizing
+//! drawing, e.g. `cargo bench -- --save-baseline main` before a change and
+//! `cargo bench -- --baseline main` after it.
+use std::sync::{Arc, Mutex};
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use thegame::{
+    palette::{Stroke, RED},
+    prelude::*,
+    renderer::{DrawCmd, Frame, FrameBuilder, Pixel},
+};
+
+const SCREEN: (u32, u32) = (320, 180);
+const SPRITE_SIZES: [u16; 3] = [8, 16, 32];
+const ENTITY_COUNTS: [usize; 3] = [1, 10, 100];
+const STROKE_LENGTHS: [u16; 3] = [16, 64, 256];
+
+/// A looping sprite with square frames made of `size` by `size` pixels.
+struct Square {
+    frames: Vec<Frame>,
+    timer: f32,
+    frame_pos: usize,
+    paused: bool,
+}
+impl Square {
+    fn new(size: u16) -> Self {
+        let frame = || {
+            FrameBuilder::new()
+                .pixels((0..size).flat_map(|y| {
+                    (0..size).map(move |x| Pixel::new(ColorScheme::Standard(RED), x, y))
+                }))
+                .build()
+        };
+        Self {
+            frames: vec![frame(), frame()],
+            timer: 0.0,
+            frame_pos: 0,
+            paused: false,
+        }
+    }
+}
+impl Sprite for Square {
+    fn frames(&self) -> &Vec<Frame> {
+        &self.frames
+    }
+    fn frame_pos(&self) -> usize {
+        self.frame_pos
+    }
+    fn timer(&self) -> f32 {
+        self.timer
+    }
+    fn frame_pos_mut(&mut self) -> &mut usize {
+        &mut self.frame_pos
+    }
+    fn timer_mut(&mut self) -> &mut f32 {
+        &mut self.timer
+    }
+    fn paused(&self) -> bool {
+        self.paused
+    }
+    fn paused_mut(&mut self) -> &mut bool {
+        &mut self.paused
+    }
+}
+
+fn pixel_draw(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pixel_draw");
+    let pixel = Pixel::new(ColorScheme::Standard(RED), 10, 10);
+    for (name, mirror) in [
+        ("none", MirrorDirectionValue::None),
+        ("flipped", MirrorDirectionValue::FlipVertical(16)),
+    ] {
+        let mut screen = HeadlessScreen::new(SCREEN.0, SCREEN.1);
+        let batch = [DrawCmd::Pixel(
+            &pixel,
+            mirror,
+            Coordinate { x: 40.0, y: 40.0 },
+        )];
+        group.bench_function(name, |b| {
+            b.iter(|| screen.draw_batch(black_box(&batch)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+/// Horizontal strokes blitted as whole rows, against the per pixel loop they
+/// were drawn with before.
+fn stroke_blit(c: &mut Criterion) {
+    let mut group = c.benchmark_group("stroke_blit");
+    let offset = Coordinate { x: 8.0, y: 40.0 };
+    for len in STROKE_LENGTHS {
+        let stroke = Pixel::new(
+            ColorScheme::Stroke(Stroke::new(RED, Direction::Horizontal(len))),
+            0,
+            0,
+        );
+        let mut screen = HeadlessScreen::new(SCREEN.0, SCREEN.1);
+        let batch = [DrawCmd::Pixel(&stroke, MirrorDirectionValue::None, offset)];
+        group.bench_with_input(BenchmarkId::new("row_copy", len), &batch, |b, batch| {
+            b.iter(|| screen.draw_batch(black_box(batch)).unwrap())
+        });
+
+        let mut screen = HeadlessScreen::new(SCREEN.0, SCREEN.1);
+        group.bench_with_input(BenchmarkId::new("per_pixel", len), &len, |b, &len| {
+            b.iter(|| per_pixel(&mut screen, black_box(len), offset, RED.to_rgba()))
+        });
+    }
+    group.finish();
+}
+
+/// The baseline `stroke_blit` compares against: one bounds checked 4 byte
+/// write per pixel of a horizontal run.
+fn per_pixel(screen: &mut HeadlessScreen, len: u16, offset: Coordinate, rgba: [u8; 4]) {
+    let (width, height) = (screen.width() as i64, screen.height() as i64);
+    let buffer = screen.frame_buffer();
+    for x in 0..len {
+        let x = (offset.x + f32::from(x)).round() as i64;
+        let y = offset.y.round() as i64;
+        if x < 0 || y < 0 || x >= width || y >= height {
+            continue;
+        }
+        let index = ((y * width + x) * 4) as usize;
+        buffer[index..index + 4].copy_from_slice(&rgba);
+    }
+}
+
+fn frame_composition(c: &mut Criterion) {
+    let mut group = c.benchmark_group("frame_composition");
+    for size in SPRITE_SIZES {
+        for count in ENTITY_COUNTS {
+            let sprites: Vec<_> = (0..count).map(|_| Square::new(size)).collect();
+            let mut screen = HeadlessScreen::new(SCREEN.0, SCREEN.1);
+            group.bench_with_input(
+                BenchmarkId::new(format!("{size}px"), count),
+                &sprites,
+                |b, sprites| {
+                    b.iter(|| {
+                        let mut batch = vec![DrawCmd::Clear];
+                        for (i, sprite) in sprites.iter().enumerate() {
+                            let offset = Coordinate {
+                                x: (i * 3 % SCREEN.0 as usize) as f32,
+                                y: (i * 7 % SCREEN.1 as usize) as f32,
+                            };
+                            Animation::<HeadlessScreen>::commands(
+                                sprite,
+                                MirrorDirection::None,
+                                offset,
+                                &mut batch,
+                            );
+                        }
+                        screen.draw_batch(&batch).unwrap()
+                    })
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+fn animation_play(c: &mut Criterion) {
+    let mut group = c.benchmark_group("animation_play");
+    for size in SPRITE_SIZES {
+        let mut sprite = Square::new(size);
+        let screen = Arc::new(Mutex::new(HeadlessScreen::new(SCREEN.0, SCREEN.1)));
+        group.bench_function(BenchmarkId::from_parameter(format!("{size}px")), |b| {
+            b.iter(|| {
+                Animation::<HeadlessScreen>::play(
+                    &mut sprite,
+                    screen.clone(),
+                    black_box(1.0 / 60.0),
+                    MirrorDirection::FlipVertical,
+                    Coordinate { x: 20.0, y: 20.0 },
+                )
+                .unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    pixel_draw,
+    stroke_blit,
+    frame_composition,
+    animation_play
+);
+criterion_main!(benches);
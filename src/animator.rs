@@ -9,11 +9,14 @@
 //! ## Responsibilities
 //! - Tracks animation progress based on frame durations and game delta time
 //! - Applies optional vertical or horizontal mirroring to rendered frames
-//! - Draws each pixel in the current frame at the given offset on the screen
+//! - Draws each pixel in the current frame at the given offset on the screen,
+//!   placing the frame's pivot there when it has one
+//! - Places the current frame's hitboxes and hurtboxes on screen alongside it
 //!
 //! ## Frame Timing
-//! If a frame does not define an explicit `duration`, a default duration is used,
-//! calculated as an even slice of 1 second (i.e., `1.0 / frame_count`).
+//! If a frame does not define an explicit `duration`, the sprite's `frame_rate`
+//! is used (i.e., `1.0 / frame_rate`). Sprites without a frame rate fall back to
+//! an even slice of 1 second (i.e., `1.0 / frame_count`).
 //!
 //! ## Mirroring
 //! Mirroring operations are performed relative to the width or height of the current
@@ -21,13 +24,18 @@
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+use crate::hitbox::Collider;
 use crate::prelude::*;
+use crate::renderer::DrawCmd;
 use crate::window::WindowError;
 
 
 /// A trait for animating a `Sprite` on a terminal interface.
 pub trait Animation<S: Screen>: Sprite {
     /// Plays the animation frame-by-frame with optional mirroring and position offset.
+    ///
+    /// Clears the screen before drawing and presents it afterwards. Use `advance`
+    /// and `commands` directly when more has to be drawn within the same frame.
     fn play(
         &mut self,
         screen: Arc<Mutex<S>>,
@@ -35,9 +43,33 @@ pub trait Animation<S: Screen>: Sprite {
         mirror: MirrorDirection,
         offset: Coordinate,
     ) -> Result<(), WindowError> {
-        // Total time to show the frame (or default to evely used interval)
+        self.advance(delta);
+
+        let mut batch = vec![DrawCmd::Clear];
+        self.commands(mirror, offset, &mut batch);
+
+        screen
+            .lock()
+            .map_err(|e| WindowError::ScreenLockError(e.to_string()))?
+            .draw_batch(&batch)
+    }
+    /// Progresses the animation timer moving onto the next frame
+    /// once the current one exhausted its view time.
+    ///
+    /// Paused animations stay where they are
+    fn advance(&mut self, delta: f32) {
+        if self.paused() {
+            return;
+        }
+        // Total time to show the frame (or default to the sprite's frame
+        // rate and lastly an evenly used interval)
         let duration = self.frames()[self.frame_pos()]
             .duration
+            .or_else(|| {
+                self.frame_rate()
+                    .filter(|fps| *fps > 0)
+                    .map(|fps| Duration::from_secs_f32(1.0 / fps as f32))
+            })
             .unwrap_or_else(|| Duration::from_secs_f32(1.0 / self.frames().len() as f32))
             .as_secs_f32();
 
@@ -47,40 +79,33 @@ pub trait Animation<S: Screen>: Sprite {
             *self.timer_mut() -= duration;
             *self.frame_pos_mut() = (self.frame_pos() + 1) % self.frames().len()
         }
-
-        let mut screen_lock = screen
-            .lock()
-            .map_err(|e| WindowError::ScreenLockError(e.to_string()))?;
-
-        screen_lock.clear()?;
-
+    }
+    /// Queues the current frame with optional mirroring and position offset
+    /// onto a batch of draw commands.
+    fn commands<'a>(
+        &'a self,
+        mirror: MirrorDirection,
+        offset: Coordinate,
+        batch: &mut Vec<DrawCmd<'a>>,
+    ) {
         let frame = &self.frames()[self.frame_pos()];
-        for p in &frame.pixels {
-            // let screen = screen.clone();
-            // Ignores the mirror direction value since the value must be covered by
-            // the frames dimensions
-            match mirror {
-                MirrorDirection::FlipVertical => p.draw(
-                    &mut *screen_lock,
-                    MirrorDirectionValue::FlipVertical(frame.width),
-                    offset.clone(),
-                ),
-                MirrorDirection::FlipHorizontal => p.draw(
-                    &mut *screen_lock,
-                    MirrorDirectionValue::FlipHorizontal(frame.height),
-                    offset.clone(),
-                ),
-                MirrorDirection::None => p.draw(
-                    &mut *screen_lock,
-                    MirrorDirectionValue::None,
-                    offset.clone(),
-                ),
-            }
-        }
-
-        screen_lock.render()?;
-
-        Ok(())
+        // Ignores the mirror direction value since the value must be covered by
+        // the frames dimensions
+        let mirror = frame.mirror_value(mirror);
+        let offset = offset + frame.anchor(&mirror);
+        batch.push(DrawCmd::Frame(frame, mirror, offset));
+    }
+    /// The current frame's hitboxes and hurtboxes placed on screen the way
+    /// `commands` would draw the frame.
+    fn colliders(&self, mirror: MirrorDirection, offset: Coordinate) -> Vec<Collider<'_>> {
+        let frame = &self.frames()[self.frame_pos()];
+        let mirror = frame.mirror_value(mirror);
+        let offset = offset + frame.anchor(&mirror);
+        frame
+            .boxes()
+            .iter()
+            .map(|b| b.place(&mirror, offset))
+            .collect()
     }
 }
 impl<S: Screen, T: Sprite> Animation<S> for T {}
@@ -90,15 +115,19 @@ mod tests {
     use std::sync::{Arc, Mutex};
 
     use crate::{
+        animator::Animation,
         layout::{Coordinate, MirrorDirection},
-        mock::{MockCharacter, MockScreen},
-        sprite::character::character::Character,
+        mock::MockCharacter,
+        palette::{Color, ColorScheme, LIGHT_GRAY, RED},
+        renderer::{Frame, FrameBuilder, Pixel},
+        sprite::{character::character::Character, sprite::Sprite},
+        window::{HeadlessScreen, Screen},
     };
 
     #[test]
     fn test_animation_frame_advance() {
         let mut sprite = MockCharacter::new();
-        let screen = Arc::new(Mutex::new(MockScreen::new(50, 50)));
+        let screen = Arc::new(Mutex::new(HeadlessScreen::new(50, 50)));
 
         // Each call to `play` will advance by one frame if delta exceeds 1 / frames.len()
         // So if delta is 1.0 and 3 frames: 1/3 per frame → will advance
@@ -139,4 +168,181 @@ mod tests {
             .unwrap();
         assert_eq!(sprite.idle().frame_pos(), 0); // loops anad start aniamtion over
     }
+
+    #[test]
+    fn test_animation_frame_timing() {
+        let mut sprite = TimedSprite {
+            frames: vec![
+                FrameBuilder::new().duration_ms(500).build(),
+                FrameBuilder::new().build(),
+                FrameBuilder::new().build(),
+            ],
+            ..Default::default()
+        };
+        let screen = Arc::new(Mutex::new(HeadlessScreen::new(50, 50)));
+
+        // Explicit frame duration wins over the sprite frame rate
+        sprite
+            .play(
+                screen.clone(),
+                0.2,
+                MirrorDirection::None,
+                Coordinate::default(),
+            )
+            .unwrap();
+        assert_eq!(sprite.frame_pos(), 0);
+        sprite
+            .play(
+                screen.clone(),
+                0.35,
+                MirrorDirection::None,
+                Coordinate::default(),
+            )
+            .unwrap();
+        assert_eq!(sprite.frame_pos(), 1);
+
+        // Frames without a duration use the 10 fps sprite default
+        // instead of 1 / 3 of a second
+        sprite
+            .play(
+                screen.clone(),
+                0.1,
+                MirrorDirection::None,
+                Coordinate::default(),
+            )
+            .unwrap();
+        assert_eq!(sprite.frame_pos(), 2);
+    }
+
+    #[test]
+    fn test_animation_pause_and_scrub() {
+        let mut sprite = TimedSprite {
+            frames: vec![FrameBuilder::new().build(); 3],
+            ..Default::default()
+        };
+        let animation: &mut dyn Animation<HeadlessScreen> = &mut sprite;
+
+        animation.pause();
+        animation.advance(1.0);
+        assert_eq!(animation.frame_pos(), 0);
+        animation.resume();
+        animation.advance(0.1);
+        assert_eq!(animation.frame_pos(), 1);
+
+        // Scrubbing starts the frame's view time over
+        animation.advance(0.05);
+        animation.set_frame(5);
+        assert_eq!((animation.frame_pos(), animation.timer()), (2, 0.0));
+        animation.reset();
+        assert_eq!(animation.frame_pos(), 0);
+    }
+
+    #[test]
+    fn test_animation_colliders_follow_frame() {
+        let pixels = vec![Pixel::new(ColorScheme::Standard(RED), 9, 3)];
+        let mut sprite = TimedSprite {
+            frames: vec![
+                FrameBuilder::new()
+                    .pixels(pixels.clone())
+                    .hurtbox("body", 0, 0, 4, 4)
+                    .build(),
+                FrameBuilder::new()
+                    .pixels(pixels)
+                    .hurtbox("body", 0, 0, 4, 4)
+                    .hitbox("sword", 6, 1, 4, 1)
+                    .build(),
+            ],
+            ..Default::default()
+        };
+        let offset = Coordinate { x: 20.0, y: 0.0 };
+
+        let colliders =
+            Animation::<HeadlessScreen>::colliders(&sprite, MirrorDirection::None, offset);
+        assert_eq!(colliders.len(), 1);
+
+        sprite.frame_pos = 1;
+        let colliders =
+            Animation::<HeadlessScreen>::colliders(&sprite, MirrorDirection::None, offset);
+        assert_eq!(colliders[1].name, "sword");
+        assert_eq!(colliders[1].rect.x, 26.0);
+
+        // Facing left the sword swings out the other side
+        let colliders =
+            Animation::<HeadlessScreen>::colliders(&sprite, MirrorDirection::FlipVertical, offset);
+        assert_eq!(colliders[1].rect.x, 20.0);
+        assert_eq!(colliders[0].rect.x, 26.0);
+    }
+
+    #[test]
+    fn test_pivot_lines_up_frames() {
+        // The second frame reaches further out, the foot stays where it is
+        let foot = Pixel::new(ColorScheme::Standard(RED), 1, 2);
+        let sprite = TimedSprite {
+            frames: [3, 5]
+                .map(|reach| {
+                    FrameBuilder::new()
+                        .pixel(foot.clone())
+                        .pixel(Pixel::new(ColorScheme::Standard(LIGHT_GRAY), reach, 0))
+                        .pivot(1, 2)
+                        .build()
+                })
+                .to_vec(),
+            ..Default::default()
+        };
+        let [r, g, b, a] = RED.to_rgba();
+        let offset = Coordinate { x: 10.0, y: 10.0 };
+
+        for frame_pos in [0, 1] {
+            for mirror in [MirrorDirection::None, MirrorDirection::FlipVertical] {
+                let mut screen = HeadlessScreen::new(20, 20);
+                let sprite = TimedSprite {
+                    frames: sprite.frames.clone(),
+                    frame_pos,
+                    ..Default::default()
+                };
+                let mut batch = Vec::new();
+                Animation::<HeadlessScreen>::commands(&sprite, mirror, offset, &mut batch);
+                screen.draw_batch(&batch).unwrap();
+                assert_eq!(
+                    screen.pixel(10, 10),
+                    Some(Color::RGBA(r, g, b, a)),
+                    "{frame_pos} {mirror:?}"
+                );
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct TimedSprite {
+        frames: Vec<Frame>,
+        timer: f32,
+        frame_pos: usize,
+        paused: bool,
+    }
+    impl Sprite for TimedSprite {
+        fn frames(&self) -> &Vec<Frame> {
+            &self.frames
+        }
+        fn frame_pos(&self) -> usize {
+            self.frame_pos
+        }
+        fn timer(&self) -> f32 {
+            self.timer
+        }
+        fn frame_pos_mut(&mut self) -> &mut usize {
+            &mut self.frame_pos
+        }
+        fn timer_mut(&mut self) -> &mut f32 {
+            &mut self.timer
+        }
+        fn paused(&self) -> bool {
+            self.paused
+        }
+        fn paused_mut(&mut self) -> &mut bool {
+            &mut self.paused
+        }
+        fn frame_rate(&self) -> Option<u32> {
+            Some(10)
+        }
+    }
 }
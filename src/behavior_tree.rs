@@ -0,0 +1,256 @@
+//! Behavior trees for NPC and enemy decision making.
+//!
+//! A tree is built from `Node`s ticked against a context `T` the AI reads and
+//! drives, e.g. an enemy's position together with its `PathFollower`. Composite
+//! nodes decide which of their children run:
+//! - A sequence runs its children in order until one doesn't succeed. A
+//!   running child is continued next tick once the conditions before it were
+//!   checked again.
+//! - A selector tries its children in order of priority until one doesn't fail.
+//!   It starts over from its first child every tick so a more important branch
+//!   interrupts a less important one that's still running.
+//!
+//! # Key Structures
+//! - **`Status`**: The outcome of ticking a node.
+//! - **`Node`**: A sequence, selector, condition or action.
+//! - **`BehaviorTree`**: The root node ticked once per frame.
+//!
+//! # Example Usage
+//! ```ignore
+//! let mut guard = BehaviorTree::new(Node::selector(vec![
+//!     Node::sequence(vec![
+//!         Node::condition(|npc: &Npc| npc.sees_player()),
+//!         Node::action(|npc: &mut Npc, delta| npc.chase(delta)),
+//!     ]),
+//!     Node::action(|npc: &mut Npc, delta| npc.patrol(delta)),
+//! ]));
+//!
+//! // Every frame
+//! guard.tick(&mut npc, delta);
+//! ```
+
+/// The outcome of ticking a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Success,
+    Failure,
+    /// Not done yet and ticked again next frame.
+    Running,
+}
+
+type Condition<T> = Box<dyn Fn(&T) -> bool + Send>;
+type Action<T> = Box<dyn FnMut(&mut T, f32) -> Status + Send>;
+
+/// A node of a behavior tree ticked against a context `T`.
+pub enum Node<T> {
+    /// Runs its children in order while they succeed, continuing a running
+    /// child on the next tick as long as the conditions before it still hold.
+    Sequence {
+        children: Vec<Node<T>>,
+        current: usize,
+    },
+    /// Runs the first of its children that doesn't fail.
+    Selector {
+        children: Vec<Node<T>>,
+        running: Option<usize>,
+    },
+    /// Succeeds when the check holds and fails otherwise.
+    Condition(Condition<T>),
+    /// Does the actual work, given the seconds passed since the last tick.
+    Action(Action<T>),
+}
+impl<T> Node<T> {
+    pub fn sequence(children: Vec<Node<T>>) -> Self {
+        Node::Sequence {
+            children,
+            current: 0,
+        }
+    }
+    pub fn selector(children: Vec<Node<T>>) -> Self {
+        Node::Selector {
+            children,
+            running: None,
+        }
+    }
+    pub fn condition(check: impl Fn(&T) -> bool + Send + 'static) -> Self {
+        Node::Condition(Box::new(check))
+    }
+    pub fn action(act: impl FnMut(&mut T, f32) -> Status + Send + 'static) -> Self {
+        Node::Action(Box::new(act))
+    }
+    /// Runs the node for a frame `delta` seconds long.
+    pub fn tick(&mut self, context: &mut T, delta: f32) -> Status {
+        match self {
+            Node::Sequence { children, current } => {
+                // Conditions guarding a running child are checked again
+                for i in 0..*current {
+                    if let Node::Condition(check) = &children[i] {
+                        if !check(context) {
+                            children[*current].reset();
+                            *current = 0;
+                            return Status::Failure;
+                        }
+                    }
+                }
+                while let Some(child) = children.get_mut(*current) {
+                    match child.tick(context, delta) {
+                        Status::Success => *current += 1,
+                        Status::Running => return Status::Running,
+                        Status::Failure => {
+                            *current = 0;
+                            return Status::Failure;
+                        }
+                    }
+                }
+                *current = 0;
+                Status::Success
+            }
+            Node::Selector { children, running } => {
+                for i in 0..children.len() {
+                    let status = children[i].tick(context, delta);
+                    if status == Status::Failure {
+                        continue;
+                    }
+                    // Whatever ran before was interrupted and starts over next time
+                    let previous = running.replace(i).filter(|&p| p != i);
+                    if let Some(previous) = previous {
+                        children[previous].reset();
+                    }
+                    if status == Status::Success {
+                        *running = None;
+                    }
+                    return status;
+                }
+                *running = None;
+                Status::Failure
+            }
+            Node::Condition(check) => {
+                if check(context) {
+                    Status::Success
+                } else {
+                    Status::Failure
+                }
+            }
+            Node::Action(act) => act(context, delta),
+        }
+    }
+    /// Forgets where composite nodes left off.
+    pub fn reset(&mut self) {
+        match self {
+            Node::Sequence { children, current } => {
+                *current = 0;
+                children.iter_mut().for_each(Node::reset);
+            }
+            Node::Selector { children, running } => {
+                *running = None;
+                children.iter_mut().for_each(Node::reset);
+            }
+            Node::Condition(_) | Node::Action(_) => (),
+        }
+    }
+}
+
+/// A behavior tree ticked once per frame.
+pub struct BehaviorTree<T> {
+    root: Node<T>,
+}
+impl<T> BehaviorTree<T> {
+    pub fn new(root: Node<T>) -> Self {
+        Self { root }
+    }
+    /// Runs the tree for a frame `delta` seconds long.
+    pub fn tick(&mut self, context: &mut T, delta: f32) -> Status {
+        self.root.tick(context, delta)
+    }
+    /// Starts the tree over from scratch, e.g. after the NPC respawned.
+    pub fn reset(&mut self) {
+        self.root.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        layout::Coordinate,
+        pathfinding::{find_path, PathFollower, WalkGrid},
+    };
+
+    #[derive(Default)]
+    struct Npc {
+        position: Coordinate,
+        sees_player: bool,
+        log: Vec<&'static str>,
+    }
+
+    /// Takes two ticks to finish.
+    fn slow(name: &'static str) -> Node<Npc> {
+        let mut ticks = 0;
+        Node::action(move |npc: &mut Npc, _| {
+            npc.log.push(name);
+            ticks += 1;
+            if ticks % 2 == 0 {
+                Status::Success
+            } else {
+                Status::Running
+            }
+        })
+    }
+
+    #[test]
+    fn test_sequence_continues_running_child() {
+        let mut tree = BehaviorTree::new(Node::sequence(vec![slow("a"), slow("b")]));
+        let mut npc = Npc::default();
+
+        let statuses: Vec<_> = (0..3).map(|_| tree.tick(&mut npc, 0.1)).collect();
+        assert_eq!(
+            statuses,
+            [Status::Running, Status::Running, Status::Success]
+        );
+        assert_eq!(npc.log, ["a", "a", "b", "b"]);
+    }
+
+    #[test]
+    fn test_selector_interrupts_lower_priority() {
+        let mut tree = BehaviorTree::new(Node::selector(vec![
+            Node::sequence(vec![
+                Node::condition(|npc: &Npc| npc.sees_player),
+                slow("chase"),
+            ]),
+            Node::sequence(vec![slow("patrol"), slow("rest")]),
+        ]));
+        let mut npc = Npc::default();
+
+        tree.tick(&mut npc, 0.1);
+        tree.tick(&mut npc, 0.1);
+        npc.sees_player = true;
+        assert_eq!(tree.tick(&mut npc, 0.1), Status::Running);
+        npc.sees_player = false;
+        tree.tick(&mut npc, 0.1);
+
+        // The interrupted patrol starts over instead of resting
+        assert_eq!(npc.log, ["patrol", "patrol", "rest", "chase", "patrol"]);
+    }
+
+    #[test]
+    fn test_action_follows_path() {
+        let grid = WalkGrid::new(4, 1);
+        let path = find_path(&grid, (0, 0), (3, 0)).unwrap();
+        let mut follower = PathFollower::new(&path, 16.0);
+        let mut tree = BehaviorTree::new(Node::action(move |npc: &mut Npc, delta| {
+            let Some(direction) = follower.steer(npc.position) else {
+                return Status::Success;
+            };
+            npc.position += direction * 10.0 * delta;
+            Status::Running
+        }));
+        let mut npc = Npc::default();
+
+        let mut ticks = 0;
+        while tree.tick(&mut npc, 0.1) == Status::Running {
+            ticks += 1;
+            assert!(ticks < 100);
+        }
+        assert!((npc.position - Coordinate { x: 48.0, y: 0.0 }).length() <= 1.0);
+    }
+}
@@ -0,0 +1,274 @@
+//! The view onto the world and the commands directing it.
+//!
+//! A `Camera` decides which part of the world ends up on screen. It either
+//! follows a target, usually the player, or stays where it was last panned to,
+//! and can zoom and shake on top of that. Pans and zooms are tweened so
+//! cutscenes and boss intros can direct the view smoothly, e.g. panning over to
+//! a boss, shaking as it lands and handing the view back to the player. A
+//! dead zone lets the target move about the middle of the screen before the
+//! view follows.
+//!
+//! World positions are turned into screen positions with `to_screen`. Zooming
+//! doesn't scale anything itself: at a zoom of `2.0` the screen shows half as
+//! many pixels in each direction, which is up to whoever presents the screen.
+//!
+//! # Key Structures
+//! - **`Camera`**: Where the view is, how far it's zoomed in and how it moves.
+//!
+//! # Example Usage
+//! ```ignore
+//! let mut camera = Camera::new(192, 108);
+//!
+//! // Boss intro
+//! camera.pan_to(boss_pos, 1.5, Easing::EaseInOut);
+//! camera.zoom_to(2.0, 1.5);
+//! camera.shake(3.0, 0.4);
+//! // ...and back to the player
+//! camera.lock_follow();
+//!
+//! // Every frame
+//! camera.update(delta, player_pos);
+//! sprite.commands(mirror, camera.to_screen(player_pos), &mut batch);
+//! ```
+use crate::{
+    hitbox::Rect,
+    layout::Coordinate,
+    tween::{Easing, Tween},
+};
+
+/// A shake fading out over its duration.
+#[derive(Debug, Clone, PartialEq)]
+struct Shake {
+    /// Largest offset in pixels, at the start.
+    intensity: f32,
+    duration: f32,
+    elapsed: f32,
+}
+
+/// The part of the world shown on screen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Camera {
+    /// Size of the screen at a zoom of `1.0`
+    viewport: Coordinate,
+    /// The world position in the middle of the screen
+    center: Coordinate,
+    following: bool,
+    /// How far the target can move about the center before it's followed
+    dead_zone: Coordinate,
+    pan: Option<Tween<Coordinate>>,
+    zoom: f32,
+    zooming: Option<Tween<f32>>,
+    shake: Option<Shake>,
+}
+impl Camera {
+    /// A camera showing the world from its origin on a `width` by `height`
+    /// screen, the same as drawing without one.
+    pub fn new(width: u32, height: u32) -> Self {
+        let viewport = Coordinate {
+            x: width as f32,
+            y: height as f32,
+        };
+        Self {
+            viewport,
+            center: viewport * 0.5,
+            following: false,
+            dead_zone: Coordinate::default(),
+            pan: None,
+            zoom: 1.0,
+            zooming: None,
+            shake: None,
+        }
+    }
+    /// Keeps the view centered on the target given to `update`.
+    pub fn lock_follow(&mut self) {
+        self.following = true;
+        self.pan = None;
+    }
+    /// Leaves the view where it is.
+    pub fn unlock_follow(&mut self) {
+        self.following = false;
+    }
+    pub fn is_following(&self) -> bool {
+        self.following
+    }
+    /// Lets the target move within a `width` by `height` box about the center
+    /// before the view follows, instead of following every step.
+    pub fn set_dead_zone(&mut self, width: f32, height: f32) {
+        self.dead_zone = Coordinate {
+            x: width.max(0.0),
+            y: height.max(0.0),
+        };
+    }
+    /// The area of the world the target can move in without the view following.
+    pub fn dead_zone(&self) -> Rect {
+        Rect {
+            x: self.center.x - self.dead_zone.x / 2.0,
+            y: self.center.y - self.dead_zone.y / 2.0,
+            width: self.dead_zone.x,
+            height: self.dead_zone.y,
+        }
+    }
+    /// Moves the view to center on `point` over `duration` seconds, no longer
+    /// following its target.
+    pub fn pan_to(&mut self, point: Coordinate, duration: f32, easing: Easing) {
+        self.following = false;
+        self.pan = Some(Tween::new(self.center, point, duration).easing(easing));
+    }
+    /// Zooms to `zoom` over `duration` seconds, where `2.0` shows everything
+    /// twice as big.
+    pub fn zoom_to(&mut self, zoom: f32, duration: f32) {
+        self.zooming =
+            Some(Tween::new(self.zoom, zoom.max(f32::EPSILON), duration).easing(Easing::EaseInOut));
+    }
+    /// Shakes the view by up to `intensity` pixels, calming down over
+    /// `duration` seconds.
+    pub fn shake(&mut self, intensity: f32, duration: f32) {
+        self.shake = Some(Shake {
+            intensity,
+            duration,
+            elapsed: 0.0,
+        });
+    }
+    /// Whether a pan or zoom is still underway.
+    pub fn is_moving(&self) -> bool {
+        self.pan.is_some() || self.zooming.is_some()
+    }
+    /// Moves the view along for `delta` seconds, centering it on `target`
+    /// while following it.
+    pub fn update(&mut self, delta: f32, target: Coordinate) {
+        if let Some(pan) = &mut self.pan {
+            self.center = pan.advance(delta);
+            if pan.is_finished() {
+                self.pan = None;
+            }
+        } else if self.following {
+            // Just far enough to keep the target at the edge of the dead zone
+            let half = self.dead_zone * 0.5;
+            let past =
+                |target: f32, center: f32, half: f32| target - (target - center).clamp(-half, half);
+            self.center = Coordinate {
+                x: past(target.x, self.center.x, half.x),
+                y: past(target.y, self.center.y, half.y),
+            };
+        }
+        if let Some(zooming) = &mut self.zooming {
+            self.zoom = zooming.advance(delta);
+            if zooming.is_finished() {
+                self.zooming = None;
+            }
+        }
+        if let Some(shake) = &mut self.shake {
+            shake.elapsed += delta;
+            if shake.elapsed >= shake.duration {
+                self.shake = None;
+            }
+        }
+    }
+    pub fn center(&self) -> Coordinate {
+        self.center
+    }
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+    /// The size of the world shown on screen at the current zoom.
+    pub fn view_size(&self) -> Coordinate {
+        self.viewport * (1.0 / self.zoom)
+    }
+    /// Where a world position ends up on the screen.
+    pub fn to_screen(&self, world: Coordinate) -> Coordinate {
+        world - self.center + self.view_size() * 0.5 + self.shake_offset()
+    }
+    /// Where a screen position is in the world, e.g. for a click.
+    pub fn to_world(&self, screen: Coordinate) -> Coordinate {
+        screen - self.view_size() * 0.5 + self.center - self.shake_offset()
+    }
+    fn shake_offset(&self) -> Coordinate {
+        let Some(shake) = &self.shake else {
+            return Coordinate::default();
+        };
+        let strength = shake.intensity * (1.0 - shake.elapsed / shake.duration).max(0.0);
+        // Two unrelated frequencies keep the shake from looking like a circle
+        Coordinate {
+            x: (shake.elapsed * 97.0).sin(),
+            y: (shake.elapsed * 71.0).cos(),
+        } * strength
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(x: f32, y: f32) -> Coordinate {
+        Coordinate { x, y }
+    }
+
+    #[test]
+    fn test_default_view_is_unchanged() {
+        let mut camera = Camera::new(100, 50);
+        camera.update(1.0, at(70.0, 7.0));
+        assert_eq!(camera.to_screen(at(3.0, 4.0)), at(3.0, 4.0));
+        assert_eq!(camera.to_world(at(3.0, 4.0)), at(3.0, 4.0));
+    }
+
+    #[test]
+    fn test_follow_and_pan() {
+        let mut camera = Camera::new(100, 50);
+        camera.lock_follow();
+        camera.update(0.1, at(200.0, 100.0));
+        assert_eq!(camera.to_screen(at(200.0, 100.0)), at(50.0, 25.0));
+
+        camera.pan_to(at(300.0, 100.0), 1.0, Easing::Linear);
+        assert!(!camera.is_following());
+        camera.update(0.5, at(200.0, 100.0));
+        assert_eq!(camera.center(), at(250.0, 100.0));
+        camera.update(0.5, at(200.0, 100.0));
+        assert!(!camera.is_moving());
+        // Stays at the end of the pan
+        camera.update(0.5, at(0.0, 0.0));
+        assert_eq!(camera.center(), at(300.0, 100.0));
+
+        camera.lock_follow();
+        camera.update(0.1, at(0.0, 0.0));
+        assert_eq!(camera.center(), at(0.0, 0.0));
+    }
+
+    #[test]
+    fn test_dead_zone() {
+        let mut camera = Camera::new(100, 50);
+        camera.lock_follow();
+        camera.set_dead_zone(20.0, 10.0);
+        camera.update(0.1, at(55.0, 25.0));
+        assert_eq!(camera.center(), at(50.0, 25.0));
+        // Walking out of it drags the view along
+        camera.update(0.1, at(70.0, 15.0));
+        assert_eq!(camera.center(), at(60.0, 20.0));
+        assert_eq!(
+            camera.dead_zone(),
+            Rect {
+                x: 50.0,
+                y: 15.0,
+                width: 20.0,
+                height: 10.0
+            }
+        );
+    }
+
+    #[test]
+    fn test_zoom_and_shake() {
+        let mut camera = Camera::new(100, 50);
+        camera.zoom_to(2.0, 1.0);
+        camera.update(1.0, at(0.0, 0.0));
+        assert_eq!(camera.zoom(), 2.0);
+        assert_eq!(camera.view_size(), at(50.0, 25.0));
+        // The center of the view stays in the middle of the smaller screen
+        assert_eq!(camera.to_screen(at(50.0, 25.0)), at(25.0, 12.5));
+
+        camera.shake(4.0, 0.5);
+        camera.update(0.1, at(0.0, 0.0));
+        let shaken = camera.to_screen(at(50.0, 25.0)) - at(25.0, 12.5);
+        assert!(shaken != Coordinate::default() && shaken.length() <= 4.0 * 2f32.sqrt());
+        camera.update(0.5, at(0.0, 0.0));
+        assert_eq!(camera.to_screen(at(50.0, 25.0)), at(25.0, 12.5));
+    }
+}
@@ -0,0 +1,145 @@
+//! Toggles for bending the rules while testing a level.
+//!
+//! `Cheats` holds what's switched on: noclip to walk through whatever would
+//! block the player, god mode to stay unharmed, a time scale to play in slow
+//! motion or fast forward, and showing every character's hitboxes and
+//! hurtboxes on the `DebugOverlay`. They're switched from the debug console
+//! with `noclip`, `god`, `timescale` and `hitboxes`, and only take effect while
+//! cheats are allowed, which by default they are in debug builds alone.
+//!
+//! Nothing in the game deals damage yet, so god mode is only kept here for
+//! whatever does to check with `god_mode`.
+//!
+//! # Key Structures
+//! - **`Cheat`**: A single toggle switched from the console.
+//! - **`Cheats`**: Which toggles are on and whether they may be.
+//!
+//! # Example Usage
+//! ```ignore
+//! let mut cheats = Cheats::new();
+//! cheats.apply(Cheat::TimeScale(0.25));
+//!
+//! if !cheats.god_mode() {
+//!     health -= damage;
+//! }
+//! ```
+
+/// The slowest the game can be played.
+pub const MIN_TIME_SCALE: f32 = 0.1;
+/// The fastest the game can be played.
+pub const MAX_TIME_SCALE: f32 = 4.0;
+
+/// A single toggle switched from the console.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cheat {
+    /// Walks through anything that would block the player.
+    Noclip(bool),
+    /// Keeps the player from being harmed.
+    GodMode(bool),
+    /// Plays the game this many times as fast, between `MIN_TIME_SCALE` and
+    /// `MAX_TIME_SCALE`, on top of the game's own `TimeScale`.
+    TimeScale(f32),
+    /// Outlines the hitboxes and hurtboxes of every character.
+    Hitboxes(bool),
+}
+
+/// Which toggles are on and whether they may be.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cheats {
+    allowed: bool,
+    noclip: bool,
+    god_mode: bool,
+    time_scale: f32,
+    hitboxes: bool,
+}
+impl Default for Cheats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Cheats {
+    /// Everything off, allowed in debug builds only.
+    pub fn new() -> Self {
+        Self {
+            allowed: cfg!(debug_assertions),
+            noclip: false,
+            god_mode: false,
+            time_scale: 1.0,
+            hitboxes: false,
+        }
+    }
+    /// Whether cheats may be switched on, e.g. to try them in a release build.
+    ///
+    /// Disallowing them switches off whatever is on
+    pub fn allowed(mut self, allowed: bool) -> Self {
+        self.set_allowed(allowed);
+        self
+    }
+    pub fn set_allowed(&mut self, allowed: bool) {
+        if !allowed {
+            *self = Self::new();
+        }
+        self.allowed = allowed;
+    }
+    pub fn is_allowed(&self) -> bool {
+        self.allowed
+    }
+    /// Switches `cheat`, returning whether it was allowed to be.
+    ///
+    /// Time scales that aren't finite numbers aren't either, being neither
+    /// slower nor faster than any other
+    pub fn apply(&mut self, cheat: Cheat) -> bool {
+        if !self.allowed || matches!(cheat, Cheat::TimeScale(scale) if !scale.is_finite()) {
+            return false;
+        }
+        match cheat {
+            Cheat::Noclip(on) => self.noclip = on,
+            Cheat::GodMode(on) => self.god_mode = on,
+            Cheat::TimeScale(scale) => {
+                self.time_scale = scale.clamp(MIN_TIME_SCALE, MAX_TIME_SCALE)
+            }
+            Cheat::Hitboxes(on) => self.hitboxes = on,
+        }
+        true
+    }
+    pub fn noclip(&self) -> bool {
+        self.noclip
+    }
+    pub fn god_mode(&self) -> bool {
+        self.god_mode
+    }
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+    pub fn hitboxes(&self) -> bool {
+        self.hitboxes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cheats_only_apply_when_allowed() {
+        let mut cheats = Cheats::new().allowed(false);
+        assert!(!cheats.apply(Cheat::Noclip(true)));
+        assert!(!cheats.noclip());
+
+        cheats.set_allowed(true);
+        assert!(cheats.apply(Cheat::Noclip(true)));
+        cheats.apply(Cheat::TimeScale(10.0));
+        assert_eq!(cheats.time_scale(), MAX_TIME_SCALE);
+        cheats.apply(Cheat::TimeScale(0.0));
+        assert_eq!(cheats.time_scale(), MIN_TIME_SCALE);
+        for scale in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+            assert!(!cheats.apply(Cheat::TimeScale(scale)));
+        }
+        assert_eq!(cheats.time_scale(), MIN_TIME_SCALE);
+
+        // Nothing stays on once cheats are taken away
+        cheats.set_allowed(false);
+        assert!(!cheats.noclip());
+        assert_eq!(cheats.time_scale(), 1.0);
+    }
+}
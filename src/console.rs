@@ -0,0 +1,283 @@
+//! A debug console taking commands over TCP or stdin while the game runs.
+//!
+//! `DebugConsole` reads commands one per line, e.g. from `nc localhost 7070`
+//! or the terminal the game was started from, and publishes them as
+//! `DebugCommand`s on the `EventBus`. Whichever system owns what a command is
+//! about handles it: `GameState` teleports the player, switches `Cheats`,
+//! shows the `DebugOverlay` and edits the character in the `Inspector`,
+//! whoever keeps the `Progress` sets flags. Every line is answered with `ok`, the error it
+//! caused, or a note that nothing handles it, so testing a level doesn't have
+//! to go through the UI.
+//!
+//! The console is only started when asked for, and reads on threads of its own
+//! so it never holds up a frame.
+//!
+//! # Key Structures
+//! - **`DebugCommand`**: A command typed into the console.
+//! - **`DebugConsole`**: Reads commands and publishes them on the bus.
+//!
+//! Errors:
+//! - `ConsoleError`: A command couldn't be read or the console not started.
+//!
+//! # Example Usage
+//! ```ignore
+//! let bus = event_handler.bus();
+//! bus.subscribe::<DebugCommand>(&mut game, 16, Backpressure::DropOldest);
+//! DebugConsole::new(&bus).listen("127.0.0.1:7070")?;
+//!
+//! // Elsewhere, owning the story progress
+//! for command in commands.try_iter() {
+//!     if let DebugCommand::SetFlag(flag, set) = command {
+//!         progress.set(&flag, set);
+//!     }
+//! }
+//! ```
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, ToSocketAddrs},
+    thread,
+};
+
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::{
+    cheats::Cheat,
+    inspector::Field,
+    layout::Coordinate,
+    overlay::Category,
+    sync::{EventBus, Publisher},
+};
+
+/// What `help` answers with.
+const HELP: &str = "\
+teleport <x> <y>        move the player
+spawn <name> <x> <y>    spawn an entity
+flag <name> <on|off>    set a story flag
+reload                  reload assets
+noclip <on|off>         walk through anything
+god <on|off>            take no damage
+timescale <scale>       play slower or faster, 0.1 to 4
+hitboxes <on|off>       outline hitboxes and hurtboxes
+overlay <box> <on|off>  outline bounds, hitboxes, hurtboxes, triggers or deadzone
+edit <field> <value>    set x, y, vx, vy or frame of the inspected character
+help                    show this";
+
+/// A command typed into the console.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DebugCommand {
+    /// Moves the player to a position in the world.
+    Teleport(Coordinate),
+    /// Spawns the entity called `name` at a position.
+    Spawn(String, Coordinate),
+    /// Sets or clears a story flag.
+    SetFlag(String, bool),
+    /// Loads assets from disk again.
+    ReloadAssets,
+    /// Switches a cheat.
+    Cheat(Cheat),
+    /// Shows or hides a category of boxes on the debug overlay.
+    Overlay(Category, bool),
+    /// Sets a component of the inspected character.
+    Edit(Field, f32),
+}
+impl DebugCommand {
+    /// Reads a command from a line typed into the console.
+    pub fn parse(line: &str) -> Result<Self, ConsoleError> {
+        let unknown = || ConsoleError::Unknown(line.trim().to_string());
+        // `NaN` and `inf` parse as well but never make sense to type
+        let number = |field: &str| {
+            field
+                .parse::<f32>()
+                .ok()
+                .filter(|n| n.is_finite())
+                .ok_or_else(|| ConsoleError::NotANumber(field.to_string()))
+        };
+        let toggle = |field: &str| match field {
+            "on" => Ok(true),
+            "off" => Ok(false),
+            _ => Err(unknown()),
+        };
+        let fields: Vec<_> = line.split_whitespace().collect();
+        Ok(match fields[..] {
+            ["teleport", x, y] => DebugCommand::Teleport(Coordinate {
+                x: number(x)?,
+                y: number(y)?,
+            }),
+            ["spawn", name, x, y] => DebugCommand::Spawn(
+                name.to_string(),
+                Coordinate {
+                    x: number(x)?,
+                    y: number(y)?,
+                },
+            ),
+            ["flag", name, on] => DebugCommand::SetFlag(name.to_string(), toggle(on)?),
+            ["reload"] => DebugCommand::ReloadAssets,
+            ["noclip", on] => DebugCommand::Cheat(Cheat::Noclip(toggle(on)?)),
+            ["god", on] => DebugCommand::Cheat(Cheat::GodMode(toggle(on)?)),
+            ["timescale", scale] => DebugCommand::Cheat(Cheat::TimeScale(number(scale)?)),
+            ["hitboxes", on] => DebugCommand::Cheat(Cheat::Hitboxes(toggle(on)?)),
+            ["overlay", name, on] => {
+                let category = Category::from_name(name).ok_or_else(unknown)?;
+                DebugCommand::Overlay(category, toggle(on)?)
+            }
+            ["edit", name, value] => {
+                let field = Field::from_name(name).ok_or_else(unknown)?;
+                DebugCommand::Edit(field, number(value)?)
+            }
+            _ => return Err(unknown()),
+        })
+    }
+}
+
+/// Reads commands and publishes them on the bus.
+#[derive(Clone)]
+pub struct DebugConsole {
+    publisher: Publisher<DebugCommand>,
+}
+impl DebugConsole {
+    pub fn new(bus: &EventBus) -> Self {
+        Self {
+            publisher: bus.publisher(),
+        }
+    }
+    /// Takes commands from every connection to `address`.
+    ///
+    /// Returns the address listened on, e.g. to find the port picked for `:0`
+    pub fn listen(self, address: impl ToSocketAddrs) -> Result<SocketAddr, ConsoleError> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        info!(%address, "debug console listening");
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(error) => {
+                        warn!(%error, "debug console connection failed");
+                        continue;
+                    }
+                };
+                let console = self.clone();
+                thread::spawn(move || {
+                    if let Ok(reader) = stream.try_clone() {
+                        let _ = console.serve(BufReader::new(reader), stream);
+                    }
+                });
+            }
+        });
+        Ok(address)
+    }
+    /// Takes commands typed into the terminal the game runs in.
+    pub fn stdin(self) {
+        thread::spawn(move || {
+            let _ = self.serve(io::stdin().lock(), io::stdout());
+        });
+    }
+    /// Runs a single command line, returning the answer to it.
+    pub fn execute(&self, line: &str) -> String {
+        if line.trim() == "help" {
+            return HELP.to_string();
+        }
+        match DebugCommand::parse(line) {
+            Ok(command) => match self.publisher.publish(command) {
+                0 => "ignored: nothing handles it".to_string(),
+                _ => "ok".to_string(),
+            },
+            Err(error) => format!("error: {error}"),
+        }
+    }
+    /// Answers every line of `input` until it's closed.
+    fn serve(&self, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            writeln!(output, "{}", self.execute(&line))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ConsoleError {
+    #[error("console failed: {0}")]
+    Io(#[from] io::Error),
+    #[error("unknown command `{0}`, try `help`")]
+    Unknown(String),
+    #[error("`{0}` is not a number")]
+    NotANumber(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::{Backpressure, Inbox, Subscriber};
+    use std::net::TcpStream;
+
+    #[derive(Default)]
+    struct Commands(Option<Inbox<DebugCommand>>);
+    impl Subscriber<DebugCommand> for Commands {
+        fn subscribe(&mut self, rx: Inbox<DebugCommand>) {
+            self.0 = Some(rx);
+        }
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert!(matches!(
+            DebugCommand::parse("teleport 4 -2.5"),
+            Ok(DebugCommand::Teleport(Coordinate { x, y })) if x == 4.0 && y == -2.5
+        ));
+        assert!(matches!(
+            DebugCommand::parse("  flag met_king on "),
+            Ok(DebugCommand::SetFlag(flag, true)) if flag == "met_king"
+        ));
+        assert!(matches!(
+            DebugCommand::parse("spawn slime 1 nowhere"),
+            Err(ConsoleError::NotANumber(field)) if field == "nowhere"
+        ));
+        assert!(matches!(
+            DebugCommand::parse("timescale 0.5"),
+            Ok(DebugCommand::Cheat(Cheat::TimeScale(scale))) if scale == 0.5
+        ));
+        assert!(matches!(
+            DebugCommand::parse("timescale NaN"),
+            Err(ConsoleError::NotANumber(field)) if field == "NaN"
+        ));
+        assert!(matches!(
+            DebugCommand::parse("overlay deadzone on"),
+            Ok(DebugCommand::Overlay(Category::DeadZone, true))
+        ));
+        assert!(matches!(
+            DebugCommand::parse("god maybe"),
+            Err(ConsoleError::Unknown(_))
+        ));
+        assert!(matches!(
+            DebugCommand::parse("fly"),
+            Err(ConsoleError::Unknown(_))
+        ));
+    }
+
+    #[test]
+    fn test_commands_over_tcp() {
+        let bus = EventBus::new();
+        let console = DebugConsole::new(&bus);
+        assert_eq!(console.execute("reload"), "ignored: nothing handles it");
+
+        let mut commands = Commands::default();
+        bus.subscribe(&mut commands, 4, Backpressure::DropOldest);
+        let address = console.listen("127.0.0.1:0").unwrap();
+        let mut stream = TcpStream::connect(address).unwrap();
+        writeln!(stream, "teleport 1 2\n\nfly").unwrap();
+
+        let mut answers = BufReader::new(stream).lines();
+        assert_eq!(answers.next().unwrap().unwrap(), "ok");
+        let answer = answers.next().unwrap().unwrap();
+        assert!(answer.starts_with("error: unknown"));
+        assert_eq!(
+            commands.0.unwrap().try_recv(),
+            Ok(DebugCommand::Teleport(Coordinate { x: 1.0, y: 2.0 }))
+        );
+    }
+}
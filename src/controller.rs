@@ -0,0 +1,369 @@
+//! Picks which of a character's animations to play for the way it moves.
+//!
+//! `CharacterController` turns a movement direction into a `Pose` and the
+//! mirroring to draw it with, e.g. walking left plays the side walk flipped.
+//! It remembers which way the character last moved as its `Facing`, which
+//! stays put while the character stands still so it idles facing that way.
+//!
+//! Characters climbing a ladder or swimming move in a `MovementMode` of their
+//! own: slower, with physics tuned for it, and playing the character's `"climb"`
+//! or `"swim"` action when it has one instead of walking.
+//!
+//! # Key Structures
+//! - **`Facing`**: The way a character looks.
+//! - **`Pose`**: Which of a `Character`'s animations to play.
+//! - **`MovementMode`**: Walking, climbing or swimming.
+//! - **`CharacterController`**: Tracks facing and picks poses from movement.
+//!
+//! # Example Usage
+//! ```ignore
+//! let mut controller = CharacterController::new();
+//!
+//! // Every frame
+//! let (animation, mirror) = controller.animation(&mut knight, input);
+//! animation.advance(delta);
+//! animation.commands(mirror, position, &mut batch);
+//!
+//! // Entering water
+//! controller.set_mode(MovementMode::Swimming, &mut body);
+//! ```
+use std::collections::HashMap;
+
+use crate::{animator::Animation, physics::Body, prelude::*};
+
+/// The way a character looks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Facing {
+    Left,
+    Right,
+    Up,
+    #[default]
+    Down,
+}
+
+/// Which of a `Character`'s animations to play.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Pose {
+    #[default]
+    Idle,
+    SideIdle,
+    BackIdle,
+    SideWalk,
+    FrontWalk,
+    BackWalk,
+}
+
+/// How a character gets around.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum MovementMode {
+    #[default]
+    Normal,
+    /// On a ladder or vine, facing away while moving in any direction.
+    Climbing,
+    Swimming,
+}
+impl MovementMode {
+    /// The fraction of the walking speed moved at.
+    pub fn speed(&self) -> f32 {
+        match self {
+            MovementMode::Normal => 1.0,
+            MovementMode::Climbing => 0.5,
+            MovementMode::Swimming => 0.6,
+        }
+    }
+    /// The `Character::action` played instead of walking, if any.
+    pub fn action(&self) -> Option<&'static str> {
+        match self {
+            MovementMode::Normal => None,
+            MovementMode::Climbing => Some("climb"),
+            MovementMode::Swimming => Some("swim"),
+        }
+    }
+    /// The physics of a mode that wasn't given any of its own.
+    fn default_physics(&self) -> Body {
+        match self {
+            // Water lets the character drift on after letting go
+            MovementMode::Swimming => Body::new().acceleration(120.0).friction(40.0),
+            _ => Body::new(),
+        }
+    }
+}
+
+/// Picks a character's animation from the way it moves.
+#[derive(Debug, Clone, Default)]
+pub struct CharacterController {
+    facing: Facing,
+    pose: Pose,
+    mode: MovementMode,
+    /// The pose and mode `animation` last played, to start the animation over
+    /// once it switches to another
+    animated: Option<(Pose, MovementMode)>,
+    /// Physics of every mode but the current one, which lives in the body
+    physics: HashMap<MovementMode, Body>,
+}
+impl CharacterController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Moves in `mode` with the acceleration, friction and limits of `body`.
+    pub fn physics(mut self, mode: MovementMode, body: Body) -> Self {
+        self.physics.insert(mode, body);
+        self
+    }
+    pub fn mode(&self) -> MovementMode {
+        self.mode
+    }
+    /// Switches to moving in `mode`, e.g. when entering water, retuning `body`
+    /// to it while keeping its momentum.
+    pub fn set_mode(&mut self, mode: MovementMode, body: &mut Body) {
+        if mode == self.mode {
+            return;
+        }
+        let mut tuning = self
+            .physics
+            .remove(&mode)
+            .unwrap_or_else(|| mode.default_physics());
+        tuning.velocity = body.velocity;
+        tuning.knockback = body.knockback;
+        self.physics
+            .insert(self.mode, std::mem::replace(body, tuning));
+        self.mode = mode;
+    }
+    /// The way the character last moved in.
+    pub fn facing(&self) -> Facing {
+        self.facing
+    }
+    /// The pose picked by the last `update`.
+    pub fn pose(&self) -> Pose {
+        self.pose
+    }
+    /// Goes back to looking `facing` in `pose`, carrying on with its animation
+    /// instead of starting it over, e.g. when restoring a snapshot.
+    pub fn resume(&mut self, facing: Facing, pose: Pose) {
+        self.facing = facing;
+        self.pose = pose;
+        self.animated = Some((pose, self.mode));
+    }
+    /// Lets the character play something other than its controller's
+    /// animations, like a cutscene action, so the next `animation` starts over.
+    pub fn interrupt(&mut self) {
+        self.animated = None;
+    }
+    /// Picks the pose and mirroring for moving in `motion`, or standing still
+    /// facing the way it last moved without it.
+    ///
+    /// Diagonal movement walks sideways
+    pub fn update(&mut self, motion: Option<Coordinate>) -> (Pose, MirrorDirection) {
+        let moving = match motion {
+            Some(Coordinate { x, .. }) if x < 0.0 => Some(Facing::Left),
+            Some(Coordinate { x, .. }) if x > 0.0 => Some(Facing::Right),
+            Some(Coordinate { y, .. }) if y > 0.0 => Some(Facing::Down),
+            Some(Coordinate { y, .. }) if y < 0.0 => Some(Facing::Up),
+            _ => None,
+        };
+        if let Some(facing) = moving {
+            self.facing = facing;
+        }
+        let (pose, mirror) = match (self.facing, moving.is_some()) {
+            (Facing::Left, false) => (Pose::SideIdle, MirrorDirection::FlipVertical),
+            (Facing::Right, false) => (Pose::SideIdle, MirrorDirection::None),
+            (Facing::Down, false) => (Pose::Idle, MirrorDirection::None),
+            (Facing::Up, false) => (Pose::BackIdle, MirrorDirection::None),
+            (Facing::Left, true) => (Pose::SideWalk, MirrorDirection::FlipVertical),
+            (Facing::Right, true) => (Pose::SideWalk, MirrorDirection::None),
+            (Facing::Down, true) => (Pose::FrontWalk, MirrorDirection::None),
+            (Facing::Up, true) => (Pose::BackWalk, MirrorDirection::None),
+        };
+        self.pose = pose;
+        (pose, mirror)
+    }
+    /// The animation of `character` to play for moving in `motion` and the
+    /// mirroring to draw it with.
+    ///
+    /// Climbing and swimming play the mode's action while moving, falling back
+    /// to walking for characters without one. Switching animations plays the
+    /// new one from its first frame
+    pub fn animation<'a, S: Screen>(
+        &mut self,
+        character: &'a mut dyn Character<S>,
+        motion: Option<Coordinate>,
+    ) -> (&'a mut dyn Animation<S>, MirrorDirection) {
+        let (pose, mirror) = self.update(motion);
+        let (animation, mirror) = self.pick(character, pose, mirror);
+        if self.animated.replace((pose, self.mode)) != Some((pose, self.mode)) {
+            animation.reset();
+        }
+        (animation, mirror)
+    }
+    /// The animation of `character` playing for the current pose, without
+    /// moving it on.
+    pub fn playing<'a, S: Screen>(
+        &self,
+        character: &'a mut dyn Character<S>,
+    ) -> &'a mut dyn Animation<S> {
+        self.pick(character, self.pose, MirrorDirection::None).0
+    }
+    /// The animation of `character` for `pose` in the current mode and the
+    /// mirroring to draw it with.
+    fn pick<'a, S: Screen>(
+        &self,
+        character: &'a mut dyn Character<S>,
+        pose: Pose,
+        mirror: MirrorDirection,
+    ) -> (&'a mut dyn Animation<S>, MirrorDirection) {
+        let moving = !matches!(pose, Pose::Idle | Pose::SideIdle | Pose::BackIdle);
+        if let Some(action) = self.mode.action().filter(|_| moving) {
+            // Looked up twice since returning the first lookup would keep
+            // `character` borrowed for the fallbacks below
+            if character.action(action).is_some() {
+                let mirror = if self.mode == MovementMode::Climbing {
+                    MirrorDirection::None
+                } else {
+                    mirror
+                };
+                return (character.action(action).unwrap(), mirror);
+            }
+        }
+        if self.mode == MovementMode::Climbing {
+            let animation = if moving {
+                character.back_walk()
+            } else {
+                character.back_idle()
+            };
+            return (animation, MirrorDirection::None);
+        }
+        let animation = match pose {
+            Pose::Idle => character.idle(),
+            Pose::SideIdle => character.side_idle(),
+            Pose::BackIdle => character.back_idle(),
+            Pose::SideWalk => character.side_walk(),
+            Pose::FrontWalk => character.front_walk(),
+            Pose::BackWalk => character.back_walk(),
+        };
+        (animation, mirror)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockCharacter;
+
+    fn walk(x: f32, y: f32) -> Option<Coordinate> {
+        Some(Coordinate { x, y })
+    }
+
+    #[test]
+    fn test_motion_picks_pose() {
+        let mut controller = CharacterController::new();
+
+        assert_eq!(
+            controller.update(walk(-1.0, 0.0)),
+            (Pose::SideWalk, MirrorDirection::FlipVertical)
+        );
+        assert_eq!(
+            controller.update(walk(1.0, 1.0)),
+            (Pose::SideWalk, MirrorDirection::None)
+        );
+        assert_eq!(
+            controller.update(walk(0.0, 1.0)),
+            (Pose::FrontWalk, MirrorDirection::None)
+        );
+        assert_eq!(
+            controller.update(walk(0.0, -1.0)),
+            (Pose::BackWalk, MirrorDirection::None)
+        );
+        assert_eq!(
+            controller.update(None),
+            (Pose::BackIdle, MirrorDirection::None)
+        );
+    }
+
+    #[test]
+    fn test_idle_keeps_facing() {
+        let mut controller = CharacterController::new();
+        assert_eq!(controller.update(None).0, Pose::Idle);
+
+        controller.update(walk(-1.0, 0.0));
+        assert_eq!(
+            controller.update(walk(0.0, 0.0)),
+            (Pose::SideIdle, MirrorDirection::FlipVertical)
+        );
+        controller.update(walk(1.0, 0.0));
+        assert_eq!(
+            controller.update(None),
+            (Pose::SideIdle, MirrorDirection::None)
+        );
+        controller.update(walk(0.0, 1.0));
+        assert_eq!(controller.update(None).0, Pose::Idle);
+    }
+
+    #[test]
+    fn test_modes_play_their_actions() {
+        let mut controller = CharacterController::new();
+        let mut character = MockCharacter::new();
+        let mut body = Body::new();
+
+        controller.set_mode(MovementMode::Climbing, &mut body);
+        let (_, mirror) = controller.animation(&mut character, walk(-1.0, 0.0));
+        assert_eq!(character.animation_trigerred, "climb");
+        assert_eq!(mirror, MirrorDirection::None);
+        controller.animation(&mut character, None);
+        assert_eq!(character.animation_trigerred, "idle");
+
+        // Without a swim animation it swims by walking
+        controller.set_mode(MovementMode::Swimming, &mut body);
+        let (_, mirror) = controller.animation(&mut character, walk(-1.0, 0.0));
+        assert_eq!(character.animation_trigerred, "side");
+        assert_eq!(mirror, MirrorDirection::FlipVertical);
+    }
+
+    #[test]
+    fn test_modes_swap_physics() {
+        let mut controller =
+            CharacterController::new().physics(MovementMode::Climbing, Body::new().max_speed(4.0));
+        let mut body = Body::new().friction(10.0);
+        body.velocity = Coordinate { x: 2.0, y: 0.0 };
+
+        controller.set_mode(MovementMode::Climbing, &mut body);
+        assert_eq!(controller.mode(), MovementMode::Climbing);
+        assert_eq!(body.max_speed, 4.0);
+        assert_eq!(body.velocity, Coordinate { x: 2.0, y: 0.0 });
+
+        // Walking physics are kept for coming back
+        controller.set_mode(MovementMode::Normal, &mut body);
+        assert_eq!(body.friction, 10.0);
+        assert_eq!(body.max_speed, f32::INFINITY);
+    }
+
+    #[test]
+    fn test_facing_is_remembered() {
+        let mut controller = CharacterController::new();
+        assert_eq!(controller.facing(), Facing::Down);
+
+        controller.update(walk(-1.0, 0.0));
+        controller.update(None);
+        assert_eq!(controller.facing(), Facing::Left);
+
+        controller.update(walk(0.0, -1.0));
+        controller.update(walk(0.0, 0.0));
+        assert_eq!(controller.facing(), Facing::Up);
+    }
+
+    #[test]
+    fn test_switching_animations_starts_over() {
+        let mut controller = CharacterController::new();
+        let mut character = MockCharacter::new();
+
+        controller
+            .animation(&mut character, walk(1.0, 0.0))
+            .0
+            .advance(0.6);
+        let (walking, _) = controller.animation(&mut character, walk(1.0, 0.0));
+        assert_eq!(walking.frame_pos(), 1);
+
+        controller.animation(&mut character, None);
+        let (walking, _) = controller.animation(&mut character, walk(-1.0, 0.0));
+        assert_eq!((walking.frame_pos(), walking.timer()), (0, 0.0));
+    }
+}
@@ -0,0 +1,403 @@
+//! Scripted sequences like intros and boss entries played in place of player control.
+//!
+//! A `Cutscene` runs a timeline of `Step`s one after the other: walking the
+//! player somewhere, waiting, showing a line of dialogue until it's confirmed,
+//! having the player act out one of their `Character` actions, fading the
+//! screen out or back in and directing the `Camera`. While one
+//! plays, `GameState` ignores movement and actions besides confirming dialogue.
+//!
+//! Cutscenes are written as plain text files with one step per line:
+//! ```text
+//! # Boss entry
+//! fade-out 0.5
+//! move 120 40
+//! play kneel 0.8
+//! fade-in 0.5
+//! camera-pan 240 40 1.5
+//! camera-zoom 2 0.5
+//! camera-shake 3 0.4
+//! wait 1.0
+//! say Who dares to enter?
+//! camera-zoom 1 0.5
+//! camera-follow
+//! ```
+//!
+//! # Key Structures
+//! - **`Step`**: A single command of a cutscene's timeline.
+//! - **`Cutscene`**: A timeline of steps and how far it has played.
+//! - **`Playback`**: How far a cutscene has played.
+//!
+//! Errors:
+//! - `CutsceneError`: Reading or parsing a cutscene file failed.
+//!
+//! # Example Usage
+//! ```ignore
+//! let intro = Cutscene::load("assets/cutscenes/intro.txt")?;
+//! state.play_cutscene(intro);
+//! ```
+use std::{fs, path::Path};
+
+use thiserror::Error;
+
+use crate::{
+    camera::Camera, grading::Grade, layout::Coordinate, palette::BLACK, renderer::DrawCmd,
+    steering::to_input, tween::Easing,
+};
+
+/// A single command of a cutscene's timeline.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    /// Does nothing for a number of seconds.
+    Wait(f32),
+    /// Walks the player to a position at their normal speed.
+    Move(Coordinate),
+    /// Shows a line of dialogue until it's confirmed.
+    Say(String),
+    /// Plays one of the player's `Character` actions by name from its first
+    /// frame for a number of seconds.
+    Play(String, f32),
+    /// Fades the screen to black over a number of seconds.
+    FadeOut(f32),
+    /// Fades the screen back in from black over a number of seconds.
+    FadeIn(f32),
+    /// Pans the camera to center on a position over a number of seconds.
+    CameraPan(Coordinate, f32),
+    /// Zooms the camera to a level over a number of seconds.
+    CameraZoom(f32, f32),
+    /// Starts shaking the camera by an intensity for a number of seconds,
+    /// moving on right away.
+    CameraShake(f32, f32),
+    /// Hands the camera back to following the player.
+    CameraFollow,
+}
+
+/// How far a `Cutscene` has played.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Playback {
+    /// The step playing.
+    pub step: usize,
+    /// Seconds spent on the step.
+    pub elapsed: f32,
+    /// Whether the step's line of dialogue was confirmed.
+    pub confirmed: bool,
+    /// Whether the step was moved onto by the latest update.
+    pub started: bool,
+}
+
+/// A timeline of steps played one after the other.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cutscene {
+    steps: Vec<Step>,
+    current: usize,
+    /// Time spent on the current step
+    elapsed: f32,
+    /// Whether the current line of dialogue was confirmed
+    confirmed: bool,
+    /// Whether the current step was moved onto by the latest update, or
+    /// none has been played yet
+    started: bool,
+    /// How far the screen is faded out from `0.0` to `1.0`
+    darkness: f32,
+}
+impl Cutscene {
+    pub fn new(steps: Vec<Step>) -> Self {
+        Self {
+            steps,
+            current: 0,
+            elapsed: 0.0,
+            confirmed: false,
+            started: true,
+            darkness: 0.0,
+        }
+    }
+    /// Reads and parses a cutscene file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CutsceneError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+    /// Parses a cutscene with one step per line, skipping blank lines and `#` comments.
+    pub fn parse(source: &str) -> Result<Self, CutsceneError> {
+        let mut steps = Vec::new();
+        for (number, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (command, args) = line.split_once(' ').unwrap_or((line, ""));
+            let mut fields = args.split_whitespace();
+            let step = match command {
+                "wait" => Step::Wait(parse(fields.next(), number)?),
+                "move" => Step::Move(Coordinate {
+                    x: parse(fields.next(), number)?,
+                    y: parse(fields.next(), number)?,
+                }),
+                "say" => Step::Say(args.trim().to_string()),
+                "play" => Step::Play(parse(fields.next(), number)?, parse(fields.next(), number)?),
+                "fade-out" => Step::FadeOut(parse(fields.next(), number)?),
+                "fade-in" => Step::FadeIn(parse(fields.next(), number)?),
+                "camera-pan" => Step::CameraPan(
+                    Coordinate {
+                        x: parse(fields.next(), number)?,
+                        y: parse(fields.next(), number)?,
+                    },
+                    parse(fields.next(), number)?,
+                ),
+                "camera-zoom" => {
+                    Step::CameraZoom(parse(fields.next(), number)?, parse(fields.next(), number)?)
+                }
+                "camera-shake" => {
+                    Step::CameraShake(parse(fields.next(), number)?, parse(fields.next(), number)?)
+                }
+                "camera-follow" => Step::CameraFollow,
+                _ => return Err(CutsceneError::UnknownStep(number + 1, command.to_string())),
+            };
+            steps.push(step);
+        }
+        Ok(Self::new(steps))
+    }
+    /// Plays the cutscene for `delta` seconds, walking `position` towards the
+    /// target of a `Move` by at most `speed * delta` and directing `camera`.
+    ///
+    /// Returns the direction the player walks in, snapped like movement input
+    /// so the matching walk animation can be picked
+    pub fn update(
+        &mut self,
+        delta: f32,
+        position: &mut Coordinate,
+        speed: f32,
+        camera: &mut Camera,
+    ) -> Option<Coordinate> {
+        let step = self.steps.get(self.current)?;
+        let starting = self.elapsed == 0.0;
+        self.started = false;
+        self.elapsed += delta;
+        let mut heading = None;
+        let done = match step {
+            Step::Wait(duration) => self.elapsed >= *duration,
+            Step::Move(target) => {
+                let offset = *target - *position;
+                let distance = offset.length();
+                let stride = speed * delta;
+                if distance <= stride {
+                    *position = *target;
+                    true
+                } else {
+                    *position += offset * (stride / distance);
+                    heading = to_input(offset);
+                    false
+                }
+            }
+            Step::Say(_) => self.confirmed,
+            Step::Play(_, duration) => self.elapsed >= *duration,
+            Step::FadeOut(duration) => {
+                self.darkness = progress(self.elapsed, *duration);
+                self.elapsed >= *duration
+            }
+            Step::FadeIn(duration) => {
+                self.darkness = 1.0 - progress(self.elapsed, *duration);
+                self.elapsed >= *duration
+            }
+            Step::CameraPan(target, duration) => {
+                if starting {
+                    camera.pan_to(*target, *duration, Easing::EaseInOut);
+                }
+                self.elapsed >= *duration
+            }
+            Step::CameraZoom(zoom, duration) => {
+                if starting {
+                    camera.zoom_to(*zoom, *duration);
+                }
+                self.elapsed >= *duration
+            }
+            Step::CameraShake(intensity, duration) => {
+                camera.shake(*intensity, *duration);
+                true
+            }
+            Step::CameraFollow => {
+                camera.lock_follow();
+                true
+            }
+        };
+        if done {
+            self.current += 1;
+            self.elapsed = 0.0;
+            self.confirmed = false;
+            self.started = true;
+        }
+        heading
+    }
+    /// Moves on from the line of dialogue currently shown.
+    pub fn confirm(&mut self) {
+        self.confirmed = self.dialogue().is_some();
+    }
+    /// The line of dialogue currently shown.
+    pub fn dialogue(&self) -> Option<&str> {
+        match self.steps.get(self.current) {
+            Some(Step::Say(line)) => Some(line),
+            _ => None,
+        }
+    }
+    /// The `Character` action the player is acting out, and whether it only
+    /// just started so it should play from its first frame.
+    pub fn action(&self) -> Option<(&str, bool)> {
+        match self.steps.get(self.current) {
+            Some(Step::Play(name, _)) => Some((name, self.started)),
+            _ => None,
+        }
+    }
+    pub fn playback(&self) -> Playback {
+        Playback {
+            step: self.current,
+            elapsed: self.elapsed,
+            confirmed: self.confirmed,
+            started: self.started,
+        }
+    }
+    /// Goes back to how far it had played by `playback`, the step's effects
+    /// on the screen aside.
+    pub fn set_playback(&mut self, playback: Playback) {
+        self.current = playback.step;
+        self.elapsed = playback.elapsed;
+        self.confirmed = playback.confirmed;
+        self.started = playback.started;
+    }
+    /// Whether every step has played. The screen stays as faded as the last
+    /// fade left it.
+    pub fn is_finished(&self) -> bool {
+        self.current >= self.steps.len()
+    }
+    /// Queues the fade onto a batch of draw commands.
+    pub fn commands(&self, batch: &mut Vec<DrawCmd<'_>>) {
+        if self.darkness > 0.0 {
+            batch.push(DrawCmd::Grade(Grade::Fade {
+                color: BLACK,
+                amount: self.darkness,
+            }));
+        }
+    }
+}
+
+fn progress(elapsed: f32, duration: f32) -> f32 {
+    if duration <= 0.0 {
+        1.0
+    } else {
+        (elapsed / duration).min(1.0)
+    }
+}
+
+fn parse<T: std::str::FromStr>(field: Option<&str>, line: usize) -> Result<T, CutsceneError> {
+    field
+        .and_then(|f| f.parse().ok())
+        .ok_or(CutsceneError::Syntax(line + 1))
+}
+
+#[derive(Debug, Error)]
+pub enum CutsceneError {
+    #[error("failed to read cutscene: {0}")]
+    ReadError(#[from] std::io::Error),
+    #[error("malformed cutscene step on line {0}")]
+    Syntax(usize),
+    #[error("unknown cutscene step on line {0}: {1}")]
+    UnknownStep(usize, String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTRO: &str = "
+        # Intro
+        fade-in 1.0
+        move 10 0
+        say Halt!
+        play bow 0.5
+        wait 0.5
+    ";
+
+    #[test]
+    fn test_parse_cutscene() {
+        let cutscene = Cutscene::parse(INTRO).unwrap();
+        assert_eq!(
+            cutscene.steps,
+            vec![
+                Step::FadeIn(1.0),
+                Step::Move(Coordinate { x: 10.0, y: 0.0 }),
+                Step::Say("Halt!".to_string()),
+                Step::Play("bow".to_string(), 0.5),
+                Step::Wait(0.5),
+            ]
+        );
+        assert!(matches!(
+            Cutscene::parse("move 10"),
+            Err(CutsceneError::Syntax(1))
+        ));
+        assert!(matches!(
+            Cutscene::parse("play bow"),
+            Err(CutsceneError::Syntax(1))
+        ));
+        assert!(matches!(
+            Cutscene::parse("\njump 4"),
+            Err(CutsceneError::UnknownStep(2, _))
+        ));
+    }
+
+    #[test]
+    fn test_cutscene_plays_steps() {
+        let mut cutscene = Cutscene::parse(INTRO).unwrap();
+        let mut position = Coordinate::default();
+        let mut camera = Camera::new(20, 20);
+
+        cutscene.update(0.5, &mut position, 4.0, &mut camera);
+        assert_eq!(cutscene.darkness, 0.5);
+        cutscene.update(0.5, &mut position, 4.0, &mut camera);
+        assert_eq!(cutscene.darkness, 0.0);
+
+        // Walks at the given speed and lands exactly on the target
+        let heading = cutscene.update(1.0, &mut position, 4.0, &mut camera);
+        assert_eq!(heading, Some(Coordinate { x: 1.0, y: 0.0 }));
+        assert_eq!(position, Coordinate { x: 4.0, y: 0.0 });
+        cutscene.update(1.0, &mut position, 4.0, &mut camera);
+        assert_eq!(cutscene.update(1.0, &mut position, 4.0, &mut camera), None);
+        assert_eq!(position, Coordinate { x: 10.0, y: 0.0 });
+
+        // Dialogue waits for confirmation however long it takes
+        cutscene.update(10.0, &mut position, 4.0, &mut camera);
+        assert_eq!(cutscene.dialogue(), Some("Halt!"));
+        cutscene.confirm();
+        cutscene.update(0.1, &mut position, 4.0, &mut camera);
+        assert_eq!(cutscene.dialogue(), None);
+
+        // Acts for as long as it was told to, starting over only at first
+        assert_eq!(cutscene.action(), Some(("bow", true)));
+        cutscene.update(0.25, &mut position, 4.0, &mut camera);
+        assert_eq!(cutscene.action(), Some(("bow", false)));
+        cutscene.update(0.25, &mut position, 4.0, &mut camera);
+        assert_eq!(cutscene.action(), None);
+
+        cutscene.update(0.5, &mut position, 4.0, &mut camera);
+        assert!(cutscene.is_finished());
+    }
+
+    #[test]
+    fn test_cutscene_directs_camera() {
+        let mut cutscene =
+            Cutscene::parse("camera-pan 40 10 1\ncamera-shake 2 0.5\ncamera-follow").unwrap();
+        let mut position = Coordinate::default();
+        let mut camera = Camera::new(20, 20);
+
+        cutscene.update(0.5, &mut position, 4.0, &mut camera);
+        camera.update(0.5, position);
+        assert!(camera.is_moving());
+        cutscene.update(0.5, &mut position, 4.0, &mut camera);
+        camera.update(0.5, position);
+        assert_eq!(camera.center(), Coordinate { x: 40.0, y: 10.0 });
+
+        cutscene.update(0.1, &mut position, 4.0, &mut camera);
+        cutscene.update(0.1, &mut position, 4.0, &mut camera);
+        assert!(cutscene.is_finished());
+        assert!(camera.is_following());
+        assert!(matches!(
+            Cutscene::parse("camera-zoom 2"),
+            Err(CutsceneError::Syntax(1))
+        ));
+    }
+}
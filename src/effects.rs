@@ -0,0 +1,275 @@
+//! Timed status effects such as poison, speed buffs or invulnerability.
+//!
+//! A `StatusEffects` set is owned by whatever the effects are attached to and
+//! progressed with the game's `delta`. Re-applying an effect that is already
+//! active follows its `Stacking` rule, periodic effects report `Tick`s and every
+//! effect reports once it `Expired` so gameplay can react to both.
+//!
+//! # Key Structures
+//! - **`Effect`**: The definition of an effect: duration, ticking, stacking and tint.
+//! - **`Stacking`**: How re-applying an active effect combines with it.
+//! - **`StatusEffects`**: The active effects of a single owner.
+//! - **`EffectEvent`**: Ticks and expiries reported by `StatusEffects::update`.
+//!
+//! # Example Usage
+//! ```ignore
+//! const POISON: Effect = Effect::new("poison", 3.0)
+//!     .ticking(0.5, 2.0)
+//!     .stacking(Stacking::Stack(3))
+//!     .tint(GREEN);
+//!
+//! effects.apply(POISON);
+//! for event in effects.update(delta) {
+//!     if let EffectEvent::Tick { magnitude, .. } = event {
+//!         health -= magnitude;
+//!     }
+//! }
+//! ```
+use crate::palette::Color;
+
+/// How re-applying an effect that is still active combines with it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stacking {
+    /// Restarts the remaining duration.
+    Refresh,
+    /// Adds the full duration onto the remaining one.
+    Extend,
+    /// Adds another independent stack up to the given count, each multiplying
+    /// the tick magnitude. Applying past the limit refreshes the oldest stack.
+    Stack(u32),
+    /// Keeps the active effect untouched.
+    Ignore,
+}
+
+/// The definition of a timed effect.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Effect {
+    pub id: &'static str,
+    /// Seconds the effect lasts once applied.
+    pub duration: f32,
+    /// Seconds between ticks, for effects acting periodically.
+    pub tick_interval: Option<f32>,
+    /// Strength of a single tick or of the effect itself such as a speed factor.
+    pub magnitude: f32,
+    pub stacking: Stacking,
+    /// Color the owner is tinted with while the effect is active.
+    pub tint: Option<Color>,
+}
+impl Effect {
+    pub const fn new(id: &'static str, duration: f32) -> Self {
+        Self {
+            id,
+            duration,
+            tick_interval: None,
+            magnitude: 1.0,
+            stacking: Stacking::Refresh,
+            tint: None,
+        }
+    }
+    /// Ticks every `interval` seconds with the given `magnitude`.
+    pub const fn ticking(mut self, interval: f32, magnitude: f32) -> Self {
+        self.tick_interval = Some(interval);
+        self.magnitude = magnitude;
+        self
+    }
+    pub const fn magnitude(mut self, magnitude: f32) -> Self {
+        self.magnitude = magnitude;
+        self
+    }
+    pub const fn stacking(mut self, stacking: Stacking) -> Self {
+        self.stacking = stacking;
+        self
+    }
+    pub const fn tint(mut self, tint: Color) -> Self {
+        self.tint = Some(tint);
+        self
+    }
+}
+
+/// Something that happened to an active effect during `StatusEffects::update`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EffectEvent {
+    /// A periodic effect acted with the magnitude of all its stacks combined.
+    Tick { id: &'static str, magnitude: f32 },
+    /// The effect ran out and was removed.
+    Expired { id: &'static str },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Active {
+    effect: Effect,
+    /// Remaining seconds of every stack, oldest first.
+    stacks: Vec<f32>,
+    /// Seconds until the next tick, starting with one as soon as applied.
+    until_tick: f32,
+}
+
+/// The active effects of a single owner.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StatusEffects {
+    active: Vec<Active>,
+}
+impl StatusEffects {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Starts an effect, combining it with an active one of the same id
+    /// according to its `Stacking`.
+    pub fn apply(&mut self, effect: Effect) {
+        let Some(active) = self.active.iter_mut().find(|a| a.effect.id == effect.id) else {
+            self.active.push(Active {
+                until_tick: 0.0,
+                stacks: vec![effect.duration],
+                effect,
+            });
+            return;
+        };
+        match effect.stacking {
+            Stacking::Refresh => active.stacks.iter_mut().for_each(|s| *s = effect.duration),
+            Stacking::Extend => active.stacks.iter_mut().for_each(|s| *s += effect.duration),
+            Stacking::Stack(max) => {
+                if active.stacks.len() < max as usize {
+                    active.stacks.push(effect.duration);
+                } else if let Some(oldest) = active.stacks.iter_mut().min_by(|a, b| a.total_cmp(b))
+                {
+                    *oldest = effect.duration;
+                }
+            }
+            Stacking::Ignore => (),
+        }
+    }
+    /// Ends an effect early without reporting it as expired.
+    pub fn remove(&mut self, id: &str) {
+        self.active.retain(|a| a.effect.id != id);
+    }
+    pub fn is_active(&self, id: &str) -> bool {
+        self.stacks(id) > 0
+    }
+    /// The number of stacks of an effect, `0` when inactive.
+    pub fn stacks(&self, id: &str) -> u32 {
+        self.active
+            .iter()
+            .find(|a| a.effect.id == id)
+            .map_or(0, |a| a.stacks.len() as u32)
+    }
+    /// The combined magnitude of an active effect or `None` when inactive.
+    pub fn magnitude(&self, id: &str) -> Option<f32> {
+        self.active
+            .iter()
+            .find(|a| a.effect.id == id)
+            .map(|a| a.effect.magnitude * a.stacks.len() as f32)
+    }
+    /// The tint of the most recently applied active effect that has one.
+    pub fn tint(&self) -> Option<Color> {
+        self.active.iter().rev().find_map(|a| a.effect.tint)
+    }
+    /// Progresses every effect by `delta` seconds.
+    ///
+    /// Ticks are reported before expiries so a final tick landing on the same
+    /// update an effect runs out still counts
+    pub fn update(&mut self, delta: f32) -> Vec<EffectEvent> {
+        let mut events = Vec::new();
+        for active in &mut self.active {
+            if let Some(interval) = active.effect.tick_interval.filter(|i| *i > 0.0) {
+                // Stacks run out during this update are still active for the
+                // part of it before they expire
+                let longest = active.stacks.iter().cloned().fold(0.0, f32::max);
+                let mut elapsed = active.until_tick;
+                while elapsed <= delta.min(longest) {
+                    let stacks = active.stacks.iter().filter(|s| **s >= elapsed).count();
+                    events.push(EffectEvent::Tick {
+                        id: active.effect.id,
+                        magnitude: active.effect.magnitude * stacks as f32,
+                    });
+                    elapsed += interval;
+                }
+                active.until_tick = elapsed - delta;
+            }
+            active.stacks.iter_mut().for_each(|s| *s -= delta);
+            active.stacks.retain(|s| *s > 0.0);
+        }
+        self.active.retain(|a| {
+            let expired = a.stacks.is_empty();
+            if expired {
+                events.push(EffectEvent::Expired { id: a.effect.id });
+            }
+            !expired
+        });
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::palette::{GREEN, RED};
+
+    const POISON: Effect = Effect::new("poison", 1.0)
+        .ticking(0.5, 2.0)
+        .stacking(Stacking::Stack(2))
+        .tint(GREEN);
+    const HASTE: Effect = Effect::new("haste", 2.0).magnitude(1.5);
+
+    fn ticks(events: &[EffectEvent]) -> Vec<f32> {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                EffectEvent::Tick { magnitude, .. } => Some(*magnitude),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ticks_and_expiry() {
+        let mut effects = StatusEffects::new();
+        effects.apply(POISON);
+
+        // Ticks right away then every half second
+        assert_eq!(ticks(&effects.update(0.25)), vec![2.0]);
+        assert_eq!(ticks(&effects.update(0.25)), vec![2.0]);
+        assert!(effects.update(0.25).is_empty());
+
+        let events = effects.update(0.5);
+        assert_eq!(ticks(&events), vec![2.0]);
+        assert_eq!(events.last(), Some(&EffectEvent::Expired { id: "poison" }));
+        assert!(!effects.is_active("poison"));
+    }
+
+    #[test]
+    fn test_stacking_rules() {
+        let mut effects = StatusEffects::new();
+        effects.apply(POISON);
+        effects.apply(POISON);
+        effects.apply(POISON);
+        assert_eq!(effects.stacks("poison"), 2);
+        assert_eq!(ticks(&effects.update(0.1)), vec![4.0]);
+
+        effects.apply(HASTE);
+        effects.update(1.5);
+        effects.apply(HASTE);
+        assert!(effects.update(1.9).is_empty());
+        assert_eq!(effects.magnitude("haste"), Some(1.5));
+
+        let extend = HASTE.stacking(Stacking::Extend);
+        effects.apply(extend.clone());
+        effects.update(2.0);
+        assert!(effects.is_active("haste"));
+
+        effects.remove("haste");
+        assert_eq!(effects.magnitude("haste"), None);
+    }
+
+    #[test]
+    fn test_tint_follows_latest_effect() {
+        let mut effects = StatusEffects::new();
+        assert_eq!(effects.tint(), None);
+
+        effects.apply(POISON);
+        effects.apply(Effect::new("burn", 0.5).tint(RED));
+        assert_eq!(effects.tint(), Some(RED));
+
+        effects.update(0.6);
+        assert_eq!(effects.tint(), Some(GREEN));
+    }
+}
@@ -8,7 +8,28 @@
 //! - Hosts and manages the main event loop via `winit`
 //! - Tracks multiple windows by their `WindowId`
 //! - Converts low-level input into high-level `Coordinate` events
-//! - Notifies subscribers (e.g., gameplay logic) of movement input
+//! - Detects double-taps and chords as actions of their own
+//! - Delivers typed text instead of gameplay input while `TextMode` is enabled
+//! - Notifies subscribers (e.g., gameplay logic) of movement input and actions
+//! - Routes a second local player's bindings to subscribers of their own
+//! - Publishes every event on a shared `EventBus`, where systems subscribe to them
+//! - Bounds every subscriber's channel, dropping or coalescing what one falling
+//!   behind can't take instead of stalling, and reports how well each keeps up
+//! - Releases held keys and notifies subscribers when window focus changes
+//! - Notifies subscribers of windows resizing, changing focus or DPI and being
+//!   asked to close, e.g. to pause or save the game
+//! - Feeds touches to on-screen `TouchControls`, repeating the joystick's
+//!   direction while it's held like a held key repeats
+//! - Redraws the screen of every window with a registered `Renderable` at a
+//!   steady rate, sleeping in between
+//! - Presents screens drawn on the game thread once their window is redrawn
+//! - Hands files dropped onto a window to its subscribers
+//! - Reports mouse clicks at the screen pixel under the cursor
+//! - Keeps window surfaces sized to the DPI of the monitor they're on
+//! - Opens a window's `SettingsMenu` on `MenuBack`, feeding it input instead
+//!   of the game and applying what's picked to the window live
+//! - Optionally updates the game itself between events (`Driven`) for
+//!   platforms that don't cope with a separate game thread
 //!
 //! ## Design Principles
 //! - Decouples platform event APIs from game logic using `GameInputHandler`
@@ -16,32 +37,53 @@
 //! - Supports injection of custom `Window` implementations for flexibility
 //!
 //! ## Example Usage
-//! ```no_run
+//! ```ignore
 //! let mut handler = EventHandler::new();
 //! handler.register_window(Box::new(MyWindow::new(...)));
 //! handler.start().unwrap(); // blocks forever
 //! ```
-use crossbeam::channel::{unbounded, Sender};
-use std::{collections::HashMap, sync::{Arc, Mutex}};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tracing::{error, info_span, trace_span};
 use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
     error::EventLoopError,
-    event::{Event, WindowEvent},
+    event::{ElementState, Event, Ime, MouseButton, WindowEvent},
     event_loop::{ControlFlow, EventLoop},
     window::WindowId,
 };
 
-use crate::input::{GameInputHandler, Input, PhysicalKeyInfo};
+use crate::input::{
+    Action, GameInput, GameInputHandler, Gesture, Input, PhysicalKeyInfo, Player, TextInput,
+    TextMode,
+};
 use crate::prelude::*;
+use crate::renderer::{draw_all, DrawCmd, DrawList};
+use crate::settings::SettingsMenu;
+use crate::sync::{Health, SubscriptionId, Topic};
+use crate::touch::{TouchControls, TouchPoint};
+use crate::ui::UiInput;
+use crate::window::{
+    window_size, Configurable, GameWindow, RedrawRequest, Renderable, PIXEL_SCALE,
+};
+
+/// How often a held joystick repeats its direction.
+const TOUCH_REPEAT: Duration = Duration::from_millis(16);
+/// How often windows with a `Renderable` are redrawn.
+const REDRAW_INTERVAL: Duration = Duration::from_millis(16);
 
 /// Central manager for event dispatch and window tracking.
 ///
 /// This struct owns the event loop and maintains a registry of windows.
 /// It provides the glue between system-level events and game/application logic.
-pub(crate) struct EventHandler {
+pub struct EventHandler {
     evtloop: EventLoop<()>,
-    windows: HashMap<WindowId, Arc<Mutex<dyn Window>>>,
-    input_handler: GameInputHandler,
-    coordinate_subscribers: Vec<Sender<Coordinate>>,
+    /// Everything the event loop's events are routed through
+    dispatcher: Dispatcher,
 }
 impl EventHandler {
     /// Get the event handler with an empty window registry.
@@ -59,12 +101,11 @@ impl EventHandler {
     /// loop on any thread.
     ///
     /// Panics if created more than once
-    pub(crate) fn new() -> EventHandler {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> EventHandler {
         Self {
             evtloop: EventLoop::new().unwrap(),
-            windows: HashMap::default(),
-            input_handler: GameInputHandler::default(),
-            coordinate_subscribers: Vec::new(),
+            dispatcher: Dispatcher::new(),
         }
     }
     /// Begins running the application's main event loop.
@@ -72,35 +113,30 @@ impl EventHandler {
     /// This function blocks the current thread and drives all window
     /// and device events. Control is handed over to the system's event dispatcher.
     /// Intended to be called once after all setup is complete.
-    pub(crate) fn start(mut self) -> Result<(), EventLoopError> {
+    ///
+    /// Returns once a window was asked to close, after `WindowLifecycle`
+    /// subscribers were told, leaving them to finish up before the process
+    /// exits, e.g. by joining the game's thread
+    pub fn start(self) -> Result<(), EventLoopError> {
+        let _span = info_span!("event_loop").entered();
+        let mut dispatcher = self.dispatcher;
         self.evtloop.run(move |event, target| {
             target.set_control_flow(ControlFlow::Wait);
             // dbg!(&event);
             match event {
-                Event::WindowEvent { event, .. } => match event {
-                    // Listening for keyboard inputs
-                    WindowEvent::KeyboardInput { event, .. } => {
-                        let input = Input::PhysicalKey(PhysicalKeyInfo {
-                            state: event.state,
-                            code: event.physical_key,
-                        });
-                        let coordinate = self.input_handler.to_coordinate(input);
-                        if let Some(c) = coordinate {
-                            for sub in &self.coordinate_subscribers {
-                                sub.try_send(c).unwrap()
-                            }
-                        }
+                Event::WindowEvent { window_id, event } => {
+                    let closing = dispatcher.handle(window_id, event);
+                    if closing {
+                        target.exit();
                     }
-                    // Exit Main Window
-                    WindowEvent::CloseRequested => target.exit(),
-                    _ => (),
-                },
+                }
                 // Event::NewEvents(start_cause) => todo!(),
                 // Event::DeviceEvent { device_id, event } => todo!(),
                 // Event::UserEvent(_) => todo!(),
                 // Event::Suspended => todo!(),
                 // Event::Resumed => todo!(),
-                // Event::AboutToWait => todo!(),
+                // Keep registered windows up to date with their content
+                Event::AboutToWait => target.set_control_flow(dispatcher.about_to_wait()),
                 // Event::LoopExiting => todo!(),
                 // Event::MemoryWarning => todo!(),
                 _ => (),
@@ -111,20 +147,981 @@ impl EventHandler {
     ///
     /// This allows the event loop to correctly dispatch input and OS events
     /// to the appropriate window handler based on the window's ID.
-    pub(crate) fn register_window(&mut self, window: Arc<Mutex<dyn Window>>) {
-        self.windows.insert(window.lock().unwrap().id(), window.clone());
+    pub fn register_window(&mut self, window: Arc<Mutex<dyn Window>>) {
+        let (id, scale_factor) = {
+            let window = window.lock().unwrap();
+            (window.id(), window.scale_factor())
+        };
+        self.dispatcher.scale_factors.insert(id, scale_factor);
+        self.dispatcher.windows.insert(id, window);
+    }
+    /// Registers what to draw into a window's own screen.
+    ///
+    /// The event loop clears, draws and presents the `screen` every time the
+    /// window is redrawn. The window must also be registered with
+    /// `register_window` to be redrawn every `REDRAW_INTERVAL` rather than
+    /// only when the system asks for it.
+    pub fn register_renderable(
+        &mut self,
+        id: WindowId,
+        screen: Arc<Mutex<dyn Screen>>,
+        content: Box<dyn Renderable>,
+    ) {
+        self.dispatcher.render_targets.insert(
+            id,
+            RenderTarget {
+                screen,
+                content,
+                draw_list: DrawList::new(),
+            },
+        );
+    }
+    /// Presents `screen` whenever its window is redrawn after `redraw` marked
+    /// it dirty, instead of whoever draws it presenting it themselves.
+    pub fn present_on_redraw(&mut self, screen: Arc<Mutex<dyn Screen>>, redraw: RedrawRequest) {
+        self.dispatcher
+            .presenters
+            .insert(redraw.id(), Presenter { screen, redraw });
+    }
+    /// Opens `menu` over `window`'s screen whenever `MenuBack` is pressed in
+    /// it, showing the window as picked and rebinding player one's keys right
+    /// away.
+    ///
+    /// The window's screen must be presented with `present_on_redraw` for the
+    /// menu to be drawn over it
+    pub fn settings_menu(&mut self, window: GameWindow, mut menu: SettingsMenu) {
+        menu.show_bindings(&self.dispatcher.input_handler);
+        self.dispatcher.settings = Some(Settings {
+            id: window.id(),
+            window: Box::new(window),
+            menu,
+        });
     }
     /// Grants access to the underlying event loop instance.
     ///
     /// Useful when external components need to reference the event loop
     /// during the window-building phase.
-    pub(crate) fn event_loop(&self) -> &EventLoop<()> {
+    pub fn event_loop(&self) -> &EventLoop<()> {
         &self.evtloop
     }
-    /// Registers a new subscriber to receive `Coordinate`.
-    pub(crate) fn subscribe_coordinate(&mut self, subscriber: &mut dyn Subscriber<Coordinate>) {
-        let (tx, rx) = unbounded::<Coordinate>();
-        subscriber.subscribe(rx);
-        self.coordinate_subscribers.push(tx);
+    /// The bus every event handled by the event loop is published on.
+    ///
+    /// Systems subscribe to `Coordinate`, `Action`, `TextInput`,
+    /// `DroppedFile`, `MouseClick`, `WindowFocus` and `WindowLifecycle`
+    /// through it, or exchange their own event types with each other.
+    /// `TextInput` is only published while text entry is enabled.
+    /// Movement is best subscribed to with `Backpressure::Coalesce`, only
+    /// ever keeping the direction currently held. `Backpressure::Block`
+    /// stalls the whole event loop while a subscriber's channel is full
+    pub fn bus(&self) -> EventBus {
+        self.dispatcher.bus.clone()
+    }
+    /// How well a subscription made through the bus or for a player keeps
+    /// up, or `None` once it's removed.
+    pub fn health(&self, id: SubscriptionId) -> Option<Health> {
+        self.dispatcher.bus.health(id).or_else(|| {
+            let two = self.dispatcher.player_two.as_ref()?;
+            two.coordinate_subscribers
+                .health(id)
+                .or_else(|| two.action_subscribers.health(id))
+        })
+    }
+    /// Stops delivering events to a subscription made through the bus or
+    /// for a player.
+    ///
+    /// A subscriber dropping its inbox is removed on its own. Returns whether
+    /// the subscription was still registered
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        let two = self.dispatcher.player_two.as_mut();
+        self.dispatcher.bus.unsubscribe(id)
+            || two.is_some_and(|two| {
+                two.coordinate_subscribers.unsubscribe(id) || two.action_subscribers.unsubscribe(id)
+            })
+    }
+    /// Registers a new subscriber to receive the `Coordinate`s of `player`'s
+    /// bindings, like `EventBus::subscribe`.
+    ///
+    /// Player one's are the ones published on the bus. Player two's aren't,
+    /// the bus couldn't tell the players apart
+    pub fn subscribe_player_coordinate(
+        &mut self,
+        player: Player,
+        subscriber: &mut dyn Subscriber<Coordinate>,
+        capacity: usize,
+        policy: Backpressure,
+    ) -> SubscriptionId {
+        match player {
+            Player::One => self.dispatcher.bus.subscribe(subscriber, capacity, policy),
+            Player::Two => {
+                let two = self.dispatcher.player_two();
+                subscribe(
+                    &mut two.coordinate_subscribers,
+                    capacity,
+                    policy,
+                    subscriber,
+                )
+            }
+        }
+    }
+    /// Registers a new subscriber to receive the `Action`s of `player`'s
+    /// bindings, like `subscribe_player_coordinate`.
+    pub fn subscribe_player_action(
+        &mut self,
+        player: Player,
+        subscriber: &mut dyn Subscriber<Action>,
+        capacity: usize,
+        policy: Backpressure,
+    ) -> SubscriptionId {
+        match player {
+            Player::One => self.dispatcher.bus.subscribe(subscriber, capacity, policy),
+            Player::Two => {
+                let two = self.dispatcher.player_two();
+                subscribe(&mut two.action_subscribers, capacity, policy, subscriber)
+            }
+        }
+    }
+    /// Detects `gesture` in `player`'s bindings besides the default double
+    /// taps, publishing the action it emits to their action subscribers.
+    pub fn add_gesture(&mut self, player: Player, gesture: Gesture) {
+        match player {
+            Player::One => self.dispatcher.input_handler.add_gesture(gesture),
+            Player::Two => self.dispatcher.player_two().handler.add_gesture(gesture),
+        }
+    }
+    /// The switch between gameplay input and text entry, e.g. for a text field
+    /// to enable while it's focused.
+    pub fn text_mode(&self) -> TextMode {
+        self.dispatcher.text_mode.clone()
+    }
+    /// Updates `game` between handling events instead of it running on its own thread.
+    pub fn drive(&mut self, game: Box<dyn Driven>) {
+        self.dispatcher.game = Some(game);
+    }
+    /// Feeds the window's touches to `controls` on top of keyboard input.
+    pub fn touch_controls(&mut self, controls: Arc<Mutex<TouchControls>>) {
+        self.dispatcher.touch = Some(controls);
+    }
+}
+
+/// Routes the events of the event loop's windows, without the event loop
+/// itself so events can be handled without a display.
+struct Dispatcher {
+    windows: HashMap<WindowId, Arc<Mutex<dyn Window>>>,
+    render_targets: HashMap<WindowId, RenderTarget>,
+    input_handler: GameInputHandler,
+    /// Where the cursor is over every window it moved over, in screen pixels
+    cursors: HashMap<WindowId, Coordinate>,
+    /// Input of a second player sharing the keyboard, once subscribed to
+    player_two: Option<PlayerInput>,
+    text_mode: TextMode,
+    /// Whether windows currently accept IME compositions
+    ime_allowed: bool,
+    touch: Option<Arc<Mutex<TouchControls>>>,
+    /// DPI of the monitor every registered window is on
+    scale_factors: HashMap<WindowId, f64>,
+    /// Screens drawn elsewhere and presented here on redraw
+    presenters: HashMap<WindowId, Presenter>,
+    /// A game updated from the event loop instead of its own thread
+    game: Option<Box<dyn Driven>>,
+    settings: Option<Settings>,
+    next_frame: Instant,
+    /// When windows with a `Renderable` are redrawn next
+    next_redraw: Instant,
+    /// Where every event handled by the event loop is published
+    bus: EventBus,
+}
+impl Dispatcher {
+    fn new() -> Self {
+        Self {
+            windows: HashMap::default(),
+            render_targets: HashMap::default(),
+            input_handler: GameInputHandler::default(),
+            cursors: HashMap::new(),
+            player_two: None,
+            text_mode: TextMode::default(),
+            ime_allowed: false,
+            touch: None,
+            scale_factors: HashMap::new(),
+            presenters: HashMap::new(),
+            game: None,
+            settings: None,
+            next_frame: Instant::now(),
+            next_redraw: Instant::now(),
+            bus: EventBus::new(),
+        }
+    }
+    /// Handles an event of the window `window_id`.
+    ///
+    /// Returns whether the event loop should exit, once subscribers were told
+    /// the window was asked to close
+    fn handle(&mut self, window_id: WindowId, event: WindowEvent) -> bool {
+        match event {
+            // Windows drawn by the event loop present their own screen
+            WindowEvent::RedrawRequested => {
+                if let Some(presenter) = self.presenters.get(&window_id) {
+                    let _span = trace_span!("present", ?window_id).entered();
+                    let menu = self
+                        .settings
+                        .as_ref()
+                        .filter(|s| s.id == window_id && s.menu.is_open())
+                        .map(|s| &s.menu as &dyn Renderable);
+                    let _ = presenter
+                        .present(menu)
+                        .inspect_err(|e| error!(error = %e, "present failed"));
+                }
+                if let Some(target) = self.render_targets.get_mut(&window_id) {
+                    let _span = trace_span!("redraw", ?window_id).entered();
+                    let _ = target
+                        .redraw()
+                        .inspect_err(|e| error!(error = %e, "redraw failed"));
+                }
+            }
+            // Listening for keyboard inputs
+            WindowEvent::KeyboardInput { event, .. } => {
+                let input = Input::PhysicalKey(PhysicalKeyInfo {
+                    state: event.state,
+                    code: event.physical_key,
+                });
+                if self.text_mode.is_enabled() {
+                    // Only confirming and cancelling still go through as actions
+                    if let Some(a) = self
+                        .input_handler
+                        .to_action(&input)
+                        .filter(|a| matches!(a.input, GameInput::MenuConfirm | GameInput::MenuBack))
+                    {
+                        self.bus.publish(a);
+                    } else if let Some(t) =
+                        TextInput::from_key(&event.logical_key, event.text.as_deref(), event.state)
+                    {
+                        self.bus.publish(t);
+                    }
+                    // Held keys are still tracked so releases aren't missed
+                    if let Some(two) = &mut self.player_two {
+                        two.handler.track_coordinate(input.clone());
+                    }
+                    self.input_handler.track_coordinate(input);
+                } else {
+                    if let Some(two) = &mut self.player_two {
+                        two.handle(&input);
+                    }
+                    let action = self.input_handler.to_action(&input);
+                    let gestures = self.input_handler.track_gestures(&input, Instant::now());
+                    let coordinate = self.input_handler.track_coordinate(input.clone());
+                    let menu = self.settings.as_mut().filter(|s| s.id == window_id);
+                    let handler = &mut self.input_handler;
+                    if menu
+                        .is_some_and(|s| s.intercept(&input, handler, action.as_ref(), coordinate))
+                    {
+                        // Stops the player walking on behind the menu
+                        let stop = Coordinate::default();
+                        self.bus.publish(stop);
+                    } else {
+                        for a in action.into_iter().chain(gestures) {
+                            self.bus.publish(a);
+                        }
+                        if let Some(c) = coordinate {
+                            self.bus.publish(c);
+                        }
+                    }
+                }
+            }
+            WindowEvent::Ime(ime) if self.text_mode.is_enabled() => {
+                let text = match ime {
+                    Ime::Preedit(text, _) => Some(TextInput::Compose(text)),
+                    Ime::Commit(text) => Some(TextInput::Insert(text)),
+                    Ime::Enabled | Ime::Disabled => None,
+                };
+                if let Some(t) = text {
+                    self.bus.publish(t);
+                }
+            }
+            WindowEvent::Touch(touch) => {
+                if let Some(controls) = self.touch.clone() {
+                    let position = screen_position(
+                        &self.presenters,
+                        &self.render_targets,
+                        &self.scale_factors,
+                        window_id,
+                        touch.location,
+                    );
+                    let mut controls = controls.lock().unwrap();
+                    let actions = controls.handle(TouchPoint {
+                        id: touch.id,
+                        phase: touch.phase,
+                        position,
+                    });
+                    for a in actions {
+                        self.bus.publish(a);
+                    }
+                    if let Some(c) = controls.direction() {
+                        self.bus.publish(c);
+                    }
+                }
+            }
+            // Moving to a monitor of another DPI keeps the window
+            // showing its screen at the same logical size
+            WindowEvent::ScaleFactorChanged {
+                scale_factor,
+                mut inner_size_writer,
+            } => {
+                if let Some(size) = self.rescale(window_id, scale_factor) {
+                    let _ = inner_size_writer
+                        .request_inner_size(size)
+                        .inspect_err(|e| error!(error = %e, "window resize failed"));
+                }
+            }
+            WindowEvent::Resized(size) => {
+                for screen in screens(&self.presenters, &self.render_targets, window_id) {
+                    let _ = screen
+                        .lock()
+                        .unwrap()
+                        .resize_surface(size.width, size.height)
+                        .inspect_err(|e| error!(error = %e, "surface resize failed"));
+                }
+                let change = WindowLifecycle {
+                    window: window_id,
+                    change: Lifecycle::Resized(size.width, size.height),
+                };
+                self.bus.publish(change);
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                let position = screen_position(
+                    &self.presenters,
+                    &self.render_targets,
+                    &self.scale_factors,
+                    window_id,
+                    position,
+                );
+                self.cursors.insert(window_id, position);
+            }
+            WindowEvent::CursorLeft { .. } => {
+                self.cursors.remove(&window_id);
+            }
+            // Clicks land wherever the cursor last moved to
+            WindowEvent::MouseInput { state, button, .. } => {
+                if let Some(&position) = self.cursors.get(&window_id) {
+                    let click = MouseClick {
+                        button,
+                        state,
+                        position,
+                    };
+                    self.bus.publish(click);
+                }
+            }
+            // Files dragged from the system onto a window
+            WindowEvent::DroppedFile(path) => {
+                self.bus.publish(DroppedFile {
+                    window: window_id,
+                    path,
+                });
+            }
+            // Key releases are never delivered while unfocused so
+            // anything held at this point would stay stuck down
+            WindowEvent::Focused(focused) => {
+                let focus = if focused {
+                    WindowFocus::Gained
+                } else {
+                    self.input_handler.reset();
+                    if let Some(two) = &mut self.player_two {
+                        two.handler.reset();
+                    }
+                    if let Some(controls) = self.touch.clone() {
+                        for a in controls.lock().unwrap().reset() {
+                            self.bus.publish(a);
+                        }
+                    }
+                    WindowFocus::Lost
+                };
+                self.bus.publish(focus);
+                let change = WindowLifecycle {
+                    window: window_id,
+                    change: Lifecycle::Focus(focus),
+                };
+                self.bus.publish(change);
+            }
+            // Exit Main Window, letting subscribers know so they can
+            // finish once the event loop returns
+            WindowEvent::CloseRequested => {
+                let change = WindowLifecycle {
+                    window: window_id,
+                    change: Lifecycle::CloseRequested,
+                };
+                self.bus.publish(change);
+                return true;
+            }
+            _ => (),
+        }
+        false
+    }
+    /// Keeps registered windows up to date with their content before the
+    /// event loop waits for events again.
+    ///
+    /// Returns how long it waits for
+    fn about_to_wait(&mut self) -> ControlFlow {
+        let text_mode = self.text_mode.is_enabled();
+        if text_mode != self.ime_allowed {
+            self.ime_allowed = text_mode;
+            for window in self.windows.values() {
+                window.lock().unwrap().set_ime_allowed(text_mode);
+            }
+        }
+        // Sleep until events arrive unless something is due sooner
+        let mut wake = None;
+        if !self.render_targets.is_empty() {
+            let now = Instant::now();
+            if now >= self.next_redraw {
+                for id in self.render_targets.keys() {
+                    if let Some(window) = self.windows.get(id) {
+                        window.lock().unwrap().request_redraw();
+                    }
+                }
+                self.next_redraw = now + REDRAW_INTERVAL;
+            }
+            wake = Some(self.next_redraw);
+        }
+        if let Some(controls) = self.touch.clone() {
+            let controls = controls.lock().unwrap();
+            if controls.is_steering() {
+                if let Some(c) = controls.direction() {
+                    self.bus.publish(c);
+                }
+                let repeat = Instant::now() + TOUCH_REPEAT;
+                wake = Some(wake.map_or(repeat, |w: Instant| w.min(repeat)));
+            }
+        }
+        if let Some(game) = &mut self.game {
+            let now = Instant::now();
+            if now >= self.next_frame {
+                let _ = game
+                    .frame()
+                    .inspect_err(|e| error!(error = %e, "game update failed"));
+                self.next_frame = now + game.frame_time();
+            }
+            wake = Some(wake.map_or(self.next_frame, |w| w.min(self.next_frame)));
+        }
+        wake.map_or(ControlFlow::Wait, ControlFlow::WaitUntil)
+    }
+    /// Records that the window `id` moved to a monitor of another DPI.
+    ///
+    /// Returns the physical size keeping its screen at the same logical size,
+    /// unless there's no screen shown in it
+    fn rescale(&mut self, id: WindowId, scale_factor: f64) -> Option<PhysicalSize<u32>> {
+        self.scale_factors.insert(id, scale_factor);
+        self.bus.publish(WindowLifecycle {
+            window: id,
+            change: Lifecycle::ScaleChanged(scale_factor),
+        });
+        let screen = screens(&self.presenters, &self.render_targets, id).next()?;
+        let screen = screen.lock().unwrap();
+        let (width, height) = screen.layout_size();
+        Some(window_size(width, height, screen.pixel_scale()).to_physical(scale_factor))
+    }
+    fn player_two(&mut self) -> &mut PlayerInput {
+        self.player_two
+            .get_or_insert_with(|| PlayerInput::new(Player::Two))
+    }
+}
+
+/// A game the event loop updates once per frame between handling events.
+pub trait Driven {
+    /// Runs a single frame without waiting for the next one.
+    fn frame(&mut self) -> Result<(), WindowError>;
+    /// How long the event loop waits before the next frame.
+    fn frame_time(&self) -> Duration;
+}
+
+/// Opens a channel of the given capacity and backpressure for `subscriber`.
+fn subscribe<T: 'static>(
+    subscribers: &mut Topic<T>,
+    capacity: usize,
+    policy: Backpressure,
+    subscriber: &mut dyn Subscriber<T>,
+) -> SubscriptionId {
+    let (id, rx) = subscribers.add(capacity, policy);
+    subscriber.subscribe(rx);
+    id
+}
+
+/// The bindings of a local player other than the first and who listens to them.
+struct PlayerInput {
+    handler: GameInputHandler,
+    coordinate_subscribers: Topic<Coordinate>,
+    action_subscribers: Topic<Action>,
+}
+impl PlayerInput {
+    fn new(player: Player) -> Self {
+        Self {
+            handler: GameInputHandler::for_player(player),
+            coordinate_subscribers: Topic::default(),
+            action_subscribers: Topic::default(),
+        }
+    }
+    /// Turns a key event into the player's movement and actions.
+    fn handle(&mut self, input: &Input) {
+        if let Some(a) = self.handler.to_action(input) {
+            self.action_subscribers.publish(a);
+        }
+        for a in self.handler.track_gestures(input, Instant::now()) {
+            self.action_subscribers.publish(a);
+        }
+        if let Some(c) = self.handler.track_coordinate(input.clone()) {
+            self.coordinate_subscribers.publish(c);
+        }
+    }
+}
+
+/// A window's screen paired with the content drawn on it.
+struct RenderTarget {
+    screen: Arc<Mutex<dyn Screen>>,
+    content: Box<dyn Renderable>,
+    draw_list: DrawList,
+}
+impl RenderTarget {
+    /// Draws the content as a fresh frame and presents it.
+    fn redraw(&mut self) -> Result<(), WindowError> {
+        let mut batch = self.draw_list.begin();
+        batch.push(DrawCmd::Clear);
+        self.content.commands(&mut batch);
+
+        let mut screen = self
+            .screen
+            .lock()
+            .map_err(|e| WindowError::ScreenLockError(e.to_string()))?;
+        draw_all(&mut *screen, &batch)?;
+        screen.render()?;
+        self.draw_list.finish(batch);
+        Ok(())
+    }
+}
+
+/// Every screen shown in a window.
+fn screens<'a>(
+    presenters: &'a HashMap<WindowId, Presenter>,
+    render_targets: &'a HashMap<WindowId, RenderTarget>,
+    id: WindowId,
+) -> impl Iterator<Item = &'a Arc<Mutex<dyn Screen>>> {
+    let presented = presenters.get(&id).map(|p| &p.screen);
+    let rendered = render_targets.get(&id).map(|t| &t.screen);
+    presented.into_iter().chain(rendered)
+}
+
+/// Where a position in a window's physical pixels is on the screen shown in it.
+///
+/// Screens fit into their window by a `Viewport` have the fit undone, others
+/// are assumed to fill it, though they may have fewer pixels than their window is laid out
+/// for, e.g. while the camera is zoomed in
+fn screen_position(
+    presenters: &HashMap<WindowId, Presenter>,
+    render_targets: &HashMap<WindowId, RenderTarget>,
+    scale_factors: &HashMap<WindowId, f64>,
+    id: WindowId,
+    position: PhysicalPosition<f64>,
+) -> Coordinate {
+    let screen = screens(presenters, render_targets, id).next();
+    let fitted = screen.and_then(|screen| screen.lock().unwrap().to_screen(position.x, position.y));
+    if let Some(position) = fitted {
+        return position;
+    }
+    let (pixel_scale, zoom) = screen.map_or((PIXEL_SCALE, 1.0), |screen| {
+        let screen = screen.lock().unwrap();
+        let zoom = screen.layout_size().0 as f64 / screen.width() as f64;
+        (screen.pixel_scale(), zoom)
+    });
+    let scale = pixel_scale * zoom * scale_factors.get(&id).copied().unwrap_or(1.0);
+    Coordinate {
+        x: (position.x / scale) as f32,
+        y: (position.y / scale) as f32,
+    }
+}
+
+/// A screen drawn elsewhere which the event loop presents.
+struct Presenter {
+    screen: Arc<Mutex<dyn Screen>>,
+    redraw: RedrawRequest,
+}
+impl Presenter {
+    /// Presents the screen if a new frame was drawn since it last was, with
+    /// `overlay` drawn over it.
+    fn present(&self, overlay: Option<&dyn Renderable>) -> Result<(), WindowError> {
+        if !self.redraw.take() {
+            return Ok(());
+        }
+        let mut screen = self
+            .screen
+            .lock()
+            .map_err(|e| WindowError::ScreenLockError(e.to_string()))?;
+        // The next frame drawn clears it again
+        if let Some(overlay) = overlay {
+            let mut batch = Vec::new();
+            overlay.commands(&mut batch);
+            draw_all(&mut *screen, &batch)?;
+        }
+        screen.render()
+    }
+}
+
+/// A window's settings menu and the window it changes.
+struct Settings {
+    id: WindowId,
+    window: Box<dyn Configurable>,
+    menu: SettingsMenu,
+}
+impl Settings {
+    /// Feeds a key's action and movement to the menu, opening it on a
+    /// pressed `MenuBack`, or binds the key in `handler` if the menu waits
+    /// for one.
+    ///
+    /// Returns whether the menu took them instead of the game
+    fn intercept(
+        &mut self,
+        key: &Input,
+        handler: &mut GameInputHandler,
+        action: Option<&Action>,
+        coordinate: Option<Coordinate>,
+    ) -> bool {
+        let Input::PhysicalKey(key) = key;
+        if key.state == ElementState::Pressed {
+            if let Some((input, code)) = self.menu.bind(key.code) {
+                handler.update_binding(&input, code);
+                return true;
+            }
+        }
+        let input = action.and_then(UiInput::from_action);
+        if !self.menu.is_open() {
+            if input == Some(UiInput::Back) {
+                self.menu.open();
+                return true;
+            }
+            return false;
+        }
+        for input in input.into_iter().chain(coordinate.map(UiInput::from)) {
+            if let Some(settings) = self.menu.handle(input) {
+                let _ = self
+                    .window
+                    .apply(&settings)
+                    .inspect_err(|e| error!(error = %e, "applying window settings failed"));
+            }
+        }
+        true
+    }
+}
+
+/// A file dragged from the system and dropped onto a window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DroppedFile {
+    pub window: WindowId,
+    pub path: PathBuf,
+}
+
+/// A mouse button pressed or released over a window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MouseClick {
+    pub button: MouseButton,
+    pub state: ElementState,
+    /// The pixel of the window's screen under the cursor.
+    pub position: Coordinate,
+}
+
+/// Focus state changes of a registered window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WindowFocus {
+    Gained,
+    Lost,
+}
+
+/// Something that happened to a registered window itself.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowLifecycle {
+    pub window: WindowId,
+    pub change: Lifecycle,
+}
+
+/// How a window changed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Lifecycle {
+    /// The window's surface is now this wide and high in physical pixels.
+    Resized(u32, u32),
+    Focus(WindowFocus),
+    /// The window moved to a monitor of this DPI.
+    ScaleChanged(f64),
+    /// The window was asked to close, right before the event loop exits and
+    /// `EventHandler::start` returns.
+    CloseRequested,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        font::BitmapFont,
+        mock::MockWindow,
+        palette::GREEN,
+        window::{WindowMode, WindowSettings},
+    };
+    use winit::{
+        event::ElementState,
+        keyboard::{KeyCode, PhysicalKey},
+    };
+
+    struct Line;
+    impl Renderable for Line {
+        fn commands<'a>(&'a self, batch: &mut Vec<DrawCmd<'a>>) {
+            batch.push(DrawCmd::Span {
+                start: (0, 1),
+                len: 2,
+                color: GREEN,
+            });
+        }
+    }
+
+    struct Listener<T>(Option<Inbox<T>>);
+    impl<T> Subscriber<T> for Listener<T> {
+        fn subscribe(&mut self, rx: Inbox<T>) {
+            self.0 = Some(rx);
+        }
+    }
+    impl<T> Listener<T> {
+        fn drain(&self) -> Vec<T> {
+            self.0.as_ref().unwrap().try_iter().collect()
+        }
+    }
+
+    /// A dispatcher with `T` subscribed to on its bus.
+    fn listening<T: Send + 'static>() -> (Dispatcher, Listener<T>) {
+        let dispatcher = Dispatcher::new();
+        let mut listener = Listener(None);
+        dispatcher
+            .bus
+            .subscribe(&mut listener, 8, Backpressure::Block);
+        (dispatcher, listener)
+    }
+
+    #[test]
+    fn test_dropped_files_reach_subscribers() {
+        let (mut dispatcher, drops) = listening::<DroppedFile>();
+        let window = unsafe { WindowId::dummy() };
+        let path = PathBuf::from("level.ron");
+
+        let exit = dispatcher.handle(window, WindowEvent::DroppedFile(path.clone()));
+
+        assert!(!exit);
+        assert_eq!(drops.drain(), vec![DroppedFile { window, path }]);
+    }
+
+    #[test]
+    fn test_close_is_published_before_exiting() {
+        let (mut dispatcher, changes) = listening::<WindowLifecycle>();
+        let window = unsafe { WindowId::dummy() };
+
+        assert!(dispatcher.handle(window, WindowEvent::CloseRequested));
+        assert_eq!(
+            changes.drain(),
+            vec![WindowLifecycle {
+                window,
+                change: Lifecycle::CloseRequested,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_losing_focus_releases_held_keys() {
+        let (mut dispatcher, focus) = listening::<WindowFocus>();
+        let window = unsafe { WindowId::dummy() };
+        let up = Input::PhysicalKey(PhysicalKeyInfo {
+            state: ElementState::Pressed,
+            code: PhysicalKey::Code(KeyCode::ArrowUp),
+        });
+        dispatcher.input_handler.track_coordinate(up);
+        assert!(dispatcher.input_handler.is_held(&GameInput::PlayerMoveUp));
+
+        assert!(!dispatcher.handle(window, WindowEvent::Focused(false)));
+        assert!(!dispatcher.input_handler.is_held(&GameInput::PlayerMoveUp));
+        assert_eq!(focus.drain(), vec![WindowFocus::Lost]);
+    }
+
+    /// Shows a `width` by `height` screen in `window`, redrawn by `dispatcher`.
+    fn showing(
+        dispatcher: &mut Dispatcher,
+        window: WindowId,
+        width: u32,
+        height: u32,
+    ) -> Arc<Mutex<HeadlessScreen>> {
+        let screen = Arc::new(Mutex::new(HeadlessScreen::new(width, height)));
+        dispatcher.render_targets.insert(
+            window,
+            RenderTarget {
+                screen: screen.clone(),
+                content: Box::new(Line),
+                draw_list: DrawList::new(),
+            },
+        );
+        screen
+    }
+
+    #[test]
+    fn test_clicks_land_on_the_fitted_screen() {
+        let (mut dispatcher, clicks) = listening::<MouseClick>();
+        let window = unsafe { WindowId::dummy() };
+        let device_id = unsafe { winit::event::DeviceId::dummy() };
+        let screen = showing(&mut dispatcher, window, 20, 10);
+
+        // Fit four times over with bars on both sides
+        dispatcher.handle(window, WindowEvent::Resized(PhysicalSize::new(100, 40)));
+        assert_eq!(screen.lock().unwrap().surface(), Some((100, 40)));
+        let position = PhysicalPosition::new(50.0, 20.0);
+        dispatcher.handle(
+            window,
+            WindowEvent::CursorMoved {
+                device_id,
+                position,
+            },
+        );
+        dispatcher.handle(
+            window,
+            WindowEvent::MouseInput {
+                device_id,
+                state: ElementState::Pressed,
+                button: MouseButton::Left,
+            },
+        );
+
+        assert_eq!(
+            clicks.drain(),
+            vec![MouseClick {
+                button: MouseButton::Left,
+                state: ElementState::Pressed,
+                position: Coordinate { x: 10.0, y: 5.0 },
+            }]
+        );
+    }
+
+    #[test]
+    fn test_rescaling_keeps_the_logical_size() {
+        let (mut dispatcher, changes) = listening::<WindowLifecycle>();
+        let window = unsafe { WindowId::dummy() };
+        assert_eq!(dispatcher.rescale(window, 2.0), None);
+
+        showing(&mut dispatcher, window, 20, 10);
+        let size = dispatcher.rescale(window, 2.0);
+
+        assert_eq!(size, Some(PhysicalSize::new(160, 80)));
+        assert_eq!(dispatcher.scale_factors.get(&window), Some(&2.0));
+        assert_eq!(changes.drain().len(), 2);
+    }
+
+    /// Presses and releases `code` on `settings` the way the dispatcher
+    /// does, returning whether the menu took the press.
+    fn tap(settings: &mut Settings, handler: &mut GameInputHandler, code: KeyCode) -> bool {
+        let mut feed = |state| {
+            let input = Input::PhysicalKey(PhysicalKeyInfo {
+                state,
+                code: PhysicalKey::Code(code),
+            });
+            let action = handler.to_action(&input);
+            let coordinate = handler.track_coordinate(input.clone());
+            settings.intercept(&input, handler, action.as_ref(), coordinate)
+        };
+        let taken = feed(ElementState::Pressed);
+        feed(ElementState::Released);
+        taken
+    }
+
+    #[test]
+    fn test_settings_menu_applies_picks_to_its_window() {
+        let window = MockWindow::default();
+        let applied = window.applied.clone();
+        let font = Arc::new(BitmapFont::builtin());
+        let mut settings = Settings {
+            id: window.id(),
+            menu: SettingsMenu::new(font, (8, 8), 1, WindowSettings::default()),
+            window: Box::new(window),
+        };
+        let mut handler = GameInputHandler::default();
+
+        assert!(!tap(&mut settings, &mut handler, KeyCode::Enter));
+        assert!(tap(&mut settings, &mut handler, KeyCode::Escape));
+        assert!(settings.menu.is_open());
+        // A bigger scale, then down to the mode
+        tap(&mut settings, &mut handler, KeyCode::Enter);
+        tap(&mut settings, &mut handler, KeyCode::ArrowDown);
+        tap(&mut settings, &mut handler, KeyCode::Enter);
+
+        let applied = applied.lock().unwrap();
+        assert_eq!(applied.len(), 2);
+        assert_eq!(applied[0].scale, PIXEL_SCALE as u32 + 1);
+        assert_eq!(applied[0].mode, WindowMode::Windowed);
+        assert_eq!(applied[1].scale, PIXEL_SCALE as u32 + 1);
+        assert_eq!(applied[1].mode, WindowMode::Borderless);
+    }
+
+    #[test]
+    fn test_settings_menu_rebinds_keys() {
+        let window = MockWindow::default();
+        let font = Arc::new(BitmapFont::builtin());
+        let mut settings = Settings {
+            id: window.id(),
+            menu: SettingsMenu::new(font, (8, 8), 1, WindowSettings::default()),
+            window: Box::new(window),
+        };
+        let mut handler = GameInputHandler::default();
+
+        tap(&mut settings, &mut handler, KeyCode::Escape);
+        // Up past the back button onto the last binding, moving up
+        tap(&mut settings, &mut handler, KeyCode::ArrowUp);
+        for _ in 0..8 {
+            tap(&mut settings, &mut handler, KeyCode::ArrowUp);
+        }
+        tap(&mut settings, &mut handler, KeyCode::Enter);
+        assert!(settings.menu.is_rebinding());
+        assert!(tap(&mut settings, &mut handler, KeyCode::KeyW));
+
+        assert!(!settings.menu.is_rebinding());
+        assert_eq!(
+            *handler.get_binding(&GameInput::PlayerMoveUp),
+            PhysicalKey::Code(KeyCode::KeyW)
+        );
+    }
+
+    #[test]
+    fn test_player_two_has_own_subscribers() {
+        let mut two = PlayerInput::new(Player::Two);
+        let (_, rx) = two.coordinate_subscribers.add(4, Backpressure::Block);
+        let (_, actions_rx) = two.action_subscribers.add(4, Backpressure::Block);
+        let press = |code| {
+            Input::PhysicalKey(PhysicalKeyInfo {
+                state: ElementState::Pressed,
+                code: PhysicalKey::Code(code),
+            })
+        };
+
+        two.handle(&press(KeyCode::ArrowUp));
+        assert!(rx.try_recv().is_err());
+        two.handle(&press(KeyCode::KeyW));
+        assert_eq!(rx.try_recv(), Ok(Coordinate { x: 0.0, y: -1.0 }));
+        two.handle(&press(KeyCode::KeyE));
+        assert_eq!(
+            actions_rx.try_recv().map(|a| a.input),
+            Ok(GameInput::PlayerDash)
+        );
+    }
+
+    #[test]
+    fn test_render_targets_are_independent() {
+        let line = Arc::new(Mutex::new(HeadlessScreen::new(3, 3)));
+        let other = Arc::new(Mutex::new(HeadlessScreen::new(3, 3)));
+        let mut target = RenderTarget {
+            screen: line.clone(),
+            content: Box::new(Line),
+            draw_list: DrawList::new(),
+        };
+
+        target.redraw().unwrap();
+        target.redraw().unwrap();
+
+        let line = line.lock().unwrap();
+        assert_eq!(&line.buffer()[16..20], &GREEN.to_rgba());
+        assert_eq!(line.pixel(2, 1), Some(Color::RGBA(0, 0, 0, 255)));
+        assert_eq!(line.renders(), 2);
+        assert_eq!(other.lock().unwrap().renders(), 0);
     }
 }
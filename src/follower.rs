@@ -0,0 +1,172 @@
+//! Party members trailing the player along the path they walked.
+//!
+//! A `Follower` records where its leader has been as a trail of breadcrumbs
+//! and walks that same trail a fixed distance behind, the way companions in
+//! classic RPGs follow the player around corners instead of cutting across
+//! them. Breadcrumbs are only dropped once the leader moved far enough from the
+//! previous one, which smooths out jitter in the leader's movement. Like a
+//! `PathFollower` it produces the same directional `Coordinate`s player input
+//! does, so followers reuse the existing animation selection.
+//!
+//! # Key Structures
+//! - **`Follower`**: A leader's trail and how far behind it to walk.
+//!
+//! # Example Usage
+//! ```ignore
+//! let mut follower = Follower::new(16.0);
+//!
+//! // Every frame
+//! follower.record(player_pos);
+//! let direction = follower.steer(companion_pos);
+//! if let Some(direction) = direction {
+//!     companion_pos += direction * speed * delta;
+//! }
+//! let (animation, mirror) = companion_controller.animation(&mut companion, direction);
+//! ```
+use std::collections::VecDeque;
+
+use crate::layout::Coordinate;
+
+/// Walks the trail of a leader a fixed distance behind it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Follower {
+    /// Where the leader has been, oldest first
+    trail: VecDeque<Coordinate>,
+    leader: Option<Coordinate>,
+    /// How far behind the leader along its trail to stay in pixels.
+    pub spacing: f32,
+    /// How far the leader must move before another breadcrumb is dropped.
+    pub step: f32,
+    /// The most breadcrumbs kept, dropping the oldest beyond it so a follower
+    /// left far behind heads for the trail instead of retracing all of it.
+    pub max_trail: usize,
+}
+impl Follower {
+    pub fn new(spacing: f32) -> Self {
+        Self {
+            trail: VecDeque::new(),
+            leader: None,
+            spacing,
+            step: 2.0,
+            max_trail: 256,
+        }
+    }
+    pub fn step(mut self, step: f32) -> Self {
+        self.step = step;
+        self
+    }
+    pub fn max_trail(mut self, max_trail: usize) -> Self {
+        self.max_trail = max_trail;
+        self
+    }
+    /// Notes where the leader is now.
+    pub fn record(&mut self, leader: Coordinate) {
+        self.leader = Some(leader);
+        let far_enough = self
+            .trail
+            .back()
+            .is_none_or(|&last| (leader - last).length() >= self.step);
+        if far_enough {
+            self.trail.push_back(leader);
+            if self.trail.len() > self.max_trail {
+                self.trail.pop_front();
+            }
+        }
+    }
+    /// Forgets the trail, e.g. after the leader was teleported.
+    pub fn clear(&mut self) {
+        self.trail.clear();
+        self.leader = None;
+    }
+    /// The direction to move from `position` along the leader's trail.
+    ///
+    /// Each axis is `-1.0`, `0.0` or `1.0` like movement input. Returns `None`
+    /// while the follower is no further than `spacing` behind the leader
+    pub fn steer(&mut self, position: Coordinate) -> Option<Coordinate> {
+        // Breadcrumbs within reach were walked past already
+        while self
+            .trail
+            .front()
+            .is_some_and(|&crumb| (crumb - position).length() <= self.step / 2.0)
+        {
+            self.trail.pop_front();
+        }
+        if self.behind(position) <= self.spacing {
+            return None;
+        }
+        let offset = *self.trail.front().or(self.leader.as_ref())? - position;
+        let axis = |d: f32| {
+            if d.abs() > self.step / 2.0 {
+                d.signum()
+            } else {
+                0.0
+            }
+        };
+        Some(Coordinate {
+            x: axis(offset.x),
+            y: axis(offset.y),
+        })
+    }
+    /// How far `position` is from the leader walking along the trail.
+    fn behind(&self, position: Coordinate) -> f32 {
+        let mut distance = 0.0;
+        let mut from = position;
+        for &crumb in self.trail.iter().chain(self.leader.as_ref()) {
+            distance += (crumb - from).length();
+            from = crumb;
+        }
+        distance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(x: f32, y: f32) -> Coordinate {
+        Coordinate { x, y }
+    }
+
+    #[test]
+    fn test_follows_around_corners() {
+        let mut follower = Follower::new(4.0).step(1.0);
+        // The leader walks right then down
+        for x in 0..=10 {
+            follower.record(at(x as f32, 0.0));
+        }
+        for y in 1..=10 {
+            follower.record(at(10.0, y as f32));
+        }
+
+        let mut position = at(0.0, 0.0);
+        let mut visited = vec![position];
+        while let Some(direction) = follower.steer(position) {
+            position += direction;
+            visited.push(position);
+        }
+
+        // Took the corner instead of cutting across it
+        assert!(visited.contains(&at(10.0, 0.0)));
+        assert!(visited.iter().all(|p| p.y == 0.0 || p.x == 10.0));
+        assert_eq!(position, at(10.0, 6.0));
+    }
+
+    #[test]
+    fn test_waits_while_close() {
+        let mut follower = Follower::new(8.0);
+        assert_eq!(follower.steer(at(0.0, 0.0)), None);
+
+        follower.record(at(0.0, 0.0));
+        follower.record(at(0.5, 0.0));
+        follower.record(at(6.0, 0.0));
+        // Small moves don't drop breadcrumbs
+        assert_eq!(follower.trail.len(), 2);
+        assert_eq!(follower.steer(at(0.0, 0.0)), None);
+
+        follower.record(at(12.0, 0.0));
+        assert_eq!(follower.steer(at(0.0, 0.0)), Some(at(1.0, 0.0)));
+
+        follower.clear();
+        assert_eq!(follower.steer(at(0.0, 0.0)), None);
+    }
+}
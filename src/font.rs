@@ -0,0 +1,360 @@
+//! Bitmap fonts loaded from BDF files and drawn as text.
+//!
+//! Fonts are parsed from the Glyph Bitmap Distribution Format (BDF), a plain
+//! text format most pixel fonts are distributed in. Each glyph row is drawn as
+//! `DrawCmd::Span` runs so text goes through the same batches as everything else.
+//!
+//! # Key Structures
+//! - **`BitmapFont`**: The glyphs and metrics of a font, able to measure and draw text.
+//! - **`Glyph`**: The bitmap and placement of a single character.
+//!
+//! Errors:
+//! - `FontError`: Reading or parsing a font file failed.
+//!
+//! A 5x7 font covering printable ASCII is built in for when no other font is
+//! at hand, e.g. for the settings menu.
+//!
+//! # Example Usage
+//! ```ignore
+//! let font = BitmapFont::load("assets/fonts/tiny.bdf")?;
+//!
+//! // Drawn twice the font's native size
+//! let mut batch = Vec::new();
+//! font.commands("Press Start", (10, 10), LIGHT_GRAY, 2, &mut batch);
+//! screen.draw_batch(&batch)?;
+//! ```
+use std::{collections::HashMap, fs, path::Path};
+
+use thiserror::Error;
+
+use crate::{palette::Color, renderer::DrawCmd};
+
+/// The font built into the game, also found at `assets/font.bdf`.
+const BUILTIN: &str = include_str!("../assets/font.bdf");
+
+/// The bitmap and placement of a single character.
+#[derive(Debug, Clone, PartialEq)]
+struct Glyph {
+    width: u32,
+    height: u32,
+    /// Offset of the bitmap's left edge from the pen position.
+    x_offset: i32,
+    /// Offset of the bitmap's bottom edge above the baseline.
+    y_offset: i32,
+    /// How far the pen moves after drawing the glyph.
+    advance: i32,
+    /// Each row left padded to whole bytes with the leftmost pixel in the high bit.
+    rows: Vec<Vec<u8>>,
+}
+impl Glyph {
+    fn is_set(&self, x: u32, y: u32) -> bool {
+        let byte = self.rows[y as usize][(x / 8) as usize];
+        byte & (0x80 >> (x % 8)) != 0
+    }
+}
+
+/// A font made of fixed bitmaps for every character it supports.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BitmapFont {
+    glyphs: HashMap<char, Glyph>,
+    ascent: i32,
+    descent: i32,
+    /// Drawn in place of characters missing from the font.
+    fallback: Option<char>,
+}
+impl BitmapFont {
+    /// Reads and parses a BDF font file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, FontError> {
+        Self::from_bdf(&fs::read_to_string(path)?)
+    }
+    /// The 5x7 font covering printable ASCII which every build has.
+    pub fn builtin() -> Self {
+        Self::from_bdf(BUILTIN).expect("the built in font is valid BDF")
+    }
+    /// Parses the contents of a BDF font.
+    pub fn from_bdf(source: &str) -> Result<Self, FontError> {
+        let mut glyphs = HashMap::new();
+        let mut ascent = None;
+        let mut descent = None;
+        let mut default_char = None;
+
+        let mut lines = source.lines().enumerate();
+        while let Some((number, line)) = lines.next() {
+            let mut fields = line.split_whitespace();
+            match fields.next() {
+                Some("FONT_ASCENT") => ascent = Some(parse(fields.next(), number)?),
+                Some("FONT_DESCENT") => descent = Some(parse(fields.next(), number)?),
+                Some("DEFAULT_CHAR") => default_char = Some(parse::<u32>(fields.next(), number)?),
+                Some("STARTCHAR") => {
+                    if let Some((c, glyph)) = parse_glyph(&mut lines)? {
+                        glyphs.insert(c, glyph);
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        Ok(Self {
+            glyphs,
+            ascent: ascent.ok_or(FontError::MissingProperty("FONT_ASCENT"))?,
+            descent: descent.ok_or(FontError::MissingProperty("FONT_DESCENT"))?,
+            fallback: default_char.map_or(Some('?'), char::from_u32),
+        })
+    }
+    /// The distance in pixels between two lines of text at `scale`.
+    pub fn line_height(&self, scale: u32) -> u32 {
+        (self.ascent + self.descent).max(0) as u32 * scale
+    }
+    /// The width and height in pixels `text` takes up when drawn at `scale`.
+    pub fn measure(&self, text: &str, scale: u32) -> (u32, u32) {
+        let width = text
+            .lines()
+            .map(|line| {
+                line.chars()
+                    .filter_map(|c| self.glyph(c))
+                    .map(|g| g.advance)
+                    .sum::<i32>()
+                    .max(0) as u32
+                    * scale
+            })
+            .max()
+            .unwrap_or_default();
+        let lines = text.lines().count().max(1) as u32;
+        (width, lines * self.line_height(scale))
+    }
+    /// Queues `text` with its top left corner at `position` onto a batch of draw commands.
+    ///
+    /// Every font pixel is drawn as a `scale` by `scale` block and each `\n`
+    /// starts a new line. Characters the font has no glyph or fallback for are skipped.
+    pub fn commands<'a>(
+        &self,
+        text: &str,
+        position: (i32, i32),
+        color: Color,
+        scale: u32,
+        batch: &mut Vec<DrawCmd<'a>>,
+    ) {
+        let scale_i = scale as i32;
+        let (mut pen_x, mut baseline) = (position.0, position.1 + self.ascent * scale_i);
+        for c in text.chars() {
+            if c == '\n' {
+                pen_x = position.0;
+                baseline += self.line_height(scale) as i32;
+                continue;
+            }
+            let Some(glyph) = self.glyph(c) else {
+                continue;
+            };
+            let left = pen_x + glyph.x_offset * scale_i;
+            let top = baseline - (glyph.y_offset + glyph.height as i32) * scale_i;
+            for y in 0..glyph.height {
+                // Neighbouring set pixels are merged into a single span
+                let mut x = 0;
+                while x < glyph.width {
+                    if !glyph.is_set(x, y) {
+                        x += 1;
+                        continue;
+                    }
+                    let start = x;
+                    while x < glyph.width && glyph.is_set(x, y) {
+                        x += 1;
+                    }
+                    for row in 0..scale_i {
+                        batch.push(DrawCmd::Span {
+                            start: (
+                                left + start as i32 * scale_i,
+                                top + y as i32 * scale_i + row,
+                            ),
+                            len: (x - start) * scale,
+                            color,
+                        });
+                    }
+                }
+            }
+            pen_x += glyph.advance * scale_i;
+        }
+    }
+    fn glyph(&self, c: char) -> Option<&Glyph> {
+        self.glyphs
+            .get(&c)
+            .or_else(|| self.fallback.and_then(|f| self.glyphs.get(&f)))
+    }
+}
+
+/// Parses the lines following `STARTCHAR` up to and including `ENDCHAR`.
+///
+/// Glyphs without a Unicode encoding are consumed but not returned
+fn parse_glyph<'a>(
+    lines: &mut impl Iterator<Item = (usize, &'a str)>,
+) -> Result<Option<(char, Glyph)>, FontError> {
+    let mut encoding = None;
+    let mut advance = None;
+    let mut bbx = None;
+    let mut rows = Vec::new();
+    let mut in_bitmap = false;
+
+    for (number, line) in lines.by_ref() {
+        let mut fields = line.split_whitespace();
+        let Some(keyword) = fields.next() else {
+            continue;
+        };
+        match keyword {
+            "ENDCHAR" => {
+                let (width, height, x_offset, y_offset) =
+                    bbx.ok_or(FontError::MissingProperty("BBX"))?;
+                if rows.len() != height as usize {
+                    return Err(FontError::Syntax(number + 1));
+                }
+                let glyph = Glyph {
+                    width,
+                    height,
+                    x_offset,
+                    y_offset,
+                    advance: advance.unwrap_or(width as i32),
+                    rows,
+                };
+                return Ok(encoding.and_then(char::from_u32).map(|c| (c, glyph)));
+            }
+            _ if in_bitmap => {
+                let bytes = (0..keyword.len())
+                    .step_by(2)
+                    .map(|i| {
+                        keyword
+                            .get(i..i + 2)
+                            .and_then(|b| u8::from_str_radix(b, 16).ok())
+                    })
+                    .collect::<Option<Vec<u8>>>()
+                    .ok_or(FontError::Syntax(number + 1))?;
+                if let Some((width, ..)) = bbx {
+                    if bytes.len() < width.div_ceil(8) as usize {
+                        return Err(FontError::Syntax(number + 1));
+                    }
+                }
+                rows.push(bytes);
+            }
+            // Negative encodings mark glyphs outside of any standard encoding
+            "ENCODING" => encoding = u32::try_from(parse::<i64>(fields.next(), number)?).ok(),
+            "DWIDTH" => advance = Some(parse(fields.next(), number)?),
+            "BBX" => {
+                bbx = Some((
+                    parse(fields.next(), number)?,
+                    parse(fields.next(), number)?,
+                    parse(fields.next(), number)?,
+                    parse(fields.next(), number)?,
+                ))
+            }
+            "BITMAP" => in_bitmap = true,
+            _ => (),
+        }
+    }
+    Err(FontError::UnexpectedEnd)
+}
+
+fn parse<T: std::str::FromStr>(field: Option<&str>, line: usize) -> Result<T, FontError> {
+    field
+        .and_then(|f| f.parse().ok())
+        .ok_or(FontError::Syntax(line + 1))
+}
+
+#[derive(Debug, Error)]
+pub enum FontError {
+    #[error("failed to read font: {0}")]
+    ReadError(#[from] std::io::Error),
+    #[error("malformed font on line {0}")]
+    Syntax(usize),
+    #[error("font is missing {0}")]
+    MissingProperty(&'static str),
+    #[error("font ended inside a glyph")]
+    UnexpectedEnd,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{palette::GREEN, prelude::*};
+
+    // A 3x3 "T" and a single pixel "." sitting on the baseline
+    const FONT: &str = "\
+STARTFONT 2.1
+FONT -test-tiny
+FONTBOUNDINGBOX 3 4 0 -1
+STARTPROPERTIES 2
+FONT_ASCENT 3
+FONT_DESCENT 1
+ENDPROPERTIES
+CHARS 2
+STARTCHAR T
+ENCODING 84
+DWIDTH 4 0
+BBX 3 3 0 0
+BITMAP
+E0
+40
+40
+ENDCHAR
+STARTCHAR period
+ENCODING 46
+DWIDTH 2 0
+BBX 1 1 0 0
+BITMAP
+80
+ENDCHAR
+ENDFONT
+";
+
+    #[test]
+    fn test_parse_bdf() {
+        let font = BitmapFont::from_bdf(FONT).unwrap();
+
+        assert_eq!(font.line_height(1), 4);
+        assert_eq!(font.glyphs.len(), 2);
+        assert_eq!(
+            font.glyphs[&'T'].rows,
+            vec![vec![0xE0], vec![0x40], vec![0x40]]
+        );
+        assert_eq!(font.measure("T.T", 1), (10, 4));
+        assert_eq!(font.measure("T\nT.", 2), (12, 16));
+    }
+
+    #[test]
+    fn test_malformed_bdf() {
+        let truncated = FONT.replace("40\nENDCHAR", "ENDCHAR");
+        assert!(matches!(
+            BitmapFont::from_bdf(&truncated),
+            Err(FontError::Syntax(_))
+        ));
+        assert!(matches!(
+            BitmapFont::from_bdf(&FONT.replace("FONT_ASCENT 3\n", "")),
+            Err(FontError::MissingProperty("FONT_ASCENT"))
+        ));
+    }
+
+    #[test]
+    fn test_builtin_covers_printable_ascii() {
+        let font = BitmapFont::builtin();
+
+        assert!((' '..='~').all(|c| font.glyphs.contains_key(&c)));
+        assert_eq!(font.line_height(1), 8);
+        assert_eq!(font.measure("Scale 4x", 2), (96, 16));
+    }
+
+    #[test]
+    fn test_draw_text() {
+        let font = BitmapFont::from_bdf(FONT).unwrap();
+        let mut screen = HeadlessScreen::new(16, 8);
+        screen.clear().unwrap();
+
+        let mut batch = Vec::new();
+        font.commands("T.", (1, 0), GREEN, 2, &mut batch);
+        screen.draw_batch(&batch).unwrap();
+
+        let lit = |x, y| screen.pixel(x, y) == Some(Color::RGBA(60, 200, 60, 255));
+        // Top bar of the T is 6 pixels wide at double size
+        assert!((1..7).all(|x| lit(x, 0) && lit(x, 1)));
+        assert!(!lit(0, 0) && !lit(7, 0));
+        // Stem is centered
+        assert!(lit(3, 4) && lit(4, 5) && !lit(2, 4));
+        // Period starts after the T's advance and sits on the baseline
+        assert!(lit(9, 4) && lit(10, 5) && !lit(9, 3) && !lit(11, 4));
+    }
+}
@@ -1,9 +1,25 @@
 //! Core game loop state responsible for rendering, updating, and input-driven movement logic.
 //!
 //! `GameState` orchestrates the runtime loop of a 2D character-based game:
-//! - Processes input via a non-blocking channel
+//! - Processes input via a non-blocking channel, moving by only the latest
+//!   direction received each frame
 //! - Advances animations and draws characters to the screen
 //! - Maintains a consistent framerate and applies delta time for smooth motion
+//! - Optionally pauses itself while the window is unfocused
+//! - Finishes its last frame once a window is asked to close
+//! - Plays cutscenes in place of player control
+//! - Lets a second local player walk the same world, kept from walking
+//!   through the first
+//! - Shows the player of another game played with over the network
+//! - Snapshots and restores the simulation state of a tick for rollback
+//! - Teleports the player and switches `Cheats` on the debug console's command
+//! - Outlines bounds, hitboxes, hurtboxes, triggers and the camera's dead zone
+//!   on a `DebugOverlay`
+//! - Inspects the character clicked on through an `Inspector`
+//! - Freezes for a few ticks when an attack connects, through a `HitStop`
+//! - Shows the world through a `Camera` cutscenes can direct
+//! - Lets rain or snow fall over the world
+//! - Draws on-screen touch controls over everything else
 //!
 //! The system is designed to be modular by:
 //! - Accepting any `Character` and `Screen` implementations
@@ -11,165 +27,1309 @@
 //!
 //! # Key Responsibilities
 //! - Drive frame updates (logic and rendering)
-//! - Apply time-based player movement
-//! - Ensure consistent frame pacing with sleep-based throttling
+//! - Apply time-based player movement through a physics `Body`
+//! - Ensure consistent frame pacing by sleeping most of a frame's leftover
+//!   time and spinning out the rest, or leave pacing to the event loop when it
+//!   drives the game instead of a thread
+//! - Time its input, update, animation and render stages with the `profiler`
+//! - Expose the frame rate cap at runtime through a shared `FrameRate`, with an
+//!   uncapped mode reporting the achievable frame rate, statistics of recent
+//!   frame times and optional smoothing of jittery frame deltas
+//! - Slow down, freeze or fast forward the simulation through a shared
+//!   `TimeScale`
 //!
 //! # Example
 //!
-//! ```
+//! ```ignore
 //! let mut state = GameState::new(...);
-//! state.start(); // begins the main game loop
+//! let game = state.start(); // begins the main game loop
+//! event_handler.start()?;
+//! game.join().unwrap(); // lets the last frame finish before exiting
+//! // or lets the event loop update the game on its own thread
+//! state.start_inline(&mut event_handler);
 //! ```
 use crossbeam::channel::Receiver;
-use log::error;
 use std::{
-    sync::{Arc, Mutex},
-    thread::{self, sleep},
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU32, AtomicU64, Ordering},
+        Arc, Mutex, MutexGuard, PoisonError,
+    },
+    thread::{self, sleep, JoinHandle},
     time::{Duration, Instant},
 };
 use thiserror::Error;
+use tracing::{error, info_span, trace_span, warn};
+
+use winit::event::{ElementState, MouseButton};
 
-use crate::prelude::*;
+use crate::{
+    animator::Animation,
+    camera::Camera,
+    cheats::{Cheat, Cheats},
+    console::DebugCommand,
+    controller::{CharacterController, MovementMode},
+    cutscene::Cutscene,
+    event::{Driven, EventHandler, Lifecycle, MouseClick, WindowFocus, WindowLifecycle},
+    hitbox::Rect,
+    hitstop::{Hit, HitStop},
+    hud::StaminaBar,
+    input::{Action, GameInput},
+    inspector::{Components, Field, Inspected, Inspector},
+    movement::{Dash, InputBuffer, Sprint, Stamina},
+    net::{Peer, PlayerState},
+    overlay::{Category, DebugOverlay},
+    physics::Body,
+    prelude::*,
+    profile_scope, profiler,
+    renderer::{DrawCmd, DrawList, Frame},
+    snapshot::{CoPlayerTick, Movement, TickState},
+    sprite::validate,
+    touch::TouchControls,
+    weather::Weather,
+    window::RedrawRequest,
+};
 
-pub(crate) struct GameState<S: Screen, C: Character<S>> {
-    input_handler: Option<Receiver<Coordinate>>,
+pub struct GameState<S: Screen, C: Character<S>> {
+    input_handler: Option<Inbox<Coordinate>>,
+    action_handler: Option<Inbox<Action>>,
+    focus_handler: Option<Inbox<WindowFocus>>,
+    lifecycle_handler: Option<Inbox<WindowLifecycle>>,
+    debug_handler: Option<Inbox<DebugCommand>>,
+    mouse_handler: Option<Inbox<MouseClick>>,
+    hit_handler: Option<Inbox<Hit>>,
+    hit_stop: HitStop,
+    cheats: Cheats,
+    overlay: DebugOverlay,
+    inspection: Option<Inspection>,
+    auto_pause: bool,
+    paused: bool,
+    /// Whether a window was asked to close, ending the game loop
+    closing: bool,
     delta: f32,
+    /// Frames simulated so far
+    tick: u64,
     player: C,
+    controller: CharacterController,
+    /// The frame and timer of the player's animation as of the last frame
+    animation: (usize, f32),
     player_pos: Coordinate,
     player_speed: f32,
+    body: Body,
+    co_player: Option<CoPlayer<C>>,
+    online: Option<Online<C>>,
+    /// Last non-idle movement direction
+    heading: Coordinate,
+    sprinting: bool,
+    sprint: Sprint,
+    dash: Dash,
+    /// Dash presses made shortly before the cooldown ends
+    dash_buffer: InputBuffer,
+    stamina: Stamina,
+    stamina_bar: StaminaBar,
+    cutscene: Option<Cutscene>,
+    camera: Camera,
+    /// The camera zoom the screen's resolution was last set for
+    zoom: f32,
+    weather: Option<Weather>,
+    touch_overlay: Option<Arc<Mutex<TouchControls>>>,
     screen: Arc<Mutex<S>>,
-    fps: Duration,
+    /// Leaves presenting frames to the event loop when set
+    redraw: Option<RedrawRequest>,
+    frame_rate: FrameRate,
+    pacer: Pacer,
+    time_scale: TimeScale,
+    draw_list: DrawList,
 }
 impl<S: Screen, C: Character<S>> GameState<S, C> {
-    pub(crate) fn new(
+    pub fn new(
         fps: u64,
         player_speed: f32,
         player_pos: Coordinate,
-        player: C,
+        mut player: C,
         screen: Arc<Mutex<S>>,
     ) -> Self {
+        // Catch broken sprite data while it's being authored
+        if cfg!(debug_assertions) {
+            report_frame_issues("idle", player.idle().frames());
+            report_frame_issues("side walk", player.side_walk().frames());
+            report_frame_issues("front walk", player.front_walk().frames());
+            report_frame_issues("back walk", player.back_walk().frames());
+        }
+        let (width, height) = screen
+            .lock()
+            .map(|s| (s.width(), s.height()))
+            .unwrap_or_default();
         Self {
             player,
+            controller: CharacterController::new(),
+            animation: (0, 0.0),
             player_pos,
             player_speed,
-            fps: Duration::from_micros(1_000_000 / fps),
+            body: Body::new(),
+            co_player: None,
+            online: None,
+            frame_rate: FrameRate::new(Some(fps)),
+            pacer: Pacer::default(),
+            time_scale: TimeScale::new(),
+            draw_list: DrawList::new(),
+            heading: Coordinate { x: 1.0, y: 0.0 },
+            sprinting: false,
+            sprint: Sprint::default(),
+            dash: Dash::default(),
+            dash_buffer: InputBuffer::default(),
+            stamina: Stamina::default(),
+            stamina_bar: StaminaBar::default(),
+            cutscene: None,
+            camera: Camera::new(width, height),
+            zoom: 1.0,
+            weather: None,
+            touch_overlay: None,
             input_handler: None,
+            action_handler: None,
+            focus_handler: None,
+            lifecycle_handler: None,
+            debug_handler: None,
+            mouse_handler: None,
+            hit_handler: None,
+            hit_stop: HitStop::default(),
+            cheats: Cheats::new(),
+            overlay: DebugOverlay::new(),
+            inspection: None,
+            auto_pause: false,
+            paused: false,
+            closing: false,
             delta: f32::default(),
+            tick: 0,
             screen,
+            redraw: None,
+        }
+    }
+    /// Pause the game whenever the window loses focus and resume
+    /// once it is regained.
+    pub fn auto_pause(&mut self, enabled: bool) {
+        self.auto_pause = enabled;
+        if !enabled {
+            self.paused = false;
+        }
+    }
+    /// A handle to change the frame rate cap and read the achieved frame rate,
+    /// usable after the game was started on its own thread.
+    pub fn frame_rate(&self) -> FrameRate {
+        self.frame_rate.clone()
+    }
+    /// A handle to how fast the simulation runs, shared with the game loop.
+    ///
+    /// Movement, animations, the camera and weather all move on at the scaled
+    /// pace, while networking and frame pacing keep to the clock
+    pub fn time_scale(&self) -> TimeScale {
+        self.time_scale.clone()
+    }
+    /// Moves the player with `body`'s acceleration, friction and limits in the
+    /// current movement mode.
+    pub fn player_physics(&mut self, body: Body) {
+        self.body = body;
+    }
+    /// Switches the player to walking, climbing or swimming, e.g. when level
+    /// code sees them entering a ladder or water.
+    pub fn movement_mode(&mut self, mode: MovementMode) {
+        self.controller.set_mode(mode, &mut self.body);
+    }
+    /// Which cheats are on.
+    pub fn cheats(&self) -> &Cheats {
+        &self.cheats
+    }
+    /// Whether the debug console may switch cheats on, by default only in
+    /// debug builds.
+    pub fn allow_cheats(&mut self, allowed: bool) {
+        self.cheats.set_allowed(allowed);
+    }
+    /// The boxes outlined over the world, e.g. to mark the level's triggers
+    /// or show a category.
+    pub fn overlay(&mut self) -> &mut DebugOverlay {
+        &mut self.overlay
+    }
+    /// How hits published on the bus freeze the game.
+    pub fn hit_stop(&mut self, hit_stop: HitStop) {
+        self.hit_stop = hit_stop;
+    }
+    /// Shows what the character clicked on is made of on `inspector`'s panel.
+    pub fn inspector(&mut self, inspector: Inspector) {
+        self.inspection = Some(Inspection {
+            inspector,
+            click: None,
+            frame: None,
+        });
+    }
+    /// Lets a second player join in, drawn and moved in the same world as
+    /// the first.
+    pub fn co_op(&mut self, co_player: CoPlayer<C>) {
+        self.co_player = Some(co_player);
+    }
+    /// Plays with the game on the other end of `peer`, showing its player as
+    /// `avatar`.
+    pub fn online(&mut self, peer: Peer, avatar: C) {
+        self.online = Some(Online {
+            peer,
+            avatar,
+            controller: CharacterController::new(),
+        });
+    }
+    /// The simulation state of the current tick.
+    pub fn snapshot(&self) -> TickState {
+        TickState {
+            tick: self.tick,
+            position: self.player_pos,
+            velocity: self.body.velocity,
+            knockback: self.body.knockback,
+            heading: self.heading,
+            sprinting: self.sprinting,
+            stamina: self.stamina.value(),
+            dash: self.dash.timers(),
+            dash_buffer: self.dash_buffer.remaining(),
+            movement: movement(&self.controller, self.animation),
+            hit_stop: self.hit_stop.remaining(),
+            cutscene: self.cutscene.as_ref().map(Cutscene::playback),
+            co_player: self.co_player.as_ref().map(|c| CoPlayerTick {
+                position: c.position,
+                velocity: c.body.velocity,
+                knockback: c.body.knockback,
+                movement: movement(&c.controller, c.animation),
+            }),
+            rng: self.weather.as_ref().map(Weather::rng),
+        }
+    }
+    /// Goes back to the tick `state` was taken on, e.g. to replay it with
+    /// input that arrived late.
+    pub fn restore(&mut self, state: &TickState) {
+        self.tick = state.tick;
+        self.player_pos = state.position;
+        // Switched first since switching swaps in the mode's physics
+        self.controller
+            .set_mode(state.movement.mode, &mut self.body);
+        self.body.velocity = state.velocity;
+        self.body.knockback = state.knockback;
+        self.heading = state.heading;
+        self.sprinting = state.sprinting;
+        self.stamina.set_value(state.stamina);
+        self.dash.set_timers(state.dash);
+        self.dash_buffer.set_remaining(state.dash_buffer);
+        self.hit_stop.set_remaining(state.hit_stop);
+        if let Some((cutscene, playback)) = self.cutscene.as_mut().zip(state.cutscene) {
+            cutscene.set_playback(playback);
+        }
+        let acting = self
+            .cutscene
+            .as_ref()
+            .and_then(Cutscene::action)
+            .filter(|(name, _)| self.player.action(name).is_some());
+        self.controller
+            .resume(state.movement.facing, state.movement.pose);
+        let animation = match acting {
+            Some((name, _)) => {
+                self.controller.interrupt();
+                self.player.action(name).unwrap()
+            }
+            None => self.controller.playing(&mut self.player),
+        };
+        self.animation = resume(animation, state.movement.frame);
+        if let Some((co_player, co)) = self.co_player.as_mut().zip(state.co_player) {
+            co_player.position = co.position;
+            co_player
+                .controller
+                .set_mode(co.movement.mode, &mut co_player.body);
+            co_player.body.velocity = co.velocity;
+            co_player.body.knockback = co.knockback;
+            co_player
+                .controller
+                .resume(co.movement.facing, co.movement.pose);
+            let animation = co_player.controller.playing(&mut co_player.character);
+            co_player.animation = resume(animation, co.movement.frame);
+        }
+        if let Some((weather, rng)) = self.weather.as_mut().zip(state.rng) {
+            weather.set_rng(rng);
+        }
+    }
+    /// Plays a cutscene, replacing any playing, and ignores player input until it's finished.
+    pub fn play_cutscene(&mut self, cutscene: Cutscene) {
+        self.cutscene = Some(cutscene);
+    }
+    /// The view onto the world, showing it as is until told otherwise.
+    pub fn camera(&mut self) -> &mut Camera {
+        &mut self.camera
+    }
+    /// Lets `weather` fall over the world, or clears the skies with `None`.
+    pub fn weather(&mut self, weather: Option<Weather>) {
+        self.weather = weather;
+    }
+    /// Draws on-screen touch controls on top of the game.
+    pub fn touch_overlay(&mut self, controls: Arc<Mutex<TouchControls>>) {
+        self.touch_overlay = Some(controls);
+    }
+    /// Only draws frames and asks for the window to be redrawn, leaving it to
+    /// the event loop to present them on `RedrawRequested`.
+    ///
+    /// The screen must be registered with `EventHandler::present_on_redraw`
+    pub fn present_on_redraw(&mut self, redraw: RedrawRequest) {
+        self.redraw = Some(redraw);
+    }
+    /// Lets `events` update the game between handling events, for platforms
+    /// that don't cope with a separate game thread.
+    ///
+    /// Use instead of `start`
+    pub fn start_inline(mut self, events: &mut EventHandler)
+    where
+        C: 'static,
+    {
+        if let Some(rx) = self.input_handler.take() {
+            events.drive(Box::new(InlineGame {
+                state: self,
+                rx,
+                last_frame: None,
+            }));
+        } else {
+            error!(error = %GameStateError::NoInputHandlerError, "game loop not started")
         }
     }
-    pub(crate) fn start(mut self) {
+    /// Runs the game loop on its own thread until a window is asked to close.
+    ///
+    /// Subscribe the game to `WindowLifecycle` on `EventHandler::bus` and
+    /// join the returned thread once `EventHandler::start` returns, so the
+    /// last frame finishes before the process exits
+    pub fn start(mut self) -> JoinHandle<()> {
         thread::spawn(move || {
+            let _span = info_span!("game_loop").entered();
             if let Some(rx) = self.input_handler.take() {
-                loop {
-                    let _ = self.update(rx.clone()).inspect_err(|e| error!("{}", e));
+                while !self.closing {
+                    let _ = self
+                        .update(Receiver::clone(&rx))
+                        .inspect_err(|e| error!(error = %e, "game update failed"));
                 }
             } else {
-                error!("{}", GameStateError::NoInputHandlerError)
+                error!(error = %GameStateError::NoInputHandlerError, "game loop not started")
             }
-        });
+        })
     }
+    /// Runs a frame and holds the frame pace.
     fn update(&mut self, rx: Receiver<Coordinate>) -> Result<(), WindowError> {
-        // Track movement
-        let input: Option<Coordinate> = if let Ok(inp) = rx.try_recv() {
-            self.player_pos += inp * self.player_speed * self.delta;
-            Some(inp)
+        let tick = Instant::now();
+        self.frame(&rx)?;
+        if self.paused {
+            // Nothing moves nor animates but we still hold the frame
+            // pace to avoid spinning while the player is away
+            let frame_time = self.frame_time();
+            sleep(frame_time);
+            self.delta = frame_time.as_secs_f32();
+            return Ok(());
+        }
+
+        // Guarantee frames arent cut short and
+        // exhaust their max view time
+        if let Some(frame_time) = self.frame_rate.frame_time() {
+            self.pacer.wait_until(tick + frame_time);
+        }
+
+        // Keep frame-rate independent and consistent
+        let delta = Instant::now().duration_since(tick).as_secs_f32();
+        self.delta = self.frame_rate.record(delta);
+
+        Ok(())
+    }
+    /// The time a frame is held for, which is never zero while paused.
+    fn frame_time(&self) -> Duration {
+        let frame_time = self.frame_rate.frame_time();
+        if self.paused {
+            frame_time.unwrap_or(PAUSED_FRAME_TIME)
+        } else {
+            frame_time.unwrap_or_default()
+        }
+    }
+    /// Runs a single frame of input, movement, animation and drawing without
+    /// holding the frame pace.
+    fn frame(&mut self, rx: &Receiver<Coordinate>) -> Result<(), WindowError> {
+        let _span = trace_span!("frame", delta = self.delta).entered();
+        let input_scope = profiler::scope("input");
+        // Track focus
+        if let Some(focus) = &self.focus_handler {
+            for f in focus.try_iter() {
+                self.paused = self.auto_pause && f == WindowFocus::Lost;
+            }
+        }
+        if let Some(lifecycle) = &self.lifecycle_handler {
+            self.closing |= lifecycle
+                .try_iter()
+                .any(|l| l.change == Lifecycle::CloseRequested);
+        }
+        if self.paused {
+            while rx.try_recv().is_ok() {}
+            if let Some(rx) = self.co_player.as_ref().and_then(|c| c.input.as_ref()) {
+                while rx.try_recv().is_ok() {}
+            }
+            drop(input_scope);
+            profiler::end_frame();
+            return Ok(());
+        }
+
+        // Take debug commands, leaving the ones about other systems to them
+        let commands: Vec<_> = self
+            .debug_handler
+            .iter()
+            .flat_map(|rx| rx.try_iter())
+            .collect();
+        for command in commands {
+            match command {
+                DebugCommand::Teleport(position) => {
+                    self.player_pos = position;
+                    self.body.velocity = Coordinate::default();
+                    self.body.knockback = Coordinate::default();
+                }
+                DebugCommand::Cheat(cheat) => self.cheat(cheat),
+                DebugCommand::Overlay(category, on) => self.overlay.show(category, on),
+                DebugCommand::Edit(field, value) => self.edit(field, value),
+                _ => (),
+            }
+        }
+        if let Some((clicks, inspection)) =
+            self.mouse_handler.as_ref().zip(self.inspection.as_mut())
+        {
+            for click in clicks.try_iter() {
+                if (click.button, click.state) == (MouseButton::Left, ElementState::Pressed) {
+                    inspection.click = Some(self.camera.to_world(click.position));
+                }
+            }
+        }
+
+        if let Some(hits) = &self.hit_handler {
+            for hit in hits.try_iter() {
+                self.hit_stop.hit(hit);
+            }
+        }
+
+        // Track actions
+        if let Some(actions) = &self.action_handler {
+            for a in actions.try_iter() {
+                if let Some(cutscene) = self.cutscene.as_mut().filter(|c| !c.is_finished()) {
+                    // Confirming dialogue is all the player gets to do
+                    if (a.input, a.state) == (GameInput::MenuConfirm, ElementState::Pressed) {
+                        cutscene.confirm();
+                    }
+                    continue;
+                }
+                match (a.input, a.state) {
+                    (GameInput::PlayerSprint, state) => {
+                        self.sprinting = state == ElementState::Pressed
+                    }
+                    (GameInput::PlayerDash, ElementState::Pressed) => self.dash_buffer.press(),
+                    _ => (),
+                }
+            }
+        }
+
+        // Track movement, only the latest direction is still held
+        let mut input: Option<Coordinate> = rx.try_iter().last();
+        drop(input_scope);
+        let update_scope = profiler::scope("update");
+        let time_scale = self.time_scale.get() * self.hit_stop.update(&mut self.camera);
+        let delta = self.delta * time_scale * self.cheats.time_scale();
+        let mut scripted = None;
+        let mut directed = false;
+        if let Some(cutscene) = self.cutscene.as_mut().filter(|c| !c.is_finished()) {
+            input = None;
+            directed = true;
+            scripted = cutscene.update(
+                delta,
+                &mut self.player_pos,
+                self.player_speed,
+                &mut self.camera,
+            );
+        }
+        if let Some(inp) = input.filter(|inp| *inp != Coordinate::default()) {
+            self.heading = inp;
+        }
+        let sprinting =
+            self.sprinting && input.is_some() && self.stamina.drain(self.sprint.drain_rate * delta);
+        if self.dash_buffer.is_pending() && self.dash.trigger(&mut self.stamina) {
+            self.dash_buffer.consume();
+        }
+        self.dash_buffer.tick(delta);
+        let dashing = self.dash.is_active();
+        let player_speed = self.player_speed * self.controller.mode().speed();
+        if dashing {
+            // Bursts keep going the way the player was last heading
+            self.body
+                .drive(self.heading * player_speed * self.dash.speed);
+        } else if let Some(inp) = input {
+            let speed = if sprinting {
+                player_speed * self.sprint.speed
+            } else {
+                player_speed
+            };
+            self.body.drive(inp * speed);
+        }
+        self.body.step(&mut self.player_pos, delta);
+        let co_motion = self
+            .co_player
+            .as_mut()
+            .and_then(|c| c.step(delta, directed));
+        if !sprinting && !dashing {
+            self.stamina.regen(delta);
+        }
+        self.dash.tick(delta);
+        self.camera.update(delta, self.player_pos);
+        self.apply_zoom()?;
+        self.tick += 1;
+
+        // Faster movement should step faster too
+        let animation_delta = if sprinting || dashing {
+            delta * self.sprint.animation
+        } else {
+            delta
+        };
+        let motion = if dashing {
+            Some(self.heading)
         } else {
-            None
+            input.or(scripted)
         };
+        drop(update_scope);
 
         // Frame animation
-        let tick = Instant::now();
-        match input {
-            // Walk to Left
-            Some(Coordinate { x: -1.0, .. }) => {
-                self.player.side_walk().play(
-                    self.screen.clone(),
-                    self.delta,
-                    MirrorDirection::FlipVertical,
-                    self.player_pos,
-                )?;
+        let animation_scope = profiler::scope("animation");
+        // Cutscenes can have the player act out any action they have instead
+        let acting = self
+            .cutscene
+            .as_ref()
+            .and_then(Cutscene::action)
+            .filter(|(name, _)| self.player.action(name).is_some());
+        let (animation, mirror) = match acting {
+            Some((name, started)) => {
+                let (_, mirror) = self.controller.update(motion);
+                self.controller.interrupt();
+                let animation = self.player.action(name).unwrap();
+                if started {
+                    animation.reset();
+                }
+                (animation, mirror)
             }
-            // Walk to Right
-            Some(Coordinate { x: 1.0, .. }) => {
-                self.player.side_walk().play(
-                    self.screen.clone(),
-                    self.delta,
-                    MirrorDirection::None,
-                    self.player_pos,
-                )?;
+            None => self.controller.animation(&mut self.player, motion),
+        };
+        animation.advance(animation_delta);
+        let mut co_animation = None;
+        let mut co_components = None;
+        if let Some(co_player) = &mut self.co_player {
+            let (co, co_mirror) = co_player
+                .controller
+                .animation(&mut co_player.character, co_motion);
+            co.advance(delta);
+            if let Some(frame) = self
+                .inspection
+                .as_mut()
+                .and_then(|i| i.take_frame(Inspected::CoPlayer))
+            {
+                co.set_frame(frame);
             }
-            // Walk Down
-            Some(Coordinate { y: 1.0, .. }) => {
-                self.player.front_walk().play(
-                    self.screen.clone(),
-                    self.delta,
-                    MirrorDirection::None,
-                    self.player_pos,
-                )?;
+            co_player.animation = (co.frame_pos(), co.timer());
+            // Players push each other apart instead of walking through, unless
+            // they're cheating
+            let push = bounds(animation, mirror, self.player_pos)
+                .zip(bounds(co, co_mirror, co_player.position))
+                .and_then(|(player, co)| player.penetration(&co))
+                .filter(|_| !self.cheats.noclip());
+            if let Some(push) = push {
+                self.player_pos += push * 0.5;
+                co_player.position += push * -0.5;
             }
-            // Walk Up
-            Some(Coordinate { y: -1.0, .. }) => {
-                self.player.back_walk().play(
-                    self.screen.clone(),
-                    self.delta,
-                    MirrorDirection::None,
-                    self.player_pos,
-                )?;
+            co_components = self.inspection.as_ref().map(|_| {
+                components(
+                    co,
+                    &co_player.controller,
+                    co_player.position,
+                    co_player.body.velocity,
+                )
+            });
+            co_animation = Some((co, co_mirror, co_player.position));
+        }
+        let mut inspected = None;
+        if let Some(inspection) = &mut self.inspection {
+            if let Some(click) = inspection.click.take() {
+                let hit = |area: Option<Rect>| area.is_some_and(|area| area.contains(click));
+                let co_bounds = co_animation
+                    .as_ref()
+                    .and_then(|(co, co_mirror, pos)| bounds(&**co, *co_mirror, *pos));
+                let picked = if hit(bounds(animation, mirror, self.player_pos)) {
+                    Some(Inspected::Player)
+                } else if hit(co_bounds) {
+                    Some(Inspected::CoPlayer)
+                } else {
+                    None
+                };
+                inspection.inspector.select(picked);
             }
-            _ => {
-                self.player.idle().play(
-                    self.screen.clone(),
-                    self.delta,
-                    MirrorDirection::None,
+            if let Some(frame) = inspection.take_frame(Inspected::Player) {
+                animation.set_frame(frame);
+            }
+            inspected = match inspection.inspector.selected() {
+                Some(Inspected::Player) => Some(components(
+                    animation,
+                    &self.controller,
                     self.player_pos,
-                )?;
+                    self.body.velocity,
+                )),
+                Some(Inspected::CoPlayer) => co_components.take(),
+                None => None,
+            };
+        }
+        self.animation = (animation.frame_pos(), animation.timer());
+        let mut remote_animation = None;
+        if let Some(online) = &mut self.online {
+            let local = PlayerState {
+                position: self.player_pos,
+                motion,
+                frame: animation.frame_pos(),
+            };
+            // A hiccup on the network shouldn't stop the game
+            let _ = online
+                .peer
+                .update(self.delta, &local)
+                .inspect_err(|e| error!(error = %e, "network update failed"));
+            if let Some(remote) = online.peer.remote() {
+                let (avatar, avatar_mirror) = online
+                    .controller
+                    .animation(&mut online.avatar, remote.motion);
+                avatar.set_frame(remote.frame);
+                remote_animation = Some((avatar, avatar_mirror, remote.position));
             }
         }
-        // Guarantee frames arent cut short and
-        // exhaust their max view time
-        let elapsed = tick.elapsed();
-        if elapsed < self.fps {
-            sleep(self.fps - elapsed)
+        if let Some(weather) = &mut self.weather {
+            weather.update(delta);
         }
 
-        // Keep frame-rate independent and consistent
-        self.delta = Instant::now().duration_since(tick).as_secs_f32();
+        // Everything in the frame is drawn under a single lock
+        let mut batch = self.draw_list.begin();
+        batch.push(DrawCmd::Clear);
+        if let Some((co, co_mirror, co_pos)) = &co_animation {
+            co.commands(*co_mirror, self.camera.to_screen(*co_pos), &mut batch);
+        }
+        if let Some((avatar, avatar_mirror, remote_pos)) = remote_animation {
+            avatar.commands(avatar_mirror, self.camera.to_screen(remote_pos), &mut batch);
+        }
+        animation.commands(mirror, self.camera.to_screen(self.player_pos), &mut batch);
+        if let Some(weather) = &self.weather {
+            weather.commands(&mut batch);
+        }
+        if self.overlay.is_visible() {
+            let overlay = &mut self.overlay;
+            let mut characters = vec![(&*animation, mirror, self.player_pos)];
+            if let Some((co, co_mirror, co_pos)) = &co_animation {
+                characters.push((&**co, *co_mirror, *co_pos));
+            }
+            for (character, mirror, position) in characters {
+                if let Some(bounds) = bounds(character, mirror, position) {
+                    overlay.add(Category::Bounds, bounds);
+                }
+                overlay.colliders(&character.colliders(mirror, position));
+            }
+            overlay.add(Category::DeadZone, self.camera.dead_zone());
+            overlay.commands(&self.camera, &mut batch);
+        }
+        let view = self.camera.view_size();
+        let (width, height) = (view.x.round() as u32, view.y.round() as u32);
+        self.hit_stop.commands(width, height, &mut batch);
+        self.stamina_bar.commands(&self.stamina, &mut batch);
+        if let Some((inspection, components)) = self.inspection.as_ref().zip(inspected) {
+            inspection.inspector.commands(&components, &mut batch);
+        }
+        if let Some(cutscene) = &self.cutscene {
+            cutscene.commands(&mut batch);
+        }
+        if let Some(controls) = &self.touch_overlay {
+            controls
+                .lock()
+                .map_err(|e| WindowError::ScreenLockError(e.to_string()))?
+                .commands(&mut batch);
+        }
+        drop(animation_scope);
+        {
+            profile_scope!("render");
+            let mut screen = self
+                .screen
+                .lock()
+                .map_err(|e| WindowError::ScreenLockError(e.to_string()))?;
+            if let Some(redraw) = &self.redraw {
+                screen.draw_commands(&batch)?;
+                redraw.request();
+            } else {
+                screen.draw_batch(&batch)?;
+            }
+        }
+        self.draw_list.finish(batch);
+        profiler::end_frame();
 
         Ok(())
     }
+    /// Sets a component of the inspected character.
+    fn edit(&mut self, field: Field, value: f32) {
+        let Some(inspection) = &mut self.inspection else {
+            return;
+        };
+        let (position, body) = match (inspection.inspector.selected(), &mut self.co_player) {
+            (Some(Inspected::Player), _) => (&mut self.player_pos, &mut self.body),
+            (Some(Inspected::CoPlayer), Some(co_player)) => {
+                (&mut co_player.position, &mut co_player.body)
+            }
+            _ => {
+                warn!(?field, "nothing is inspected to edit");
+                return;
+            }
+        };
+        match field {
+            Field::X => position.x = value,
+            Field::Y => position.y = value,
+            Field::VelocityX => body.velocity.x = value,
+            Field::VelocityY => body.velocity.y = value,
+            // Shown from the next frame, once the animation is known
+            Field::Frame => inspection.frame = Some(value.max(0.0) as usize),
+        }
+    }
+    fn cheat(&mut self, cheat: Cheat) {
+        if !self.cheats.apply(cheat) {
+            warn!(?cheat, "cheats aren't allowed");
+        } else if let Cheat::Hitboxes(on) = cheat {
+            self.overlay.show(Category::Hitbox, on);
+            self.overlay.show(Category::Hurtbox, on);
+        }
+    }
+    /// Shows fewer, bigger pixels while the camera is zoomed in, laying out
+    /// the touch controls for the new resolution.
+    fn apply_zoom(&mut self) -> Result<(), WindowError> {
+        let zoom = self.camera.zoom();
+        if zoom == self.zoom {
+            return Ok(());
+        }
+        {
+            let mut screen = self
+                .screen
+                .lock()
+                .map_err(|e| WindowError::ScreenLockError(e.to_string()))?;
+            let (width, height) = screen.layout_size();
+            let scaled = |size: u32| ((size as f32 / zoom).round() as u32).max(1);
+            screen.set_resolution(scaled(width), scaled(height))?;
+        }
+        if let Some(controls) = &self.touch_overlay {
+            controls
+                .lock()
+                .map_err(|e| WindowError::ScreenLockError(e.to_string()))?
+                .relayout(1.0 / zoom);
+        }
+        self.zoom = zoom;
+        Ok(())
+    }
+}
+
+/// A second local player walking the same world as the first.
+///
+/// Subscribe it to player two's input with
+/// `EventHandler::subscribe_player_coordinate` before handing it to
+/// `GameState::co_op`.
+pub struct CoPlayer<C> {
+    character: C,
+    controller: CharacterController,
+    /// The frame and timer of the co-player's animation as of the last frame
+    animation: (usize, f32),
+    position: Coordinate,
+    speed: f32,
+    body: Body,
+    input: Option<Inbox<Coordinate>>,
+}
+impl<C> CoPlayer<C> {
+    pub fn new(character: C, position: Coordinate, speed: f32) -> Self {
+        Self {
+            character,
+            controller: CharacterController::new(),
+            animation: (0, 0.0),
+            position,
+            speed,
+            body: Body::new(),
+            input: None,
+        }
+    }
+    /// Moves the player with `body`'s acceleration, friction and limits.
+    pub fn physics(mut self, body: Body) -> Self {
+        self.body = body;
+        self
+    }
+    pub fn position(&self) -> Coordinate {
+        self.position
+    }
+    /// Moves along with the latest input for `delta` seconds, ignoring it
+    /// while a cutscene `directed` the players.
+    ///
+    /// Returns the direction moved in
+    fn step(&mut self, delta: f32, directed: bool) -> Option<Coordinate> {
+        let input = self
+            .input
+            .as_ref()
+            .and_then(|rx| rx.try_iter().last())
+            .filter(|_| !directed);
+        if let Some(input) = input {
+            self.body
+                .drive(input * self.speed * self.controller.mode().speed());
+        }
+        self.body.step(&mut self.position, delta);
+        input
+    }
+}
+impl<C> Subscriber<Coordinate> for CoPlayer<C> {
+    fn subscribe(&mut self, rx: Inbox<Coordinate>) {
+        self.input = Some(rx);
+    }
+}
+
+/// The player of another game shown in this one.
+struct Online<C> {
+    peer: Peer,
+    avatar: C,
+    /// Picks the avatar's animation from how the remote player moves
+    controller: CharacterController,
+}
+
+/// The inspector panel and what was asked of it since the last frame.
+struct Inspection {
+    inspector: Inspector,
+    /// Where in the world the last click landed, until it's looked up
+    click: Option<Coordinate>,
+    /// A frame to show for the inspected character
+    frame: Option<usize>,
+}
+impl Inspection {
+    /// The frame to show for `inspected`, once, if it's the one inspected.
+    fn take_frame(&mut self, inspected: Inspected) -> Option<usize> {
+        self.frame
+            .take_if(|_| self.inspector.selected() == Some(inspected))
+    }
+}
+
+/// How a character moves under `controller` with its animation at `frame`.
+fn movement(controller: &CharacterController, frame: (usize, f32)) -> Movement {
+    Movement {
+        mode: controller.mode(),
+        facing: controller.facing(),
+        pose: controller.pose(),
+        frame,
+    }
+}
+
+/// Picks `animation` up again at `frame`, returning where it actually is.
+fn resume<S: Screen>(
+    animation: &mut dyn Animation<S>,
+    (frame, timer): (usize, f32),
+) -> (usize, f32) {
+    animation.set_frame(frame);
+    *animation.timer_mut() = timer;
+    (animation.frame_pos(), animation.timer())
+}
+
+/// What the inspector shows for a character playing `animation`.
+fn components<S: Screen>(
+    animation: &dyn Animation<S>,
+    controller: &CharacterController,
+    position: Coordinate,
+    velocity: Coordinate,
+) -> Components {
+    Components {
+        position,
+        velocity,
+        health: None,
+        pose: controller.pose(),
+        mode: controller.mode(),
+        frame: animation.frame_pos(),
+        frames: animation.frames().len(),
+    }
+}
+
+/// The area the current frame of `animation` covers when drawn with `mirror`
+/// at `position`.
+fn bounds<S: Screen>(
+    animation: &dyn Animation<S>,
+    mirror: MirrorDirection,
+    position: Coordinate,
+) -> Option<Rect> {
+    let frame = animation.frames().get(animation.frame_pos())?;
+    let corner = position + frame.anchor(&frame.mirror_value(mirror));
+    Some(Rect {
+        x: corner.x,
+        y: corner.y,
+        width: frame.width.into(),
+        height: frame.height.into(),
+    })
+}
+
+/// Logs everything wrong with the frames of one of the player's animations.
+fn report_frame_issues(animation: &str, frames: &[Frame]) {
+    for issue in validate(frames) {
+        warn!(animation, %issue, "invalid sprite frame");
+    }
+}
+
+/// A `GameState` updated by the event loop instead of its own thread.
+struct InlineGame<S: Screen, C: Character<S>> {
+    state: GameState<S, C>,
+    rx: Inbox<Coordinate>,
+    /// When the previous frame started
+    last_frame: Option<Instant>,
 }
+impl<S: Screen, C: Character<S>> Driven for InlineGame<S, C> {
+    fn frame(&mut self) -> Result<(), WindowError> {
+        // The time between frames is up to the event loop
+        let now = Instant::now();
+        if let Some(last) = self.last_frame.replace(now) {
+            let delta = now.duration_since(last).as_secs_f32();
+            self.state.delta = self.state.frame_rate.record(delta);
+        }
+        self.state.frame(&self.rx)
+    }
+    fn frame_time(&self) -> Duration {
+        self.state.frame_time()
+    }
+}
+/// Frame time held while paused when the frame rate isn't capped.
+const PAUSED_FRAME_TIME: Duration = Duration::from_millis(16);
+/// The least time left to spin at the end of a frame.
+const MIN_SPIN: Duration = Duration::from_micros(500);
+/// The most time left to spin at the end of a frame, however coarse sleeps are.
+const MAX_SPIN: Duration = Duration::from_millis(16);
+
+/// Waits out the rest of a frame by sleeping most of the way and spinning for
+/// the rest.
+///
+/// Sleeping alone overshoots by as much as the system's timer is coarse, about
+/// 15ms on Windows, which drags the frame rate below its cap. How much time is
+/// left to spin is learned from how far sleeps overshot
+#[derive(Debug, Clone)]
+struct Pacer {
+    spin: Duration,
+}
+impl Default for Pacer {
+    fn default() -> Self {
+        Self { spin: MIN_SPIN }
+    }
+}
+impl Pacer {
+    fn wait_until(&mut self, deadline: Instant) {
+        loop {
+            let now = Instant::now();
+            let remaining = deadline.saturating_duration_since(now);
+            if remaining.is_zero() {
+                return;
+            }
+            if remaining <= self.spin {
+                std::hint::spin_loop();
+                continue;
+            }
+            let planned = remaining - self.spin;
+            sleep(planned);
+            // Grows right away but shrinks slowly, so one sleep waking up on
+            // time doesn't make the next frames late
+            let overshoot = now.elapsed().saturating_sub(planned);
+            self.spin = overshoot
+                .max(self.spin - self.spin / 16)
+                .clamp(MIN_SPIN, MAX_SPIN);
+        }
+    }
+}
+
+/// Recent frames `FrameRate::stats` are taken over.
+const STATS_FRAMES: usize = 120;
+/// Recent frames a smoothed delta is averaged over.
+const SMOOTHED_FRAMES: usize = 4;
+
+/// Frame times of the recent frames.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FrameStats {
+    pub average: Duration,
+    /// The time 95% of the frames took at most
+    pub p95: Duration,
+    pub max: Duration,
+}
+
+/// Recent frame times and the smoothing of the deltas taken from them.
+#[derive(Debug, Default)]
+struct FrameTimes {
+    recent: VecDeque<f32>,
+    smoothing: bool,
+    /// Time measured but not yet handed out in smoothed deltas
+    debt: f32,
+}
+impl FrameTimes {
+    fn push(&mut self, delta: f32) {
+        if self.recent.len() == STATS_FRAMES {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(delta);
+    }
+    /// The average of the last few deltas, handing out the time it differs
+    /// from what was measured once it adds up to more than a frame, so a real
+    /// hitch still catches up.
+    fn smooth(&mut self, delta: f32) -> f32 {
+        let last = self.recent.iter().rev().take(SMOOTHED_FRAMES);
+        let average = last.clone().sum::<f32>() / last.count().max(1) as f32;
+        self.debt += delta - average;
+        if self.debt.abs() > average {
+            return average + std::mem::take(&mut self.debt);
+        }
+        average
+    }
+    fn stats(&self) -> FrameStats {
+        if self.recent.is_empty() {
+            return FrameStats::default();
+        }
+        let mut sorted: Vec<f32> = self.recent.iter().copied().collect();
+        sorted.sort_by(f32::total_cmp);
+        let p95 = ((sorted.len() - 1) as f32 * 0.95).round() as usize;
+        FrameStats {
+            average: Duration::from_secs_f32(sorted.iter().sum::<f32>() / sorted.len() as f32),
+            p95: Duration::from_secs_f32(sorted[p95]),
+            max: Duration::from_secs_f32(sorted[sorted.len() - 1]),
+        }
+    }
+}
+
+/// A shared handle to the game loop's frame rate.
+///
+/// Clones refer to the same game loop so the cap can be changed from another
+/// thread (e.g. a settings menu) while the game is running
+#[derive(Debug, Clone)]
+pub struct FrameRate {
+    /// Microseconds per frame or `0` when uncapped.
+    frame_time: Arc<AtomicU64>,
+    /// Bits of the last frame's achieved `f32` frames per second.
+    achieved: Arc<AtomicU32>,
+    times: Arc<Mutex<FrameTimes>>,
+}
+impl FrameRate {
+    pub(crate) fn new(target: Option<u64>) -> Self {
+        let frame_rate = Self {
+            frame_time: Arc::new(AtomicU64::new(0)),
+            achieved: Arc::new(AtomicU32::new(0)),
+            times: Arc::new(Mutex::new(FrameTimes::default())),
+        };
+        frame_rate.set_target(target);
+        frame_rate
+    }
+    /// Caps the game loop at `target` frames per second, or lets it run as fast
+    /// as it can with `None` to benchmark the achievable frame rate.
+    pub fn set_target(&self, target: Option<u64>) {
+        let micros = target
+            .filter(|fps| *fps > 0)
+            .map_or(0, |fps| 1_000_000 / fps);
+        self.frame_time.store(micros, Ordering::Relaxed);
+    }
+    /// The frame rate cap or `None` when uncapped.
+    pub fn target(&self) -> Option<u64> {
+        self.frame_time().map(|t| 1_000_000 / t.as_micros() as u64)
+    }
+    /// The frames per second the last frame ran at.
+    pub fn achieved(&self) -> f32 {
+        f32::from_bits(self.achieved.load(Ordering::Relaxed))
+    }
+    /// The average, 95th percentile and longest time of the recent frames.
+    pub fn stats(&self) -> FrameStats {
+        self.times().stats()
+    }
+    /// Evens out the deltas the game moves on by between frames, so the odd
+    /// frame the scheduler held back a little doesn't show as a stutter.
+    ///
+    /// No time is lost, what the smoothed deltas leave out is caught up on
+    /// once it adds up to a frame
+    pub fn smooth(&self, enabled: bool) {
+        let mut times = self.times();
+        times.smoothing = enabled;
+        times.debt = 0.0;
+    }
+    fn frame_time(&self) -> Option<Duration> {
+        match self.frame_time.load(Ordering::Relaxed) {
+            0 => None,
+            micros => Some(Duration::from_micros(micros)),
+        }
+    }
+    /// Records a frame lasting `delta` seconds, returning the delta the game
+    /// moves on by.
+    fn record(&self, delta: f32) -> f32 {
+        if delta > 0.0 {
+            self.achieved
+                .store((1.0 / delta).to_bits(), Ordering::Relaxed);
+        }
+        let mut times = self.times();
+        times.push(delta);
+        if times.smoothing {
+            times.smooth(delta)
+        } else {
+            delta
+        }
+    }
+    fn times(&self) -> MutexGuard<'_, FrameTimes> {
+        self.times.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// A shared handle to how fast the game's simulation runs.
+///
+/// Clones refer to the same game loop so slow motion can be set from another
+/// thread (e.g. a combat system) while the game is running
+#[derive(Debug, Clone)]
+pub struct TimeScale {
+    /// Bits of the `f32` multiplier applied to every frame's delta.
+    scale: Arc<AtomicU32>,
+}
+impl TimeScale {
+    fn new() -> Self {
+        Self {
+            scale: Arc::new(AtomicU32::new(1.0f32.to_bits())),
+        }
+    }
+    /// Runs the simulation `scale` times as fast, where `0.0` freezes it and
+    /// `1.0` plays it as normal.
+    pub fn set(&self, scale: f32) {
+        let scale = if scale.is_finite() {
+            scale.max(0.0)
+        } else {
+            1.0
+        };
+        self.scale.store(scale.to_bits(), Ordering::Relaxed);
+    }
+    pub fn get(&self) -> f32 {
+        f32::from_bits(self.scale.load(Ordering::Relaxed))
+    }
+}
+
 impl<S: Screen, C: Character<S>> Subscriber<Coordinate> for GameState<S, C> {
-    fn subscribe(&mut self, rx: Receiver<Coordinate>) {
+    fn subscribe(&mut self, rx: Inbox<Coordinate>) {
         self.input_handler = Some(rx);
     }
 }
+impl<S: Screen, C: Character<S>> Subscriber<Action> for GameState<S, C> {
+    fn subscribe(&mut self, rx: Inbox<Action>) {
+        self.action_handler = Some(rx);
+    }
+}
+impl<S: Screen, C: Character<S>> Subscriber<WindowFocus> for GameState<S, C> {
+    fn subscribe(&mut self, rx: Inbox<WindowFocus>) {
+        self.focus_handler = Some(rx);
+    }
+}
+impl<S: Screen, C: Character<S>> Subscriber<WindowLifecycle> for GameState<S, C> {
+    fn subscribe(&mut self, rx: Inbox<WindowLifecycle>) {
+        self.lifecycle_handler = Some(rx);
+    }
+}
+impl<S: Screen, C: Character<S>> Subscriber<MouseClick> for GameState<S, C> {
+    fn subscribe(&mut self, rx: Inbox<MouseClick>) {
+        self.mouse_handler = Some(rx);
+    }
+}
+impl<S: Screen, C: Character<S>> Subscriber<Hit> for GameState<S, C> {
+    fn subscribe(&mut self, rx: Inbox<Hit>) {
+        self.hit_handler = Some(rx);
+    }
+}
+impl<S: Screen, C: Character<S>> Subscriber<DebugCommand> for GameState<S, C> {
+    fn subscribe(&mut self, rx: Inbox<DebugCommand>) {
+        self.debug_handler = Some(rx);
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use crate::{
-        game::GameState,
+        camera::Camera,
+        cheats::{Cheat, Cheats},
+        console::DebugCommand,
+        controller::{CharacterController, MovementMode},
+        cutscene::Cutscene,
+        event::{Driven, Lifecycle, MouseClick, WindowFocus, WindowLifecycle},
+        font::BitmapFont,
+        game::{CoPlayer, FrameRate, GameState, InlineGame, Pacer, TimeScale, MAX_SPIN, MIN_SPIN},
+        hitbox::Rect,
+        hitstop::{Hit, HitStop},
+        hud::StaminaBar,
+        input::{Action, GameInput},
+        inspector::{Field, Inspected, Inspector},
         layout::Coordinate,
-        mock::{MockCharacter, MockScreen},
+        mock::{MockCharacter, MockWindow},
+        movement::{Dash, InputBuffer, Sprint, Stamina},
+        net::Peer,
+        overlay::{Category, DebugOverlay},
+        palette::Color,
+        physics::Body,
+        renderer::DrawList,
+        sync::Subscriber,
+        touch::{TouchButton, TouchControls, TouchPoint},
+        window::{HeadlessScreen, RedrawRequest, Screen},
     };
-    use crossbeam::channel;
+    use crossbeam::channel::{self, Receiver};
     use std::{
-        sync::{Arc, Mutex},
-        time::Duration,
+        sync::{atomic::Ordering, Arc, Mutex},
+        thread::sleep,
+        time::{Duration, Instant},
+    };
+    use winit::{
+        event::{ElementState, MouseButton, TouchPhase},
+        window::WindowId,
     };
 
-    #[test]
-    fn test_player_movement_applied() {
-        let (tx, rx) = channel::unbounded();
-        let mut gs = GameState {
-            input_handler: Some(rx.clone()),
-            delta: 1.0,
+    fn mock_game_state(
+        rx: Receiver<Coordinate>,
+        delta: f32,
+    ) -> GameState<HeadlessScreen, MockCharacter> {
+        GameState {
+            input_handler: Some(rx.into()),
+            action_handler: None,
+            focus_handler: None,
+            lifecycle_handler: None,
+            debug_handler: None,
+            mouse_handler: None,
+            hit_handler: None,
+            hit_stop: HitStop::default(),
+            cheats: Cheats::new(),
+            overlay: DebugOverlay::new(),
+            inspection: None,
+            auto_pause: false,
+            paused: false,
+            closing: false,
+            delta,
+            tick: 0,
             player: MockCharacter::new(),
+            controller: CharacterController::new(),
+            animation: (0, 0.0),
             player_pos: Coordinate::default(),
             player_speed: 10.0,
-            screen: Arc::new(Mutex::new(MockScreen::new(50, 50))),
-            fps: Duration::from_millis(16),
-        };
+            body: Body::new(),
+            co_player: None,
+            online: None,
+            heading: Coordinate { x: 1.0, y: 0.0 },
+            sprinting: false,
+            sprint: Sprint::default(),
+            dash: Dash::default(),
+            dash_buffer: InputBuffer::default(),
+            stamina: Stamina::default(),
+            stamina_bar: StaminaBar::default(),
+            cutscene: None,
+            camera: Camera::new(50, 50),
+            zoom: 1.0,
+            weather: None,
+            touch_overlay: None,
+            screen: Arc::new(Mutex::new(HeadlessScreen::new(50, 50))),
+            redraw: None,
+            frame_rate: FrameRate::new(Some(60)),
+            pacer: Pacer::default(),
+            time_scale: TimeScale::new(),
+            draw_list: DrawList::new(),
+        }
+    }
+
+    #[test]
+    fn test_player_movement_applied() {
+        let (tx, rx) = channel::unbounded();
+        let mut gs = mock_game_state(rx.clone(), 1.0);
 
         tx.send(Coordinate { x: 1.0, y: -1.0 }).unwrap();
         gs.update(rx).unwrap();
@@ -179,36 +1339,281 @@ mod tests {
     }
 
     #[test]
-    fn test_framerate_independence() {
+    fn test_stale_movement_is_skipped() {
+        let (tx, rx) = channel::unbounded();
+        let mut gs = mock_game_state(rx.clone(), 1.0);
+
+        // Repeats piling up between frames only move the player once, the
+        // way the key last held points
+        for x in [1.0, 1.0, -1.0] {
+            tx.send(Coordinate { x, y: 0.0 }).unwrap();
+        }
+        gs.frame(&rx).unwrap();
+        assert_eq!(gs.player_pos, Coordinate { x: -10.0, y: 0.0 });
+        assert!(rx.is_empty());
+    }
+
+    #[test]
+    fn test_player_physics() {
+        let (tx, rx) = channel::unbounded();
+        let mut gs = mock_game_state(rx.clone(), 0.5);
+        gs.player_physics(Body::new().acceleration(20.0).friction(40.0));
+
+        tx.send(Coordinate { x: 1.0, y: 0.0 }).unwrap();
+        gs.frame(&rx).unwrap();
+        // Half way up to speed
+        assert_eq!(gs.player_pos, Coordinate { x: 5.0, y: 0.0 });
+
+        // Stops short of where walking at speed would have gone
+        gs.frame(&rx).unwrap();
+        assert_eq!(gs.player_pos, Coordinate { x: 5.0, y: 0.0 });
+    }
+
+    #[test]
+    fn test_movement_mode_slows_player() {
+        let (tx, rx) = channel::unbounded();
+        let mut gs = mock_game_state(rx.clone(), 1.0);
+        gs.movement_mode(MovementMode::Climbing);
+
+        tx.send(Coordinate { x: 0.0, y: -1.0 }).unwrap();
+        gs.frame(&rx).unwrap();
+
+        assert_eq!(gs.player_pos, Coordinate { x: 0.0, y: -5.0 });
+        assert_eq!(gs.player.animation_trigerred, "climb");
+    }
+
+    #[test]
+    fn test_co_op_players_collide() {
         let (_, rx) = channel::unbounded();
-        let mut gs = GameState {
-            input_handler: Some(rx.clone()),
-            delta: 0.0,
-            player: MockCharacter::new(),
-            player_pos: Coordinate::default(),
-            player_speed: 10.0,
-            screen: Arc::new(Mutex::new(MockScreen::new(50, 50))),
-            fps: Duration::from_millis(16),
+        let mut gs = mock_game_state(rx.clone(), 0.1);
+        let (tx, co_rx) = channel::unbounded();
+        let mut co_player =
+            CoPlayer::new(MockCharacter::new(), Coordinate { x: 20.0, y: 0.0 }, 10.0);
+        co_player.subscribe(co_rx.into());
+        gs.co_op(co_player);
+
+        // Player two walks into player one and keeps on pushing
+        for _ in 0..20 {
+            tx.send(Coordinate { x: -1.0, y: 0.0 }).unwrap();
+            gs.frame(&rx).unwrap();
+        }
+
+        let co_player = gs.co_player.as_ref().unwrap();
+        assert!(gs.player_pos.x < 0.0);
+        // Mock frames are 5 pixels wide
+        assert!((co_player.position().x - gs.player_pos.x - 5.0).abs() < 1e-3);
+        assert_eq!(co_player.character.animation_trigerred, "side");
+        assert_eq!(gs.player.animation_trigerred, "idle");
+    }
+
+    #[test]
+    fn test_online_shows_remote_player() {
+        let (_, rx) = channel::unbounded();
+        let mut host = mock_game_state(rx.clone(), 0.1);
+        let mut client = mock_game_state(rx.clone(), 0.1);
+        client.player_pos = Coordinate { x: 10.0, y: 0.0 };
+        let peer = Peer::host("127.0.0.1:0").unwrap();
+        let address = peer.local_addr().unwrap();
+        host.online(peer, MockCharacter::new());
+        client.online(
+            Peer::connect("127.0.0.1:0", address).unwrap(),
+            MockCharacter::new(),
+        );
+
+        let heard = |gs: &GameState<_, _>| gs.online.as_ref().unwrap().peer.remote();
+        for _ in 0..100 {
+            client.frame(&rx).unwrap();
+            host.frame(&rx).unwrap();
+            if heard(&host).is_some() {
+                break;
+            }
+            sleep(Duration::from_millis(1));
+        }
+
+        let remote = heard(&host).unwrap();
+        assert_eq!(remote.position, Coordinate { x: 10.0, y: 0.0 });
+        assert_eq!(host.online.unwrap().avatar.animation_trigerred, "idle");
+    }
+
+    #[test]
+    fn test_restored_snapshot_replays_the_same() {
+        let (tx, rx) = channel::unbounded();
+        let (hit_tx, hit_rx) = channel::unbounded();
+        let mut gs = mock_game_state(rx.clone(), 0.1);
+        gs.player_physics(Body::new().acceleration(50.0).friction(20.0));
+        gs.hit_handler = Some(hit_rx.into());
+        gs.hit_stop(HitStop::new(2));
+        // Swims on from where the last play left off, climbs and walks
+        // through a hit, and when told gets hit again so the next play starts
+        // frozen
+        let play = |gs: &mut GameState<_, _>, hit_at_end: bool| {
+            let steps = [
+                (1.0, MovementMode::Swimming),
+                (1.0, MovementMode::Swimming),
+                (1.0, MovementMode::Swimming),
+                (-1.0, MovementMode::Climbing),
+                (1.0, MovementMode::Normal),
+                (1.0, MovementMode::Normal),
+                (1.0, MovementMode::Swimming),
+            ];
+            for (i, (x, mode)) in steps.into_iter().enumerate() {
+                gs.movement_mode(mode);
+                if i == 3 || (hit_at_end && i == steps.len() - 1) {
+                    hit_tx.send(Hit { strength: 1.0 }).unwrap();
+                }
+                tx.send(Coordinate { x, y: 0.0 }).unwrap();
+                gs.frame(&rx).unwrap();
+            }
+            gs.snapshot()
         };
 
+        play(&mut gs, true);
+        let saved = gs.snapshot();
+        assert_eq!(saved.hit_stop, 1);
+        assert_eq!(saved.movement.mode, MovementMode::Swimming);
+        let first = play(&mut gs, false);
+        assert_eq!(first.tick, saved.tick + 7);
+        assert_ne!(first.checksum(), saved.checksum());
+
+        gs.restore(&saved);
+        assert_eq!(gs.snapshot(), saved);
+        assert_eq!(play(&mut gs, false).checksum(), first.checksum());
+    }
+
+    #[test]
+    fn test_camera_zoom_changes_resolution() {
+        let (_, rx) = channel::unbounded();
+        let mut gs = mock_game_state(rx.clone(), 0.5);
+        let controls = Arc::new(Mutex::new(TouchControls::new().button(TouchButton::new(
+            Coordinate { x: 40.0, y: 40.0 },
+            4.0,
+            GameInput::PlayerDash,
+        ))));
+        gs.touch_overlay(controls.clone());
+
+        gs.camera().zoom_to(2.0, 0.5);
+        gs.frame(&rx).unwrap();
+        {
+            let screen = gs.screen.lock().unwrap();
+            assert_eq!((screen.width(), screen.height()), (25, 25));
+        }
+        // Still under the touch that would have pressed it before zooming in
+        let touch = |x, y| TouchPoint {
+            id: 1,
+            phase: TouchPhase::Started,
+            position: Coordinate { x, y },
+        };
+        assert_eq!(controls.lock().unwrap().handle(touch(20.0, 20.0)).len(), 1);
+
+        gs.camera().zoom_to(1.0, 0.5);
+        gs.frame(&rx).unwrap();
+        assert_eq!(gs.screen.lock().unwrap().width(), 50);
+    }
+
+    #[test]
+    fn test_framerate_independence() {
+        let (_, rx) = channel::unbounded();
+        let mut gs = mock_game_state(rx.clone(), 0.0);
+
         gs.update(rx).unwrap();
 
         assert!(gs.delta > 0.016 && gs.delta < 0.017);
     }
 
     #[test]
-    fn test_idle_animation() {
+    fn test_uncapped_frame_rate() {
+        let (_, rx) = channel::unbounded();
+        let mut gs = mock_game_state(rx.clone(), 0.0);
+        let frame_rate = gs.frame_rate();
+        assert_eq!(frame_rate.target(), Some(60));
+
+        frame_rate.set_target(None);
+        gs.update(rx).unwrap();
+
+        assert_eq!(frame_rate.target(), None);
+        assert!(gs.delta < 0.016);
+        assert!(frame_rate.achieved() > 60.0);
+    }
+
+    #[test]
+    fn test_pacer_waits_until_the_deadline() {
+        let mut pacer = Pacer::default();
+        for _ in 0..3 {
+            let start = Instant::now();
+            pacer.wait_until(start + Duration::from_millis(5));
+            let waited = start.elapsed();
+            assert!(waited >= Duration::from_millis(5));
+            assert!(waited < Duration::from_millis(50));
+        }
+        assert!((MIN_SPIN..=MAX_SPIN).contains(&pacer.spin));
+    }
+
+    #[test]
+    fn test_frame_stats_and_smoothing() {
+        let frame_rate = FrameRate::new(Some(60));
+        for i in 0..100 {
+            frame_rate.record(if i == 50 { 0.1 } else { 0.01 });
+        }
+        let stats = frame_rate.stats();
+        assert_eq!(stats.max, Duration::from_secs_f32(0.1));
+        assert_eq!(stats.p95, Duration::from_secs_f32(0.01));
+        assert!((stats.average.as_secs_f32() - 0.0109).abs() < 1e-4);
+
+        // Jitter is evened out without losing any time
+        frame_rate.smooth(true);
+        let jitter = [0.012, 0.020, 0.012, 0.020, 0.012, 0.020, 0.012, 0.020];
+        let smoothed: Vec<f32> = jitter.iter().map(|&d| frame_rate.record(d)).collect();
+        let spread = |deltas: &[f32]| {
+            let max = deltas.iter().copied().fold(0.0, f32::max);
+            max - deltas.iter().copied().fold(max, f32::min)
+        };
+        assert!(spread(&smoothed[4..]) < spread(&jitter) / 4.0);
+        let owed = jitter.iter().sum::<f32>() - smoothed.iter().sum::<f32>();
+        assert!(owed.abs() < 0.016);
+    }
+
+    #[test]
+    fn test_inline_frames_leave_pacing_to_caller() {
         let (tx, rx) = channel::unbounded();
-        let mut gs = GameState {
-            input_handler: Some(rx.clone()),
-            delta: 1.0,
-            player: MockCharacter::new(),
-            player_pos: Coordinate::default(),
-            player_speed: 10.0,
-            screen: Arc::new(Mutex::new(MockScreen::new(50, 50))),
-            fps: Duration::from_millis(16),
+        let state = mock_game_state(rx.clone(), 1.0);
+        let mut game = InlineGame {
+            state,
+            rx: rx.into(),
+            last_frame: None,
         };
 
+        tx.send(Coordinate { x: 1.0, y: 0.0 }).unwrap();
+        game.frame().unwrap();
+        assert_eq!(game.state.player_pos, Coordinate { x: 10.0, y: 0.0 });
+        assert_eq!(game.frame_time(), Duration::from_micros(16_666));
+
+        // The next frame only moves by the time since the previous one
+        game.frame().unwrap();
+        assert!(game.state.delta < 0.016);
+        assert!(game.state.player_pos.x < 10.16);
+    }
+
+    #[test]
+    fn test_present_on_redraw() {
+        let (_, rx) = channel::unbounded();
+        let mut gs = mock_game_state(rx.clone(), 0.0);
+        let window = Arc::new(Mutex::new(MockWindow::default()));
+        let redraw = RedrawRequest::new(window.clone());
+        gs.present_on_redraw(redraw.clone());
+
+        gs.update(rx).unwrap();
+
+        // Drawn but left for the event loop to present
+        assert_eq!(gs.screen.lock().unwrap().renders(), 0);
+        assert_eq!(window.lock().unwrap().redraws.load(Ordering::Relaxed), 1);
+        assert!(redraw.take());
+    }
+
+    #[test]
+    fn test_idle_animation() {
+        let (tx, rx) = channel::unbounded();
+        let mut gs = mock_game_state(rx.clone(), 1.0);
+
         tx.send(Coordinate { x: 0.0, y: 0.0 }).unwrap();
         gs.update(rx).unwrap();
         
@@ -217,15 +1622,7 @@ mod tests {
     #[test]
     fn test_left_side_walk_animation() {
         let (tx, rx) = channel::unbounded();
-        let mut gs = GameState {
-            input_handler: Some(rx.clone()),
-            delta: 1.0,
-            player: MockCharacter::new(),
-            player_pos: Coordinate::default(),
-            player_speed: 10.0,
-            screen: Arc::new(Mutex::new(MockScreen::new(50, 50))),
-            fps: Duration::from_millis(16),
-        };
+        let mut gs = mock_game_state(rx.clone(), 1.0);
 
         tx.send(Coordinate { x: -1.0, y: 0.0 }).unwrap();
         gs.update(rx).unwrap();
@@ -235,15 +1632,7 @@ mod tests {
     #[test]
     fn test_right_side_walk_animation() {
         let (tx, rx) = channel::unbounded();
-        let mut gs = GameState {
-            input_handler: Some(rx.clone()),
-            delta: 1.0,
-            player: MockCharacter::new(),
-            player_pos: Coordinate::default(),
-            player_speed: 10.0,
-            screen: Arc::new(Mutex::new(MockScreen::new(50, 50))),
-            fps: Duration::from_millis(16),
-        };
+        let mut gs = mock_game_state(rx.clone(), 1.0);
 
         tx.send(Coordinate { x: 1.0, y: 0.0 }).unwrap();
         gs.update(rx).unwrap();
@@ -253,15 +1642,7 @@ mod tests {
     #[test]
     fn test_back_animation() {
         let (tx, rx) = channel::unbounded();
-        let mut gs = GameState {
-            input_handler: Some(rx.clone()),
-            delta: 1.0,
-            player: MockCharacter::new(),
-            player_pos: Coordinate::default(),
-            player_speed: 10.0,
-            screen: Arc::new(Mutex::new(MockScreen::new(50, 50))),
-            fps: Duration::from_millis(16),
-        };
+        let mut gs = mock_game_state(rx.clone(), 1.0);
 
         tx.send(Coordinate { x: 0.0, y: -1.0 }).unwrap();
         gs.update(rx).unwrap();
@@ -271,21 +1652,368 @@ mod tests {
     #[test]
     fn test_front_animation() {
         let (tx, rx) = channel::unbounded();
-        let mut gs = GameState {
-            input_handler: Some(rx.clone()),
-            delta: 1.0,
-            player: MockCharacter::new(),
-            player_pos: Coordinate::default(),
-            player_speed: 10.0,
-            screen: Arc::new(Mutex::new(MockScreen::new(50, 50))),
-            fps: Duration::from_millis(16),
-        };
+        let mut gs = mock_game_state(rx.clone(), 1.0);
 
         tx.send(Coordinate { x: 0.0, y: 1.0 }).unwrap();
         gs.update(rx).unwrap();
         
         assert_eq!(gs.player.animation_trigerred, "front")
     }
+    #[test]
+    fn test_debug_teleport() {
+        let (tx, rx) = channel::unbounded();
+        let (debug_tx, debug_rx) = channel::unbounded();
+        let mut gs = mock_game_state(rx.clone(), 1.0);
+        gs.debug_handler = Some(debug_rx.into());
+
+        tx.send(Coordinate { x: 1.0, y: 0.0 }).unwrap();
+        gs.frame(&rx).unwrap();
+        let target = Coordinate { x: -40.0, y: 25.0 };
+        debug_tx.send(DebugCommand::Teleport(target)).unwrap();
+        debug_tx.send(DebugCommand::ReloadAssets).unwrap();
+        gs.frame(&rx).unwrap();
+
+        // Lands where it was sent, not carried on by the walk before
+        assert_eq!(gs.player_pos, target);
+        assert_eq!(gs.body.velocity, Coordinate::default());
+    }
+    #[test]
+    fn test_time_scale() {
+        let (tx, rx) = channel::unbounded();
+        let mut frozen = mock_game_state(rx.clone(), 0.1);
+        let time_scale = frozen.time_scale();
+        time_scale.set(0.0);
+        tx.send(Coordinate { x: 1.0, y: 0.0 }).unwrap();
+        frozen.frame(&rx).unwrap();
+        assert_eq!(frozen.player_pos, Coordinate::default());
+        assert_eq!(frozen.player.animation_trigerred, "side");
+
+        // Scales on top of the cheat
+        time_scale.set(2.0);
+        frozen.allow_cheats(true);
+        frozen.cheat(Cheat::TimeScale(0.5));
+        let mut normal = mock_game_state(rx.clone(), 0.1);
+        for gs in [&mut frozen, &mut normal] {
+            tx.send(Coordinate { x: 1.0, y: 0.0 }).unwrap();
+            gs.frame(&rx).unwrap();
+        }
+        assert_eq!(frozen.player_pos, normal.player_pos);
+        time_scale.set(f32::NAN);
+        assert_eq!(time_scale.get(), 1.0);
+    }
+
+    #[test]
+    fn test_hit_stop_freezes_on_a_hit() {
+        let (tx, rx) = channel::unbounded();
+        let (hit_tx, hit_rx) = channel::unbounded();
+        let mut gs = mock_game_state(rx.clone(), 0.1);
+        gs.hit_handler = Some(hit_rx.into());
+        gs.hit_stop(HitStop::new(2).zoom(2.0));
+
+        hit_tx.send(Hit { strength: 1.0 }).unwrap();
+        for _ in 0..2 {
+            tx.send(Coordinate { x: 1.0, y: 0.0 }).unwrap();
+            gs.frame(&rx).unwrap();
+            assert_eq!(gs.player_pos, Coordinate::default());
+            assert_eq!(gs.camera.zoom(), 2.0);
+        }
+        tx.send(Coordinate { x: 1.0, y: 0.0 }).unwrap();
+        gs.frame(&rx).unwrap();
+        assert_eq!(gs.player_pos, Coordinate { x: 1.0, y: 0.0 });
+        assert!(gs.camera.zoom() < 2.0);
+    }
+    #[test]
+    fn test_debug_cheats() {
+        let (tx, rx) = channel::unbounded();
+        let (debug_tx, debug_rx) = channel::unbounded();
+        let mut slowed = mock_game_state(rx.clone(), 0.2);
+        slowed.debug_handler = Some(debug_rx.into());
+        slowed.allow_cheats(true);
+        for cheat in [Cheat::TimeScale(0.5), Cheat::Noclip(true)] {
+            debug_tx.send(DebugCommand::Cheat(cheat)).unwrap();
+        }
+        let mut normal = mock_game_state(rx.clone(), 0.1);
+        let (_, co_rx) = channel::unbounded();
+        let mut co_player =
+            CoPlayer::new(MockCharacter::new(), Coordinate { x: 8.0, y: 0.0 }, 10.0);
+        co_player.subscribe(co_rx.into());
+        slowed.co_op(co_player);
+
+        for _ in 0..20 {
+            tx.send(Coordinate { x: 1.0, y: 0.0 }).unwrap();
+            slowed.frame(&rx).unwrap();
+            tx.send(Coordinate { x: 1.0, y: 0.0 }).unwrap();
+            normal.frame(&rx).unwrap();
+        }
+
+        // Twice the frame time at half the speed plays the same
+        assert_eq!(slowed.player_pos, normal.player_pos);
+        // and walks right through player two
+        assert!(slowed.player_pos.x > slowed.co_player.as_ref().unwrap().position().x);
+        assert!(slowed.cheats().noclip());
+    }
+
+    #[test]
+    fn test_debug_overlay() {
+        let (_, rx) = channel::unbounded();
+        let (debug_tx, debug_rx) = channel::unbounded();
+        let mut gs = mock_game_state(rx.clone(), 0.1);
+        gs.debug_handler = Some(debug_rx.into());
+        let door = Rect {
+            x: 20.0,
+            y: 20.0,
+            width: 4.0,
+            height: 4.0,
+        };
+        gs.overlay().mark(Category::Trigger, door);
+        gs.frame(&rx).unwrap();
+        let pixel = |gs: &GameState<HeadlessScreen, MockCharacter>, x, y| {
+            gs.screen.lock().unwrap().pixel(x, y)
+        };
+        let color = |category: Category| {
+            let [r, g, b, a] = category.color().to_rgba();
+            Some(Color::RGBA(r, g, b, a))
+        };
+        assert_ne!(pixel(&gs, 20, 20), color(Category::Trigger));
+
+        for category in [Category::Trigger, Category::Bounds] {
+            debug_tx
+                .send(DebugCommand::Overlay(category, true))
+                .unwrap();
+        }
+        gs.frame(&rx).unwrap();
+        assert_eq!(pixel(&gs, 20, 20), color(Category::Trigger));
+        // The player's frame is outlined over it
+        assert_eq!(pixel(&gs, 0, 0), color(Category::Bounds));
+    }
+
+    #[test]
+    fn test_inspect_clicked_player() {
+        let (_, rx) = channel::unbounded();
+        let (mouse_tx, mouse_rx) = channel::unbounded();
+        let (debug_tx, debug_rx) = channel::unbounded();
+        let mut gs = mock_game_state(rx.clone(), 0.1);
+        gs.mouse_handler = Some(mouse_rx.into());
+        gs.debug_handler = Some(debug_rx.into());
+        let font = "STARTFONT 2.1\nFONT_ASCENT 1\nFONT_DESCENT 0\nENDFONT\n";
+        let font = Arc::new(BitmapFont::from_bdf(font).unwrap());
+        gs.inspector(Inspector::new(font));
+        let click = |x, y| MouseClick {
+            button: MouseButton::Left,
+            state: ElementState::Pressed,
+            position: Coordinate { x, y },
+        };
+        let selected = |gs: &GameState<HeadlessScreen, MockCharacter>| {
+            gs.inspection.as_ref().unwrap().inspector.selected()
+        };
+
+        mouse_tx.send(click(2.0, 2.0)).unwrap();
+        gs.frame(&rx).unwrap();
+        assert_eq!(selected(&gs), Some(Inspected::Player));
+
+        debug_tx.send(DebugCommand::Edit(Field::X, 30.0)).unwrap();
+        gs.frame(&rx).unwrap();
+        assert_eq!(gs.player_pos.x, 30.0);
+
+        // The player isn't where it was clicked anymore
+        mouse_tx.send(click(2.0, 2.0)).unwrap();
+        gs.frame(&rx).unwrap();
+        assert_eq!(selected(&gs), None);
+    }
+
+    #[test]
+    fn test_auto_pause_on_focus_lost() {
+        let (tx, rx) = channel::unbounded();
+        let (focus_tx, focus_rx) = channel::unbounded();
+        let mut gs = mock_game_state(rx.clone(), 1.0);
+        gs.focus_handler = Some(focus_rx.into());
+        gs.auto_pause(true);
+
+        focus_tx.send(WindowFocus::Lost).unwrap();
+        tx.send(Coordinate { x: 1.0, y: 0.0 }).unwrap();
+        gs.update(rx.clone()).unwrap();
+
+        // Movement is discarded while paused
+        assert!(gs.paused);
+        assert_eq!(gs.player_pos, Coordinate::default());
+        assert_eq!(gs.player.animation_trigerred, "");
+
+        focus_tx.send(WindowFocus::Gained).unwrap();
+        tx.send(Coordinate { x: 1.0, y: 0.0 }).unwrap();
+        gs.update(rx).unwrap();
+
+        assert!(!gs.paused);
+        assert_eq!(gs.player.animation_trigerred, "side")
+    }
+    #[test]
+    fn test_sprint_multiplies_speed() {
+        let (tx, rx) = channel::unbounded();
+        let (action_tx, action_rx) = channel::unbounded();
+        let mut gs = mock_game_state(rx.clone(), 1.0);
+        gs.action_handler = Some(action_rx.into());
+
+        action_tx
+            .send(Action {
+                input: GameInput::PlayerSprint,
+                state: ElementState::Pressed,
+            })
+            .unwrap();
+        tx.send(Coordinate { x: 1.0, y: 0.0 }).unwrap();
+        gs.update(rx.clone()).unwrap();
+
+        assert_eq!(gs.player_pos, Coordinate { x: 17.5, y: 0.0 });
+        assert_eq!(gs.stamina.value(), 75.0);
+
+        // Standing still while holding sprint recovers stamina
+        gs.delta = 1.0;
+        gs.update(rx).unwrap();
+        assert_eq!(gs.stamina.value(), 90.0);
+    }
+    #[test]
+    fn test_dash_bursts_along_heading() {
+        let (tx, rx) = channel::unbounded();
+        let (action_tx, action_rx) = channel::unbounded();
+        let mut gs = mock_game_state(rx.clone(), 0.1);
+        gs.action_handler = Some(action_rx.into());
+
+        tx.send(Coordinate { x: 0.0, y: 1.0 }).unwrap();
+        gs.update(rx.clone()).unwrap();
+        assert_eq!(gs.player_pos, Coordinate { x: 0.0, y: 1.0 });
+
+        // Dash while idle still goes down and plays the walk
+        let dash = Action {
+            input: GameInput::PlayerDash,
+            state: ElementState::Pressed,
+        };
+        action_tx.send(dash).unwrap();
+        gs.delta = 0.1;
+        gs.update(rx.clone()).unwrap();
+        assert_eq!(gs.player_pos, Coordinate { x: 0.0, y: 5.0 });
+        assert_eq!(gs.player.animation_trigerred, "front");
+        assert_eq!(gs.stamina.value(), 70.0);
+
+        // Cooldown swallows the next dash while the burst finishes
+        action_tx.send(dash).unwrap();
+        gs.delta = 0.1;
+        gs.update(rx.clone()).unwrap();
+        assert_eq!(gs.stamina.value(), 70.0);
+
+        gs.delta = 0.1;
+        gs.update(rx).unwrap();
+        assert_eq!(gs.player.animation_trigerred, "idle");
+    }
+    #[test]
+    fn test_early_dash_is_buffered() {
+        let (_, rx) = channel::unbounded();
+        let (action_tx, action_rx) = channel::unbounded();
+        let mut gs = mock_game_state(rx.clone(), 0.1);
+        gs.action_handler = Some(action_rx.into());
+        gs.stamina = Stamina::new(100.0, 0.0);
+        let dash = Action {
+            input: GameInput::PlayerDash,
+            state: ElementState::Pressed,
+        };
+
+        action_tx.send(dash).unwrap();
+        gs.update(rx.clone()).unwrap();
+        gs.delta = 0.85;
+        gs.update(rx.clone()).unwrap();
+
+        // Pressed just before the cooldown ends
+        action_tx.send(dash).unwrap();
+        gs.delta = 0.1;
+        gs.update(rx.clone()).unwrap();
+        assert_eq!(gs.stamina.value(), 70.0);
+
+        gs.delta = 0.01;
+        gs.update(rx).unwrap();
+        assert_eq!(gs.stamina.value(), 40.0);
+        assert!(gs.dash.is_active());
+    }
+    #[test]
+    fn test_focus_lost_without_auto_pause() {
+        let (_, rx) = channel::unbounded();
+        let (focus_tx, focus_rx) = channel::unbounded();
+        let mut gs = mock_game_state(rx.clone(), 1.0);
+        gs.focus_handler = Some(focus_rx.into());
+
+        focus_tx.send(WindowFocus::Lost).unwrap();
+        gs.update(rx).unwrap();
+
+        assert!(!gs.paused);
+        assert_eq!(gs.player.animation_trigerred, "idle")
+    }
+    #[test]
+    fn test_game_loop_ends_when_closing() {
+        let (_tx, rx) = channel::unbounded();
+        let (lifecycle_tx, lifecycle_rx) = channel::unbounded();
+        let mut gs = mock_game_state(rx, 1.0);
+        gs.lifecycle_handler = Some(lifecycle_rx.into());
+        let game = gs.start();
+
+        lifecycle_tx
+            .send(WindowLifecycle {
+                window: unsafe { WindowId::dummy() },
+                change: Lifecycle::CloseRequested,
+            })
+            .unwrap();
+        game.join().unwrap();
+    }
+    #[test]
+    fn test_cutscene_takes_over_player() {
+        let (tx, rx) = channel::unbounded();
+        let (action_tx, action_rx) = channel::unbounded();
+        let mut gs = mock_game_state(rx.clone(), 1.0);
+        gs.action_handler = Some(action_rx.into());
+        gs.play_cutscene(Cutscene::parse("move -15 0\nsay Halt!").unwrap());
+
+        // Input is dropped while the player walks left on their own
+        tx.send(Coordinate { x: 0.0, y: 1.0 }).unwrap();
+        action_tx
+            .send(Action {
+                input: GameInput::PlayerDash,
+                state: ElementState::Pressed,
+            })
+            .unwrap();
+        gs.update(rx.clone()).unwrap();
+        assert_eq!(gs.player_pos, Coordinate { x: -10.0, y: 0.0 });
+        assert_eq!(gs.player.animation_trigerred, "side");
+        assert_eq!(gs.stamina.value(), 100.0);
+        gs.delta = 1.0;
+        gs.update(rx.clone()).unwrap();
+        assert_eq!(gs.player_pos, Coordinate { x: -15.0, y: 0.0 });
+
+        let confirm = Action {
+            input: GameInput::MenuConfirm,
+            state: ElementState::Pressed,
+        };
+        action_tx.send(confirm).unwrap();
+        gs.delta = 1.0;
+        gs.update(rx.clone()).unwrap();
+        assert!(gs.cutscene.as_ref().unwrap().is_finished());
+
+        tx.send(Coordinate { x: 0.0, y: 1.0 }).unwrap();
+        gs.delta = 1.0;
+        gs.update(rx).unwrap();
+        assert_eq!(gs.player_pos, Coordinate { x: -15.0, y: 10.0 });
+    }
+    #[test]
+    fn test_cutscene_plays_character_actions() {
+        let (_tx, rx) = channel::unbounded();
+        let mut gs = mock_game_state(rx.clone(), 1.0);
+        gs.play_cutscene(Cutscene::parse("play climb 1.5\nplay sneeze 1").unwrap());
+
+        gs.delta = 1.0;
+        gs.update(rx.clone()).unwrap();
+        assert_eq!(gs.player.animation_trigerred, "climb");
+        // Characters without the action carry on as usual
+        gs.delta = 1.0;
+        gs.update(rx.clone()).unwrap();
+        gs.player.animation_trigerred.clear();
+        gs.delta = 1.0;
+        gs.update(rx).unwrap();
+        assert_eq!(gs.player.animation_trigerred, "idle");
+    }
 }
 
 #[derive(Debug, Error)]
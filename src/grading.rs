@@ -0,0 +1,189 @@
+//! Whole-screen color grading applied as a post-processing step.
+//!
+//! A `Grade` remaps the color of every pixel drawn so far. It's submitted as a
+//! `DrawCmd::Grade` so it runs inside the frame's batch right before the screen
+//! is rendered, and anything queued after it (e.g. the HUD) stays ungraded.
+//!
+//! # Key Structures
+//! - **`Grade`**: A multiply tint, a fade towards a color or a lookup table remap.
+//! - **`ColorLut`**: Per channel lookup tables for arbitrary color curves.
+//! - **`DayNight`**: A clock producing the tint of the current time of day.
+//!
+//! # Example Usage
+//! ```ignore
+//! let mut batch = vec![DrawCmd::Clear];
+//! world_commands(&mut batch);
+//! batch.push(DrawCmd::Grade(day_night.grade()));
+//! // Damage flash
+//! batch.push(DrawCmd::Grade(Grade::Fade { color: RED, amount: 0.4 }));
+//! hud_commands(&mut batch);
+//! screen.draw_batch(&batch)?;
+//! ```
+use std::sync::Arc;
+
+use crate::palette::Color;
+
+/// A remapping of every pixel's color, leaving alpha untouched.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Grade {
+    /// Scales the red, green and blue channels.
+    Multiply([f32; 3]),
+    /// Blends towards `color` by `amount` from `0.0` (unchanged) to `1.0` (solid).
+    Fade { color: Color, amount: f32 },
+    /// Remaps every channel through a lookup table.
+    Lut(Arc<ColorLut>),
+}
+impl Grade {
+    /// Grades every RGBA pixel of `buffer` in place.
+    pub fn apply(&self, buffer: &mut [u8]) {
+        match self {
+            Grade::Multiply(factors) => {
+                let lut = ColorLut::from_fn(|channel, value| {
+                    (value as f32 * factors[channel]).round().clamp(0.0, 255.0) as u8
+                });
+                lut.apply(buffer);
+            }
+            Grade::Fade { color, amount } => {
+                let amount = amount.clamp(0.0, 1.0);
+                if amount == 0.0 {
+                    return;
+                }
+                let target = color.to_rgba();
+                let lut = ColorLut::from_fn(|channel, value| {
+                    let value = value as f32;
+                    (value + (target[channel] as f32 - value) * amount).round() as u8
+                });
+                lut.apply(buffer);
+            }
+            Grade::Lut(lut) => lut.apply(buffer),
+        }
+    }
+}
+
+/// A lookup table per color channel mapping every input value to an output value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorLut {
+    channels: [[u8; 256]; 3],
+}
+impl ColorLut {
+    /// Builds the table from a curve of the channel index (red, green, blue)
+    /// and the input value.
+    pub fn from_fn(curve: impl Fn(usize, u8) -> u8) -> Self {
+        let mut channels = [[0; 256]; 3];
+        for (channel, table) in channels.iter_mut().enumerate() {
+            for (value, out) in table.iter_mut().enumerate() {
+                *out = curve(channel, value as u8);
+            }
+        }
+        Self { channels }
+    }
+    fn apply(&self, buffer: &mut [u8]) {
+        for pixel in buffer.chunks_exact_mut(4) {
+            for (channel, value) in pixel[..3].iter_mut().enumerate() {
+                *value = self.channels[channel][*value as usize];
+            }
+        }
+    }
+}
+
+/// A clock cycling through a day, tinting the world darker and bluer at night.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DayNight {
+    /// Seconds a full day takes.
+    pub length: f32,
+    /// Seconds into the current day, with `0.0` being midday.
+    time: f32,
+    /// Channel factors at midnight.
+    pub night: [f32; 3],
+}
+impl DayNight {
+    pub fn new(length: f32) -> Self {
+        Self {
+            length,
+            time: 0.0,
+            night: [0.35, 0.4, 0.65],
+        }
+    }
+    /// Moves the clock forward by `delta` seconds.
+    pub fn advance(&mut self, delta: f32) {
+        if self.length > 0.0 {
+            self.time = (self.time + delta) % self.length;
+        }
+    }
+    /// How dark it currently is from `0.0` at midday to `1.0` at midnight.
+    pub fn darkness(&self) -> f32 {
+        if self.length <= 0.0 {
+            return 0.0;
+        }
+        let angle = self.time / self.length * std::f32::consts::TAU;
+        (1.0 - angle.cos()) / 2.0
+    }
+    /// The grade for the current time of day.
+    pub fn grade(&self) -> Grade {
+        let darkness = self.darkness();
+        Grade::Multiply(self.night.map(|night| 1.0 + (night - 1.0) * darkness))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{palette::BLACK, prelude::*, renderer::DrawCmd};
+
+    #[test]
+    fn test_grades() {
+        let mut buffer = vec![200, 100, 50, 255, 0, 0, 0, 128];
+
+        Grade::Multiply([0.5, 1.0, 2.0]).apply(&mut buffer);
+        assert_eq!(buffer, vec![100, 100, 100, 255, 0, 0, 0, 128]);
+
+        Grade::Fade {
+            color: Color::RGB(200, 0, 0),
+            amount: 0.5,
+        }
+        .apply(&mut buffer);
+        assert_eq!(buffer, vec![150, 50, 50, 255, 100, 0, 0, 128]);
+
+        let invert = ColorLut::from_fn(|_, value| 255 - value);
+        Grade::Lut(Arc::new(invert)).apply(&mut buffer);
+        assert_eq!(buffer, vec![105, 205, 205, 255, 155, 255, 255, 128]);
+    }
+
+    #[test]
+    fn test_day_night_cycle() {
+        let mut clock = DayNight::new(60.0);
+        assert_eq!(clock.grade(), Grade::Multiply([1.0, 1.0, 1.0]));
+
+        clock.advance(30.0);
+        assert!((clock.darkness() - 1.0).abs() < 1e-6);
+        clock.advance(30.0);
+        assert!(clock.darkness().abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_grade_skips_later_commands() {
+        let mut screen = HeadlessScreen::new(2, 1);
+        screen
+            .draw_batch(&[
+                DrawCmd::Clear,
+                DrawCmd::Span {
+                    start: (0, 0),
+                    len: 2,
+                    color: Color::RGB(100, 100, 100),
+                },
+                DrawCmd::Grade(Grade::Fade {
+                    color: BLACK,
+                    amount: 1.0,
+                }),
+                DrawCmd::Span {
+                    start: (1, 0),
+                    len: 1,
+                    color: Color::RGB(100, 100, 100),
+                },
+            ])
+            .unwrap();
+
+        assert_eq!(&screen.buffer()[0..4], &[0, 0, 0, 255]);
+        assert_eq!(&screen.buffer()[4..8], &[100, 100, 100, 255]);
+    }
+}
@@ -0,0 +1,216 @@
+//! Named collision boxes authored on animation frames.
+//!
+//! Frames can carry hitboxes, the areas an attack connects with on that frame,
+//! and hurtboxes, the areas a character can be hit on. Boxes are authored in the
+//! frame's own pixel space with `FrameBuilder` and placed in screen space with the
+//! same mirroring and offset the frame is drawn with, so a flipped attack swings
+//! the right way.
+//!
+//! # Key Structures
+//! - **`FrameBox`**: A named box in frame space.
+//! - **`BoxKind`**: Whether a box deals or receives hits.
+//! - **`Collider`**: A `FrameBox` placed on screen, ready for overlap tests.
+//! - **`Rect`**: An axis aligned rectangle in screen space.
+//!
+//! # Example Usage
+//! ```ignore
+//! let swing = FrameBuilder::new()
+//!     .pixels(pixels)
+//!     .hurtbox("body", 2, 0, 6, 12)
+//!     .hitbox("sword", 8, 4, 5, 2)
+//!     .build();
+//!
+//! for hit in attacker.animation().colliders(mirror, attacker_pos) {
+//!     if hit.kind == BoxKind::Hit && target_colliders.iter().any(|t| hit.hits(t)) {
+//!         // ...
+//!     }
+//! }
+//! ```
+use crate::layout::{Coordinate, MirrorDirectionValue};
+
+/// Whether a box deals or receives hits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoxKind {
+    /// Deals damage to overlapping hurtboxes.
+    Hit,
+    /// Can be damaged by overlapping hitboxes.
+    Hurt,
+}
+
+/// A named box in the pixel space of a single frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameBox {
+    pub name: String,
+    pub kind: BoxKind,
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+}
+impl FrameBox {
+    /// The box on screen for a frame drawn with `mirror` at `offset`.
+    pub fn place(&self, mirror: &MirrorDirectionValue, offset: Coordinate) -> Collider<'_> {
+        // Pixels mirror as `size - x` so the far edge of the box becomes its near edge
+        let (x, y) = match *mirror {
+            MirrorDirectionValue::FlipVertical(width) => (
+                width as f32 - (self.x + self.width) as f32 + 1.0,
+                self.y as f32,
+            ),
+            MirrorDirectionValue::FlipHorizontal(height) => (
+                self.x as f32,
+                height as f32 - (self.y + self.height) as f32 + 1.0,
+            ),
+            MirrorDirectionValue::None => (self.x as f32, self.y as f32),
+        };
+        Collider {
+            name: &self.name,
+            kind: self.kind,
+            rect: Rect {
+                x: offset.x + x,
+                y: offset.y + y,
+                width: self.width.into(),
+                height: self.height.into(),
+            },
+        }
+    }
+}
+
+/// A frame's box placed on screen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Collider<'a> {
+    pub name: &'a str,
+    pub kind: BoxKind,
+    pub rect: Rect,
+}
+impl Collider<'_> {
+    /// Whether this is a hitbox overlapping the hurtbox `other`.
+    pub fn hits(&self, other: &Collider) -> bool {
+        self.kind == BoxKind::Hit && other.kind == BoxKind::Hurt && self.rect.overlaps(&other.rect)
+    }
+}
+
+/// An axis aligned rectangle in screen space.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+impl Rect {
+    /// Whether the two rectangles share any area, touching edges excluded.
+    pub fn overlaps(&self, other: &Rect) -> bool {
+        self.x < other.x + other.width
+            && other.x < self.x + self.width
+            && self.y < other.y + other.height
+            && other.y < self.y + self.height
+    }
+    /// Whether `point` is inside the rectangle, e.g. to pick what was clicked.
+    pub fn contains(&self, point: Coordinate) -> bool {
+        (self.x..self.x + self.width).contains(&point.x)
+            && (self.y..self.y + self.height).contains(&point.y)
+    }
+    /// The shortest move that takes this rectangle out of `other`, if they
+    /// overlap.
+    pub fn penetration(&self, other: &Rect) -> Option<Coordinate> {
+        if !self.overlaps(other) {
+            return None;
+        }
+        let x = (self.x + self.width).min(other.x + other.width) - self.x.max(other.x);
+        let y = (self.y + self.height).min(other.y + other.height) - self.y.max(other.y);
+        // Pushed away from the other's center, or right and down from the same one
+        let away = |from: f32, to: f32| if from < to { -1.0 } else { 1.0 };
+        Some(if x <= y {
+            Coordinate {
+                x: x * away(self.x + self.width / 2.0, other.x + other.width / 2.0),
+                y: 0.0,
+            }
+        } else {
+            Coordinate {
+                x: 0.0,
+                y: y * away(self.y + self.height / 2.0, other.y + other.height / 2.0),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sword() -> FrameBox {
+        FrameBox {
+            name: "sword".into(),
+            kind: BoxKind::Hit,
+            x: 6,
+            y: 2,
+            width: 3,
+            height: 1,
+        }
+    }
+
+    #[test]
+    fn test_place_with_mirroring() {
+        let offset = Coordinate { x: 10.0, y: 20.0 };
+        let sword = sword();
+
+        let placed = sword.place(&MirrorDirectionValue::None, offset);
+        assert_eq!((placed.rect.x, placed.rect.y), (16.0, 22.0));
+
+        // Pixels 6..=8 of a frame 8 wide mirror onto 0..=2
+        let placed = sword.place(&MirrorDirectionValue::FlipVertical(8), offset);
+        assert_eq!((placed.rect.x, placed.rect.y), (10.0, 22.0));
+
+        let placed = sword.place(&MirrorDirectionValue::FlipHorizontal(4), offset);
+        assert_eq!((placed.rect.x, placed.rect.y), (16.0, 22.0));
+        assert_eq!(placed.name, "sword");
+    }
+
+    #[test]
+    fn test_hits_only_hurtboxes() {
+        let sword = sword();
+        let hit = sword.place(&MirrorDirectionValue::None, Coordinate::default());
+        let mut body = sword.clone();
+        body.kind = BoxKind::Hurt;
+        let hurt = body.place(&MirrorDirectionValue::None, Coordinate { x: 2.0, y: 0.0 });
+        let away = body.place(&MirrorDirectionValue::None, Coordinate { x: 3.0, y: 0.0 });
+
+        assert!(hit.hits(&hurt));
+        assert!(!hurt.hits(&hit));
+        assert!(!hit.hits(&away));
+        assert!(!hit.hits(&hit));
+    }
+
+    #[test]
+    fn test_penetration() {
+        let rect = |x, y| Rect {
+            x,
+            y,
+            width: 4.0,
+            height: 4.0,
+        };
+        assert_eq!(rect(0.0, 0.0).penetration(&rect(4.0, 0.0)), None);
+        assert_eq!(
+            rect(0.0, 0.0).penetration(&rect(3.0, 1.0)),
+            Some(Coordinate { x: -1.0, y: 0.0 })
+        );
+        assert_eq!(
+            rect(1.0, 3.0).penetration(&rect(0.0, 0.0)),
+            Some(Coordinate { x: 0.0, y: 1.0 })
+        );
+        let [a, b] = [rect(2.0, 2.0); 2];
+        assert_eq!(a.penetration(&b), Some(Coordinate { x: 4.0, y: 0.0 }));
+    }
+
+    #[test]
+    fn test_contains() {
+        let rect = Rect {
+            x: 1.0,
+            y: 1.0,
+            width: 2.0,
+            height: 2.0,
+        };
+        assert!(rect.contains(Coordinate { x: 1.0, y: 2.5 }));
+        assert!(!rect.contains(Coordinate { x: 3.0, y: 2.0 }));
+    }
+}
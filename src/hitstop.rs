@@ -0,0 +1,172 @@
+//! A brief freeze when an attack connects, to give hits weight.
+//!
+//! Whatever resolves combat publishes a `Hit` on the `EventBus` once a hitbox
+//! meets a hurtbox. `GameState` answers it by freezing the simulation for a
+//! few ticks, scaling the delta of each to nothing on top of its `TimeScale`,
+//! and can punch the camera in and flash the screen while it does. Hits landing
+//! during a freeze make it last as long as the strongest of them, never longer.
+//!
+//! # Key Structures
+//! - **`Hit`**: An attack connecting, published by combat.
+//! - **`HitStop`**: How long hits freeze the game for and what else they do.
+//!
+//! # Example Usage
+//! ```ignore
+//! state.hit_stop(HitStop::new(4).zoom(1.1).flash(Color::RGB(255, 255, 255)));
+//! bus.subscribe::<Hit>(&mut state, 16, Backpressure::DropOldest);
+//!
+//! // Wherever combat is resolved
+//! if hit.hits(&hurtbox) {
+//!     hits.publish(Hit { strength: 1.5 });
+//! }
+//! ```
+use crate::{camera::Camera, palette::Color, renderer::DrawCmd};
+
+/// Seconds the camera takes to zoom back out after a freeze.
+const RECOVERY: f32 = 0.1;
+
+/// An attack connecting, published by combat.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hit {
+    /// How hard the attack hit, scaling the freeze, where `1.0` is a normal
+    /// hit.
+    pub strength: f32,
+}
+
+/// How long hits freeze the game for and what else they do.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HitStop {
+    ticks: u32,
+    zoom: Option<f32>,
+    flash: Option<Color>,
+    /// Ticks left frozen
+    remaining: u32,
+    /// The camera zoom to go back to once unfrozen
+    zoomed_from: Option<f32>,
+    /// Whether the flash is due in the next frame drawn
+    flashing: bool,
+}
+impl Default for HitStop {
+    fn default() -> Self {
+        Self::new(3)
+    }
+}
+impl HitStop {
+    /// Freezes for `ticks` on a normal hit, without zooming or flashing.
+    pub fn new(ticks: u32) -> Self {
+        Self {
+            ticks,
+            zoom: None,
+            flash: None,
+            remaining: 0,
+            zoomed_from: None,
+            flashing: false,
+        }
+    }
+    /// Zooms the camera in by `zoom` times while frozen.
+    pub fn zoom(mut self, zoom: f32) -> Self {
+        self.zoom = Some(zoom);
+        self
+    }
+    /// Fills the screen with `color` for the first frame of a freeze.
+    pub fn flash(mut self, color: Color) -> Self {
+        self.flash = Some(color);
+        self
+    }
+    /// Freezes the game for `hit`, or longer if frozen for a stronger one.
+    pub fn hit(&mut self, hit: Hit) {
+        let ticks = (self.ticks as f32 * hit.strength.max(0.0)).round() as u32;
+        if self.remaining == 0 && ticks > 0 {
+            self.flashing = self.flash.is_some();
+        }
+        self.remaining = self.remaining.max(ticks);
+    }
+    pub fn is_frozen(&self) -> bool {
+        self.remaining > 0
+    }
+    /// Ticks left frozen.
+    pub fn remaining(&self) -> u32 {
+        self.remaining
+    }
+    pub fn set_remaining(&mut self, remaining: u32) {
+        self.remaining = remaining;
+    }
+    /// Moves on a tick, zooming `camera` as the freeze starts and ends.
+    ///
+    /// Returns how much of the tick's time passes, `0.0` while frozen
+    pub fn update(&mut self, camera: &mut Camera) -> f32 {
+        if self.remaining == 0 {
+            if let Some(zoom) = self.zoomed_from.take() {
+                camera.zoom_to(zoom, RECOVERY);
+            }
+            return 1.0;
+        }
+        if let Some(zoom) = self.zoom.filter(|_| self.zoomed_from.is_none()) {
+            self.zoomed_from = Some(camera.zoom());
+            camera.zoom_to(camera.zoom() * zoom, 0.0);
+        }
+        self.remaining -= 1;
+        0.0
+    }
+    /// Queues the flash, if one is due, over a `width` by `height` screen.
+    pub fn commands<'a>(&mut self, width: u32, height: u32, batch: &mut Vec<DrawCmd<'a>>) {
+        let Some(color) = self.flash.filter(|_| self.flashing) else {
+            return;
+        };
+        self.flashing = false;
+        batch.push(DrawCmd::Rect {
+            start: (0, 0),
+            width,
+            height,
+            color,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::palette::RED;
+
+    #[test]
+    fn test_freeze_lasts_as_long_as_the_strongest_hit() {
+        let mut camera = Camera::new(10, 10);
+        let mut hit_stop = HitStop::new(2);
+        assert_eq!(hit_stop.update(&mut camera), 1.0);
+
+        hit_stop.hit(Hit { strength: 2.0 });
+        assert_eq!(hit_stop.update(&mut camera), 0.0);
+        // A weaker hit doesn't add to what's left
+        hit_stop.hit(Hit { strength: 1.0 });
+        for _ in 0..3 {
+            assert_eq!(hit_stop.update(&mut camera), 0.0);
+        }
+        assert!(!hit_stop.is_frozen());
+        assert_eq!(hit_stop.update(&mut camera), 1.0);
+
+        hit_stop.hit(Hit { strength: 0.0 });
+        assert!(!hit_stop.is_frozen());
+    }
+
+    #[test]
+    fn test_zoom_and_flash() {
+        let mut camera = Camera::new(10, 10);
+        let mut hit_stop = HitStop::new(1).zoom(2.0).flash(RED);
+        hit_stop.hit(Hit { strength: 1.0 });
+        hit_stop.update(&mut camera);
+        camera.update(0.0, Default::default());
+        assert_eq!(camera.zoom(), 2.0);
+
+        let mut batch = Vec::new();
+        hit_stop.commands(10, 4, &mut batch);
+        assert_eq!(batch.len(), 1);
+        // Only the first frame flashes
+        batch.clear();
+        hit_stop.commands(10, 4, &mut batch);
+        assert!(batch.is_empty());
+
+        hit_stop.update(&mut camera);
+        camera.update(RECOVERY, Default::default());
+        assert_eq!(camera.zoom(), 1.0);
+    }
+}
@@ -0,0 +1,73 @@
+//! Heads-up display elements drawn over the game world.
+//!
+//! HUD elements are drawn in screen space after the world has been drawn and
+//! before the screen is rendered, so they always stay on top and never move
+//! with the player.
+//!
+//! # Key Structures
+//! - **`StaminaBar`**: A horizontal bar showing the player's remaining `Stamina`.
+use crate::{
+    movement::Stamina,
+    palette::{GREEN, MIDNIGHT},
+    renderer::DrawCmd,
+};
+
+/// A horizontal bar filled proportionally to the remaining stamina.
+pub(crate) struct StaminaBar {
+    x: u16,
+    y: u16,
+    width: u16,
+}
+impl StaminaBar {
+    pub(crate) fn new(x: u16, y: u16, width: u16) -> Self {
+        Self { x, y, width }
+    }
+    /// Queues the bar for the given stamina onto a batch of draw commands.
+    pub(crate) fn commands(&self, stamina: &Stamina, batch: &mut Vec<DrawCmd>) {
+        let filled = (self.width as f32 * stamina.ratio()).round() as u16;
+        let filled = filled.min(self.width);
+        if filled > 0 {
+            batch.push(DrawCmd::Span {
+                start: (self.x.into(), self.y.into()),
+                len: filled.into(),
+                color: GREEN,
+            });
+        }
+        if filled < self.width {
+            batch.push(DrawCmd::Span {
+                start: ((self.x + filled).into(), self.y.into()),
+                len: (self.width - filled).into(),
+                color: MIDNIGHT,
+            });
+        }
+    }
+}
+impl Default for StaminaBar {
+    fn default() -> Self {
+        Self::new(2, 2, 20)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{prelude::*, window::HeadlessScreen};
+
+    #[test]
+    fn test_stamina_bar_fill() {
+        let mut screen = HeadlessScreen::new(50, 50);
+        let mut stamina = Stamina::new(100.0, 0.0);
+        stamina.drain(25.0);
+
+        let mut batch = Vec::new();
+        StaminaBar::new(0, 0, 8).commands(&stamina, &mut batch);
+        screen.draw_batch(&batch).unwrap();
+
+        // Green for the 6 remaining segments and dark for the empty ones
+        for x in 0..8 {
+            let idx = x * 4;
+            let expected = if x < 6 { GREEN } else { MIDNIGHT };
+            assert_eq!(&screen.buffer()[idx..idx + 4], &expected.to_rgba());
+        }
+    }
+}
@@ -5,11 +5,15 @@
 //! - Mapping physical keys (e.g., `WASD` or arrow keys) to high-level game actions (e.g., `PlayerMoveUp`).
 //! - Handling key press and release events to determine player actions, such as movement direction.
 //! - Supporting remapping of keys for customizable controls.
+//! - Giving two local players their own bindings on a shared keyboard.
 //! - Translating key events into movement coordinates for game logic.
+//! - Translating key events into discrete actions (e.g., sprinting or dashing).
+//! - Detecting timed gestures like double-taps and chords as actions of their own.
+//! - Translating typed keys and IME compositions into `TextInput` while text entry is enabled.
 //!
 //! # Example
 //!
-//! ```
+//! ```ignore
 //! use crate::layout::{Coordinate, GameInputHandler, GameInput};
 //! use winit::keyboard::KeyCode;
 //!
@@ -24,14 +28,21 @@
 //!     state: ElementState::Pressed,
 //!     code: KeyCode::W.into(),
 //! });
-//! let movement = input_handler.to_coordinate(input);
+//! let movement = input_handler.track_coordinate(input);
 //! assert_eq!(movement, Some(Coordinate { x: 0.0, y: -1.0 }));
 //! ```
 use crate::layout::Coordinate;
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
 use winit::{
     event::ElementState,
-    keyboard::{KeyCode, PhysicalKey},
+    keyboard::{Key, KeyCode, NamedKey, PhysicalKey},
 };
 
 /// Responsible for abstracting and centralizing input management for player controls.
@@ -42,13 +53,70 @@ use winit::{
 pub(crate) struct GameInputHandler {
     binding: HashMap<GameInput, PhysicalKey>,
     mapping: HashSet<PhysicalKey>,
+    gestures: Vec<Gesture>,
+    /// When each input was last freshly pressed
+    presses: HashMap<GameInput, Instant>,
+    /// Whether the arrow keys navigate menus whatever they're bound to
+    arrow_navigation: bool,
 }
 impl GameInputHandler {
+    /// The default bindings of a local `player`, so two players can share a
+    /// keyboard.
+    ///
+    /// Player one moves with the arrow keys and player two with `WASD`
+    pub(crate) fn for_player(player: Player) -> Self {
+        let binding = match player {
+            Player::One => [
+                (GameInput::PlayerMoveUp, KeyCode::ArrowUp),
+                (GameInput::PlayerMoveLeft, KeyCode::ArrowLeft),
+                (GameInput::PlayerMoveRight, KeyCode::ArrowRight),
+                (GameInput::PlayerMoveDown, KeyCode::ArrowDown),
+                (GameInput::PlayerSprint, KeyCode::ShiftLeft),
+                (GameInput::PlayerDash, KeyCode::Space),
+                (GameInput::MenuConfirm, KeyCode::Enter),
+                (GameInput::MenuBack, KeyCode::Escape),
+            ],
+            Player::Two => [
+                (GameInput::PlayerMoveUp, KeyCode::KeyW),
+                (GameInput::PlayerMoveLeft, KeyCode::KeyA),
+                (GameInput::PlayerMoveRight, KeyCode::KeyD),
+                (GameInput::PlayerMoveDown, KeyCode::KeyS),
+                (GameInput::PlayerSprint, KeyCode::KeyQ),
+                (GameInput::PlayerDash, KeyCode::KeyE),
+                (GameInput::MenuConfirm, KeyCode::KeyF),
+                (GameInput::MenuBack, KeyCode::KeyR),
+            ],
+        };
+        Self {
+            binding: binding
+                .into_iter()
+                .map(|(input, code)| (input, PhysicalKey::Code(code)))
+                .collect(),
+            mapping: HashSet::new(),
+            // Double-tapping a direction dashes that way
+            gestures: [
+                GameInput::PlayerMoveUp,
+                GameInput::PlayerMoveLeft,
+                GameInput::PlayerMoveRight,
+                GameInput::PlayerMoveDown,
+            ]
+            .into_iter()
+            .map(|input| Gesture::DoubleTap {
+                input,
+                window: DOUBLE_TAP_WINDOW,
+                emit: GameInput::PlayerDash,
+            })
+            .collect(),
+            presses: HashMap::new(),
+            // Menus are navigated by player one
+            arrow_navigation: player == Player::One,
+        }
+    }
     /// Converts a raw key event into a coordinate, if it matches a known input mapping.
     ///
     /// UI overlay and Player actions consume these coordinates
-    pub(crate) fn to_coordinate(&mut self, key: Input) -> Option<Coordinate> {
-        let coordinate = match key {
+    pub(crate) fn track_coordinate(&mut self, key: Input) -> Option<Coordinate> {
+        match key {
             Input::PhysicalKey(key) => {
                 if key.state == ElementState::Pressed {
                     self.mapping.insert(key.code);
@@ -75,29 +143,131 @@ impl GameInputHandler {
                     && self.is_held(&GameInput::PlayerMoveRight)
                 {
                     Some(Coordinate { x: 1.0, y: -1.0 })
-                } else if self.is_held(&GameInput::PlayerMoveLeft) || key.code == KeyCode::ArrowLeft
+                } else if self.is_held(&GameInput::PlayerMoveLeft)
+                    || self.is_arrow(&key.code, KeyCode::ArrowLeft)
                 {
                     Some(Coordinate { x: -1.0, y: 0.0 })
                 } else if self.is_held(&GameInput::PlayerMoveRight)
-                    || key.code == KeyCode::ArrowRight
+                    || self.is_arrow(&key.code, KeyCode::ArrowRight)
                 {
                     Some(Coordinate { x: 1.0, y: 0.0 })
-                } else if self.is_held(&GameInput::PlayerMoveUp) || key.code == KeyCode::ArrowUp {
+                } else if self.is_held(&GameInput::PlayerMoveUp)
+                    || self.is_arrow(&key.code, KeyCode::ArrowUp)
+                {
                     Some(Coordinate { x: 0.0, y: -1.0 })
-                } else if self.is_held(&GameInput::PlayerMoveDown) || key.code == KeyCode::ArrowDown
+                } else if self.is_held(&GameInput::PlayerMoveDown)
+                    || self.is_arrow(&key.code, KeyCode::ArrowDown)
                 {
                     Some(Coordinate { x: 0.0, y: 1.0 })
                 } else {
                     None
                 }
             }
-        };
+        }
+    }
+    /// Converts a raw key event into an `Action`, if it matches a known action binding.
+    ///
+    /// Must be called before `track_coordinate` for the same event so repeated
+    /// presses of an already held key can be ignored
+    pub(crate) fn to_action(&self, key: &Input) -> Option<Action> {
+        match key {
+            Input::PhysicalKey(key) => {
+                if key.state == ElementState::Pressed && self.mapping.contains(&key.code) {
+                    return None;
+                }
+                [
+                    GameInput::PlayerSprint,
+                    GameInput::PlayerDash,
+                    GameInput::MenuConfirm,
+                    GameInput::MenuBack,
+                ]
+                .into_iter()
+                .find(|input| *self.get_binding(input) == key.code)
+                .map(|input| Action {
+                    input,
+                    state: key.state,
+                })
+            }
+        }
+    }
+    /// Converts a raw key event pressed at `now` into the actions of every
+    /// gesture it completes.
+    ///
+    /// Like `to_action`, it must be called before `track_coordinate` for the same
+    /// event so key repeats don't count as taps
+    pub(crate) fn track_gestures(&mut self, key: &Input, now: Instant) -> Vec<Action> {
+        let Input::PhysicalKey(key) = key;
+        if key.state == ElementState::Released || self.mapping.contains(&key.code) {
+            return Vec::new();
+        }
+        let pressed: Vec<_> = self
+            .binding
+            .iter()
+            .filter(|(_, code)| **code == key.code)
+            .map(|(input, _)| *input)
+            .collect();
 
-        coordinate
+        let mut actions = Vec::new();
+        for input in pressed {
+            let within = |other: &GameInput, window: Duration| {
+                self.presses
+                    .get(other)
+                    .is_some_and(|at| now.duration_since(*at) <= window)
+            };
+            let mut tapped = false;
+            for gesture in &self.gestures {
+                let emit = match *gesture {
+                    Gesture::DoubleTap {
+                        input: tap,
+                        window,
+                        emit,
+                    } if tap == input && within(&tap, window) => {
+                        tapped = true;
+                        emit
+                    }
+                    Gesture::Chord {
+                        inputs: [a, b],
+                        window,
+                        emit,
+                    } if (a == input && within(&b, window))
+                        || (b == input && within(&a, window)) =>
+                    {
+                        emit
+                    }
+                    _ => continue,
+                };
+                actions.push(Action {
+                    input: emit,
+                    state: ElementState::Pressed,
+                });
+            }
+            // A third tap starts a new double-tap rather than completing another
+            if tapped {
+                self.presses.remove(&input);
+            } else {
+                self.presses.insert(input, now);
+            }
+        }
+        actions
+    }
+    /// Detects `gesture` from now on besides the default ones.
+    pub(crate) fn add_gesture(&mut self, gesture: Gesture) {
+        self.gestures.push(gesture);
+    }
+    fn is_arrow(&self, code: &PhysicalKey, arrow: KeyCode) -> bool {
+        self.arrow_navigation && *code == arrow
+    }
+    /// Releases every key currently considered held.
+    ///
+    /// Used when the window loses focus since the matching key release
+    /// events will never be delivered to us
+    pub(crate) fn reset(&mut self) {
+        self.mapping.clear();
+        self.presses.clear();
     }
     pub(crate) fn is_held(&self, input: &GameInput) -> bool {
         let binding = self.get_binding(input);
-        self.mapping.get(binding).is_some()
+        self.mapping.contains(binding)
     }
     pub(crate) fn get_binding(&self, input: &GameInput) -> &PhysicalKey {
         self.binding.get(input).unwrap()
@@ -108,28 +278,32 @@ impl GameInputHandler {
 }
 impl Default for GameInputHandler {
     fn default() -> Self {
-        Self {
-            binding: [
-                (GameInput::PlayerMoveUp, PhysicalKey::Code(KeyCode::ArrowUp)),
-                (
-                    GameInput::PlayerMoveLeft,
-                    PhysicalKey::Code(KeyCode::ArrowLeft),
-                ),
-                (
-                    GameInput::PlayerMoveRight,
-                    PhysicalKey::Code(KeyCode::ArrowRight),
-                ),
-                (
-                    GameInput::PlayerMoveDown,
-                    PhysicalKey::Code(KeyCode::ArrowDown),
-                ),
-            ]
-            .into(),
-            mapping: HashSet::new(),
-        }
+        Self::for_player(Player::One)
     }
 }
 
+/// How quickly a direction must be tapped twice to dash.
+const DOUBLE_TAP_WINDOW: Duration = Duration::from_millis(250);
+
+/// A timed pattern of presses emitting an action of its own once performed.
+///
+/// Gestures only emit presses since they have no matching release.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Gesture {
+    /// The same input pressed twice within `window`.
+    DoubleTap {
+        input: GameInput,
+        window: Duration,
+        emit: GameInput,
+    },
+    /// Two inputs pressed within `window` of each other in either order.
+    Chord {
+        inputs: [GameInput; 2],
+        window: Duration,
+        emit: GameInput,
+    },
+}
+
 /// Represents a high-level abstraction of user input events.
 ///
 /// Used to decouple game logic from raw platform-specific input events.
@@ -144,12 +318,73 @@ pub(crate) struct PhysicalKeyInfo {
 }
 
 /// Stores a comprehensive list of all accepted input actions
-#[derive(PartialEq, Eq, Hash)]
-pub(crate) enum GameInput {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameInput {
     PlayerMoveUp,
     PlayerMoveLeft,
     PlayerMoveRight,
     PlayerMoveDown,
+    PlayerSprint,
+    PlayerDash,
+    MenuConfirm,
+    MenuBack,
+}
+
+/// One of the players sharing the same machine.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Player {
+    #[default]
+    One,
+    Two,
+}
+
+/// A non-directional `GameInput` that was pressed or released.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Action {
+    pub input: GameInput,
+    pub state: ElementState,
+}
+
+/// Text typed while text entry is enabled.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextInput {
+    /// Text typed or committed by an IME.
+    Insert(String),
+    /// Text an IME is still composing, replacing the previous composition.
+    Compose(String),
+    /// Removes the last character.
+    Backspace,
+}
+impl TextInput {
+    /// The text input of a key press producing `text`, if it produces any.
+    ///
+    /// Control characters like tabs are dropped, confirming and cancelling
+    /// are left to the `MenuConfirm` and `MenuBack` actions
+    pub(crate) fn from_key(key: &Key, text: Option<&str>, state: ElementState) -> Option<Self> {
+        if state == ElementState::Released {
+            return None;
+        }
+        if *key == Key::Named(NamedKey::Backspace) {
+            return Some(TextInput::Backspace);
+        }
+        let text: String = text?.chars().filter(|c| !c.is_control()).collect();
+        (!text.is_empty()).then_some(TextInput::Insert(text))
+    }
+}
+
+/// A shared switch between gameplay input and text entry.
+///
+/// While enabled, typed keys are delivered as `TextInput` instead of
+/// movement and actions, except for confirming and cancelling.
+#[derive(Debug, Clone, Default)]
+pub struct TextMode(Arc<AtomicBool>);
+impl TextMode {
+    pub fn enable(&self, enabled: bool) {
+        self.0.store(enabled, Ordering::Relaxed);
+    }
+    pub fn is_enabled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
 }
 
 #[cfg(test)]
@@ -158,7 +393,7 @@ mod tests {
     use winit::event::ElementState;
 
     #[test]
-    fn test_to_coordinate_with_physical_key() {
+    fn test_track_coordinate_with_physical_key() {
         let mut handler = GameInputHandler::default();
         handler.update_binding(&GameInput::PlayerMoveUp, PhysicalKey::Code(KeyCode::KeyW));
         handler.update_binding(&GameInput::PlayerMoveLeft, PhysicalKey::Code(KeyCode::KeyA));
@@ -416,8 +651,157 @@ mod tests {
         ];
 
         for (input, expected_coord) in test_cases {
-            let result = handler.to_coordinate(input.clone());
+            let result = handler.track_coordinate(input.clone());
             assert_eq!(result, expected_coord, "Failed for {:?}", input);
         }
     }
+
+    #[test]
+    fn test_to_action() {
+        let mut handler = GameInputHandler::default();
+        let press = Input::PhysicalKey(PhysicalKeyInfo {
+            state: ElementState::Pressed,
+            code: PhysicalKey::Code(KeyCode::ShiftLeft),
+        });
+        let release = Input::PhysicalKey(PhysicalKeyInfo {
+            state: ElementState::Released,
+            code: PhysicalKey::Code(KeyCode::ShiftLeft),
+        });
+
+        assert_eq!(
+            handler.to_action(&press),
+            Some(Action {
+                input: GameInput::PlayerSprint,
+                state: ElementState::Pressed
+            })
+        );
+        handler.track_coordinate(press.clone());
+
+        // Key repeats of a held key are not new actions
+        assert_eq!(handler.to_action(&press), None);
+
+        assert_eq!(
+            handler.to_action(&release),
+            Some(Action {
+                input: GameInput::PlayerSprint,
+                state: ElementState::Released
+            })
+        );
+
+        // Movement keys are not actions
+        let arrow = Input::PhysicalKey(PhysicalKeyInfo {
+            state: ElementState::Pressed,
+            code: PhysicalKey::Code(KeyCode::ArrowUp),
+        });
+        assert_eq!(handler.to_action(&arrow), None);
+    }
+
+    #[test]
+    fn test_reset_releases_held_keys() {
+        let mut handler = GameInputHandler::default();
+
+        // Hold left then lose focus before the release arrives
+        handler.track_coordinate(Input::PhysicalKey(PhysicalKeyInfo {
+            state: ElementState::Pressed,
+            code: PhysicalKey::Code(KeyCode::ArrowLeft),
+        }));
+        assert!(handler.is_held(&GameInput::PlayerMoveLeft));
+        handler.reset();
+        assert!(!handler.is_held(&GameInput::PlayerMoveLeft));
+
+        // Stale left key must not combine with a fresh key press
+        let result = handler.track_coordinate(Input::PhysicalKey(PhysicalKeyInfo {
+            state: ElementState::Pressed,
+            code: PhysicalKey::Code(KeyCode::ArrowUp),
+        }));
+        assert_eq!(result, Some(Coordinate { x: 0.0, y: -1.0 }));
+    }
+
+    #[test]
+    fn test_players_share_the_keyboard() {
+        let press = |code: KeyCode| {
+            Input::PhysicalKey(PhysicalKeyInfo {
+                state: ElementState::Pressed,
+                code: PhysicalKey::Code(code),
+            })
+        };
+        let mut one = GameInputHandler::for_player(Player::One);
+        let mut two = GameInputHandler::for_player(Player::Two);
+
+        // Player one's arrows don't move player two
+        assert_eq!(two.track_coordinate(press(KeyCode::ArrowLeft)), None);
+        assert_eq!(
+            two.track_coordinate(press(KeyCode::KeyW)),
+            Some(Coordinate { x: 0.0, y: -1.0 })
+        );
+        assert_eq!(one.track_coordinate(press(KeyCode::KeyW)), None);
+        assert_eq!(
+            two.to_action(&press(KeyCode::KeyE)).map(|a| a.input),
+            Some(GameInput::PlayerDash)
+        );
+        assert_eq!(one.to_action(&press(KeyCode::KeyE)), None);
+    }
+
+    #[test]
+    fn test_double_tap_and_chord() {
+        let mut handler = GameInputHandler::default();
+        handler.add_gesture(Gesture::Chord {
+            inputs: [GameInput::PlayerSprint, GameInput::MenuBack],
+            window: Duration::from_millis(50),
+            emit: GameInput::MenuConfirm,
+        });
+        let start = Instant::now();
+        let mut tap = |code: KeyCode, ms: u64| {
+            let mut gestures = Vec::new();
+            for state in [ElementState::Pressed, ElementState::Released] {
+                let input = Input::PhysicalKey(PhysicalKeyInfo {
+                    state,
+                    code: PhysicalKey::Code(code),
+                });
+                gestures.extend(
+                    handler
+                        .track_gestures(&input, start + Duration::from_millis(ms))
+                        .into_iter()
+                        .map(|a| a.input),
+                );
+                handler.track_coordinate(input);
+            }
+            gestures
+        };
+
+        assert_eq!(tap(KeyCode::ArrowLeft, 0), []);
+        assert_eq!(tap(KeyCode::ArrowLeft, 200), [GameInput::PlayerDash]);
+        // A third tap only starts over
+        assert_eq!(tap(KeyCode::ArrowLeft, 300), []);
+        assert_eq!(tap(KeyCode::ArrowRight, 400), []);
+        assert_eq!(tap(KeyCode::ArrowRight, 700), []);
+
+        assert_eq!(tap(KeyCode::Escape, 1000), []);
+        assert_eq!(tap(KeyCode::ShiftLeft, 1040), [GameInput::MenuConfirm]);
+        assert_eq!(tap(KeyCode::ShiftLeft, 2000), []);
+    }
+
+    #[test]
+    fn test_text_from_key() {
+        let typed = |key: Key, text| TextInput::from_key(&key, text, ElementState::Pressed);
+
+        assert_eq!(
+            typed(Key::Character("a".into()), Some("a")),
+            Some(TextInput::Insert("a".into()))
+        );
+        assert_eq!(
+            typed(Key::Named(NamedKey::Backspace), Some("\u{8}")),
+            Some(TextInput::Backspace)
+        );
+        assert_eq!(typed(Key::Named(NamedKey::Tab), Some("\t")), None);
+        assert_eq!(typed(Key::Named(NamedKey::ArrowLeft), None), None);
+        assert_eq!(
+            TextInput::from_key(
+                &Key::Character("a".into()),
+                Some("a"),
+                ElementState::Released
+            ),
+            None
+        );
+    }
 }
@@ -0,0 +1,213 @@
+//! A panel showing what a clicked character is made of, updated live.
+//!
+//! While an `Inspector` is set up, clicking a character picks it and a panel
+//! in the corner of the screen lists its `Components`: where it is, how fast
+//! it moves, its health, the animation playing and the frame shown. The panel
+//! is drawn from the character's state every frame, so it follows along as
+//! the game plays. Clicking anywhere else closes it.
+//!
+//! Components are edited from the debug console, e.g. `edit x 40` moves the
+//! inspected character and `edit frame 0` rewinds its animation.
+//!
+//! # Key Structures
+//! - **`Inspected`**: Which character is inspected.
+//! - **`Components`**: The state of a character shown on the panel.
+//! - **`Field`**: A component that can be edited.
+//! - **`Inspector`**: The panel and what it shows.
+//!
+//! # Example Usage
+//! ```ignore
+//! let font = Arc::new(BitmapFont::load("assets/font.bdf")?);
+//! state.inspector(Inspector::new(font).position(4, 12));
+//! event_handler
+//!     .bus()
+//!     .subscribe::<MouseClick>(&mut state, 16, Backpressure::DropOldest);
+//! ```
+use std::sync::Arc;
+
+use crate::{
+    controller::{MovementMode, Pose},
+    font::BitmapFont,
+    layout::Coordinate,
+    palette::{Color, LIGHT_GRAY, MIDNIGHT},
+    renderer::DrawCmd,
+};
+
+/// The color of the panel's text.
+const TEXT: Color = LIGHT_GRAY;
+
+/// Which character is inspected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Inspected {
+    Player,
+    /// The second local player.
+    CoPlayer,
+}
+
+/// The state of a character shown on the panel.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Components {
+    pub position: Coordinate,
+    pub velocity: Coordinate,
+    /// The character's health, for characters that have any.
+    pub health: Option<f32>,
+    pub pose: Pose,
+    pub mode: MovementMode,
+    /// The frame of the animation shown.
+    pub frame: usize,
+    /// How many frames the animation has.
+    pub frames: usize,
+}
+impl Components {
+    /// The components as the lines shown on the panel.
+    pub fn text(&self) -> String {
+        let health = self
+            .health
+            .map_or_else(|| "-".to_string(), |health| format!("{health:.0}"));
+        let Coordinate { x, y } = self.position;
+        let Coordinate { x: vx, y: vy } = self.velocity;
+        [
+            format!("position {x:.1} {y:.1}"),
+            format!("velocity {vx:.1} {vy:.1}"),
+            format!("health {health}"),
+            format!("animation {:?} {:?}", self.pose, self.mode),
+            format!("frame {}/{}", self.frame + 1, self.frames),
+        ]
+        .join("\n")
+    }
+}
+
+/// A component that can be edited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    X,
+    Y,
+    VelocityX,
+    VelocityY,
+    /// The frame of the animation shown, counted from zero.
+    Frame,
+}
+impl Field {
+    /// The field called `name` on the debug console.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "x" => Field::X,
+            "y" => Field::Y,
+            "vx" => Field::VelocityX,
+            "vy" => Field::VelocityY,
+            "frame" => Field::Frame,
+            _ => return None,
+        })
+    }
+}
+
+/// The panel and what it shows.
+pub struct Inspector {
+    font: Arc<BitmapFont>,
+    position: (i32, i32),
+    scale: u32,
+    selected: Option<Inspected>,
+}
+impl Inspector {
+    /// A panel in the top left corner with nothing inspected.
+    pub fn new(font: Arc<BitmapFont>) -> Self {
+        Self {
+            font,
+            position: (2, 2),
+            scale: 1,
+            selected: None,
+        }
+    }
+    /// Where the panel's top left corner is on the screen.
+    pub fn position(mut self, x: i32, y: i32) -> Self {
+        self.position = (x, y);
+        self
+    }
+    /// How big the panel's text is drawn.
+    pub fn scale(mut self, scale: u32) -> Self {
+        self.scale = scale.max(1);
+        self
+    }
+    /// Inspects `inspected`, or closes the panel for `None`.
+    pub fn select(&mut self, inspected: Option<Inspected>) {
+        self.selected = inspected;
+    }
+    pub fn selected(&self) -> Option<Inspected> {
+        self.selected
+    }
+    /// Queues the panel for `components` onto a batch of draw commands.
+    pub fn commands<'a>(&self, components: &Components, batch: &mut Vec<DrawCmd<'a>>) {
+        let text = components.text();
+        let (width, height) = self.font.measure(&text, self.scale);
+        let (x, y) = self.position;
+        // A margin of a pixel keeps the text off the backdrop's edge
+        for row in y - 1..y + height as i32 + 1 {
+            batch.push(DrawCmd::Span {
+                start: (x - 1, row),
+                len: width + 2,
+                color: MIDNIGHT,
+            });
+        }
+        self.font
+            .commands(&text, self.position, TEXT, self.scale, batch);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window::{HeadlessScreen, Screen};
+
+    // Every glyph is a solid 1x2 block advancing by 2
+    const FONT: &str = "\
+STARTFONT 2.1
+FONT_ASCENT 2
+FONT_DESCENT 0
+DEFAULT_CHAR 32
+STARTCHAR default
+ENCODING 32
+DWIDTH 2 0
+BBX 1 2 0 0
+BITMAP
+80
+80
+ENDCHAR
+ENDFONT
+";
+
+    #[test]
+    fn test_components_text() {
+        let components = Components {
+            position: Coordinate { x: 1.25, y: -3.0 },
+            health: Some(80.0),
+            pose: Pose::SideWalk,
+            frame: 1,
+            frames: 4,
+            ..Default::default()
+        };
+        assert_eq!(
+            components.text(),
+            "position 1.2 -3.0\nvelocity 0.0 0.0\nhealth 80\nanimation SideWalk Normal\nframe 2/4"
+        );
+        assert_eq!(Field::from_name("vx"), Some(Field::VelocityX));
+        assert_eq!(Field::from_name("hp"), None);
+    }
+
+    #[test]
+    fn test_panel_sits_on_a_backdrop() {
+        let font = Arc::new(BitmapFont::from_bdf(FONT).unwrap());
+        let inspector = Inspector::new(font).position(3, 3);
+        let mut batch = Vec::new();
+        inspector.commands(&Components::default(), &mut batch);
+        let mut screen = HeadlessScreen::new(100, 20);
+        screen.draw_commands(&batch).unwrap();
+
+        let rgba = |color: Color| {
+            let [r, g, b, a] = color.to_rgba();
+            Some(Color::RGBA(r, g, b, a))
+        };
+        assert_eq!(screen.pixel(2, 2), rgba(MIDNIGHT));
+        assert_eq!(screen.pixel(3, 3), rgba(TEXT));
+        assert_ne!(screen.pixel(1, 1), rgba(MIDNIGHT));
+    }
+}
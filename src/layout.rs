@@ -11,12 +11,12 @@
 //! - **MirrorDirection**: Represents the transformation to mirror a coordinate either horizontally or vertically.
 //!
 //! # Key Features:
-//! - **Coordinate Operations**: Supports addition, addition assignment, and scalar multiplication for easy manipulation of coordinates.
+//! - **Coordinate Operations**: Supports addition, subtraction, addition assignment, and scalar multiplication for easy manipulation of coordinates.
 //! - **Direction Enum**: Provides an easy way to define movement or alignment along vertical or horizontal axes.
 //! - **Mirroring Transformation**: Offers the ability to mirror coordinates across an axis, useful for flipped rendering or effects.
 //!
 //! # Example Usage:
-//! ```rust
+//! ```ignore
 //! // Creating a coordinate
 //! let point = Coordinate { x: 10.0, y: 5.0 };
 //!
@@ -29,7 +29,7 @@
 //! let mirrored = Coordinate::mirror(10, 20); // Assuming width is 20
 //! assert_eq!(mirrored, 10); // Mirrored position (20 - 10)
 //! ```
-use std::ops::{Add, AddAssign, Mul};
+use std::ops::{Add, AddAssign, Mul, Sub};
 
 /// Represents a 2D position on the pixels grid.
 ///
@@ -42,6 +42,12 @@ pub struct Coordinate {
     /// Vertical position (rows).
     pub y: f32,
 }
+impl Coordinate {
+    /// The distance from the origin.
+    pub fn length(&self) -> f32 {
+        self.x.hypot(self.y)
+    }
+}
 impl From<(f32, f32)> for Coordinate {
     fn from(value: (f32, f32)) -> Self {
         Coordinate {
@@ -66,6 +72,16 @@ impl Add for Coordinate {
         }
     }
 }
+impl Sub for Coordinate {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+        }
+    }
+}
 impl Mul<f32> for Coordinate {
     type Output = Self;
 
@@ -87,7 +103,7 @@ pub enum Direction {
 }
 
 /// Represents a mirroring transformation across an axis in the pixels coordinate system.
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub enum MirrorDirectionValue {
     /// Flip across the horizontal axis, affecting the vertical (Y) direction.
     FlipHorizontal(u16),
@@ -96,6 +112,7 @@ pub enum MirrorDirectionValue {
     None,
 }
 /// Represents a mirroring transformation across an axis in the pixels coordinate system.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MirrorDirection {
     /// Flip across the horizontal axis
     FlipHorizontal,
@@ -0,0 +1,54 @@
+//! A small pixel-art game engine and the game built on top of it.
+//!
+//! The engine pieces (windowing, input, rendering and animation) are exposed as
+//! modules so they can be driven either by the bundled game binary or by other
+//! consumers such as tests and benchmarks running on a `HeadlessScreen`.
+pub mod animator;
+pub mod behavior_tree;
+pub mod camera;
+pub mod cheats;
+pub mod console;
+pub mod controller;
+pub mod cutscene;
+pub mod effects;
+pub mod event;
+pub mod follower;
+pub mod font;
+pub mod game;
+pub mod grading;
+pub mod hitbox;
+pub mod hitstop;
+pub mod hud;
+pub mod input;
+pub mod inspector;
+pub mod layout;
+pub mod lighting;
+pub mod logging;
+#[cfg(test)]
+mod mock;
+pub mod movement;
+pub mod net;
+pub mod overlay;
+pub mod palette;
+pub mod pathfinding;
+pub mod physics;
+pub mod prefab;
+pub mod prelude;
+pub mod profiler;
+pub mod progress;
+pub mod renderer;
+pub mod settings;
+pub mod shop;
+pub mod snapshot;
+pub mod spatial;
+pub mod splitscreen;
+pub mod sprite;
+pub mod steering;
+pub mod sync;
+pub mod touch;
+pub mod transition;
+pub mod tween;
+pub mod ui;
+pub mod viewport;
+pub mod weather;
+pub mod window;
@@ -0,0 +1,163 @@
+//! A simple 2D lighting pass darkening the world outside of light sources.
+//!
+//! `Lighting` holds the ambient light level and the lights currently in the
+//! world. Submitted as a `DrawCmd::Lighting` after the world layer, every pixel
+//! drawn so far is multiplied by the light reaching it so anything queued later
+//! (e.g. the HUD) stays fully lit.
+//!
+//! # Key Structures
+//! - **`Light`**: A circular light source fading out towards its radius.
+//! - **`Lighting`**: The ambient light and every light source of a frame.
+//!
+//! # Example Usage
+//! ```ignore
+//! let mut lighting = Lighting::new(0.15);
+//! lighting.add(Light::new(player_pos, 48.0));
+//! lighting.add(Light::new(torch_pos, 24.0).color(Color::RGB(255, 180, 90)));
+//!
+//! world_commands(&mut batch);
+//! batch.push(DrawCmd::Lighting(&lighting));
+//! hud_commands(&mut batch);
+//! ```
+use crate::{layout::Coordinate, palette::Color};
+
+/// A circular light source fading out from its center to its `radius`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Light {
+    pub position: Coordinate,
+    pub radius: f32,
+    /// Brightness at the center where `1.0` lights a pixel fully.
+    pub intensity: f32,
+    pub color: Color,
+}
+impl Light {
+    /// A full intensity white light.
+    pub fn new(position: Coordinate, radius: f32) -> Self {
+        Self {
+            position,
+            radius,
+            intensity: 1.0,
+            color: Color::RGB(255, 255, 255),
+        }
+    }
+    pub fn intensity(mut self, intensity: f32) -> Self {
+        self.intensity = intensity;
+        self
+    }
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+    /// The light's contribution per channel at `point`.
+    fn at(&self, point: Coordinate) -> [f32; 3] {
+        let distance = (point - self.position).length();
+        if self.radius <= 0.0 || distance >= self.radius {
+            return [0.0; 3];
+        }
+        // Squared falloff gives a soft edge instead of a flat disc
+        let falloff = (1.0 - distance / self.radius).powi(2) * self.intensity;
+        let [r, g, b, _] = self.color.to_rgba();
+        [r, g, b].map(|c| c as f32 / 255.0 * falloff)
+    }
+}
+
+/// The ambient light level and every light source lighting a frame.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Lighting {
+    /// Light reaching every pixel from `0.0` (pitch black) to `1.0` (no darkness).
+    pub ambient: f32,
+    lights: Vec<Light>,
+}
+impl Lighting {
+    pub fn new(ambient: f32) -> Self {
+        Self {
+            ambient,
+            lights: Vec::new(),
+        }
+    }
+    pub fn add(&mut self, light: Light) {
+        self.lights.push(light);
+    }
+    /// Removes every light so they can be re-added at their new positions.
+    pub fn clear(&mut self) {
+        self.lights.clear();
+    }
+    pub fn lights(&self) -> &[Light] {
+        &self.lights
+    }
+    /// The light reaching `point` per channel, at most `1.0`.
+    pub fn light_at(&self, point: Coordinate) -> [f32; 3] {
+        let mut light = [self.ambient; 3];
+        for source in &self.lights {
+            for (total, add) in light.iter_mut().zip(source.at(point)) {
+                *total += add;
+            }
+        }
+        light.map(|c| c.clamp(0.0, 1.0))
+    }
+    /// Darkens every RGBA pixel of a `width` by `height` buffer by the light reaching it.
+    pub(crate) fn apply(&self, buffer: &mut [u8], width: u32, height: u32) {
+        if self.ambient >= 1.0 {
+            return;
+        }
+        for (i, pixel) in buffer
+            .chunks_exact_mut(4)
+            .take((width * height) as usize)
+            .enumerate()
+        {
+            let point = Coordinate {
+                x: (i as u32 % width) as f32,
+                y: (i as u32 / width) as f32,
+            };
+            let light = self.light_at(point);
+            for (value, light) in pixel[..3].iter_mut().zip(light) {
+                *value = (*value as f32 * light).round() as u8;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{prelude::*, renderer::DrawCmd};
+
+    #[test]
+    fn test_light_falloff() {
+        let mut lighting = Lighting::new(0.2);
+        lighting.add(Light::new(Coordinate::default(), 10.0));
+        lighting.add(Light::new(Coordinate { x: 10.0, y: 0.0 }, 10.0).intensity(0.5));
+
+        assert_eq!(lighting.light_at(Coordinate::default()), [1.0; 3]);
+        // Halfway between both lights
+        let [r, ..] = lighting.light_at(Coordinate { x: 5.0, y: 0.0 });
+        assert!((r - (0.2 + 0.25 + 0.125)).abs() < 1e-6);
+        assert_eq!(lighting.light_at(Coordinate { x: 0.0, y: 30.0 }), [0.2; 3]);
+
+        lighting.clear();
+        assert!(lighting.lights().is_empty());
+    }
+
+    #[test]
+    fn test_lighting_darkens_world() {
+        let mut lighting = Lighting::new(0.0);
+        lighting.add(Light::new(Coordinate::default(), 2.0).color(Color::RGB(255, 0, 0)));
+
+        let mut screen = HeadlessScreen::new(4, 1);
+        screen
+            .draw_batch(&[
+                DrawCmd::Clear,
+                DrawCmd::Span {
+                    start: (0, 0),
+                    len: 4,
+                    color: Color::RGB(200, 200, 200),
+                },
+                DrawCmd::Lighting(&lighting),
+            ])
+            .unwrap();
+
+        assert_eq!(&screen.buffer()[0..4], &[200, 0, 0, 255]);
+        assert_eq!(&screen.buffer()[4..8], &[50, 0, 0, 255]);
+        assert_eq!(&screen.buffer()[8..12], &[0, 0, 0, 255]);
+    }
+}
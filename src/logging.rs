@@ -0,0 +1,90 @@
+//! Sets up where the engine's logs and `tracing` spans end up.
+//!
+//! The engine is instrumented with `tracing` spans and events (the event loop,
+//! game loop and renderer). Without a `tracing` subscriber installed, they are
+//! forwarded to `log` records, which `init` routes to the console and a rotating
+//! log file through `log4rs`.
+//!
+//! # Example Usage
+//! ```ignore
+//! // Uses the appenders and levels configured in the file or falls back to
+//! // the console and `log/thegame.log` when it doesn't exist
+//! logging::init("log4rs.yaml")?;
+//! ```
+use std::path::Path;
+
+use log::LevelFilter;
+use log4rs::{
+    append::{
+        console::ConsoleAppender,
+        rolling_file::{
+            policy::compound::{
+                roll::fixed_window::FixedWindowRoller, trigger::size::SizeTrigger, CompoundPolicy,
+            },
+            RollingFileAppender,
+        },
+    },
+    config::{Appender, Config, Root},
+    encode::pattern::PatternEncoder,
+};
+use thiserror::Error;
+
+const PATTERN: &str = "{d} - {l} - {t} - {m}\n";
+const LOG_FILE: &str = "log/thegame.log";
+/// Size a log file grows to before it's rotated.
+const ROTATE_SIZE: u64 = 1024 * 1024;
+/// Rotated log files kept besides the current one.
+const ROTATE_COUNT: u32 = 3;
+
+/// Installs the logger from a `log4rs` config file, or the default console
+/// and rotating file setup when the file doesn't exist.
+pub fn init(config: impl AsRef<Path>) -> Result<(), LoggingError> {
+    let config = config.as_ref();
+    if config.exists() {
+        log4rs::init_file(config, Default::default())
+            .map_err(|e| LoggingError::ConfigError(e.to_string()))
+    } else {
+        log4rs::init_config(default_config()?)?;
+        Ok(())
+    }
+}
+
+/// Debug level logs to the console and `log/thegame.log`, rotated once it
+/// grows past a megabyte.
+fn default_config() -> Result<Config, LoggingError> {
+    let roller = FixedWindowRoller::builder()
+        .build(&format!("{LOG_FILE}.{{}}"), ROTATE_COUNT)
+        .map_err(|e| LoggingError::ConfigError(e.to_string()))?;
+    let file = RollingFileAppender::builder()
+        .encoder(Box::new(PatternEncoder::new(PATTERN)))
+        .build(
+            LOG_FILE,
+            Box::new(CompoundPolicy::new(
+                Box::new(SizeTrigger::new(ROTATE_SIZE)),
+                Box::new(roller),
+            )),
+        )
+        .map_err(|e| LoggingError::ConfigError(e.to_string()))?;
+    let stdout = ConsoleAppender::builder()
+        .encoder(Box::new(PatternEncoder::new(PATTERN)))
+        .build();
+
+    Config::builder()
+        .appender(Appender::builder().build("stdout", Box::new(stdout)))
+        .appender(Appender::builder().build("file", Box::new(file)))
+        .build(
+            Root::builder()
+                .appender("stdout")
+                .appender("file")
+                .build(LevelFilter::Debug),
+        )
+        .map_err(|e| LoggingError::ConfigError(e.to_string()))
+}
+
+#[derive(Debug, Error)]
+pub enum LoggingError {
+    #[error("invalid logging config: {0}")]
+    ConfigError(String),
+    #[error("logger already installed: {0}")]
+    AlreadyInstalledError(#[from] log::SetLoggerError),
+}
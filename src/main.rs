@@ -1,29 +1,29 @@
-mod animator;
-mod event;
-mod game;
-mod input;
-mod layout;
-mod mock;
-mod palette;
-mod prelude;
-mod renderer;
-mod sprite;
-mod sync;
-mod window;
+use std::sync::Arc;
 
-use event::EventHandler;
-use game::GameState;
-use prelude::*;
-use window::GameWindow;
+use thegame::{
+    event::{EventHandler, WindowFocus, WindowLifecycle},
+    font::BitmapFont,
+    game::GameState,
+    input::Action,
+    logging,
+    prelude::*,
+    settings::SettingsMenu,
+    window::{GameWindow, RedrawRequest, WindowSettings},
+};
 
 fn main() {
+    logging::init("log4rs.yaml").unwrap();
+
     let mut event_handler = EventHandler::new();
 
     let mut window = GameWindow::new(320, 180, "The Little Knight".into(), &event_handler).unwrap();
     let screen = window.screen();
     let inner_window = window.window();
     
-    event_handler.register_window(inner_window);
+    event_handler.register_window(inner_window.clone());
+    // The game only draws while the event loop presents on redraw
+    let redraw = RedrawRequest::new(inner_window);
+    event_handler.present_on_redraw(screen.clone(), redraw.clone());
     
     let mut game = GameState::new(
         30,
@@ -32,8 +32,25 @@ fn main() {
         Knight::new(),
         screen,
     );
-    event_handler.subscribe_coordinate(&mut game);
-    game.start();
+    game.auto_pause(true);
+    game.present_on_redraw(redraw);
+    let bus = event_handler.bus();
+    // Only the direction currently held matters to the player
+    bus.subscribe::<Coordinate>(&mut game, 1, Backpressure::Coalesce);
+    bus.subscribe::<Action>(&mut game, 256, Backpressure::DropOldest);
+    bus.subscribe::<WindowFocus>(&mut game, 256, Backpressure::DropOldest);
+    bus.subscribe::<WindowLifecycle>(&mut game, 256, Backpressure::DropOldest);
+    let frame_rate = game.frame_rate();
+    let game = game.start();
+
+    // Escape opens the settings
+    let font = Arc::new(BitmapFont::builtin());
+    let menu = SettingsMenu::new(font, (8, 8), 1, WindowSettings::default())
+        .monitors(window.monitors())
+        .frame_rate(frame_rate);
+    event_handler.settings_menu(window, menu);
 
     event_handler.start().unwrap();
+    // Let the game finish its last frame before exiting
+    game.join().unwrap();
 }
@@ -1,39 +1,12 @@
 use crate::animator::Animation;
 use crate::prelude::*;
 use crate::renderer::{Frame, Pixel};
-use crate::window::WindowError;
-
-pub(crate) struct MockScreen {
-    pub(crate) buffer: Vec<u8>,
-    width: u32,
-    height: u32,
-}
-impl MockScreen {
-    pub(crate) fn new(width: u32, height: u32) -> Self {
-        MockScreen {
-            buffer: vec![0; (width * height * 4) as usize], // RGBA buffer
-            width,
-            height,
-        }
-    }
-}
-impl Screen for MockScreen {
-    fn width(&self) -> u32 {
-        self.width
-    }
-    fn height(&self) -> u32 {
-        self.height
-    }
-    fn frame_buffer(&mut self) -> &mut [u8] {
-        &mut self.buffer
-    }
-    fn render(&mut self) -> Result<(), WindowError> {
-        Ok(())
-    }
-    fn clear(&mut self) -> Result<(), WindowError> {
-        Ok(())
-    }
-}
+use crate::window::{Configurable, Window, WindowSettings};
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc, Mutex,
+};
+use winit::window::WindowId;
 
 #[derive(Default)]
 pub(crate) struct MockCharacter {
@@ -56,57 +29,59 @@ impl MockCharacter {
 }
 // Mocked with no output since animation will play an empty
 // frame container
-impl Character<MockScreen> for MockCharacter {
-    fn idle(&mut self) -> &mut dyn Animation<MockScreen> {
+impl Character<HeadlessScreen> for MockCharacter {
+    fn idle(&mut self) -> &mut dyn Animation<HeadlessScreen> {
         self.animation_trigerred = "idle".into();
         &mut self.idle
     }
-    fn side_walk(&mut self) -> &mut dyn Animation<MockScreen> {
+    fn side_walk(&mut self) -> &mut dyn Animation<HeadlessScreen> {
         self.animation_trigerred = "side".into();
         &mut self.side_walk
     }
 
-    fn front_walk(&mut self) -> &mut dyn Animation<MockScreen> {
+    fn front_walk(&mut self) -> &mut dyn Animation<HeadlessScreen> {
         self.animation_trigerred = "front".into();
         &mut self.front_walk
     }
 
-    fn back_walk(&mut self) -> &mut dyn Animation<MockScreen> {
+    fn back_walk(&mut self) -> &mut dyn Animation<HeadlessScreen> {
         self.animation_trigerred = "back".into();
         &mut self.back_walk
     }
+
+    fn action(&mut self, name: &str) -> Option<&mut dyn Animation<HeadlessScreen>> {
+        if name != "climb" {
+            return None;
+        }
+        self.animation_trigerred = "climb".into();
+        Some(&mut self.back_walk)
+    }
+}
+/// A single blue pixel frame of a 5 by 5 sprite.
+fn frame(x: u16, y: u16) -> Frame {
+    let mut frame = Frame::new(
+        vec![Pixel::new(
+            ColorScheme::Standard(Color::RGB(0, 0, 255)),
+            x,
+            y,
+        )],
+        None,
+    );
+    frame.height = 5;
+    frame.width = 5;
+    frame
 }
 #[derive(Default)]
 struct MockIdle {
     frames: Vec<Frame>,
     timer: f32,
     frame_pos: usize,
+    paused: bool,
 }
 impl MockIdle {
     pub(crate) fn new() -> Self {
         Self {
-            frames: vec![
-                Frame {
-                    pixels: vec![Pixel::new(
-                        ColorScheme::Standard(Color::RGB(0, 0, 255)),
-                        0,
-                        1,
-                    )],
-                    height: 5,
-                    width: 5,
-                    duration: None,
-                },
-                Frame {
-                    pixels: vec![Pixel::new(
-                        ColorScheme::Standard(Color::RGB(0, 0, 255)),
-                        1,
-                        1,
-                    )],
-                    height: 5,
-                    width: 5,
-                    duration: None,
-                },
-            ],
+            frames: vec![frame(0, 1), frame(1, 1)],
             ..Default::default()
         }
     }
@@ -116,32 +91,12 @@ struct MockSide {
     frames: Vec<Frame>,
     timer: f32,
     frame_pos: usize,
+    paused: bool,
 }
 impl MockSide {
     pub(crate) fn new() -> Self {
         Self {
-            frames: vec![
-                Frame {
-                    pixels: vec![Pixel::new(
-                        ColorScheme::Standard(Color::RGB(0, 0, 255)),
-                        0,
-                        2,
-                    )],
-                    height: 5,
-                    width: 5,
-                    duration: None,
-                },
-                Frame {
-                    pixels: vec![Pixel::new(
-                        ColorScheme::Standard(Color::RGB(0, 0, 255)),
-                        1,
-                        2,
-                    )],
-                    height: 5,
-                    width: 5,
-                    duration: None,
-                },
-            ],
+            frames: vec![frame(0, 2), frame(1, 2)],
             ..Default::default()
         }
     }
@@ -151,32 +106,12 @@ struct MockFront {
     frames: Vec<Frame>,
     timer: f32,
     frame_pos: usize,
+    paused: bool,
 }
 impl MockFront {
     pub(crate) fn new() -> Self {
         Self {
-            frames: vec![
-                Frame {
-                    pixels: vec![Pixel::new(
-                        ColorScheme::Standard(Color::RGB(0, 0, 255)),
-                        0,
-                        3,
-                    )],
-                    height: 5,
-                    width: 5,
-                    duration: None,
-                },
-                Frame {
-                    pixels: vec![Pixel::new(
-                        ColorScheme::Standard(Color::RGB(0, 0, 255)),
-                        1,
-                        3,
-                    )],
-                    height: 5,
-                    width: 5,
-                    duration: None,
-                },
-            ],
+            frames: vec![frame(0, 3), frame(1, 3)],
             ..Default::default()
         }
     }
@@ -186,32 +121,12 @@ pub(crate) struct MockBack {
     frames: Vec<Frame>,
     timer: f32,
     frame_pos: usize,
+    paused: bool,
 }
 impl MockBack {
     pub(crate) fn new() -> Self {
         Self {
-            frames: vec![
-                Frame {
-                    pixels: vec![Pixel::new(
-                        ColorScheme::Standard(Color::RGB(0, 0, 255)),
-                        0,
-                        4,
-                    )],
-                    height: 5,
-                    width: 5,
-                    duration: None,
-                },
-                Frame {
-                    pixels: vec![Pixel::new(
-                        ColorScheme::Standard(Color::RGB(0, 0, 255)),
-                        1,
-                        4,
-                    )],
-                    height: 5,
-                    width: 5,
-                    duration: None,
-                },
-            ],
+            frames: vec![frame(0, 4), frame(1, 4)],
             ..Default::default()
         }
     }
@@ -238,6 +153,14 @@ macro_rules! impl_sprite {
             fn timer_mut(&mut self) -> &mut f32 {
                 &mut self.timer
             }
+
+            fn paused(&self) -> bool {
+                self.paused
+            }
+
+            fn paused_mut(&mut self) -> &mut bool {
+                &mut self.paused
+            }
         }
     };
 }
@@ -245,3 +168,30 @@ impl_sprite!(MockIdle);
 impl_sprite!(MockSide);
 impl_sprite!(MockFront);
 impl_sprite!(MockBack);
+
+/// A window that only counts the redraws asked of it and records the
+/// settings it was shown with.
+#[derive(Default)]
+pub(crate) struct MockWindow {
+    pub(crate) redraws: AtomicU32,
+    pub(crate) applied: Arc<Mutex<Vec<WindowSettings>>>,
+}
+impl Window for MockWindow {
+    fn id(&self) -> WindowId {
+        // SAFETY: Never handed to winit
+        unsafe { WindowId::dummy() }
+    }
+    fn request_redraw(&self) {
+        self.redraws.fetch_add(1, Ordering::Relaxed);
+    }
+    fn set_ime_allowed(&self, _: bool) {}
+    fn scale_factor(&self) -> f64 {
+        1.0
+    }
+}
+impl Configurable for MockWindow {
+    fn apply(&self, settings: &WindowSettings) -> Result<(), WindowError> {
+        self.applied.lock().unwrap().push(*settings);
+        Ok(())
+    }
+}
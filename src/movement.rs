@@ -0,0 +1,337 @@
+//! Stamina-backed movement abilities for the player.
+//!
+//! This module keeps the tuning and bookkeeping for movement that goes beyond
+//! plain walking, leaving `GameState` to only decide when they are used.
+//!
+//! # Key Structures
+//! - **`Stamina`**: A regenerating resource consumed by sprinting and dashing.
+//! - **`Sprint`**: A held modifier speeding up both movement and animation playback.
+//! - **`Dash`**: A short burst of speed gated by a cooldown and a stamina cost.
+//! - **`InputBuffer`**: Remembers a press for a short window so it isn't lost
+//!   when pressed slightly too early (e.g. a dash still on cooldown).
+//! - **`CoyoteTime`**: Keeps allowing a jump for a short window after walking
+//!   off a ledge.
+//!
+//! # Example Usage
+//! ```ignore
+//! let mut stamina = Stamina::default();
+//! let mut dash = Dash::default();
+//!
+//! // Burst forward if the cooldown allows it
+//! if dash.trigger(&mut stamina) {
+//!     // ...
+//! }
+//! dash.tick(delta);
+//! ```
+
+/// A regenerating resource consumed by movement abilities.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Stamina {
+    value: f32,
+    max: f32,
+    /// Amount recovered per second while not in use.
+    regen_rate: f32,
+}
+impl Stamina {
+    pub(crate) fn new(max: f32, regen_rate: f32) -> Self {
+        Self {
+            value: max,
+            max,
+            regen_rate,
+        }
+    }
+    pub(crate) fn value(&self) -> f32 {
+        self.value
+    }
+    /// Sets the stamina left, e.g. when restoring a snapshot.
+    pub(crate) fn set_value(&mut self, value: f32) {
+        self.value = value.clamp(0.0, self.max);
+    }
+    /// The remaining stamina as a fraction from `0.0` to `1.0`.
+    pub(crate) fn ratio(&self) -> f32 {
+        if self.max <= 0.0 {
+            return 0.0;
+        }
+        self.value / self.max
+    }
+    /// Consumes `amount` of stamina only if there is enough left.
+    ///
+    /// Returns whether the stamina was spent
+    pub(crate) fn spend(&mut self, amount: f32) -> bool {
+        if self.value < amount {
+            return false;
+        }
+        self.value -= amount;
+        true
+    }
+    /// Consumes up to `amount` of stamina.
+    ///
+    /// Returns whether any stamina was left to drain
+    pub(crate) fn drain(&mut self, amount: f32) -> bool {
+        if self.value <= 0.0 {
+            return false;
+        }
+        self.value = (self.value - amount).max(0.0);
+        true
+    }
+    /// Recovers stamina over `delta` seconds.
+    pub(crate) fn regen(&mut self, delta: f32) {
+        self.value = (self.value + self.regen_rate * delta).min(self.max);
+    }
+}
+impl Default for Stamina {
+    fn default() -> Self {
+        Self::new(100.0, 15.0)
+    }
+}
+
+/// A held modifier multiplying movement speed at the cost of stamina.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Sprint {
+    /// Multiplier applied to the player's speed.
+    pub(crate) speed: f32,
+    /// Multiplier applied to animation playback so steps keep up with the speed.
+    pub(crate) animation: f32,
+    /// Stamina drained per second of sprinting.
+    pub(crate) drain_rate: f32,
+}
+impl Default for Sprint {
+    fn default() -> Self {
+        Self {
+            speed: 1.75,
+            animation: 1.5,
+            drain_rate: 25.0,
+        }
+    }
+}
+
+/// A short burst of speed with a cooldown.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Dash {
+    /// Multiplier applied to the player's speed while dashing.
+    pub(crate) speed: f32,
+    /// Length of the burst in seconds.
+    pub(crate) duration: f32,
+    /// Time in seconds before another dash can start.
+    pub(crate) cooldown: f32,
+    /// Stamina spent up front to dash.
+    pub(crate) cost: f32,
+    active: f32,
+    recharge: f32,
+}
+impl Dash {
+    /// Starts a dash if the cooldown elapsed and enough stamina is left.
+    ///
+    /// Returns whether the dash started
+    pub(crate) fn trigger(&mut self, stamina: &mut Stamina) -> bool {
+        if self.recharge > 0.0 || !stamina.spend(self.cost) {
+            return false;
+        }
+        self.active = self.duration;
+        self.recharge = self.cooldown;
+        true
+    }
+    /// Progresses the burst and cooldown timers by `delta` seconds.
+    pub(crate) fn tick(&mut self, delta: f32) {
+        self.active = (self.active - delta).max(0.0);
+        self.recharge = (self.recharge - delta).max(0.0);
+    }
+    pub(crate) fn is_active(&self) -> bool {
+        self.active > 0.0
+    }
+    /// Seconds left of the burst and of the cooldown.
+    pub(crate) fn timers(&self) -> (f32, f32) {
+        (self.active, self.recharge)
+    }
+    pub(crate) fn set_timers(&mut self, (active, recharge): (f32, f32)) {
+        self.active = active;
+        self.recharge = recharge;
+    }
+}
+impl Default for Dash {
+    fn default() -> Self {
+        Self {
+            speed: 4.0,
+            duration: 0.15,
+            cooldown: 1.0,
+            cost: 30.0,
+            active: 0.0,
+            recharge: 0.0,
+        }
+    }
+}
+
+/// Remembers a press for `window` seconds until it's consumed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputBuffer {
+    pub window: f32,
+    remaining: f32,
+}
+impl InputBuffer {
+    pub fn new(window: f32) -> Self {
+        Self {
+            window,
+            remaining: 0.0,
+        }
+    }
+    /// Buffers a press, restarting the window if one was already pending.
+    pub fn press(&mut self) {
+        self.remaining = self.window;
+    }
+    pub fn is_pending(&self) -> bool {
+        self.remaining > 0.0
+    }
+    /// Takes the pending press, if there is one.
+    pub fn consume(&mut self) -> bool {
+        let pending = self.is_pending();
+        self.remaining = 0.0;
+        pending
+    }
+    /// Progresses the window by `delta` seconds.
+    pub fn tick(&mut self, delta: f32) {
+        self.remaining = (self.remaining - delta).max(0.0);
+    }
+    /// Seconds left for the pending press.
+    pub fn remaining(&self) -> f32 {
+        self.remaining
+    }
+    pub fn set_remaining(&mut self, remaining: f32) {
+        self.remaining = remaining;
+    }
+}
+impl Default for InputBuffer {
+    /// A 120ms window.
+    fn default() -> Self {
+        Self::new(0.12)
+    }
+}
+
+/// Allows jumping for `window` seconds after last standing on the ground.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoyoteTime {
+    pub window: f32,
+    /// Time left to jump, reset while grounded
+    remaining: f32,
+}
+impl CoyoteTime {
+    pub fn new(window: f32) -> Self {
+        Self {
+            window,
+            remaining: 0.0,
+        }
+    }
+    /// Progresses by `delta` seconds with whether the player stands on the ground.
+    pub fn update(&mut self, grounded: bool, delta: f32) {
+        self.remaining = if grounded {
+            self.window
+        } else {
+            (self.remaining - delta).max(0.0)
+        };
+    }
+    pub fn can_jump(&self) -> bool {
+        self.remaining > 0.0
+    }
+    /// Uses up the window for a jump so it can't be jumped twice in the air.
+    ///
+    /// Returns whether the jump is allowed
+    pub fn consume(&mut self) -> bool {
+        let allowed = self.can_jump();
+        self.remaining = 0.0;
+        allowed
+    }
+}
+impl Default for CoyoteTime {
+    /// A 100ms window.
+    fn default() -> Self {
+        Self::new(0.1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stamina_drain_and_regen() {
+        let mut stamina = Stamina::new(10.0, 5.0);
+
+        assert!(stamina.drain(4.0));
+        assert_eq!(stamina.value(), 6.0);
+        assert!(stamina.drain(10.0));
+        assert_eq!(stamina.value(), 0.0);
+        assert!(!stamina.drain(1.0));
+
+        stamina.regen(1.0);
+        assert_eq!(stamina.ratio(), 0.5);
+        stamina.regen(10.0);
+        assert_eq!(stamina.value(), 10.0);
+    }
+
+    #[test]
+    fn test_stamina_spend_requires_enough() {
+        let mut stamina = Stamina::new(10.0, 0.0);
+
+        assert!(stamina.spend(8.0));
+        assert!(!stamina.spend(8.0));
+        assert_eq!(stamina.value(), 2.0);
+    }
+
+    #[test]
+    fn test_dash_cooldown() {
+        let mut stamina = Stamina::new(100.0, 0.0);
+        let mut dash = Dash::default();
+
+        assert!(dash.trigger(&mut stamina));
+        assert!(dash.is_active());
+        assert_eq!(stamina.value(), 70.0);
+
+        // Burst ends before the cooldown does
+        dash.tick(0.2);
+        assert!(!dash.is_active());
+        assert!(!dash.trigger(&mut stamina));
+
+        dash.tick(1.0);
+        assert!(dash.trigger(&mut stamina));
+    }
+
+    #[test]
+    fn test_dash_needs_stamina() {
+        let mut stamina = Stamina::new(20.0, 0.0);
+        let mut dash = Dash::default();
+
+        assert!(!dash.trigger(&mut stamina));
+        assert!(!dash.is_active());
+        assert_eq!(stamina.value(), 20.0);
+    }
+
+    #[test]
+    fn test_input_buffer_window() {
+        let mut buffer = InputBuffer::new(0.12);
+        assert!(!buffer.consume());
+
+        buffer.press();
+        buffer.tick(0.1);
+        assert!(buffer.consume());
+        assert!(!buffer.is_pending());
+
+        buffer.press();
+        buffer.tick(0.13);
+        assert!(!buffer.consume());
+    }
+
+    #[test]
+    fn test_coyote_time() {
+        let mut coyote = CoyoteTime::new(0.1);
+        coyote.update(true, 0.016);
+        coyote.update(false, 0.05);
+        assert!(coyote.can_jump());
+        coyote.update(false, 0.06);
+        assert!(!coyote.can_jump());
+
+        // No second jump off the same ledge
+        coyote.update(true, 0.016);
+        coyote.update(false, 0.016);
+        assert!(coyote.consume());
+        assert!(!coyote.consume());
+    }
+}
@@ -0,0 +1,286 @@
+//! Two running games showing each other's player over the network.
+//!
+//! A `Peer` sends where its player is and how it's animating to the other
+//! game over UDP and keeps what it hears back. One side hosts and learns who
+//! it plays with from the first state it receives, the other connects to it.
+//!
+//! States are sent on a fixed network tick of `NET_TICK`, whatever the frame
+//! rate of either game, and stamped with the tick they were sent on. Since
+//! both sides tick at the same pace, the receiving side knows how far apart
+//! two states were sent and moves the remote player smoothly between them
+//! instead of jumping 20 times a second. States arriving late or out of order
+//! are dropped.
+//!
+//! # Key Structures
+//! - **`PlayerState`**: Where a player is and how it's animating.
+//! - **`Peer`**: The socket to the other game and the states heard from it.
+//!
+//! Errors:
+//! - `NetError`: The socket failed or a state couldn't be read.
+//!
+//! # Example Usage
+//! ```ignore
+//! // On one machine
+//! let peer = Peer::host("0.0.0.0:7777")?;
+//! // and on the other
+//! let peer = Peer::connect("0.0.0.0:0", "192.168.1.20:7777")?;
+//!
+//! state.online(peer, Knight::new());
+//! ```
+use std::{
+    io::ErrorKind,
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+    time::Duration,
+};
+
+use thiserror::Error;
+use tracing::warn;
+
+use crate::layout::Coordinate;
+
+/// How often a `Peer` sends its player's state.
+pub const NET_TICK: Duration = Duration::from_millis(50);
+
+/// The largest datagram a state is read from.
+const MAX_PACKET: usize = 128;
+
+/// Where a player is and how it's animating.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlayerState {
+    pub position: Coordinate,
+    /// The way the player is moving, which picks the animation played.
+    pub motion: Option<Coordinate>,
+    /// The frame of the animation shown.
+    pub frame: usize,
+}
+impl PlayerState {
+    /// Writes the state sent on `tick` as a single line of text.
+    fn encode(&self, tick: u32) -> String {
+        let motion = match self.motion {
+            Some(Coordinate { x, y }) => format!("{x} {y}"),
+            None => "-".into(),
+        };
+        format!(
+            "state {tick} {} {} {} {motion}",
+            self.position.x, self.position.y, self.frame
+        )
+    }
+    /// Reads a state and the tick it was sent on.
+    fn decode(packet: &str) -> Result<(u32, Self), NetError> {
+        let malformed = || NetError::Malformed(packet.to_string());
+        let fields: Vec<_> = packet.split_whitespace().collect();
+        let (tick, x, y, frame, motion) = match fields[..] {
+            ["state", tick, x, y, frame, "-"] => (tick, x, y, frame, None),
+            ["state", tick, x, y, frame, mx, my] => (tick, x, y, frame, Some((mx, my))),
+            _ => return Err(malformed()),
+        };
+        let number = |field: &str| field.parse::<f32>().map_err(|_| malformed());
+        let motion = match motion {
+            Some((x, y)) => Some(Coordinate {
+                x: number(x)?,
+                y: number(y)?,
+            }),
+            None => None,
+        };
+        let state = PlayerState {
+            position: Coordinate {
+                x: number(x)?,
+                y: number(y)?,
+            },
+            motion,
+            frame: frame.parse().map_err(|_| malformed())?,
+        };
+        Ok((tick.parse().map_err(|_| malformed())?, state))
+    }
+}
+
+/// A state heard from the other game.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Snapshot {
+    tick: u32,
+    state: PlayerState,
+}
+
+/// One end of a game played over the network.
+pub struct Peer {
+    socket: UdpSocket,
+    /// Who states are sent to, once known
+    remote: Option<SocketAddr>,
+    /// Seconds towards the next network tick
+    clock: f32,
+    tick: u32,
+    previous: Option<Snapshot>,
+    latest: Option<Snapshot>,
+    /// Seconds since the latest snapshot arrived
+    since_latest: f32,
+}
+impl Peer {
+    /// Waits for another game to connect on `bind`.
+    pub fn host(bind: impl ToSocketAddrs) -> Result<Self, NetError> {
+        Self::new(UdpSocket::bind(bind)?, None)
+    }
+    /// Plays with the game hosting at `host`, sending from `bind`.
+    pub fn connect(bind: impl ToSocketAddrs, host: impl ToSocketAddrs) -> Result<Self, NetError> {
+        let host = host.to_socket_addrs()?.next().ok_or(NetError::Unresolved)?;
+        Self::new(UdpSocket::bind(bind)?, Some(host))
+    }
+    fn new(socket: UdpSocket, remote: Option<SocketAddr>) -> Result<Self, NetError> {
+        // Frames can't wait on the network
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            remote,
+            clock: 0.0,
+            tick: 0,
+            previous: None,
+            latest: None,
+            since_latest: 0.0,
+        })
+    }
+    pub fn local_addr(&self) -> Result<SocketAddr, NetError> {
+        Ok(self.socket.local_addr()?)
+    }
+    /// Whether a state was heard from the other game yet.
+    pub fn is_connected(&self) -> bool {
+        self.latest.is_some()
+    }
+    /// Moves on `delta` seconds, sending `local` if a network tick passed and
+    /// taking in every state that arrived since.
+    pub fn update(&mut self, delta: f32, local: &PlayerState) -> Result<(), NetError> {
+        self.clock += delta;
+        self.since_latest += delta;
+        let ticks = (self.clock / NET_TICK.as_secs_f32()) as u32;
+        if ticks > 0 {
+            self.clock -= ticks as f32 * NET_TICK.as_secs_f32();
+            self.tick += ticks;
+            if let Some(remote) = self.remote {
+                self.socket
+                    .send_to(local.encode(self.tick).as_bytes(), remote)?;
+            }
+        }
+        self.receive()
+    }
+    /// The other game's player, moved smoothly between the states heard.
+    pub fn remote(&self) -> Option<PlayerState> {
+        let latest = self.latest?;
+        let Some(previous) = self.previous else {
+            return Some(latest.state);
+        };
+        let apart = (latest.tick - previous.tick) as f32 * NET_TICK.as_secs_f32();
+        let progress = (self.since_latest / apart).min(1.0);
+        let from = previous.state.position;
+        Some(PlayerState {
+            position: from + (latest.state.position - from) * progress,
+            ..latest.state
+        })
+    }
+    fn receive(&mut self) -> Result<(), NetError> {
+        let mut packet = [0; MAX_PACKET];
+        loop {
+            let (len, from) = match self.socket.recv_from(&mut packet) {
+                Ok(received) => received,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return Ok(()),
+                Err(e) => return Err(e.into()),
+            };
+            // Hosts play with whoever gets in touch first
+            let remote = *self.remote.get_or_insert(from);
+            if from != remote {
+                continue;
+            }
+            let packet = String::from_utf8_lossy(&packet[..len]);
+            let (tick, state) = match PlayerState::decode(&packet) {
+                Ok(decoded) => decoded,
+                Err(error) => {
+                    warn!(%error, %from, "dropped player state");
+                    continue;
+                }
+            };
+            if self.latest.is_some_and(|latest| tick <= latest.tick) {
+                continue;
+            }
+            self.previous = self.latest.replace(Snapshot { tick, state });
+            self.since_latest = 0.0;
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum NetError {
+    #[error("network error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("host address resolved to nothing")]
+    Unresolved,
+    #[error("malformed player state: {0}")]
+    Malformed(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(x: f32, y: f32) -> PlayerState {
+        PlayerState {
+            position: Coordinate { x, y },
+            motion: Some(Coordinate { x: 1.0, y: 0.0 }),
+            frame: 2,
+        }
+    }
+
+    /// Updates `peer` until it heard the state sent on `tick`, since datagrams
+    /// on the loopback can take a moment to arrive.
+    fn wait_for(peer: &mut Peer, tick: u32) {
+        for _ in 0..100 {
+            peer.update(0.0, &at(0.0, 0.0)).unwrap();
+            if peer.latest.is_some_and(|latest| latest.tick >= tick) {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn test_state_round_trip() {
+        let state = at(1.5, -3.0);
+        assert_eq!(PlayerState::decode(&state.encode(7)).unwrap(), (7, state));
+        let still = PlayerState {
+            motion: None,
+            ..state
+        };
+        assert_eq!(PlayerState::decode(&still.encode(8)).unwrap(), (8, still));
+        assert!(matches!(
+            PlayerState::decode("state 1 x 0 0 -"),
+            Err(NetError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn test_peers_sync_smoothly() {
+        let mut host = Peer::host("127.0.0.1:0").unwrap();
+        let mut client = Peer::connect("127.0.0.1:0", host.local_addr().unwrap()).unwrap();
+        let tick = NET_TICK.as_secs_f32();
+
+        // Nothing is sent before a network tick passed
+        client.update(tick / 2.0, &at(0.0, 0.0)).unwrap();
+        wait_for(&mut host, 1);
+        assert!(!host.is_connected());
+
+        client.update(tick * 0.6, &at(0.0, 0.0)).unwrap();
+        wait_for(&mut host, 1);
+        assert_eq!(host.remote(), Some(at(0.0, 0.0)));
+
+        // The host found out who to answer
+        host.update(tick, &at(3.0, 0.0)).unwrap();
+        wait_for(&mut client, 1);
+        assert_eq!(client.remote(), Some(at(3.0, 0.0)));
+
+        client.update(tick, &at(10.0, 0.0)).unwrap();
+        wait_for(&mut host, 2);
+        // Starts out from the previous state, halfway there half a tick later
+        assert_eq!(host.remote().unwrap().position, Coordinate::default());
+        host.update(tick / 2.0, &at(0.0, 0.0)).unwrap();
+        assert_eq!(
+            host.remote().unwrap().position,
+            Coordinate { x: 5.0, y: 0.0 }
+        );
+    }
+}
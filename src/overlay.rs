@@ -0,0 +1,257 @@
+//! Outlines of the boxes the game collides, hits and triggers with.
+//!
+//! A `DebugOverlay` is drawn over the world to show what's otherwise
+//! invisible: the area each character's frame covers, the hitboxes and
+//! hurtboxes authored on it, level triggers and the camera's dead zone. Every
+//! `Category` has a color of its own and is shown or hidden on its own at
+//! runtime, e.g. from the debug console with `overlay hitboxes on`.
+//!
+//! Boxes are given in world space. Boxes that change every frame, like a
+//! character's, are `add`ed each frame and gone once drawn, while boxes that
+//! stay put, like triggers, are `mark`ed once.
+//!
+//! # Key Structures
+//! - **`Category`**: A kind of box, drawn in its own color.
+//! - **`DebugOverlay`**: The boxes to outline and which categories are shown.
+//!
+//! # Example Usage
+//! ```ignore
+//! let mut overlay = DebugOverlay::new();
+//! overlay.show(Category::Trigger, true);
+//! overlay.mark(Category::Trigger, door);
+//!
+//! // Every frame
+//! overlay.colliders(&animation.colliders(mirror, player_pos));
+//! overlay.commands(&camera, &mut batch);
+//! ```
+use crate::{
+    camera::Camera,
+    hitbox::{BoxKind, Collider, Rect},
+    layout::Coordinate,
+    palette::{Color, GREEN, LIGHT_BROWN, LIGHT_GRAY, RED},
+    renderer::DrawCmd,
+};
+
+/// A kind of box, drawn in its own color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Category {
+    /// The area a character's frame covers, which characters collide with.
+    Bounds,
+    /// Areas an attack connects with.
+    Hitbox,
+    /// Areas a character can be hit on.
+    Hurtbox,
+    /// Areas that set something off when walked into.
+    Trigger,
+    /// Where the player can move without the camera following.
+    DeadZone,
+}
+impl Category {
+    pub const ALL: [Category; 5] = [
+        Category::Bounds,
+        Category::Hitbox,
+        Category::Hurtbox,
+        Category::Trigger,
+        Category::DeadZone,
+    ];
+    pub fn color(self) -> Color {
+        match self {
+            Category::Bounds => LIGHT_GRAY,
+            Category::Hitbox => RED,
+            Category::Hurtbox => GREEN,
+            Category::Trigger => LIGHT_BROWN,
+            Category::DeadZone => Color::RGB(80, 160, 255),
+        }
+    }
+    /// What the category is called on the debug console.
+    pub fn name(self) -> &'static str {
+        match self {
+            Category::Bounds => "bounds",
+            Category::Hitbox => "hitboxes",
+            Category::Hurtbox => "hurtboxes",
+            Category::Trigger => "triggers",
+            Category::DeadZone => "deadzone",
+        }
+    }
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.name() == name)
+    }
+}
+
+/// The boxes to outline and which categories are shown.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DebugOverlay {
+    shown: Vec<Category>,
+    /// Boxes drawn every frame until cleared
+    marks: Vec<(Category, Rect)>,
+    /// Boxes drawn in the next frame only
+    added: Vec<(Category, Rect)>,
+}
+impl DebugOverlay {
+    /// An overlay with every category hidden.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn show(&mut self, category: Category, shown: bool) {
+        self.shown.retain(|c| *c != category);
+        if shown {
+            self.shown.push(category);
+        }
+    }
+    pub fn is_shown(&self, category: Category) -> bool {
+        self.shown.contains(&category)
+    }
+    /// Whether any category is shown at all.
+    pub fn is_visible(&self) -> bool {
+        !self.shown.is_empty()
+    }
+    /// Outlines `rect` every frame, until the marks are cleared.
+    pub fn mark(&mut self, category: Category, rect: Rect) {
+        self.marks.push((category, rect));
+    }
+    pub fn clear_marks(&mut self) {
+        self.marks.clear();
+    }
+    /// Outlines `rect` in the next frame drawn.
+    pub fn add(&mut self, category: Category, rect: Rect) {
+        if self.is_shown(category) {
+            self.added.push((category, rect));
+        }
+    }
+    /// Outlines a frame's hitboxes and hurtboxes in the next frame drawn.
+    pub fn colliders(&mut self, colliders: &[Collider]) {
+        for collider in colliders {
+            let category = match collider.kind {
+                BoxKind::Hit => Category::Hitbox,
+                BoxKind::Hurt => Category::Hurtbox,
+            };
+            self.add(category, collider.rect);
+        }
+    }
+    /// Outlines the boxes of every shown category as seen through `camera`.
+    pub fn commands<'a>(&mut self, camera: &Camera, batch: &mut Vec<DrawCmd<'a>>) {
+        for &(category, rect) in self.marks.iter().chain(&self.added) {
+            if !self.shown.contains(&category) {
+                continue;
+            }
+            let corner = camera.to_screen(Coordinate {
+                x: rect.x,
+                y: rect.y,
+            });
+            let on_screen = Rect {
+                x: corner.x,
+                y: corner.y,
+                ..rect
+            };
+            outline(on_screen, category.color(), batch);
+        }
+        self.added.clear();
+    }
+}
+
+/// Draws the edges of `rect` a pixel wide.
+fn outline<'a>(rect: Rect, color: Color, batch: &mut Vec<DrawCmd<'a>>) {
+    let (x, y) = (rect.x.round() as i32, rect.y.round() as i32);
+    let (width, height) = (rect.width.round() as i32, rect.height.round() as i32);
+    if width <= 0 || height <= 0 {
+        return;
+    }
+    for row in [y, y + height - 1] {
+        batch.push(DrawCmd::Span {
+            start: (x, row),
+            len: width as u32,
+            color,
+        });
+    }
+    for row in y + 1..y + height - 1 {
+        for column in [x, x + width - 1] {
+            batch.push(DrawCmd::Span {
+                start: (column, row),
+                len: 1,
+                color,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window::{HeadlessScreen, Screen};
+
+    fn rect(x: f32, y: f32, width: f32, height: f32) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    fn rgba(color: Color) -> Option<Color> {
+        let [r, g, b, a] = color.to_rgba();
+        Some(Color::RGBA(r, g, b, a))
+    }
+
+    #[test]
+    fn test_outlines_shown_categories() {
+        let mut overlay = DebugOverlay::new();
+        overlay.show(Category::Hurtbox, true);
+        let body = Collider {
+            name: "body",
+            kind: BoxKind::Hurt,
+            rect: rect(1.0, 1.0, 4.0, 3.0),
+        };
+        let sword = Collider {
+            name: "sword",
+            kind: BoxKind::Hit,
+            rect: rect(5.0, 5.0, 2.0, 2.0),
+        };
+        overlay.colliders(&[body, sword]);
+        let mut batch = Vec::new();
+        overlay.commands(&Camera::new(8, 8), &mut batch);
+        let mut screen = HeadlessScreen::new(8, 8);
+        screen.draw_commands(&batch).unwrap();
+
+        let green = rgba(GREEN);
+        for (x, y) in [(1, 1), (4, 1), (1, 2), (4, 2), (1, 3), (4, 3)] {
+            assert_eq!(screen.pixel(x, y), green, "({x}, {y})");
+        }
+        // Only the edges of shown categories are drawn
+        assert_ne!(screen.pixel(2, 2), green);
+        assert_ne!(screen.pixel(5, 5), rgba(RED));
+
+        // Added boxes are gone once drawn
+        batch.clear();
+        overlay.commands(&Camera::new(8, 8), &mut batch);
+        assert!(batch.is_empty());
+    }
+
+    #[test]
+    fn test_marks_stay_and_follow_the_camera() {
+        let mut overlay = DebugOverlay::new();
+        overlay.mark(Category::Trigger, rect(10.0, 10.0, 2.0, 2.0));
+        let mut camera = Camera::new(8, 8);
+        camera.lock_follow();
+        camera.update(0.1, Coordinate { x: 10.0, y: 10.0 });
+
+        let mut batch = Vec::new();
+        overlay.commands(&camera, &mut batch);
+        assert!(batch.is_empty());
+
+        overlay.show(Category::Trigger, true);
+        for _ in 0..2 {
+            batch.clear();
+            overlay.commands(&camera, &mut batch);
+            assert!(matches!(
+                batch[0],
+                DrawCmd::Span {
+                    start: (4, 4),
+                    len: 2,
+                    ..
+                }
+            ));
+        }
+        assert_eq!(Category::from_name("triggers"), Some(Category::Trigger));
+    }
+}
@@ -15,40 +15,224 @@
 //!   either horizontally or vertically, over a specified range.
 //! - **`Stroke` Struct**: Defines a solid-colored stroke that is rendered in a specific direction
 //!   (either vertical or horizontal) for a given length.
+//! - **`Gradient` Struct**: Blends smoothly from one color to another over a range.
+//! - **`Dither` Struct**: Blends from one color to another over a range using only
+//!   the two colors, in an ordered dither pattern, so large shapes like skies and
+//!   water don't show bands of flat color.
+//!
+//! Colors can be read from hex strings like `#cd853f`, built from hue, saturation
+//! and value, and brightened or darkened, e.g. for lighting and damage flashes.
+//! Tweens blend between them through their `Lerp` implementation.
+//!
+//! Errors:
+//! - `ColorError`: A hex string isn't a color.
 
 //! # Color Definitions
 //! Several common colors are predefined for convenience:
-//! - `LIGHT_BROWN`, `MIDNIGHT`, `LIGHT_GRAY`, `DARK_BROWN`, `RED`, `BLACK`, and `TRANSPARENT`.
-//! These colors can be used as `Color` values in various `ColorScheme` options.
+//! - `LIGHT_BROWN`, `MIDNIGHT`, `LIGHT_GRAY`, `DARK_BROWN`, `RED`, `GREEN`, `BLACK`, and `TRANSPARENT`.
+//!   These colors can be used as `Color` values in various `ColorScheme` options.
 
 //! # Example Usage
 //! To create a pixel with a checkered pattern, use the `CheckPattern` and `ColorScheme::CheckPattern`:
-//! ```rust
+//! ```ignore
 //! let checkered_pattern = CheckPattern::new(Color::RGB(255, 0, 0), Color::RGB(0, 0, 255), Direction::Horizontal(4));
 //! let pixel = ColorScheme::CheckPattern(checkered_pattern);
 //! ```
 //! To create a pixel with a stroke, use the `Stroke` and `ColorScheme::Stroke`:
-//! ```rust
+//! ```ignore
 //! let stroke = Stroke::new(Color::RGB(0, 255, 0), Direction::Vertical(5));
 //! let pixel = ColorScheme::Stroke(stroke);
 //! ```
+//! To fade a row of sky from blue to white, use the `Dither` and `ColorScheme::Dither`:
+//! ```ignore
+//! let sky = Dither::new(Color::RGB(40, 80, 200), LIGHT_GRAY, Direction::Horizontal(64));
+//! let pixel = ColorScheme::Dither(sky);
+//! ```
 
-use crate::prelude::*;
+use thiserror::Error;
+
+use crate::{prelude::*, tween::Lerp};
+
+/// Thresholds of a 4x4 ordered dither, out of 16.
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
 
 pub const LIGHT_BROWN: Color = Color::RGB(205, 133, 63);
 pub const MIDNIGHT: Color = Color::RGB(8, 8, 8);
 pub const LIGHT_GRAY: Color = Color::RGB(188, 188, 188);
 pub const DARK_BROWN: Color = Color::RGB(139, 69, 19);
 pub const RED: Color = Color::RGB(255, 0, 0);
+pub const GREEN: Color = Color::RGB(60, 200, 60);
 pub const BLACK: Color = Color::RGB(0, 0, 0);
 pub const TRANSPARENT: Color = Color::RGBA(0, 0, 0, 0);
 
 /// Defines the coloring of a pixel.
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Clone, Debug, Copy, PartialEq)]
 pub enum Color {
     RGB(u8, u8, u8),
     RGBA(u8, u8, u8, u8),
 }
+impl Color {
+    /// The color as raw RGBA bytes, fully opaque for `RGB`.
+    pub fn to_rgba(self) -> [u8; 4] {
+        match self {
+            Color::RGB(r, g, b) => [r, g, b, 255],
+            Color::RGBA(r, g, b, a) => [r, g, b, a],
+        }
+    }
+    /// Reads `RRGGBB` as an `RGB` or `RRGGBBAA` as an `RGBA`, with or without a
+    /// leading `#`.
+    pub fn from_hex(hex: &str) -> Result<Self, ColorError> {
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        let invalid = || ColorError::Hex(hex.to_string());
+        if !digits.is_ascii() || !matches!(digits.len(), 6 | 8) {
+            return Err(invalid());
+        }
+        let channel = |i: usize| u8::from_str_radix(&digits[i * 2..i * 2 + 2], 16);
+        let (r, g, b) = (
+            channel(0).map_err(|_| invalid())?,
+            channel(1).map_err(|_| invalid())?,
+            channel(2).map_err(|_| invalid())?,
+        );
+        match digits.len() {
+            8 => Ok(Color::RGBA(r, g, b, channel(3).map_err(|_| invalid())?)),
+            _ => Ok(Color::RGB(r, g, b)),
+        }
+    }
+    /// An opaque color of `hue` in degrees, wrapping around past `360.0`, and
+    /// of `saturation` and `value` from `0.0` to `1.0`.
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Self {
+        let (saturation, value) = (saturation.clamp(0.0, 1.0), value.clamp(0.0, 1.0));
+        let hue = hue.rem_euclid(360.0) / 60.0;
+        let chroma = value * saturation;
+        let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+        let (r, g, b) = match hue as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let channel = |c: f32| ((c + value - chroma) * 255.0).round() as u8;
+        Color::RGB(channel(r), channel(g), channel(b))
+    }
+    /// The hue in degrees, saturation and value of the color, ignoring its
+    /// alpha.
+    pub fn to_hsv(self) -> (f32, f32, f32) {
+        let [r, g, b, _] = self.to_rgba().map(|c| c as f32 / 255.0);
+        let max = r.max(g).max(b);
+        let chroma = max - r.min(g).min(b);
+        let hue = if chroma == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / chroma).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / chroma + 2.0)
+        } else {
+            60.0 * ((r - g) / chroma + 4.0)
+        };
+        let saturation = if max == 0.0 { 0.0 } else { chroma / max };
+        (hue, saturation, max)
+    }
+    /// The color `amount` of the way to white, from `0.0` to `1.0`, keeping
+    /// its alpha.
+    pub fn brighten(self, amount: f32) -> Self {
+        self.map_rgb(|c| c + (255.0 - c) * amount.clamp(0.0, 1.0))
+    }
+    /// The color `amount` of the way to black, from `0.0` to `1.0`, keeping
+    /// its alpha.
+    pub fn darken(self, amount: f32) -> Self {
+        self.map_rgb(|c| c * (1.0 - amount.clamp(0.0, 1.0)))
+    }
+    fn map_rgb(self, f: impl Fn(f32) -> f32) -> Self {
+        let channel = |c: u8| f(c as f32).round().clamp(0.0, 255.0) as u8;
+        match self {
+            Color::RGB(r, g, b) => Color::RGB(channel(r), channel(g), channel(b)),
+            Color::RGBA(r, g, b, a) => Color::RGBA(channel(r), channel(g), channel(b), a),
+        }
+    }
+}
+
+/// A blend from one color to another across a directional range.
+#[derive(Clone, Copy)]
+pub struct Gradient {
+    /// The color at the start of the range.
+    pub(crate) from: Color,
+    /// The color at the end of the range.
+    pub(crate) to: Color,
+    /// The direction and length of the gradient.
+    pub(crate) range: Direction,
+}
+impl Gradient {
+    /// Creates a new `Gradient` from one color to the other over the range.
+    pub fn new(from: Color, to: Color, range: Direction) -> Self {
+        Self { from, to, range }
+    }
+    /// The color `i` cells into the range.
+    pub(crate) fn color_at(&self, i: u16) -> Color {
+        self.from.lerp(&self.to, progress(i, &self.range))
+    }
+}
+
+/// A blend between two colors drawn as a pattern of only those two.
+///
+/// Each cell picks one of the colors by comparing how far it is blended
+/// against the threshold of where it lands in a 4x4 ordered dither, so nearby
+/// strokes of the same blend line up into one pattern.
+#[derive(Clone, Copy)]
+pub struct Dither {
+    /// The color the blend starts with.
+    pub(crate) a: Color,
+    /// The color the blend ends with.
+    pub(crate) b: Color,
+    /// How much of `b` there is at the start and at the end of the range.
+    pub(crate) amount: (f32, f32),
+    /// The direction and length of the pattern.
+    pub(crate) range: Direction,
+}
+impl Dither {
+    /// Creates a new `Dither` going from all `a` to all `b` over the range.
+    pub fn new(a: Color, b: Color, range: Direction) -> Self {
+        Self {
+            a,
+            b,
+            amount: (0.0, 1.0),
+            range,
+        }
+    }
+    /// Mixes `amount` of `b`, from `0.0` to `1.0`, into `a` all along the range
+    /// instead, e.g. `0.5` for an even checkerboard.
+    pub fn mix(mut self, amount: f32) -> Self {
+        self.amount = (amount, amount);
+        self
+    }
+    /// The color of the cell `i` into the range, which sits at `x` and `y`.
+    pub(crate) fn color_at(&self, i: u16, x: u16, y: u16) -> Color {
+        let amount = self.amount.0.lerp(&self.amount.1, progress(i, &self.range));
+        let threshold = (BAYER[y as usize % 4][x as usize % 4] as f32 + 0.5) / 16.0;
+        if amount > threshold {
+            self.b
+        } else {
+            self.a
+        }
+    }
+}
+
+/// How far along `range` the cell `i` into it is, from `0.0` to `1.0`.
+fn progress(i: u16, range: &Direction) -> f32 {
+    let (Direction::Vertical(len) | Direction::Horizontal(len)) = *range;
+    match len {
+        0 | 1 => 0.0,
+        _ => i as f32 / (len - 1) as f32,
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ColorError {
+    #[error("invalid hex color {0}")]
+    Hex(String),
+}
 /// Defines the color styling for a `Pixel`.
 #[derive(Clone, Copy)]
 pub enum ColorScheme {
@@ -63,6 +247,10 @@ pub enum ColorScheme {
     ///
     /// Used for creating vertical or horizontal lines.
     Stroke(Stroke),
+    /// A smooth blend between two colors in a specified direction and length.
+    Gradient(Gradient),
+    /// A blend between two colors dithered from just the two of them.
+    Dither(Dither),
 }
 
 /// A checkered pattern composed of two alternating colors.
@@ -99,3 +287,55 @@ impl Stroke {
         Self { color, range }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_from_hex() {
+        assert_eq!(Color::from_hex("#cd853f").unwrap(), LIGHT_BROWN);
+        assert_eq!(
+            Color::from_hex("00000000").unwrap().to_rgba(),
+            TRANSPARENT.to_rgba()
+        );
+        for hex in ["#cd853", "#gg853f", "#cd853f0", "#cd85é"] {
+            assert!(matches!(Color::from_hex(hex), Err(ColorError::Hex(_))));
+        }
+    }
+
+    #[test]
+    fn test_hsv_and_brightness() {
+        assert_eq!(Color::from_hsv(0.0, 1.0, 1.0), RED);
+        assert_eq!(Color::from_hsv(480.0, 1.0, 1.0), Color::RGB(0, 255, 0));
+        let (hue, saturation, value) = LIGHT_BROWN.to_hsv();
+        assert_eq!(Color::from_hsv(hue, saturation, value), LIGHT_BROWN);
+        assert_eq!(BLACK.to_hsv(), (0.0, 0.0, 0.0));
+
+        assert_eq!(RED.darken(0.5), Color::RGB(128, 0, 0));
+        assert_eq!(
+            Color::RGBA(0, 100, 200, 50).brighten(1.0),
+            Color::RGBA(255, 255, 255, 50)
+        );
+    }
+
+    #[test]
+    fn test_gradient_and_dither_blend_over_the_range() {
+        let gradient = Gradient::new(BLACK, Color::RGB(200, 100, 0), Direction::Horizontal(5));
+        let colors: Vec<_> = (0..5).map(|i| gradient.color_at(i)).collect();
+        assert_eq!(colors[0], BLACK);
+        assert_eq!(colors[2], Color::RGB(100, 50, 0));
+        assert_eq!(colors[4], Color::RGB(200, 100, 0));
+
+        let dither = Dither::new(BLACK, RED, Direction::Horizontal(16));
+        assert_eq!(dither.color_at(0, 0, 0), BLACK);
+        assert_eq!(dither.color_at(15, 15, 0), RED);
+        // An even mix covers half of every 4x4 block
+        let checker = Dither::new(BLACK, RED, Direction::Horizontal(4)).mix(0.5);
+        let red = (0..4)
+            .flat_map(|y| (0..4).map(move |x| (x, y)))
+            .filter(|&(x, y)| checker.color_at(x, x, y) == RED)
+            .count();
+        assert_eq!(red, 8);
+    }
+}
@@ -0,0 +1,370 @@
+//! A* pathfinding over a grid of walkable tiles.
+//!
+//! Paths are searched over anything implementing `Walkable` so the same search
+//! works for a tilemap's collision data or a plain `WalkGrid`. Found paths can be
+//! smoothed down to the corners that matter and followed with a `PathFollower`,
+//! which produces the same directional `Coordinate`s player input does so
+//! AI-controlled characters reuse the existing animation selection.
+//!
+//! # Key Structures
+//! - **`Walkable`**: Grid dimensions and which tiles can be walked on.
+//! - **`WalkGrid`**: A simple owned `Walkable` grid of blocked and open tiles.
+//! - **`PathFollower`**: Steers a position along a path one waypoint at a time.
+//!
+//! # Example Usage
+//! ```ignore
+//! let mut grid = WalkGrid::new(20, 12);
+//! grid.block(5, 0..8);
+//!
+//! let path = find_path(&grid, (0, 0), (10, 2)).map(|p| smooth_path(&grid, &p));
+//! let mut follower = PathFollower::new(&path.unwrap(), 16.0);
+//!
+//! // Fed to the NPC the same way a key press is fed to the player
+//! if let Some(direction) = follower.steer(npc_pos) {
+//!     npc_pos += direction * speed * delta;
+//! }
+//! ```
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    ops::Range,
+};
+
+use crate::layout::Coordinate;
+
+/// Cost of a straight step, scaled so diagonals stay integers.
+const STRAIGHT: u32 = 10;
+/// Cost of a diagonal step, roughly `STRAIGHT * sqrt(2)`.
+const DIAGONAL: u32 = 14;
+
+/// A grid whose tiles are either walkable or blocked.
+pub trait Walkable {
+    fn width(&self) -> u32;
+    fn height(&self) -> u32;
+    /// Whether the tile at `(x, y)` can be walked on.
+    ///
+    /// Tiles outside the grid are never walkable
+    fn is_walkable(&self, x: i32, y: i32) -> bool;
+}
+
+/// An owned grid of walkable tiles, all open until blocked.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WalkGrid {
+    width: u32,
+    height: u32,
+    blocked: Vec<bool>,
+}
+impl WalkGrid {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            blocked: vec![false; (width * height) as usize],
+        }
+    }
+    /// Blocks the tiles of column `x` over the `rows`.
+    pub fn block(&mut self, x: u32, rows: Range<u32>) {
+        for y in rows {
+            self.set_blocked(x, y, true);
+        }
+    }
+    pub fn set_blocked(&mut self, x: u32, y: u32, blocked: bool) {
+        if x < self.width && y < self.height {
+            self.blocked[(y * self.width + x) as usize] = blocked;
+        }
+    }
+}
+impl Walkable for WalkGrid {
+    fn width(&self) -> u32 {
+        self.width
+    }
+    fn height(&self) -> u32 {
+        self.height
+    }
+    fn is_walkable(&self, x: i32, y: i32) -> bool {
+        if x < 0 || y < 0 || x as u32 >= self.width || y as u32 >= self.height {
+            return false;
+        }
+        !self.blocked[(y as u32 * self.width + x as u32) as usize]
+    }
+}
+
+/// Finds the shortest path of tiles from `start` to `goal`, both included.
+///
+/// Movement is allowed in eight directions, but diagonals never cut past the
+/// corner of a blocked tile. Returns `None` when the goal can't be reached
+pub fn find_path(
+    grid: &impl Walkable,
+    start: (i32, i32),
+    goal: (i32, i32),
+) -> Option<Vec<(i32, i32)>> {
+    if !grid.is_walkable(start.0, start.1) || !grid.is_walkable(goal.0, goal.1) {
+        return None;
+    }
+
+    let mut open = BinaryHeap::new();
+    let mut came_from = HashMap::new();
+    let mut cost = HashMap::from([(start, 0)]);
+    open.push(Reverse((heuristic(start, goal), start)));
+
+    while let Some(Reverse((_, current))) = open.pop() {
+        if current == goal {
+            let mut path = vec![current];
+            let mut tile = current;
+            while let Some(&previous) = came_from.get(&tile) {
+                path.push(previous);
+                tile = previous;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        let current_cost = cost[&current];
+        for (next, step) in neighbours(grid, current) {
+            let next_cost = current_cost + step;
+            if cost.get(&next).is_none_or(|&c| next_cost < c) {
+                cost.insert(next, next_cost);
+                came_from.insert(next, current);
+                open.push(Reverse((next_cost + heuristic(next, goal), next)));
+            }
+        }
+    }
+    None
+}
+
+/// Removes every waypoint that can be skipped by walking straight past it.
+///
+/// The first and last tiles are always kept
+pub fn smooth_path(grid: &impl Walkable, path: &[(i32, i32)]) -> Vec<(i32, i32)> {
+    let Some(&first) = path.first() else {
+        return Vec::new();
+    };
+    let mut smoothed = vec![first];
+    let mut anchor = 0;
+    while anchor < path.len() - 1 {
+        // Furthest waypoint still in a straight line of sight
+        let mut next = anchor + 1;
+        for candidate in (anchor + 2..path.len()).rev() {
+            if line_of_sight(grid, path[anchor], path[candidate]) {
+                next = candidate;
+                break;
+            }
+        }
+        smoothed.push(path[next]);
+        anchor = next;
+    }
+    smoothed
+}
+
+/// Whether every tile touched by a straight line between two tiles is walkable.
+fn line_of_sight(grid: &impl Walkable, from: (i32, i32), to: (i32, i32)) -> bool {
+    let (dx, dy) = ((to.0 - from.0).abs(), (to.1 - from.1).abs());
+    let (sx, sy) = ((to.0 - from.0).signum(), (to.1 - from.1).signum());
+    let (mut x, mut y) = from;
+    // Walks every tile the line passes through, not only the ones it crosses most of
+    let mut error = dx - dy;
+    for _ in 0..dx + dy {
+        let e = 2 * error;
+        if e > -dy && e < dx {
+            // Passing exactly through a corner touches both neighbours
+            if !grid.is_walkable(x + sx, y) || !grid.is_walkable(x, y + sy) {
+                return false;
+            }
+            error += dx - dy;
+            x += sx;
+            y += sy;
+        } else if e > -dy {
+            error -= dy;
+            x += sx;
+        } else {
+            error += dx;
+            y += sy;
+        }
+        if !grid.is_walkable(x, y) {
+            return false;
+        }
+        if (x, y) == to {
+            break;
+        }
+    }
+    true
+}
+
+fn heuristic(from: (i32, i32), to: (i32, i32)) -> u32 {
+    let dx = from.0.abs_diff(to.0);
+    let dy = from.1.abs_diff(to.1);
+    STRAIGHT * dx.max(dy) + (DIAGONAL - STRAIGHT) * dx.min(dy)
+}
+
+fn neighbours(grid: &impl Walkable, (x, y): (i32, i32)) -> Vec<((i32, i32), u32)> {
+    let mut tiles = Vec::with_capacity(8);
+    for (dx, dy) in [
+        (0, -1),
+        (-1, 0),
+        (1, 0),
+        (0, 1),
+        (-1, -1),
+        (1, -1),
+        (-1, 1),
+        (1, 1),
+    ] {
+        let (nx, ny) = (x + dx, y + dy);
+        if !grid.is_walkable(nx, ny) {
+            continue;
+        }
+        if dx != 0 && dy != 0 {
+            // No squeezing diagonally between two blocked tiles or past a corner
+            if !grid.is_walkable(x + dx, y) || !grid.is_walkable(x, y + dy) {
+                continue;
+            }
+            tiles.push(((nx, ny), DIAGONAL));
+        } else {
+            tiles.push(((nx, ny), STRAIGHT));
+        }
+    }
+    tiles
+}
+
+/// Steers a position through the waypoints of a path in order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathFollower {
+    waypoints: Vec<Coordinate>,
+    next: usize,
+    /// How close in pixels a waypoint must be approached on both axes to count
+    /// as reached.
+    tolerance: f32,
+}
+impl PathFollower {
+    /// Follows a path of tiles, aiming for the top left corner of each tile.
+    pub fn new(path: &[(i32, i32)], tile_size: f32) -> Self {
+        Self {
+            waypoints: path
+                .iter()
+                .map(|&(x, y)| Coordinate {
+                    x: x as f32 * tile_size,
+                    y: y as f32 * tile_size,
+                })
+                .collect(),
+            next: 0,
+            tolerance: 1.0,
+        }
+    }
+    pub fn tolerance(mut self, tolerance: f32) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.waypoints.len()
+    }
+    /// The direction to move from `position` towards the next waypoint.
+    ///
+    /// Each axis is `-1.0`, `0.0` or `1.0` like movement input. Returns `None`
+    /// once the last waypoint was reached
+    pub fn steer(&mut self, position: Coordinate) -> Option<Coordinate> {
+        let axis = |d: f32| {
+            if d.abs() > self.tolerance {
+                d.signum()
+            } else {
+                0.0
+            }
+        };
+        while let Some(&waypoint) = self.waypoints.get(self.next) {
+            let offset = waypoint - position;
+            let step = Coordinate {
+                x: axis(offset.x),
+                y: axis(offset.y),
+            };
+            // Close enough on both axes that neither would move any closer
+            if step != Coordinate::default() {
+                return Some(step);
+            }
+            self.next += 1;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_path_around_wall() {
+        let mut grid = WalkGrid::new(5, 5);
+        grid.block(2, 0..4);
+
+        let path = find_path(&grid, (0, 0), (4, 0)).unwrap();
+
+        assert_eq!(path.first(), Some(&(0, 0)));
+        assert_eq!(path.last(), Some(&(4, 0)));
+        assert!(path.iter().all(|&(x, y)| grid.is_walkable(x, y)));
+        // Has to go down and around the bottom of the wall
+        assert!(path.contains(&(2, 4)));
+        // Every step moves to a neighbouring tile
+        assert!(path
+            .windows(2)
+            .all(|w| (w[0].0 - w[1].0).abs() <= 1 && (w[0].1 - w[1].1).abs() <= 1));
+    }
+
+    #[test]
+    fn test_find_path_unreachable() {
+        let mut grid = WalkGrid::new(5, 5);
+        grid.block(2, 0..5);
+
+        assert_eq!(find_path(&grid, (0, 0), (4, 0)), None);
+        assert_eq!(find_path(&grid, (0, 0), (2, 2)), None);
+        assert_eq!(find_path(&grid, (0, 0), (9, 0)), None);
+    }
+
+    #[test]
+    fn test_no_corner_cutting() {
+        let mut grid = WalkGrid::new(3, 3);
+        grid.set_blocked(1, 0, true);
+
+        let path = find_path(&grid, (0, 0), (2, 0)).unwrap();
+        assert!(!path.windows(2).any(|w| w == [(0, 0), (1, 1)]));
+        assert_eq!(path.len(), 5);
+    }
+
+    #[test]
+    fn test_smooth_path() {
+        let grid = WalkGrid::new(10, 10);
+        let path = find_path(&grid, (0, 0), (9, 3)).unwrap();
+        assert_eq!(smooth_path(&grid, &path), vec![(0, 0), (9, 3)]);
+
+        let mut grid = WalkGrid::new(5, 5);
+        grid.block(2, 0..4);
+        let path = find_path(&grid, (0, 0), (4, 0)).unwrap();
+        let smoothed = smooth_path(&grid, &path);
+        assert!(smoothed.len() < path.len());
+        assert!(smoothed
+            .windows(2)
+            .all(|w| line_of_sight(&grid, w[0], w[1])));
+    }
+
+    #[test]
+    fn test_path_follower() {
+        let mut follower = PathFollower::new(&[(0, 0), (2, 0), (2, 1)], 10.0);
+        let mut position = Coordinate::default();
+
+        assert_eq!(
+            follower.steer(position),
+            Some(Coordinate { x: 1.0, y: 0.0 })
+        );
+        position.x = 20.0;
+        assert_eq!(
+            follower.steer(position),
+            Some(Coordinate { x: 0.0, y: 1.0 })
+        );
+        position.y = 10.0;
+        assert_eq!(follower.steer(position), None);
+        assert!(follower.is_finished());
+
+        // Off by less than the tolerance on both axes, though further away
+        // than it diagonally
+        let mut follower = PathFollower::new(&[(1, 1), (3, 1)], 10.0);
+        assert_eq!(
+            follower.steer(Coordinate { x: 10.8, y: 10.8 }),
+            Some(Coordinate { x: 1.0, y: 0.0 })
+        );
+    }
+}
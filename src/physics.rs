@@ -0,0 +1,199 @@
+//! A minimal physics integrator for anything that moves.
+//!
+//! A `Body` is given the velocity it should reach, e.g. from input or AI
+//! steering, and speeds up towards it with its `acceleration`, slows down with
+//! its `friction` once nothing drives it and never exceeds its `max_speed`.
+//! Knockback is applied as an impulse scaled by the body's `mass` and slides off
+//! on its own, regardless of where the body is being driven to. Everything that
+//! moves through a `Body` moves by the same rules.
+//!
+//! The defaults reach any velocity and stop at once, which is how the player
+//! walks unless tuned otherwise.
+//!
+//! # Key Structures
+//! - **`Body`**: Velocity and the rules it changes by, stepped every frame.
+//!
+//! # Example Usage
+//! ```ignore
+//! let mut body = Body::new().acceleration(400.0).friction(600.0).max_speed(80.0);
+//!
+//! // Every frame
+//! body.drive(input * speed);
+//! body.step(&mut position, delta);
+//!
+//! // Hit by something heavy
+//! body.impulse(Coordinate { x: -120.0, y: 0.0 });
+//! ```
+use crate::layout::Coordinate;
+
+/// Velocity and the rules it changes by.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Body {
+    /// The velocity the body moves with on its own accord.
+    pub velocity: Coordinate,
+    /// Velocity from impulses, sliding off with `knockback_friction`.
+    pub knockback: Coordinate,
+    /// Speed gained per second towards the driven velocity.
+    pub acceleration: f32,
+    /// Speed lost per second while not driven.
+    pub friction: f32,
+    /// Speed lost per second by knockback.
+    pub knockback_friction: f32,
+    pub max_speed: f32,
+    /// Divides impulses, so heavier bodies are knocked back less.
+    pub mass: f32,
+    target: Option<Coordinate>,
+}
+impl Body {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn acceleration(mut self, acceleration: f32) -> Self {
+        self.acceleration = acceleration;
+        self
+    }
+    pub fn friction(mut self, friction: f32) -> Self {
+        self.friction = friction;
+        self
+    }
+    pub fn knockback_friction(mut self, friction: f32) -> Self {
+        self.knockback_friction = friction;
+        self
+    }
+    pub fn max_speed(mut self, max_speed: f32) -> Self {
+        self.max_speed = max_speed;
+        self
+    }
+    pub fn mass(mut self, mass: f32) -> Self {
+        self.mass = mass;
+        self
+    }
+    /// Moves towards `velocity` on the next step.
+    ///
+    /// Must be called before every step the body should keep being driven for,
+    /// it slows down with friction otherwise
+    pub fn drive(&mut self, velocity: Coordinate) {
+        self.target = Some(velocity);
+    }
+    /// Knocks the body back, more so the lighter it is.
+    pub fn impulse(&mut self, impulse: Coordinate) {
+        if self.mass > 0.0 {
+            self.knockback += impulse * (1.0 / self.mass);
+        }
+    }
+    /// Updates the velocity over `delta` seconds and moves `position` with it.
+    ///
+    /// A step of no time, e.g. while the game is frozen, changes nothing
+    pub fn step(&mut self, position: &mut Coordinate, delta: f32) {
+        let target = self.target.take();
+        if delta <= 0.0 {
+            return;
+        }
+        self.velocity = match target {
+            Some(target) => approach(self.velocity, target, self.acceleration * delta),
+            None => approach(self.velocity, Coordinate::default(), self.friction * delta),
+        };
+        let speed = self.velocity.length();
+        if speed > self.max_speed {
+            self.velocity = self.velocity * (self.max_speed / speed);
+        }
+        *position += (self.velocity + self.knockback) * delta;
+        self.knockback = approach(
+            self.knockback,
+            Coordinate::default(),
+            self.knockback_friction * delta,
+        );
+    }
+    /// Whether the body is moving at all.
+    pub fn is_moving(&self) -> bool {
+        self.velocity != Coordinate::default() || self.knockback != Coordinate::default()
+    }
+}
+impl Default for Body {
+    fn default() -> Self {
+        Self {
+            velocity: Coordinate::default(),
+            knockback: Coordinate::default(),
+            acceleration: f32::INFINITY,
+            friction: f32::INFINITY,
+            knockback_friction: 400.0,
+            max_speed: f32::INFINITY,
+            mass: 1.0,
+            target: None,
+        }
+    }
+}
+
+/// Moves `from` at most `step` towards `to`.
+fn approach(from: Coordinate, to: Coordinate, step: f32) -> Coordinate {
+    let offset = to - from;
+    let distance = offset.length();
+    if distance <= step {
+        to
+    } else {
+        from + offset * (step / distance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RIGHT: Coordinate = Coordinate { x: 10.0, y: 0.0 };
+
+    #[test]
+    fn test_default_body_moves_instantly() {
+        let mut body = Body::new();
+        let mut position = Coordinate::default();
+
+        body.drive(RIGHT);
+        body.step(&mut position, 0.0);
+        assert_eq!(position, Coordinate::default());
+        body.drive(RIGHT);
+        body.step(&mut position, 0.5);
+        assert_eq!(position, Coordinate { x: 5.0, y: 0.0 });
+
+        body.step(&mut position, 0.5);
+        assert_eq!(position, Coordinate { x: 5.0, y: 0.0 });
+        assert!(!body.is_moving());
+    }
+
+    #[test]
+    fn test_acceleration_friction_and_max_speed() {
+        let mut body = Body::new().acceleration(20.0).friction(5.0).max_speed(8.0);
+        let mut position = Coordinate::default();
+
+        body.drive(RIGHT);
+        body.step(&mut position, 0.25);
+        assert_eq!(body.velocity, Coordinate { x: 5.0, y: 0.0 });
+        body.drive(RIGHT);
+        body.step(&mut position, 0.25);
+        assert_eq!(body.velocity, Coordinate { x: 8.0, y: 0.0 });
+
+        // Slides to a stop
+        body.step(&mut position, 1.0);
+        assert_eq!(body.velocity, Coordinate { x: 3.0, y: 0.0 });
+        body.step(&mut position, 1.0);
+        assert!(!body.is_moving());
+    }
+
+    #[test]
+    fn test_knockback_scales_with_mass() {
+        let mut light = Body::new().knockback_friction(10.0);
+        let mut heavy = light.clone().mass(4.0);
+        let (mut a, mut b) = (Coordinate::default(), Coordinate::default());
+
+        light.impulse(RIGHT * 2.0);
+        heavy.impulse(RIGHT * 2.0);
+        light.step(&mut a, 0.5);
+        heavy.step(&mut b, 0.5);
+        assert_eq!(a, Coordinate { x: 10.0, y: 0.0 });
+        assert_eq!(b, Coordinate { x: 2.5, y: 0.0 });
+        assert_eq!(light.knockback, Coordinate { x: 15.0, y: 0.0 });
+
+        // Driving doesn't cancel knockback
+        light.drive(Coordinate::default());
+        light.step(&mut a, 0.5);
+        assert_eq!(a, Coordinate { x: 17.5, y: 0.0 });
+    }
+}
@@ -0,0 +1,233 @@
+//! Named entity definitions spawned from levels and the debug console.
+//!
+//! A `Prefab` describes what an entity is made of before it's placed: the
+//! sprite it plays and the default value of each of its components, like its
+//! health or speed. Prefabs are collected into `Prefabs` and looked up by name
+//! wherever something is spawned, e.g. for the console's `spawn slime 40 20`. A
+//! placement can override the defaults it needs to with `Prefab::with`.
+//!
+//! Prefabs are written as plain text files, each starting with its name and
+//! followed by one component per line:
+//! ```text
+//! # Enemies
+//! prefab slime
+//! sprite slime
+//! health 30
+//! speed 12
+//!
+//! prefab king_slime
+//! sprite slime_big
+//! health 300
+//! ```
+//!
+//! # Key Structures
+//! - **`Prefab`**: An entity's sprite and the defaults of its components.
+//! - **`Prefabs`**: Every prefab known, by name.
+//!
+//! Errors:
+//! - `PrefabError`: Reading or parsing a prefab file failed.
+//!
+//! # Example Usage
+//! ```ignore
+//! let prefabs = Prefabs::load("assets/prefabs.txt")?;
+//!
+//! if let DebugCommand::Spawn(name, position) = command {
+//!     let slime = prefabs.get(&name).ok_or(PrefabError::Unknown(name))?;
+//!     let health: f32 = slime.value("health").unwrap_or(1.0);
+//!     spawn(slime.sprite(), position, health);
+//! }
+//! ```
+use std::{collections::HashMap, fs, path::Path, str::FromStr};
+
+use thiserror::Error;
+
+/// An entity's sprite and the defaults of its components.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Prefab {
+    name: String,
+    /// Components in the order they were written, each set once
+    components: Vec<(String, String)>,
+}
+impl Prefab {
+    /// A prefab without any components.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            components: Vec::new(),
+        }
+    }
+    /// Sets `component` to `value`, replacing its default.
+    pub fn with(mut self, component: &str, value: &str) -> Self {
+        self.set(component, value);
+        self
+    }
+    pub fn set(&mut self, component: &str, value: &str) {
+        match self.components.iter_mut().find(|(c, _)| c == component) {
+            Some((_, current)) => *current = value.to_string(),
+            None => self
+                .components
+                .push((component.to_string(), value.to_string())),
+        }
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// The name of the sprite the entity plays, if it's visible at all.
+    pub fn sprite(&self) -> Option<&str> {
+        self.get("sprite")
+    }
+    /// The value of `component` as written.
+    pub fn get(&self, component: &str) -> Option<&str> {
+        self.components
+            .iter()
+            .find(|(c, _)| c == component)
+            .map(|(_, value)| value.as_str())
+    }
+    /// The value of `component`, or `None` if it isn't set or isn't a `T`.
+    pub fn value<T: FromStr>(&self, component: &str) -> Option<T> {
+        self.get(component)?.parse().ok()
+    }
+    /// Every component and its value in the order they were set.
+    pub fn components(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.components
+            .iter()
+            .map(|(c, value)| (c.as_str(), value.as_str()))
+    }
+}
+
+/// Every prefab known, by name.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Prefabs {
+    prefabs: HashMap<String, Prefab>,
+}
+impl Prefabs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Reads and parses a prefab file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PrefabError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+    /// Parses prefabs each started by a `prefab <name>` line, skipping blank
+    /// lines and `#` comments.
+    pub fn parse(source: &str) -> Result<Self, PrefabError> {
+        let mut prefabs = Self::new();
+        let mut current: Option<Prefab> = None;
+        for (number, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (component, value) = line.split_once(' ').unwrap_or((line, ""));
+            let value = value.trim();
+            if value.is_empty() {
+                return Err(PrefabError::Syntax(number + 1));
+            }
+            if component == "prefab" {
+                if prefabs.prefabs.contains_key(value)
+                    || current.as_ref().is_some_and(|p| p.name == value)
+                {
+                    return Err(PrefabError::Duplicate(number + 1, value.to_string()));
+                }
+                prefabs.extend(current.replace(Prefab::new(value)));
+                continue;
+            }
+            let Some(prefab) = &mut current else {
+                return Err(PrefabError::Syntax(number + 1));
+            };
+            prefab.set(component, value);
+        }
+        prefabs.extend(current);
+        Ok(prefabs)
+    }
+    /// Adds `prefab`, replacing any of the same name.
+    pub fn insert(&mut self, prefab: Prefab) {
+        self.prefabs.insert(prefab.name.clone(), prefab);
+    }
+    pub fn get(&self, name: &str) -> Option<&Prefab> {
+        self.prefabs.get(name)
+    }
+    pub fn len(&self) -> usize {
+        self.prefabs.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.prefabs.is_empty()
+    }
+}
+impl Extend<Prefab> for Prefabs {
+    fn extend<I: IntoIterator<Item = Prefab>>(&mut self, prefabs: I) {
+        for prefab in prefabs {
+            self.insert(prefab);
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum PrefabError {
+    #[error("failed to read prefabs: {0}")]
+    ReadError(#[from] std::io::Error),
+    #[error("malformed prefab line {0}")]
+    Syntax(usize),
+    #[error("prefab on line {0} is already defined: {1}")]
+    Duplicate(usize, String),
+    #[error("no prefab called {0}")]
+    Unknown(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENEMIES: &str = "
+        # Enemies
+        prefab slime
+        sprite slime
+        health 30
+        speed 12
+
+        prefab sign
+        text Beware of slimes
+    ";
+
+    #[test]
+    fn test_parse_prefabs() {
+        let prefabs = Prefabs::parse(ENEMIES).unwrap();
+        assert_eq!(prefabs.len(), 2);
+        let slime = prefabs.get("slime").unwrap();
+        assert_eq!(slime.sprite(), Some("slime"));
+        assert_eq!(slime.value::<f32>("health"), Some(30.0));
+        let sign = prefabs.get("sign").unwrap();
+        assert_eq!(sign.get("text"), Some("Beware of slimes"));
+        assert_eq!(sign.sprite(), None);
+
+        assert!(matches!(
+            Prefabs::parse("health 30"),
+            Err(PrefabError::Syntax(1))
+        ));
+        assert!(matches!(
+            Prefabs::parse("prefab slime\nhealth"),
+            Err(PrefabError::Syntax(2))
+        ));
+        assert!(matches!(
+            Prefabs::parse("prefab slime\nprefab slime"),
+            Err(PrefabError::Duplicate(2, name)) if name == "slime"
+        ));
+    }
+
+    #[test]
+    fn test_placement_overrides_defaults() {
+        let prefabs = Prefabs::parse(ENEMIES).unwrap();
+        let tough = prefabs
+            .get("slime")
+            .unwrap()
+            .clone()
+            .with("health", "80")
+            .with("color", "green");
+
+        assert_eq!(tough.value::<u32>("health"), Some(80));
+        assert_eq!(tough.value::<u32>("color"), None);
+        // Overriding keeps the order components were written in
+        let components: Vec<_> = tough.components().map(|(c, _)| c).collect();
+        assert_eq!(components, ["sprite", "health", "speed", "color"]);
+    }
+}
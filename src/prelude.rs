@@ -5,6 +5,6 @@ pub use crate::{
     sprite::character::character::Character,
     sprite::character::knight::Knight,
     sprite::sprite::Sprite,
-    sync::Subscriber,
-    window::{GameWindowScreen, Screen, Window, WindowError},
+    sync::{Backpressure, EventBus, Inbox, Subscriber},
+    window::{GameWindowScreen, HeadlessScreen, Screen, Window, WindowError},
 };
@@ -0,0 +1,294 @@
+//! Lightweight per-frame timing of the engine's systems.
+//!
+//! Code is instrumented with `profile_scope!`, timing everything until the end
+//! of the enclosing block, or with `scope` for spans that don't line up with a
+//! block. Timings are only collected while profiling is enabled. Every
+//! `end_frame` sums up the frame's scopes into a `FrameProfile` for the debug
+//! overlay, and every scope is kept for dumping as a chrome tracing file
+//! (viewable with `chrome://tracing` or Perfetto).
+//!
+//! # Key Structures
+//! - **`Profiler`**: Collects scope timings; the engine reports into a global one.
+//! - **`Scope`**: Times a span from its creation until it's dropped.
+//! - **`FrameProfile`**: The summed up timings of a single frame.
+//!
+//! # Example Usage
+//! ```ignore
+//! profiler::enable(true);
+//! {
+//!     profile_scope!("render");
+//!     screen.draw_batch(&batch)?;
+//! }
+//! profiler::end_frame();
+//!
+//! profiler::last_frame().commands(&font, (2, 2), LIGHT_GRAY, 1, &mut batch);
+//! profiler::write_chrome_trace("trace.json")?;
+//! ```
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex, MutexGuard, PoisonError,
+    },
+    time::{Duration, Instant},
+};
+
+use thiserror::Error;
+
+use crate::{font::BitmapFont, palette::Color, renderer::DrawCmd};
+
+/// Scopes kept for the chrome trace before the oldest are dropped.
+const MAX_TRACE_SCOPES: usize = 100_000;
+
+/// The profiler the engine and `profile_scope!` report into.
+pub static PROFILER: Mutex<Profiler> = Mutex::new(Profiler::new());
+
+/// Times everything until the end of the enclosing block under `name`.
+#[macro_export]
+macro_rules! profile_scope {
+    ($name:expr) => {
+        let _profile_scope = $crate::profiler::scope($name);
+    };
+}
+
+/// A single timed span.
+#[derive(Debug, Clone, PartialEq)]
+struct Timing {
+    name: &'static str,
+    /// Start relative to when profiling was enabled.
+    start: Duration,
+    duration: Duration,
+    thread: u64,
+}
+
+/// Collects the timings of instrumented scopes.
+#[derive(Debug, Default)]
+pub struct Profiler {
+    /// When profiling was enabled or `None` while disabled.
+    epoch: Option<Instant>,
+    current: Vec<Timing>,
+    last: FrameProfile,
+    trace: Vec<Timing>,
+}
+impl Profiler {
+    pub const fn new() -> Self {
+        Self {
+            epoch: None,
+            current: Vec::new(),
+            last: FrameProfile { scopes: Vec::new() },
+            trace: Vec::new(),
+        }
+    }
+    /// Starts or stops collecting timings, discarding those collected so far.
+    pub fn enable(&mut self, enabled: bool) {
+        self.epoch = enabled.then(Instant::now);
+        self.current.clear();
+        self.trace.clear();
+        self.last = FrameProfile::default();
+    }
+    pub fn is_enabled(&self) -> bool {
+        self.epoch.is_some()
+    }
+    fn record(&mut self, name: &'static str, start: Instant, end: Instant) {
+        let Some(epoch) = self.epoch else {
+            return;
+        };
+        self.current.push(Timing {
+            name,
+            start: start.saturating_duration_since(epoch),
+            duration: end - start,
+            thread: thread_id(),
+        });
+    }
+    /// Sums up the scopes recorded since the previous call into the frame's profile.
+    pub fn end_frame(&mut self) {
+        if !self.is_enabled() {
+            return;
+        }
+        let mut profile = FrameProfile::default();
+        for timing in &self.current {
+            match profile
+                .scopes
+                .iter_mut()
+                .find(|(name, _)| *name == timing.name)
+            {
+                Some((_, total)) => *total += timing.duration,
+                None => profile.scopes.push((timing.name, timing.duration)),
+            }
+        }
+        self.last = profile;
+        self.trace.append(&mut self.current);
+        if self.trace.len() > MAX_TRACE_SCOPES {
+            self.trace.drain(..self.trace.len() - MAX_TRACE_SCOPES);
+        }
+    }
+    /// The profile of the most recently ended frame.
+    pub fn last_frame(&self) -> &FrameProfile {
+        &self.last
+    }
+    /// Writes every kept scope as chrome tracing JSON.
+    pub fn chrome_trace(&self, mut writer: impl Write) -> io::Result<()> {
+        writer.write_all(b"[")?;
+        for (i, timing) in self.trace.iter().enumerate() {
+            if i > 0 {
+                writer.write_all(b",")?;
+            }
+            write!(
+                writer,
+                r#"{{"name":{:?},"ph":"X","ts":{},"dur":{},"pid":1,"tid":{}}}"#,
+                timing.name,
+                timing.start.as_micros(),
+                timing.duration.as_micros(),
+                timing.thread
+            )?;
+        }
+        writer.write_all(b"]")
+    }
+}
+
+/// Times a span under `name` from its creation until it's dropped.
+#[must_use = "the scope is timed until it's dropped"]
+pub struct Scope<'a> {
+    profiler: &'a Mutex<Profiler>,
+    name: &'static str,
+    start: Instant,
+}
+impl<'a> Scope<'a> {
+    pub fn new(profiler: &'a Mutex<Profiler>, name: &'static str) -> Self {
+        Self {
+            profiler,
+            name,
+            start: Instant::now(),
+        }
+    }
+}
+impl Drop for Scope<'_> {
+    fn drop(&mut self) {
+        let end = Instant::now();
+        lock(self.profiler).record(self.name, self.start, end);
+    }
+}
+
+/// The summed up duration of every scope of a single frame.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FrameProfile {
+    /// Scope names in the order they first ran with their total duration.
+    scopes: Vec<(&'static str, Duration)>,
+}
+impl FrameProfile {
+    pub fn scopes(&self) -> &[(&'static str, Duration)] {
+        &self.scopes
+    }
+    /// The total duration of every scope named `name`.
+    pub fn total(&self, name: &str) -> Option<Duration> {
+        self.scopes
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, total)| *total)
+    }
+    /// Queues a line per scope with its duration in milliseconds onto a batch
+    /// of draw commands.
+    pub fn commands<'a>(
+        &self,
+        font: &BitmapFont,
+        position: (i32, i32),
+        color: Color,
+        scale: u32,
+        batch: &mut Vec<DrawCmd<'a>>,
+    ) {
+        let text = self
+            .scopes
+            .iter()
+            .map(|(name, total)| format!("{name} {:.2}ms", total.as_secs_f64() * 1000.0))
+            .collect::<Vec<_>>()
+            .join("\n");
+        font.commands(&text, position, color, scale, batch);
+    }
+}
+
+/// Times everything until the returned `Scope` is dropped under `name`.
+pub fn scope(name: &'static str) -> Scope<'static> {
+    Scope::new(&PROFILER, name)
+}
+/// Starts or stops collecting timings in the global profiler.
+pub fn enable(enabled: bool) {
+    lock(&PROFILER).enable(enabled);
+}
+/// Ends the current frame of the global profiler.
+pub fn end_frame() {
+    lock(&PROFILER).end_frame();
+}
+/// The profile of the global profiler's most recently ended frame.
+pub fn last_frame() -> FrameProfile {
+    lock(&PROFILER).last_frame().clone()
+}
+/// Dumps the global profiler's scopes as a chrome tracing JSON file.
+pub fn write_chrome_trace(path: impl AsRef<Path>) -> Result<(), ProfilerError> {
+    let file = BufWriter::new(File::create(path)?);
+    lock(&PROFILER).chrome_trace(file)?;
+    Ok(())
+}
+
+/// Profiling is best effort so a scope panicking mid record doesn't stop others.
+fn lock(profiler: &Mutex<Profiler>) -> MutexGuard<'_, Profiler> {
+    profiler.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// A small stable id per thread for the trace's rows.
+fn thread_id() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(1);
+    thread_local! {
+        static ID: u64 = NEXT.fetch_add(1, Ordering::Relaxed);
+    }
+    ID.with(|id| *id)
+}
+
+#[derive(Debug, Error)]
+pub enum ProfilerError {
+    #[error("failed to write trace: {0}")]
+    WriteError(#[from] io::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_profile_sums_scopes() {
+        let profiler = Mutex::new(Profiler::new());
+        drop(Scope::new(&profiler, "ignored"));
+
+        lock(&profiler).enable(true);
+        for _ in 0..2 {
+            let _update = Scope::new(&profiler, "update");
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        drop(Scope::new(&profiler, "render"));
+        lock(&profiler).end_frame();
+
+        let profiler = lock(&profiler);
+        let frame = profiler.last_frame();
+        let names: Vec<_> = frame.scopes().iter().map(|(name, _)| *name).collect();
+        assert_eq!(names, vec!["update", "render"]);
+        assert!(frame.total("update").unwrap() >= Duration::from_millis(4));
+        assert_eq!(frame.total("ignored"), None);
+    }
+
+    #[test]
+    fn test_chrome_trace() {
+        let profiler = Mutex::new(Profiler::new());
+        lock(&profiler).enable(true);
+        drop(Scope::new(&profiler, "input"));
+        drop(Scope::new(&profiler, "render"));
+        lock(&profiler).end_frame();
+
+        let mut trace = Vec::new();
+        lock(&profiler).chrome_trace(&mut trace).unwrap();
+        let trace = String::from_utf8(trace).unwrap();
+        assert!(trace.starts_with(r#"[{"name":"input","ph":"X","ts":"#));
+        assert!(trace.contains(r#"},{"name":"render""#));
+        assert!(trace.ends_with("}]"));
+    }
+}
@@ -0,0 +1,252 @@
+//! Story progress: global flags and the state of every quest.
+//!
+//! Level triggers and dialogue branch on `Progress` instead of tracking their
+//! own booleans. Every change is published as a `ProgressEvent` through the
+//! `EventBus` so systems can react to a quest being completed without polling,
+//! and the whole store can be written to and restored from a plain text save.
+//!
+//! # Key Structures
+//! - **`QuestState`**: How far along a quest is.
+//! - **`ProgressEvent`**: A flag or quest that changed.
+//! - **`Progress`**: The flags and quests of a playthrough.
+//!
+//! Errors:
+//! - `ProgressError`: Restoring progress from a save failed.
+//!
+//! # Example Usage
+//! ```ignore
+//! let mut progress = Progress::new().publisher(bus.publisher());
+//! progress.set("met_king", true);
+//! progress.set_quest("rescue_princess", QuestState::Active);
+//!
+//! if progress.is_set("met_king") {
+//!     cutscene = Cutscene::load("assets/cutscenes/king_again.txt")?;
+//! }
+//! fs::write("save/progress.txt", progress.save())?;
+//! ```
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+    str::FromStr,
+};
+
+use thiserror::Error;
+
+use crate::sync::Publisher;
+
+/// How far along a quest is.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum QuestState {
+    #[default]
+    NotStarted,
+    Active,
+    Completed,
+    Failed,
+}
+impl QuestState {
+    fn name(&self) -> &'static str {
+        match self {
+            QuestState::NotStarted => "not-started",
+            QuestState::Active => "active",
+            QuestState::Completed => "completed",
+            QuestState::Failed => "failed",
+        }
+    }
+}
+impl FromStr for QuestState {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [
+            QuestState::NotStarted,
+            QuestState::Active,
+            QuestState::Completed,
+            QuestState::Failed,
+        ]
+        .into_iter()
+        .find(|state| state.name() == s)
+        .ok_or(())
+    }
+}
+
+/// A flag or quest that changed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProgressEvent {
+    Flag { name: String, set: bool },
+    Quest { name: String, state: QuestState },
+}
+
+/// The flags and quests of a playthrough.
+#[derive(Default)]
+pub struct Progress {
+    flags: BTreeSet<String>,
+    quests: BTreeMap<String, QuestState>,
+    publisher: Option<Publisher<ProgressEvent>>,
+}
+impl Progress {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Publishes every change from now on.
+    pub fn publisher(mut self, publisher: Publisher<ProgressEvent>) -> Self {
+        self.publisher = Some(publisher);
+        self
+    }
+    pub fn is_set(&self, flag: &str) -> bool {
+        self.flags.contains(flag)
+    }
+    /// Sets or clears a flag, publishing the change if there was one.
+    pub fn set(&mut self, flag: &str, set: bool) {
+        let changed = if set {
+            self.flags.insert(flag.to_string())
+        } else {
+            self.flags.remove(flag)
+        };
+        if changed {
+            self.publish(ProgressEvent::Flag {
+                name: flag.to_string(),
+                set,
+            });
+        }
+    }
+    pub fn quest(&self, quest: &str) -> QuestState {
+        self.quests.get(quest).copied().unwrap_or_default()
+    }
+    /// Moves a quest to `state`, publishing the change if there was one.
+    pub fn set_quest(&mut self, quest: &str, state: QuestState) {
+        let previous = if state == QuestState::NotStarted {
+            self.quests.remove(quest)
+        } else {
+            self.quests.insert(quest.to_string(), state)
+        };
+        if previous.unwrap_or_default() != state {
+            self.publish(ProgressEvent::Quest {
+                name: quest.to_string(),
+                state,
+            });
+        }
+    }
+    fn publish(&self, event: ProgressEvent) {
+        if let Some(publisher) = &self.publisher {
+            publisher.publish(event);
+        }
+    }
+    /// Writes every set flag and started quest, one per line.
+    pub fn save(&self) -> String {
+        let mut save = String::new();
+        for flag in &self.flags {
+            let _ = writeln!(save, "flag {flag}");
+        }
+        for (quest, state) in &self.quests {
+            let _ = writeln!(save, "quest {quest} {}", state.name());
+        }
+        save
+    }
+    /// Replaces the flags and quests with those of a save, without publishing them.
+    pub fn load(&mut self, save: &str) -> Result<(), ProgressError> {
+        let mut flags = BTreeSet::new();
+        let mut quests = BTreeMap::new();
+        for (number, line) in save.lines().enumerate() {
+            let fields: Vec<_> = line.split_whitespace().collect();
+            match fields[..] {
+                [] => (),
+                ["flag", flag] => {
+                    flags.insert(flag.to_string());
+                }
+                ["quest", quest, state] => {
+                    let state = state
+                        .parse()
+                        .map_err(|_| ProgressError::Syntax(number + 1))?;
+                    quests.insert(quest.to_string(), state);
+                }
+                _ => return Err(ProgressError::Syntax(number + 1)),
+            }
+        }
+        self.flags = flags;
+        self.quests = quests;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ProgressError {
+    #[error("malformed progress save on line {0}")]
+    Syntax(usize),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::{Backpressure, EventBus, Inbox, Subscriber};
+
+    #[derive(Default)]
+    struct Listener {
+        rx: Option<Inbox<ProgressEvent>>,
+    }
+    impl Subscriber<ProgressEvent> for Listener {
+        fn subscribe(&mut self, rx: Inbox<ProgressEvent>) {
+            self.rx = Some(rx);
+        }
+    }
+
+    #[test]
+    fn test_changes_are_published() {
+        let bus = EventBus::new();
+        let mut listener = Listener::default();
+        bus.subscribe(&mut listener, 8, Backpressure::DropNewest);
+        let mut progress = Progress::new().publisher(bus.publisher());
+
+        progress.set("met_king", true);
+        progress.set("met_king", true);
+        progress.set_quest("rescue", QuestState::Active);
+        progress.set_quest("rescue", QuestState::Active);
+        progress.set("met_king", false);
+
+        let events: Vec<_> = listener.rx.unwrap().try_iter().collect();
+        assert_eq!(
+            events,
+            [
+                ProgressEvent::Flag {
+                    name: "met_king".into(),
+                    set: true
+                },
+                ProgressEvent::Quest {
+                    name: "rescue".into(),
+                    state: QuestState::Active
+                },
+                ProgressEvent::Flag {
+                    name: "met_king".into(),
+                    set: false
+                },
+            ]
+        );
+        assert!(!progress.is_set("met_king"));
+        assert_eq!(progress.quest("rescue"), QuestState::Active);
+        assert_eq!(progress.quest("unknown"), QuestState::NotStarted);
+    }
+
+    #[test]
+    fn test_save_round_trip() {
+        let mut progress = Progress::new();
+        progress.set("bridge_open", true);
+        progress.set_quest("rescue", QuestState::Completed);
+        progress.set_quest("fetch", QuestState::Failed);
+
+        let save = progress.save();
+        assert_eq!(
+            save,
+            "flag bridge_open\nquest fetch failed\nquest rescue completed\n"
+        );
+
+        let mut restored = Progress::new();
+        restored.load(&save).unwrap();
+        assert!(restored.is_set("bridge_open"));
+        assert_eq!(restored.quest("rescue"), QuestState::Completed);
+        assert!(matches!(
+            restored.load("quest rescue done"),
+            Err(ProgressError::Syntax(1))
+        ));
+        // A failed load keeps what was there
+        assert!(restored.is_set("bridge_open"));
+    }
+}
@@ -14,6 +14,17 @@
 //! - A `Frame` contains a collection of `Pixel` elements and is responsible for determining its own size and layout.
 //! - Each `Pixel` contains a set of window coordinates and a color, which can be styled using `ColorScheme`.
 //! - Frames can be created with optional durations for animation timing.
+//! - Frames can have a pivot, e.g. at a character's feet, which is placed where
+//!   the frame is drawn instead of its top left corner so frames of differing
+//!   sizes line up and mirror in place.
+//! - Fully transparent pixels are skipped when drawn, leaving what's under them
+//!   visible, unless a frame's `Keying` says to write them as is.
+//! - Frames loaded from images are trimmed down to their visible pixels and
+//!   remember where those sat in the image, so they're drawn in the same place.
+//! - Frames rasterize their pixels into a tight RGBA grid which is what gets drawn,
+//!   a row at a time, while the pixels stay around for authoring.
+//! - `FrameBuilder` offers an ergonomic way to author frames, their durations and
+//!   their hitboxes and hurtboxes.
 //!
 //! # Pixel Creation
 //! - `Pixel` supports multiple color schemes, including:
@@ -23,17 +34,40 @@
 //!
 //! # Rendering and Drawing
 //! - Pixels can be drawn onto a screen (implementing the `Screen` trait), with support for mirroring and positional offsets.
+//! - `DrawCmd`s describe everything drawn in a frame so it can be submitted to a
+//!   `Screen` in one batch, including `Grade`s and `Lighting` post-processing
+//!   what was drawn before them.
+//! - `DrawCmd::Rect`, `DrawCmd::Line` and `DrawCmd::Circle` draw shapes straight
+//!   onto the frame buffer, clipped to the screen, for UI and debug drawing.
+//! - `DrawCmd::PushClip` keeps what's drawn after it inside of a `Clip`, e.g. a UI
+//!   panel, until the matching `DrawCmd::PopClip`, with clips nesting inside
+//!   each other.
+//! - A `DrawCmd::Blend` in a batch picks how everything drawn after it, such as
+//!   a layer of glows or shadows, combines with what's underneath.
+//! - A `Compositor` holds an off-screen buffer a full frame is assembled in before it's
+//!   presented in one step, so partially drawn frames are never shown.
 //! - Mirroring can be applied to create flipped versions of the pixel, either vertically or horizontally.
 //!
 //! # Example Usage
 //! To create a `Frame` with a pixel:
-//! ```rust
+//! ```ignore
 //! let pixel = Pixel::new(ColorScheme::Standard(Color::RGB(255, 0, 0)), 10, 20);
 //! let frame = Frame::new(vec![pixel], None);
 //! ```
+//! Or with an explicit display time:
+//! ```ignore
+//! let frame = FrameBuilder::new().pixel(pixel).duration_ms(120).build();
+//! ```
 
-use crate::{layout::MirrorDirectionValue, prelude::*};
-use std::{ops::Range, time::Duration};
+use crate::{
+    grading::Grade,
+    hitbox::{BoxKind, FrameBox},
+    layout::MirrorDirectionValue,
+    lighting::Lighting,
+    prelude::*,
+    window::WindowError,
+};
+use std::{borrow::Cow, ops::Range, sync::OnceLock, time::Duration};
 
 /// A container for window-rendered `Pixel`s.
 ///
@@ -43,24 +77,114 @@ use std::{ops::Range, time::Duration};
 /// NOTE: `f32` in frame creation context should always be postive since
 /// we coerce between `f32` and `u16`
 #[derive(Clone, Debug)]
-pub(crate) struct Frame {
+pub struct Frame {
     /// A collection of `Pixel` that make up this frame.
     pub(crate) pixels: Vec<Pixel>,
     pub(crate) height: u16,
     pub(crate) width: u16,
     pub(crate) duration: Option<Duration>,
+    /// Hitboxes and hurtboxes active while the frame is shown.
+    pub(crate) boxes: Vec<FrameBox>,
+    /// The point placed where the frame is drawn, the top left corner if unset.
+    pub(crate) pivot: Option<(u16, u16)>,
+    /// Where the pixels sat in the image the frame was trimmed from.
+    pub(crate) trim: Option<Trim>,
+    pub(crate) keying: Keying,
+    /// The pixels rasterized for drawing.
+    pub(crate) raster: Raster,
+    /// The raster mirrored vertically and horizontally, made on first use.
+    pub(crate) flipped: [OnceLock<Raster>; 2],
 }
 impl Frame {
     /// Creates a new `Frame` with the given pixels
-    pub(crate) fn new(pixels: Vec<Pixel>, duration: Option<Duration>) -> Self {
+    pub fn new(pixels: Vec<Pixel>, duration: Option<Duration>) -> Self {
         let (width, height) = Frame::get_dimesions(&pixels);
         Self {
+            raster: Raster::new(&pixels, Keying::default()),
+            flipped: Default::default(),
             pixels,
             height,
             width,
             duration,
+            boxes: Vec::new(),
+            pivot: None,
+            trim: None,
+            keying: Keying::default(),
+        }
+    }
+    /// The collision boxes authored on this frame.
+    pub fn boxes(&self) -> &[FrameBox] {
+        &self.boxes
+    }
+    pub fn pivot(&self) -> Option<(u16, u16)> {
+        self.pivot
+    }
+    pub fn trim(&self) -> Option<Trim> {
+        self.trim
+    }
+    pub fn keying(&self) -> Keying {
+        self.keying
+    }
+    /// How far the frame's top left corner ends up from where it's drawn with
+    /// the given mirroring, putting the pivot there instead or, for a trimmed
+    /// frame, the image's corner.
+    pub(crate) fn anchor(&self, mirror: &MirrorDirectionValue) -> Coordinate {
+        let (x, y) = if let Some((x, y)) = self.pivot {
+            // The pivot mirrors along with the pixels around it
+            let (x, y) = match *mirror {
+                MirrorDirectionValue::FlipVertical(width) => (width as f32 - x as f32, y as f32),
+                MirrorDirectionValue::FlipHorizontal(height) => {
+                    (x as f32, height as f32 - y as f32)
+                }
+                MirrorDirectionValue::None => (x as f32, y as f32),
+            };
+            (-x, -y)
+        } else if let Some(trim) = self.trim {
+            // Mirrored across the whole image like it was never trimmed
+            let (x, y) = (trim.x as f32, trim.y as f32);
+            let far = |image: u16, size: u16, at: f32| image as f32 - 1.0 - at - size as f32;
+            match *mirror {
+                MirrorDirectionValue::FlipVertical(width) => (far(trim.width, width, x), y),
+                MirrorDirectionValue::FlipHorizontal(height) => (x, far(trim.height, height, y)),
+                MirrorDirectionValue::None => (x, y),
+            }
+        } else {
+            (0.0, 0.0)
+        };
+        Coordinate { x, y }
+    }
+    /// The concrete mirroring of this frame, covering its own dimensions.
+    pub(crate) fn mirror_value(&self, mirror: MirrorDirection) -> MirrorDirectionValue {
+        match mirror {
+            MirrorDirection::FlipVertical => MirrorDirectionValue::FlipVertical(self.width),
+            MirrorDirection::FlipHorizontal => MirrorDirectionValue::FlipHorizontal(self.height),
+            MirrorDirection::None => MirrorDirectionValue::None,
+        }
+    }
+    /// The raster to draw for the given mirroring.
+    ///
+    /// Mirroring across the frame's own dimensions is cached so flipped frames
+    /// only get computed once
+    fn raster(&self, mirror: &MirrorDirectionValue) -> Cow<'_, Raster> {
+        match *mirror {
+            MirrorDirectionValue::None => Cow::Borrowed(&self.raster),
+            MirrorDirectionValue::FlipVertical(width) if width == self.width => {
+                Cow::Borrowed(self.flipped[0].get_or_init(|| self.raster.mirrored(mirror)))
+            }
+            MirrorDirectionValue::FlipHorizontal(height) if height == self.height => {
+                Cow::Borrowed(self.flipped[1].get_or_init(|| self.raster.mirrored(mirror)))
+            }
+            _ => Cow::Owned(self.raster.mirrored(mirror)),
         }
     }
+    /// Rebuilds what's drawn from the frame's pixels and drops the cached
+    /// flipped variants.
+    ///
+    /// Has to be called once the frame's pixels changed to show the change.
+    pub fn invalidate_cache(&mut self) {
+        self.raster = Raster::new(&self.pixels, self.keying);
+        self.flipped = Default::default();
+    }
     /// Calculates the maximum width and height based on pixel positions.
     fn get_dimesions(pixels: &Vec<Pixel>) -> (u16, u16) {
         let mut width: u16 = 0;
@@ -75,14 +199,805 @@ impl Frame {
         }
         (width, height)
     }
-    /// Updates the stored width and height of the frame based on its pixel data.
+    /// Updates the stored width and height and the raster of the frame based on
+    /// its pixel data.
     ///
-    /// Should be called whenever modifications to pixel positions are made such as
-    /// `Pixel::move_pos`
+    /// Should be called whenever modifications to pixels are made such as
+    /// `Pixel::move_pos` or `Pixel::change_color`
     pub(crate) fn resize(&mut self) {
         let (width, height) = Frame::get_dimesions(&self.pixels);
         self.height = height;
         self.width = width;
+        self.invalidate_cache();
+    }
+}
+
+/// What drawing a frame does with its fully transparent pixels.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Keying {
+    /// Skips them, leaving whatever was drawn underneath, so layers and tiles
+    /// composite over each other.
+    #[default]
+    AlphaZero,
+    /// Writes them like any other pixel, cutting a transparent hole.
+    None,
+}
+
+/// Where a trimmed frame's pixels sat in the image they were cut from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Trim {
+    /// The image position of the frame's top left corner.
+    pub x: u16,
+    pub y: u16,
+    /// The size of the whole image in pixels.
+    pub width: u16,
+    pub height: u16,
+}
+
+/// A frame's pixels laid out as a tight RGBA grid drawn a row at a time.
+///
+/// Cells no pixel covers are left untouched when drawn, so besides the grid the
+/// covered runs of every row are kept.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Raster {
+    /// Frame position of the grid's top left cell.
+    origin: (i32, i32),
+    width: usize,
+    data: Vec<u8>,
+    /// Covered cells as `(row, column, length)` runs within the grid.
+    runs: Vec<(usize, usize, usize)>,
+}
+impl Raster {
+    pub(crate) fn new(pixels: &[Pixel], keying: Keying) -> Self {
+        Raster::from_cells(
+            pixels
+                .iter()
+                .flat_map(|p| &p.pixels)
+                .map(|(color, c)| (c.x as i32, c.y as i32, color.to_rgba()))
+                .filter(move |(_, _, rgba)| keying == Keying::None || rgba[3] > 0),
+        )
+    }
+    /// Whether no cell is covered at all.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+    /// Lays out cells given as `(x, y, rgba)` where later cells cover earlier
+    /// ones like when drawn one by one.
+    fn from_cells(cells: impl Iterator<Item = (i32, i32, [u8; 4])> + Clone) -> Self {
+        let Some((min_x, min_y, max_x, max_y)) =
+            cells
+                .clone()
+                .fold(None, |bounds: Option<(i32, i32, i32, i32)>, (x, y, _)| {
+                    Some(match bounds {
+                        None => (x, y, x, y),
+                        Some((x0, y0, x1, y1)) => (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+                    })
+                })
+        else {
+            return Self::default();
+        };
+        let width = (max_x - min_x + 1) as usize;
+        let height = (max_y - min_y + 1) as usize;
+
+        let mut data = vec![0; width * height * 4];
+        let mut covered = vec![false; width * height];
+        for (x, y, rgba) in cells {
+            let i = (y - min_y) as usize * width + (x - min_x) as usize;
+            data[i * 4..i * 4 + 4].copy_from_slice(&rgba);
+            covered[i] = true;
+        }
+
+        let mut runs = Vec::new();
+        for (row, cells) in covered.chunks(width).enumerate() {
+            let mut column = 0;
+            while column < cells.len() {
+                let len = cells[column..].iter().take_while(|c| **c).count();
+                if len > 0 {
+                    runs.push((row, column, len));
+                }
+                column += len.max(1);
+            }
+        }
+        Self {
+            origin: (min_x, min_y),
+            width,
+            data,
+            runs,
+        }
+    }
+    /// A copy with every cell mirrored the way `Pixel`s are.
+    fn mirrored(&self, mirror: &MirrorDirectionValue) -> Self {
+        let cells = self.runs.iter().flat_map(|&(row, column, len)| {
+            (column..column + len).map(move |column| {
+                let i = (row * self.width + column) * 4;
+                let (x, y) = (self.origin.0 + column as i32, self.origin.1 + row as i32);
+                let (x, y) = match *mirror {
+                    MirrorDirectionValue::FlipVertical(width) => (width as i32 - x, y),
+                    MirrorDirectionValue::FlipHorizontal(height) => (x, height as i32 - y),
+                    MirrorDirectionValue::None => (x, y),
+                };
+                let rgba: [u8; 4] = self.data[i..i + 4].try_into().unwrap();
+                (x, y, rgba)
+            })
+        });
+        Raster::from_cells(cells)
+    }
+    /// Draws every covered cell at the position offset.
+    fn draw<S: Screen + ?Sized>(&self, screen: &mut S, offset: Coordinate, brush: Brush) {
+        let (screen_width, screen_height) = (screen.width(), screen.height());
+        let clip = brush.clip.within(screen_width, screen_height);
+        let buffer = screen.frame_buffer();
+        for &(row, column, len) in &self.runs {
+            let src = (row * self.width + column) * 4;
+            let src = &self.data[src..src + len * 4];
+
+            let y = (offset.y + (self.origin.1 + row as i32) as f32).round() as i32;
+            if y < clip.top || y >= clip.bottom {
+                continue;
+            }
+            let row = y as usize * screen_width as usize;
+            let start = offset.x + (self.origin.0 + column as i32) as f32;
+
+            // Rounding only shifts every cell in the run equally when positive
+            if start >= 0.0 {
+                let x = start.round() as i32;
+                let skip = (clip.left - x).clamp(0, len as i32) as usize;
+                let end = (clip.right - x).clamp(0, len as i32) as usize;
+                if end > skip {
+                    let dst = (row + x as usize + skip) * 4;
+                    brush.blend.apply(
+                        &mut buffer[dst..dst + (end - skip) * 4],
+                        &src[skip * 4..end * 4],
+                    );
+                }
+                continue;
+            }
+            for (i, rgba) in src.chunks_exact(4).enumerate() {
+                let x = (start + i as f32).round() as i32;
+                if x < clip.left {
+                    continue;
+                }
+                if x >= clip.right {
+                    break;
+                }
+                let dst = (row + x as usize) * 4;
+                brush.blend.apply(&mut buffer[dst..dst + 4], rgba);
+            }
+        }
+    }
+}
+
+/// An off-screen RGBA buffer frames are composed into before being presented.
+///
+/// Drawing straight into the presented surface means any clear or partial draw
+/// can end up on screen. Composing here and copying the finished result once
+/// per render keeps every presented frame whole.
+pub(crate) struct Compositor {
+    buffer: Vec<u8>,
+}
+impl Compositor {
+    pub(crate) fn new(width: u32, height: u32) -> Self {
+        Self {
+            buffer: vec![0; (width * height * 4) as usize],
+        }
+    }
+    /// The buffer to compose the next frame into.
+    pub(crate) fn buffer_mut(&mut self) -> &mut [u8] {
+        &mut self.buffer
+    }
+    /// Erases the composed frame by setting all pixels to the given color.
+    pub(crate) fn clear(&mut self, color: Color) {
+        fill(&mut self.buffer, color.to_rgba());
+    }
+    /// Copies the composed frame into the presented `target` in one step.
+    ///
+    /// Only as much as both have is copied should their sizes ever differ
+    pub(crate) fn present(&self, target: &mut [u8]) {
+        let len = target.len().min(self.buffer.len());
+        target[..len].copy_from_slice(&self.buffer[..len]);
+    }
+}
+
+/// A single drawing operation submitted as part of a batch with `Screen::draw_batch`.
+#[derive(Clone, Debug)]
+pub enum DrawCmd<'a> {
+    /// Erases everything drawn so far.
+    Clear,
+    /// Draws a `Pixel` with optional mirroring and position offset.
+    Pixel(&'a Pixel, MirrorDirectionValue, Coordinate),
+    /// Draws a whole `Frame` with optional mirroring and position offset.
+    Frame(&'a Frame, MirrorDirectionValue, Coordinate),
+    /// Draws `len` pixels of `color` in a row starting at `start`.
+    Span {
+        start: (i32, i32),
+        len: u32,
+        color: Color,
+    },
+    /// Fills a `width` by `height` rectangle of `color` from its top left
+    /// corner at `start`.
+    Rect {
+        start: (i32, i32),
+        width: u32,
+        height: u32,
+        color: Color,
+    },
+    /// Draws a one pixel wide line of `color` from `from` to `to`, both ends
+    /// included.
+    Line {
+        from: (i32, i32),
+        to: (i32, i32),
+        color: Color,
+    },
+    /// Draws a circle of `color` around `center`, filled or just its outline.
+    Circle {
+        center: (i32, i32),
+        radius: u32,
+        color: Color,
+        filled: bool,
+    },
+    /// Color grades everything drawn so far in the batch.
+    Grade(Grade),
+    /// Darkens everything drawn so far outside of the light sources.
+    Lighting(&'a Lighting),
+    /// Combines every `Pixel`, `Frame` and shape drawn after it with what's
+    /// underneath the given way, until the next `Blend` or the batch ends.
+    Blend(Blend),
+    /// Keeps every `Pixel`, `Frame` and shape drawn after it inside of the
+    /// clip, and inside of the clips pushed before it, until it's popped.
+    PushClip(Clip),
+    /// Stops clipping to the last clip pushed.
+    PopClip,
+}
+impl DrawCmd<'_> {
+    /// Draws the command on its own, without any blending.
+    pub(crate) fn draw<S: Screen + ?Sized>(&self, screen: &mut S) -> Result<(), WindowError> {
+        self.draw_with(screen, Brush::default())
+    }
+    fn draw_with<S: Screen + ?Sized>(
+        &self,
+        screen: &mut S,
+        brush: Brush,
+    ) -> Result<(), WindowError> {
+        match self {
+            DrawCmd::Clear => screen.clear()?,
+            DrawCmd::Pixel(pixel, mirror, offset) => {
+                pixel.draw(screen, mirror.clone(), *offset, brush)
+            }
+            DrawCmd::Frame(frame, mirror, offset) => {
+                frame.raster(mirror).draw(screen, *offset, brush)
+            }
+            DrawCmd::Span { start, len, color } => {
+                let (width, height) = (screen.width(), screen.height());
+                blend_span(
+                    screen.frame_buffer(),
+                    width,
+                    height,
+                    *start,
+                    *len,
+                    *color,
+                    brush,
+                )
+            }
+            DrawCmd::Rect {
+                start,
+                width,
+                height,
+                color,
+            } => Canvas::new(screen, brush).fill_rect(*start, *width, *height, *color),
+            DrawCmd::Line { from, to, color } => {
+                Canvas::new(screen, brush).draw_line(*from, *to, *color)
+            }
+            DrawCmd::Circle {
+                center,
+                radius,
+                color,
+                filled,
+            } => Canvas::new(screen, brush).draw_circle(*center, *radius, *color, *filled),
+            DrawCmd::Grade(grade) => grade.apply(screen.frame_buffer()),
+            DrawCmd::Lighting(lighting) => {
+                let (width, height) = (screen.width(), screen.height());
+                lighting.apply(screen.frame_buffer(), width, height)
+            }
+            // Only change how later commands are drawn
+            DrawCmd::Blend(_) | DrawCmd::PushClip(_) | DrawCmd::PopClip => (),
+        }
+        Ok(())
+    }
+}
+
+/// Draws every command of `batch` in order, keeping track of its blending and
+/// clipping.
+pub(crate) fn draw_all<S: Screen + ?Sized>(
+    screen: &mut S,
+    batch: &[DrawCmd],
+) -> Result<(), WindowError> {
+    let mut brush = Brush::default();
+    let mut clips = Vec::new();
+    for cmd in batch {
+        match cmd {
+            DrawCmd::Blend(blend) => brush.blend = *blend,
+            DrawCmd::PushClip(clip) => {
+                clips.push(brush.clip);
+                brush.clip = brush.clip.intersect(*clip);
+            }
+            // Popping more than was pushed leaves drawing unclipped
+            DrawCmd::PopClip => brush.clip = clips.pop().unwrap_or_default(),
+            _ => cmd.draw_with(screen, brush)?,
+        }
+    }
+    Ok(())
+}
+
+/// How the commands of a batch are being drawn at a point in it.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Brush {
+    pub(crate) blend: Blend,
+    pub(crate) clip: Clip,
+}
+
+/// A rectangle of the screen drawing is kept inside of, e.g. a UI panel or a
+/// minimap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Clip {
+    left: i32,
+    top: i32,
+    /// Just past the last column and row inside
+    right: i32,
+    bottom: i32,
+}
+impl Default for Clip {
+    /// Everything, leaving drawing unclipped.
+    fn default() -> Self {
+        Self {
+            left: i32::MIN,
+            top: i32::MIN,
+            right: i32::MAX,
+            bottom: i32::MAX,
+        }
+    }
+}
+impl Clip {
+    /// A `width` by `height` clip from its top left corner at `x` and `y`.
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+        Self {
+            left: x,
+            top: y,
+            right: x.saturating_add_unsigned(width),
+            bottom: y.saturating_add_unsigned(height),
+        }
+    }
+    /// The part of the screen inside both clips, which may be nothing.
+    pub fn intersect(self, other: Clip) -> Self {
+        let (left, top) = (self.left.max(other.left), self.top.max(other.top));
+        Self {
+            left,
+            top,
+            right: self.right.min(other.right).max(left),
+            bottom: self.bottom.min(other.bottom).max(top),
+        }
+    }
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        (self.left..self.right).contains(&x) && (self.top..self.bottom).contains(&y)
+    }
+    /// The part of the clip on a `width` by `height` screen.
+    fn within(self, width: u32, height: u32) -> Self {
+        self.intersect(Clip::new(0, 0, width, height))
+    }
+}
+
+/// How drawn colors combine with what's already on screen.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Blend {
+    /// Replaces what's underneath.
+    #[default]
+    Normal,
+    /// Adds onto what's underneath, brightening it, e.g. for glows.
+    Additive,
+    /// Multiplies what's underneath, darkening it, e.g. for shadows.
+    Multiply,
+}
+impl Blend {
+    /// Blends the RGBA cells of `src` onto those of `dst`, by as much as each
+    /// source cell is opaque for the modes other than `Normal`.
+    pub(crate) fn apply(self, dst: &mut [u8], src: &[u8]) {
+        if self == Blend::Normal {
+            dst.copy_from_slice(src);
+            return;
+        }
+        for (dst, src) in dst.chunks_exact_mut(4).zip(src.chunks_exact(4)) {
+            let alpha = src[3] as u32;
+            for (dst, &src) in dst[..3].iter_mut().zip(&src[..3]) {
+                let (d, s) = (*dst as u32, src as u32);
+                *dst = match self {
+                    Blend::Additive => (d + s * alpha / 255).min(255),
+                    // Multiplying by white leaves the color as is
+                    Blend::Multiply => d * (255 * 255 - (255 - s) * alpha) / (255 * 255),
+                    Blend::Normal => s,
+                } as u8;
+            }
+        }
+    }
+}
+
+/// A draw command buffer reused from frame to frame.
+///
+/// Batches borrow whatever they draw so they can't be kept around between
+/// frames as is. `begin` lends out the buffer emptied for the caller's borrows
+/// and `finish` takes it back keeping its capacity, so once the buffer grew to
+/// fit a frame queuing commands no longer allocates.
+#[derive(Debug, Default)]
+pub struct DrawList {
+    buffer: Vec<DrawCmd<'static>>,
+}
+impl DrawList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// The emptied buffer to queue this frame's commands onto.
+    pub fn begin<'a>(&mut self) -> Vec<DrawCmd<'a>> {
+        recycle(std::mem::take(&mut self.buffer))
+    }
+    /// Hands the drawn batch back for the next frame to reuse.
+    pub fn finish(&mut self, batch: Vec<DrawCmd<'_>>) {
+        self.buffer = recycle(batch);
+    }
+}
+
+/// Empties a batch and changes the lifetime of the commands it can hold.
+///
+/// Collecting a `Vec`'s own iterator into items of the same layout reuses its
+/// allocation so this never allocates.
+fn recycle<'b>(mut batch: Vec<DrawCmd<'_>>) -> Vec<DrawCmd<'b>> {
+    batch.clear();
+    batch.into_iter().map(|_| unreachable!()).collect()
+}
+
+/// Writes `len` pixels of `color` in a row starting at `start`, clipped to the screen bounds.
+pub(crate) fn fill_span(
+    buffer: &mut [u8],
+    width: u32,
+    height: u32,
+    start: (i32, i32),
+    len: u32,
+    color: Color,
+) {
+    let (x, y) = start;
+    if y < 0 || y as u32 >= height {
+        return;
+    }
+    let from = x.max(0) as i64;
+    let to = (x as i64 + len as i64).min(width as i64);
+    if from >= to {
+        return;
+    }
+    let row = y as usize * width as usize;
+    let from = (row + from as usize) * 4;
+    let to = (row + to as usize) * 4;
+    fill(&mut buffer[from..to], color.to_rgba());
+}
+/// A screen's frame buffer shapes are drawn straight onto, clipped to its
+/// bounds and the brush's clip.
+pub(crate) struct Canvas<'a> {
+    buffer: &'a mut [u8],
+    width: u32,
+    height: u32,
+    brush: Brush,
+}
+impl<'a> Canvas<'a> {
+    pub(crate) fn new<S: Screen + ?Sized>(screen: &'a mut S, brush: Brush) -> Self {
+        let (width, height) = (screen.width(), screen.height());
+        Self {
+            buffer: screen.frame_buffer(),
+            width,
+            height,
+            brush,
+        }
+    }
+    fn span(&mut self, start: (i32, i32), len: u32, color: Color) {
+        let (width, height, brush) = (self.width, self.height, self.brush);
+        blend_span(self.buffer, width, height, start, len, color, brush)
+    }
+    /// Fills a `width` by `height` rectangle from its top left corner at
+    /// `start`.
+    pub(crate) fn fill_rect(&mut self, start: (i32, i32), width: u32, height: u32, color: Color) {
+        let (x, y) = start;
+        // Rows off the screen are left out before drawing
+        let from = y.max(0) as i64;
+        let to = (y as i64 + height as i64).min(self.height as i64);
+        for row in from..to {
+            self.span((x, row as i32), width, color);
+        }
+    }
+    /// Draws a line from `from` to `to`, both ends included.
+    ///
+    /// Only the part of the line on screen is walked, however far off its
+    /// ends are
+    pub(crate) fn draw_line(&mut self, from: (i32, i32), to: (i32, i32), color: Color) {
+        let clip = self.brush.clip.within(self.width, self.height);
+        let Some(((mut x, mut y), to)) = clip_line(from, to, clip) else {
+            return;
+        };
+        let (dx, dy) = ((to.0 - x).abs(), -(to.1 - y).abs());
+        let (step_x, step_y) = ((to.0 - x).signum(), (to.1 - y).signum());
+        let mut error = dx + dy;
+        loop {
+            self.span((x as i32, y as i32), 1, color);
+            if (x, y) == to {
+                break;
+            }
+            let doubled = 2 * error;
+            if doubled >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if doubled <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
+    }
+    /// Draws a circle around `center`, filled with one span per row or just
+    /// its outline.
+    ///
+    /// Every pixel is drawn once so blending doesn't build up where the
+    /// circle's eighths meet. Radii past `MAX_RADIUS` are drawn at it
+    pub(crate) fn draw_circle(
+        &mut self,
+        center: (i32, i32),
+        radius: u32,
+        color: Color,
+        filled: bool,
+    ) {
+        let radius = radius.min(MAX_RADIUS) as i32;
+        let (mut x, mut y) = (radius, 0);
+        let mut error = 1 - radius;
+        while x >= y {
+            let stepping_in = error >= 0;
+            if filled {
+                // Rows near the middle are visited once each, at their widest
+                self.mirrored_rows(center, y, x, color);
+                // Rows near the top and bottom are widest just before the
+                // outline steps in past them
+                if stepping_in && x > y {
+                    self.mirrored_rows(center, x, y, color);
+                }
+            } else {
+                self.mirrored_points(center, (x, y), color);
+                if x != y {
+                    self.mirrored_points(center, (y, x), color);
+                }
+            }
+            y += 1;
+            if stepping_in {
+                x -= 1;
+                error += 2 * (y - x) + 1;
+            } else {
+                error += 2 * y + 1;
+            }
+        }
+    }
+    /// Spans `half` either side of `center`, `row` above and below it.
+    fn mirrored_rows(&mut self, center: (i32, i32), row: i32, half: i32, color: Color) {
+        let (cx, cy) = center;
+        let len = (2 * half + 1) as u32;
+        let left = cx.saturating_sub(half);
+        self.span((left, cy.saturating_add(row)), len, color);
+        if row != 0 {
+            self.span((left, cy.saturating_sub(row)), len, color);
+        }
+    }
+    /// The point `offset` from `center` mirrored into every quarter, each
+    /// drawn once even where mirrors overlap.
+    fn mirrored_points(&mut self, center: (i32, i32), offset: (i32, i32), color: Color) {
+        let (cx, cy) = center;
+        let (dx, dy) = offset;
+        for (sx, sy) in [(1, 1), (-1, 1), (1, -1), (-1, -1)] {
+            if (sx < 0 && dx == 0) || (sy < 0 && dy == 0) {
+                continue;
+            }
+            let point = (cx.saturating_add(sx * dx), cy.saturating_add(sy * dy));
+            self.span(point, 1, color);
+        }
+    }
+}
+
+/// The largest radius a circle is drawn with, keeping huge ones from walking
+/// far past any screen.
+const MAX_RADIUS: u32 = 1 << 15;
+
+/// The part of the line from `from` to `to` inside `clip`, if any,
+/// by Liang–Barsky.
+fn clip_line(from: (i32, i32), to: (i32, i32), clip: Clip) -> Option<((i64, i64), (i64, i64))> {
+    if clip.left >= clip.right || clip.top >= clip.bottom {
+        return None;
+    }
+    let (x, y) = (f64::from(from.0), f64::from(from.1));
+    let (dx, dy) = (f64::from(to.0) - x, f64::from(to.1) - y);
+    let (mut enter, mut leave) = (0.0_f64, 1.0_f64);
+    // How far the line heads out past each edge, and how far inside it starts
+    let edges = [
+        (-dx, x - f64::from(clip.left)),
+        (dx, f64::from(clip.right - 1) - x),
+        (-dy, y - f64::from(clip.top)),
+        (dy, f64::from(clip.bottom - 1) - y),
+    ];
+    for (outward, inside) in edges {
+        if outward == 0.0 {
+            if inside < 0.0 {
+                return None;
+            }
+            continue;
+        }
+        let at = inside / outward;
+        if outward < 0.0 {
+            enter = enter.max(at);
+        } else {
+            leave = leave.min(at);
+        }
+    }
+    if enter > leave {
+        return None;
+    }
+    let point = |t: f64| ((x + t * dx).round() as i64, (y + t * dy).round() as i64);
+    Some((point(enter), point(leave)))
+}
+
+/// Draws a row of `color` like `fill_span`, clipped and blended with what's
+/// underneath as `brush` says.
+fn blend_span(
+    buffer: &mut [u8],
+    width: u32,
+    height: u32,
+    start: (i32, i32),
+    len: u32,
+    color: Color,
+    brush: Brush,
+) {
+    let clip = brush.clip.within(width, height);
+    let (x, y) = start;
+    if y < clip.top || y >= clip.bottom {
+        return;
+    }
+    let from = (x as i64).max(clip.left as i64);
+    let to = (x as i64 + len as i64).min(clip.right as i64);
+    if to <= from {
+        return;
+    }
+    if brush.blend == Blend::Normal {
+        return fill_span(
+            buffer,
+            width,
+            height,
+            (from as i32, y),
+            (to - from) as u32,
+            color,
+        );
+    }
+    let row = y as usize * width as usize;
+    let rgba = color.to_rgba();
+    for column in from..to {
+        let i = (row + column as usize) * 4;
+        brush.blend.apply(&mut buffer[i..i + 4], &rgba);
+    }
+}
+/// Repeats a single RGBA pixel across the whole buffer.
+///
+/// Each pass copies everything written so far, so only a handful of
+/// `copy_within` calls are needed instead of one write per pixel.
+pub(crate) fn fill(buffer: &mut [u8], rgba: [u8; 4]) {
+    if buffer.len() < 4 {
+        return;
+    }
+    buffer[..4].copy_from_slice(&rgba);
+    let mut filled = 4;
+    while filled < buffer.len() {
+        let n = filled.min(buffer.len() - filled);
+        buffer.copy_within(..n, filled);
+        filled += n;
+    }
+}
+
+/// Builder for authoring a `Frame` and its display time.
+///
+/// Frames built without a duration fall back to the owning sprite's
+/// default frame rate during playback.
+#[derive(Clone, Debug, Default)]
+pub struct FrameBuilder {
+    pixels: Vec<Pixel>,
+    duration: Option<Duration>,
+    boxes: Vec<FrameBox>,
+    pivot: Option<(u16, u16)>,
+    trim: Option<Trim>,
+    keying: Keying,
+}
+impl FrameBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Appends a single `Pixel` to the frame.
+    pub fn pixel(mut self, pixel: Pixel) -> Self {
+        self.pixels.push(pixel);
+        self
+    }
+    /// Appends all `Pixel`s to the frame.
+    pub fn pixels(mut self, pixels: impl IntoIterator<Item = Pixel>) -> Self {
+        self.pixels.extend(pixels);
+        self
+    }
+    /// How long the frame stays on screen.
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+    /// How long the frame stays on screen in milliseconds.
+    pub fn duration_ms(self, millis: u64) -> Self {
+        self.duration(Duration::from_millis(millis))
+    }
+    /// Places the frame's `x`, `y` where it's drawn, e.g. a character's feet,
+    /// instead of its top left corner.
+    pub fn pivot(mut self, x: u16, y: u16) -> Self {
+        self.pivot = Some((x, y));
+        self
+    }
+    /// Whether fully transparent pixels are skipped or written as is.
+    pub fn keying(mut self, keying: Keying) -> Self {
+        self.keying = keying;
+        self
+    }
+    /// Draws the frame as a part cut from a bigger image.
+    pub(crate) fn trim(mut self, trim: Trim) -> Self {
+        self.trim = Some(trim);
+        self
+    }
+    /// Adds a named area dealing hits while the frame is shown.
+    pub fn hitbox(self, name: impl Into<String>, x: u16, y: u16, width: u16, height: u16) -> Self {
+        self.collision_box(name, BoxKind::Hit, x, y, width, height)
+    }
+    /// Adds a named area that can be hit while the frame is shown.
+    pub fn hurtbox(self, name: impl Into<String>, x: u16, y: u16, width: u16, height: u16) -> Self {
+        self.collision_box(name, BoxKind::Hurt, x, y, width, height)
+    }
+    fn collision_box(
+        mut self,
+        name: impl Into<String>,
+        kind: BoxKind,
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+    ) -> Self {
+        self.boxes.push(FrameBox {
+            name: name.into(),
+            kind,
+            x,
+            y,
+            width,
+            height,
+        });
+        self
+    }
+    pub fn build(self) -> Frame {
+        let mut frame = Frame::new(self.pixels, self.duration);
+        frame.boxes = self.boxes;
+        frame.pivot = self.pivot;
+        frame.trim = self.trim;
+        if self.keying != frame.keying {
+            frame.keying = self.keying;
+            frame.invalidate_cache();
+        }
+        frame
+    }
+}
+impl From<Frame> for FrameBuilder {
+    fn from(frame: Frame) -> Self {
+        Self {
+            pixels: frame.pixels,
+            duration: frame.duration,
+            boxes: frame.boxes,
+            pivot: frame.pivot,
+            trim: frame.trim,
+            keying: frame.keying,
+        }
     }
 }
 
@@ -92,11 +1007,11 @@ impl Frame {
 /// more window cells depending on the `ColorScheme`. Coordinates are specified
 /// in window cell units, but a single `Pixel` may span multiple cells.
 #[derive(Clone, Debug)]
-pub(crate) struct Pixel {
-    pixels: Vec<(Color, Coordinate)>,
+pub struct Pixel {
+    pub(crate) pixels: Vec<(Color, Coordinate)>,
 }
 impl Pixel {
-    pub(crate) fn new(color: ColorScheme, x: u16, y: u16) -> Self {
+    pub fn new(color: ColorScheme, x: u16, y: u16) -> Self {
         let pixels = match color {
             ColorScheme::Standard(color) => vec![(
                 color,
@@ -131,6 +1046,20 @@ impl Pixel {
                 }
                 pixels
             }
+            ColorScheme::Gradient(gradient) => Pixel::extract_range(&gradient.range)
+                .map(|i| {
+                    (
+                        gradient.color_at(i),
+                        Pixel::pattern_to_coordinate(&gradient.range, x, y, i),
+                    )
+                })
+                .collect(),
+            ColorScheme::Dither(dither) => Pixel::extract_range(&dither.range)
+                .map(|i| {
+                    let at = Pixel::pattern_to_coordinate(&dither.range, x, y, i);
+                    (dither.color_at(i, at.x as u16, at.y as u16), at)
+                })
+                .collect(),
         };
         Self { pixels }
     }
@@ -207,20 +1136,81 @@ impl Pixel {
     fn mirror(x: u16, width_height: u16) -> u16 {
         width_height - x
     }
+    /// The on-screen start and color of this `Pixel` when it is a single colored
+    /// horizontal run such as a stroke, otherwise `None`.
+    fn span(&self, mirror: &MirrorDirectionValue, offset: Coordinate) -> Option<(i32, i32, Color)> {
+        if self.pixels.len() < 2 {
+            return None;
+        }
+        let (color, first) = self.pixels[0];
+        for (i, (c, coordinate)) in self.pixels.iter().enumerate() {
+            if *c != color || coordinate.y != first.y || coordinate.x != first.x + i as f32 {
+                return None;
+            }
+        }
+
+        // Mirroring a run vertically makes its last pixel the start
+        let last_x = first.x + (self.pixels.len() - 1) as f32;
+        let start = match mirror {
+            MirrorDirectionValue::FlipVertical(max_width) => Coordinate {
+                x: Pixel::mirror(last_x as u16, *max_width).into(),
+                y: first.y,
+            },
+            MirrorDirectionValue::FlipHorizontal(max_height) => Coordinate {
+                x: first.x,
+                y: Pixel::mirror(first.y as u16, *max_height).into(),
+            },
+            MirrorDirectionValue::None => first,
+        };
+        let start = Coordinate {
+            x: offset.x + start.x,
+            y: offset.y + start.y,
+        };
+
+        // Rounding only shifts every pixel in the run equally when positive
+        if start.x < 0.0 {
+            return None;
+        }
+        Some((start.x.round() as i32, start.y.round() as i32, color))
+    }
     /// Draws this `Pixel` to the given frame buffer by drawing all the avaliable pixels
     /// with optional mirroring and position offset.
-    pub(crate) fn draw<S: Screen>(
+    ///
+    /// Fully transparent pixels are skipped, leaving what's underneath
+    pub(crate) fn draw<S: Screen + ?Sized>(
         &self,
         screen: &mut S,
         mirror: MirrorDirectionValue,
         offset: Coordinate,
+        brush: Brush,
     ) {
         let screen_width = screen.width();
         let screen_height = screen.height();
+        let clip = brush.clip.within(screen_width, screen_height);
         let screen_buffer = screen.frame_buffer();
 
+        // Contiguous runs are written as a whole row at once
+        if let Some((x, y, color)) = self.span(&mirror, offset) {
+            if color.to_rgba()[3] == 0 {
+                return;
+            }
+            blend_span(
+                screen_buffer,
+                screen_width,
+                screen_height,
+                (x, y),
+                self.pixels.len() as u32,
+                color,
+                brush,
+            );
+            return;
+        }
+
         for pixel in &self.pixels {
             let (color, coordinate) = pixel;
+            if color.to_rgba()[3] == 0 {
+                continue;
+            }
             // Applied mirror transformation if applicable
             let area = match mirror {
                 MirrorDirectionValue::FlipVertical(max_width) => Coordinate {
@@ -240,30 +1230,19 @@ impl Pixel {
                 y: offset.y + area.y,
             };
 
-            // Stays in the screen bounds
+            // Stays in the screen bounds and the clip
             let x = area.x.round() as i32;
             let y = area.y.round() as i32;
-            if x < 0 || y < 0 || x as u32 >= screen_width || y as u32 >= screen_height {
+            if !clip.contains(x, y) {
                 continue;
             }
 
             // Row-major layout formula is used for RGB and RGBA support
             // since we only do power of two resolutions
             let idx = ((y as u32 * screen_width) + (x as u32)) as usize * 4;
-            match color {
-                Color::RGB(r, g, b) => {
-                    screen_buffer[idx] = *r; // Red
-                    screen_buffer[idx + 1] = *g; // Green
-                    screen_buffer[idx + 2] = *b; // Blue
-                    screen_buffer[idx + 3] = 255; // Alpha
-                }
-                Color::RGBA(r, g, b, a) => {
-                    screen_buffer[idx] = *r; // Red
-                    screen_buffer[idx + 1] = *g; // Green
-                    screen_buffer[idx + 2] = *b; // Blue
-                    screen_buffer[idx + 3] = *a; // Alpha
-                }
-            }
+            brush
+                .blend
+                .apply(&mut screen_buffer[idx..idx + 4], &color.to_rgba());
         }
     }
 }
@@ -274,8 +1253,8 @@ mod tests {
 
     use super::*;
     use crate::{
-        mock::MockScreen,
-        palette::{CheckPattern, Stroke},
+        palette::{CheckPattern, Stroke, BLACK, GREEN, RED, TRANSPARENT},
+        window::HeadlessScreen,
     };
 
     #[test]
@@ -320,6 +1299,125 @@ mod tests {
         assert_eq!(pixel.column_pos(1), Some(5));
     }
 
+    #[test]
+    fn test_frame_builder() {
+        let frame = FrameBuilder::new()
+            .pixel(Pixel::new(
+                ColorScheme::Standard(Color::RGB(255, 0, 0)),
+                4,
+                2,
+            ))
+            .pixels(vec![Pixel::new(
+                ColorScheme::Standard(Color::RGB(255, 0, 0)),
+                1,
+                6,
+            )])
+            .duration_ms(120)
+            .build();
+
+        assert_eq!(frame.pixels.len(), 2);
+        assert_eq!(frame.width, 4);
+        assert_eq!(frame.height, 6);
+        assert_eq!(frame.duration, Some(Duration::from_millis(120)));
+
+        // Re-authoring an existing frame keeps its pixels
+        let frame = FrameBuilder::from(frame).duration_ms(80).build();
+        assert_eq!(frame.pixels.len(), 2);
+        assert_eq!(frame.duration, Some(Duration::from_millis(80)));
+    }
+
+    #[test]
+    fn test_compositor_present() {
+        let mut compositor = Compositor::new(2, 2);
+        let mut target = vec![7; 16];
+
+        compositor.clear(Color::RGB(0, 0, 0));
+        compositor.buffer_mut()[4..8].copy_from_slice(&[255, 0, 0, 255]);
+
+        // Nothing reaches the target until presented
+        assert_eq!(target, vec![7; 16]);
+
+        compositor.present(&mut target);
+        assert_eq!(&target[0..4], &[0, 0, 0, 255]);
+        assert_eq!(&target[4..8], &[255, 0, 0, 255]);
+        assert_eq!(&target[8..16], &[0, 0, 0, 255, 0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_fill_span_clips() {
+        let mut buffer = vec![0; 4 * 4 * 2];
+        let red = Color::RGB(255, 0, 0);
+
+        fill_span(&mut buffer, 4, 2, (-2, 1), 4, red);
+        fill_span(&mut buffer, 4, 2, (3, 0), 5, red);
+        fill_span(&mut buffer, 4, 2, (0, 2), 4, red);
+
+        let drawn: Vec<bool> = buffer.chunks_exact(4).map(|p| p[0] == 255).collect();
+        assert_eq!(
+            drawn,
+            vec![false, false, false, true, true, true, false, false]
+        );
+    }
+
+    #[test]
+    fn test_span_matches_per_pixel_draw() {
+        let stroke = Pixel::new(
+            ColorScheme::Stroke(Stroke {
+                color: Color::RGB(0, 0, 255),
+                range: Direction::Horizontal(4),
+            }),
+            3,
+            2,
+        );
+        let checks = [
+            (MirrorDirectionValue::None, Coordinate { x: 1.4, y: 0.6 }),
+            (MirrorDirectionValue::FlipVertical(8), Coordinate::default()),
+            (
+                MirrorDirectionValue::FlipHorizontal(5),
+                Coordinate::default(),
+            ),
+            (MirrorDirectionValue::None, Coordinate { x: 8.0, y: 0.0 }),
+            (MirrorDirectionValue::None, Coordinate { x: -4.5, y: 0.0 }),
+        ];
+        for (mirror, offset) in checks {
+            let mut fast = HeadlessScreen::new(10, 10);
+            stroke.draw(&mut fast, mirror.clone(), offset, Brush::default());
+
+            // Break the run up so only the per pixel path can draw it
+            let mut slow = HeadlessScreen::new(10, 10);
+            for i in 0..stroke.len() {
+                let (color, coordinate) = stroke.pixels[i];
+                Pixel {
+                    pixels: vec![(color, coordinate)],
+                }
+                .draw(&mut slow, mirror.clone(), offset, Brush::default());
+            }
+
+            assert_eq!(fast.buffer(), slow.buffer(), "Failed for {:?}", offset);
+        }
+    }
+
+    #[test]
+    fn test_draw_batch() {
+        let mut screen = HeadlessScreen::new(4, 1);
+        let pixel = Pixel::new(ColorScheme::Standard(Color::RGB(255, 0, 0)), 0, 0);
+
+        screen
+            .draw_batch(&[
+                DrawCmd::Span {
+                    start: (1, 0),
+                    len: 3,
+                    color: Color::RGB(0, 255, 0),
+                },
+                DrawCmd::Pixel(&pixel, MirrorDirectionValue::None, Coordinate::default()),
+            ])
+            .unwrap();
+
+        assert_eq!(&screen.buffer()[0..4], &[255, 0, 0, 255]);
+        assert_eq!(&screen.buffer()[4..8], &[0, 255, 0, 255]);
+        assert_eq!(&screen.buffer()[12..16], &[0, 255, 0, 255]);
+    }
+
     #[test]
     fn test_move_pos() {
         let mut pixel = Pixel::new(ColorScheme::Standard(Color::RGB(255, 0, 0)), 10, 20);
@@ -344,7 +1442,7 @@ mod tests {
 
     #[test]
     fn test_draw_rgb() {
-        let screen = Arc::new(Mutex::new(MockScreen::new(50, 50)));
+        let screen = Arc::new(Mutex::new(HeadlessScreen::new(50, 50)));
         let pixel = Pixel::new(ColorScheme::Standard(Color::RGB(255, 0, 0)), 5, 5);
 
         // Simulate drawing the pixel onto the screen
@@ -352,6 +1450,7 @@ mod tests {
             &mut *screen.lock().unwrap(),
             MirrorDirectionValue::None,
             Coordinate { x: 0.0, y: 0.0 },
+            Brush::default(),
         );
 
         // Check the pixel data in the screen buffer
@@ -359,15 +1458,15 @@ mod tests {
         let screen = Arc::into_inner(screen).unwrap();
         let screen = screen.into_inner().unwrap();
         let idx = (5 * 50 + 5) as usize * 4; // Pixel at (5, 5)
-        assert_eq!(screen.buffer[idx], 255); // Red channel
-        assert_eq!(screen.buffer[idx + 1], 0); // Green channel
-        assert_eq!(screen.buffer[idx + 2], 0); // Blue channel
-        assert_eq!(screen.buffer[idx + 3], 255); // Alpha channel
+        assert_eq!(screen.buffer()[idx], 255); // Red channel
+        assert_eq!(screen.buffer()[idx + 1], 0); // Green channel
+        assert_eq!(screen.buffer()[idx + 2], 0); // Blue channel
+        assert_eq!(screen.buffer()[idx + 3], 255); // Alpha channel
     }
 
     #[test]
     fn test_draw_rgba() {
-        let screen = Arc::new(Mutex::new(MockScreen::new(50, 50)));
+        let screen = Arc::new(Mutex::new(HeadlessScreen::new(50, 50)));
         let pixel = Pixel::new(ColorScheme::Standard(Color::RGBA(255, 0, 0, 180)), 5, 5);
 
         // Simulate drawing the pixel onto the screen
@@ -375,6 +1474,7 @@ mod tests {
             &mut *screen.lock().unwrap(),
             MirrorDirectionValue::None,
             Coordinate { x: 0.0, y: 0.0 },
+            Brush::default(),
         );
 
         // Check the pixel data in the screen buffer
@@ -382,15 +1482,254 @@ mod tests {
         let screen = Arc::into_inner(screen).unwrap();
         let screen = screen.into_inner().unwrap();
         let idx = (5 * 50 + 5) as usize * 4; // Pixel at (5, 5)
-        assert_eq!(screen.buffer[idx], 255); // Red channel
-        assert_eq!(screen.buffer[idx + 1], 0); // Green channel
-        assert_eq!(screen.buffer[idx + 2], 0); // Blue channel
-        assert_eq!(screen.buffer[idx + 3], 180); // Alpha channel
+        assert_eq!(screen.buffer()[idx], 255); // Red channel
+        assert_eq!(screen.buffer()[idx + 1], 0); // Green channel
+        assert_eq!(screen.buffer()[idx + 2], 0); // Blue channel
+        assert_eq!(screen.buffer()[idx + 3], 180); // Alpha channel
+    }
+
+    #[test]
+    fn test_transparent_pixels_are_keyed_out() {
+        let under = Pixel::new(
+            ColorScheme::Stroke(Stroke::new(RED, Direction::Horizontal(3))),
+            0,
+            0,
+        );
+        // A hole over the middle of the run, both on its own and as a run
+        let holes = [
+            Pixel::new(ColorScheme::Standard(TRANSPARENT), 1, 0),
+            Pixel::new(
+                ColorScheme::Stroke(Stroke::new(TRANSPARENT, Direction::Horizontal(2))),
+                1,
+                0,
+            ),
+        ];
+        let red = RED.to_rgba();
+        for hole in holes {
+            let keyed = Frame::new(vec![hole.clone()], None);
+            let cut = FrameBuilder::new()
+                .pixel(hole.clone())
+                .keying(Keying::None)
+                .build();
+            let mut screen = HeadlessScreen::new(3, 1);
+            screen
+                .draw_commands(&[
+                    DrawCmd::Pixel(&under, MirrorDirectionValue::None, Coordinate::default()),
+                    DrawCmd::Pixel(&hole, MirrorDirectionValue::None, Coordinate::default()),
+                    DrawCmd::Frame(&keyed, MirrorDirectionValue::None, Coordinate::default()),
+                ])
+                .unwrap();
+            assert_eq!(&screen.buffer()[4..8], red);
+
+            screen
+                .draw_commands(&[DrawCmd::Frame(
+                    &cut,
+                    MirrorDirectionValue::None,
+                    Coordinate::default(),
+                )])
+                .unwrap();
+            assert_eq!(&screen.buffer()[4..8], TRANSPARENT.to_rgba());
+            assert_eq!(&screen.buffer()[0..4], red);
+        }
+    }
+
+    #[test]
+    fn test_shapes_clip_to_the_screen() {
+        let drawn = |screen: &HeadlessScreen| -> Vec<(u32, u32)> {
+            (0..screen.height())
+                .flat_map(|y| (0..screen.width()).map(move |x| (x, y)))
+                .filter(|&(x, y)| screen.pixel(x, y) == Some(Color::RGBA(255, 0, 0, 255)))
+                .collect()
+        };
+        let mut screen = HeadlessScreen::new(4, 4);
+        screen
+            .draw_commands(&[DrawCmd::Rect {
+                start: (-1, 2),
+                width: 3,
+                height: 5,
+                color: RED,
+            }])
+            .unwrap();
+        assert_eq!(drawn(&screen), [(0, 2), (1, 2), (0, 3), (1, 3)]);
+
+        let mut screen = HeadlessScreen::new(4, 4);
+        screen
+            .draw_commands(&[DrawCmd::Line {
+                from: (-2, -2),
+                to: (5, 5),
+                color: RED,
+            }])
+            .unwrap();
+        assert_eq!(drawn(&screen), [(0, 0), (1, 1), (2, 2), (3, 3)]);
+
+        let circle = |filled| {
+            let mut screen = HeadlessScreen::new(5, 5);
+            screen
+                .draw_commands(&[
+                    DrawCmd::Blend(Blend::Additive),
+                    DrawCmd::Circle {
+                        center: (2, 2),
+                        radius: 2,
+                        color: Color::RGBA(255, 0, 0, 128),
+                        filled,
+                    },
+                ])
+                .unwrap();
+            screen
+        };
+        // Drawn once over every covered pixel, not building up where rows meet
+        let filled = circle(true);
+        let covered = filled.buffer().chunks_exact(4).filter(|p| p[0] > 0);
+        assert!(covered.clone().all(|p| p[0] == 128));
+        assert_eq!(covered.count(), 21);
+        let outline = circle(false);
+        let covered = outline.buffer().chunks_exact(4).filter(|p| p[0] > 0);
+        assert!(covered.clone().all(|p| p[0] == 128));
+        assert_eq!(covered.count(), 12);
+        assert_eq!(&outline.buffer()[(2 * 5 + 2) * 4..][..4], [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_far_off_shapes_draw_only_whats_on_screen() {
+        let mut screen = HeadlessScreen::new(4, 4);
+        screen
+            .draw_commands(&[
+                DrawCmd::Line {
+                    from: (i32::MIN, i32::MIN),
+                    to: (i32::MAX, i32::MAX),
+                    color: RED,
+                },
+                // Entirely off screen, so nothing to walk
+                DrawCmd::Line {
+                    from: (-2_000_000_000, 10),
+                    to: (2_000_000_000, 10),
+                    color: GREEN,
+                },
+                DrawCmd::Circle {
+                    center: (i32::MAX, i32::MAX),
+                    radius: u32::MAX,
+                    color: GREEN,
+                    filled: true,
+                },
+            ])
+            .unwrap();
+        for i in 0..4 {
+            assert_eq!(screen.pixel(i, i), Some(Color::RGBA(255, 0, 0, 255)));
+        }
+        assert_eq!(screen.pixel(1, 0), Some(Color::RGBA(0, 0, 0, 0)));
+
+        // Covering the whole screen from the middle of it
+        let mut screen = HeadlessScreen::new(4, 4);
+        screen
+            .draw_commands(&[DrawCmd::Circle {
+                center: (2, 2),
+                radius: u32::MAX,
+                color: GREEN,
+                filled: true,
+            }])
+            .unwrap();
+        assert!(screen
+            .buffer()
+            .chunks_exact(4)
+            .all(|p| p == [60, 200, 60, 255]));
+    }
+
+    #[test]
+    fn test_clips_nest_and_pop() {
+        let fill = |color| DrawCmd::Rect {
+            start: (0, 0),
+            width: 6,
+            height: 6,
+            color,
+        };
+        let frame = FrameBuilder::new()
+            .pixels((0..6).map(|y| {
+                Pixel::new(
+                    ColorScheme::Stroke(Stroke::new(GREEN, Direction::Horizontal(6))),
+                    0,
+                    y,
+                )
+            }))
+            .build();
+        let mut screen = HeadlessScreen::new(6, 6);
+        screen
+            .draw_commands(&[
+                DrawCmd::PushClip(Clip::new(1, 1, 4, 4)),
+                fill(RED),
+                // Only what's inside of both clips is drawn
+                DrawCmd::PushClip(Clip::new(3, -2, 8, 4)),
+                DrawCmd::Frame(&frame, MirrorDirectionValue::None, Coordinate::default()),
+                DrawCmd::PopClip,
+                DrawCmd::PopClip,
+                DrawCmd::PopClip,
+                DrawCmd::Line {
+                    from: (0, 5),
+                    to: (5, 5),
+                    color: BLACK,
+                },
+            ])
+            .unwrap();
+        let rows: Vec<String> = (0..6)
+            .map(|y| {
+                (0..6)
+                    .map(|x| match screen.pixel(x, y).map(Color::to_rgba) {
+                        Some(c) if c == RED.to_rgba() => 'r',
+                        Some(c) if c == GREEN.to_rgba() => 'g',
+                        Some(c) if c == BLACK.to_rgba() => 'b',
+                        _ => '.',
+                    })
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            ["......", ".rrgg.", ".rrrr.", ".rrrr.", ".rrrr.", "bbbbbb"]
+        );
+    }
+
+    #[test]
+    fn test_blend_modes() {
+        let gray = Pixel::new(
+            ColorScheme::Stroke(Stroke::new(
+                Color::RGB(100, 100, 100),
+                Direction::Horizontal(3),
+            )),
+            0,
+            0,
+        );
+        let glow = Pixel::new(ColorScheme::Standard(Color::RGBA(100, 0, 0, 128)), 0, 0);
+        let shadow = Pixel::new(
+            ColorScheme::Stroke(Stroke::new(
+                Color::RGB(128, 128, 128),
+                Direction::Horizontal(2),
+            )),
+            1,
+            0,
+        );
+        let at = Coordinate::default();
+        let mut screen = HeadlessScreen::new(3, 1);
+        screen
+            .draw_commands(&[
+                DrawCmd::Pixel(&gray, MirrorDirectionValue::None, at),
+                DrawCmd::Blend(Blend::Additive),
+                DrawCmd::Pixel(&glow, MirrorDirectionValue::None, at),
+                DrawCmd::Blend(Blend::Multiply),
+                DrawCmd::Pixel(&shadow, MirrorDirectionValue::None, at),
+            ])
+            .unwrap();
+        assert_eq!(&screen.buffer()[0..4], [150, 100, 100, 255]);
+        assert_eq!(&screen.buffer()[4..8], [50, 50, 50, 255]);
+
+        // Every batch starts out drawing normally
+        screen
+            .draw_commands(&[DrawCmd::Pixel(&glow, MirrorDirectionValue::None, at)])
+            .unwrap();
+        assert_eq!(&screen.buffer()[0..4], [100, 0, 0, 128]);
     }
 
     #[test]
     fn test_mirror_flip_vertical() {
-        let screen = Arc::new(Mutex::new(MockScreen::new(50, 50)));
+        let screen = Arc::new(Mutex::new(HeadlessScreen::new(50, 50)));
         let pixel = Pixel::new(ColorScheme::Standard(Color::RGB(255, 0, 0)), 5, 3);
 
         // Flip vertically at line 10
@@ -398,20 +1737,21 @@ mod tests {
             &mut *screen.lock().unwrap(),
             MirrorDirectionValue::FlipVertical(5),
             Coordinate { x: 0.0, y: 0.0 },
+            Brush::default(),
         );
 
         // Check the pixel's mirrored position
         let idx_original = (3 * 50 + 5) as usize * 4; // Pixel at (5, 3)
-        let idx_mirrored = (3 * 50 + 0) as usize * 4; // Pixel at (0, 3)
+        let idx_mirrored = (3 * 50) as usize * 4; // Pixel at (0, 3)
         let screen = Arc::into_inner(screen).unwrap();
         let screen = screen.into_inner().unwrap();
-        assert_eq!(screen.buffer[idx_original], 0); // Should not be original pixel
-        assert_eq!(screen.buffer[idx_mirrored], 255); // Should be mirrored pixel
+        assert_eq!(screen.buffer()[idx_original], 0); // Should not be original pixel
+        assert_eq!(screen.buffer()[idx_mirrored], 255); // Should be mirrored pixel
     }
 
     #[test]
     fn test_mirror_flip_horizontal() {
-        let screen = Arc::new(Mutex::new(MockScreen::new(50, 50)));
+        let screen = Arc::new(Mutex::new(HeadlessScreen::new(50, 50)));
         let pixel = Pixel::new(ColorScheme::Standard(Color::RGB(255, 0, 0)), 5, 3);
 
         // Flip vertically at line 10
@@ -419,14 +1759,139 @@ mod tests {
             &mut *screen.lock().unwrap(),
             MirrorDirectionValue::FlipHorizontal(3),
             Coordinate { x: 0.0, y: 0.0 },
+            Brush::default(),
         );
 
         // Check the pixel's mirrored position
         let idx_original = (3 * 50 + 5) as usize * 4; // Pixel at (5, 3)
-        let idx_mirrored = (0 * 50 + 5) as usize * 4; // Pixel at (5, 0)
+        let idx_mirrored = 5_usize * 4; // Pixel at (5, 0)
         let screen = Arc::into_inner(screen).unwrap();
         let screen = screen.into_inner().unwrap();
-        assert_eq!(screen.buffer[idx_original], 0); // Should not be original pixel
-        assert_eq!(screen.buffer[idx_mirrored], 255); // Should be mirrored pixel
+        assert_eq!(screen.buffer()[idx_original], 0); // Should not be original pixel
+        assert_eq!(screen.buffer()[idx_mirrored], 255); // Should be mirrored pixel
+    }
+
+    #[test]
+    fn test_draw_list_reuses_buffer() {
+        let pixel = Pixel::new(ColorScheme::Standard(Color::RGB(255, 0, 0)), 1, 1);
+        let mut draw_list = DrawList::new();
+
+        let mut batch = draw_list.begin();
+        batch
+            .extend((0..32).map(|_| {
+                DrawCmd::Pixel(&pixel, MirrorDirectionValue::None, Coordinate::default())
+            }));
+        let (ptr, capacity) = (batch.as_ptr() as usize, batch.capacity());
+        draw_list.finish(batch);
+
+        let batch = draw_list.begin();
+        assert!(batch.is_empty());
+        assert_eq!(batch.as_ptr() as usize, ptr);
+        assert_eq!(batch.capacity(), capacity);
+    }
+
+    #[test]
+    fn test_frame_raster_matches_pixels() {
+        let frame = FrameBuilder::new()
+            .pixel(Pixel::new(
+                ColorScheme::Stroke(Stroke {
+                    range: Direction::Horizontal(4),
+                    color: Color::RGB(0, 255, 0),
+                }),
+                1,
+                2,
+            ))
+            .pixel(Pixel::new(
+                ColorScheme::CheckPattern(CheckPattern {
+                    range: Direction::Vertical(3),
+                    a: Color::RGB(255, 0, 0),
+                    b: Color::RGB(0, 0, 255),
+                }),
+                3,
+                1,
+            ))
+            .pixel(Pixel::new(
+                ColorScheme::Standard(Color::RGBA(0, 0, 0, 0)),
+                2,
+                2,
+            ))
+            .build();
+
+        for mirror in [
+            MirrorDirection::None,
+            MirrorDirection::FlipVertical,
+            MirrorDirection::FlipHorizontal,
+        ] {
+            let mirror = frame.mirror_value(mirror);
+            for offset in [
+                Coordinate { x: 2.0, y: 3.0 },
+                Coordinate { x: -2.5, y: 0.5 },
+                Coordinate { x: 7.4, y: 1.0 },
+            ] {
+                let mut pixels = HeadlessScreen::new(10, 8);
+                let mut raster = HeadlessScreen::new(10, 8);
+                let background = DrawCmd::Span {
+                    start: (0, 3),
+                    len: 10,
+                    color: Color::RGB(9, 9, 9),
+                };
+
+                let mut batch = vec![DrawCmd::Clear, background.clone()];
+                batch.extend(
+                    frame
+                        .pixels
+                        .iter()
+                        .map(|p| DrawCmd::Pixel(p, mirror.clone(), offset)),
+                );
+                pixels.draw_batch(&batch).unwrap();
+                raster
+                    .draw_batch(&[
+                        DrawCmd::Clear,
+                        background,
+                        DrawCmd::Frame(&frame, mirror.clone(), offset),
+                    ])
+                    .unwrap();
+
+                assert_eq!(pixels.buffer(), raster.buffer(), "{mirror:?} {offset:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_flipped_frames_are_cached() {
+        let mut frame = FrameBuilder::new()
+            .pixel(Pixel::new(
+                ColorScheme::Standard(Color::RGB(255, 0, 0)),
+                0,
+                0,
+            ))
+            .pixel(Pixel::new(
+                ColorScheme::Standard(Color::RGB(0, 255, 0)),
+                3,
+                1,
+            ))
+            .build();
+        let mirror = frame.mirror_value(MirrorDirection::FlipVertical);
+        let mut screen = HeadlessScreen::new(4, 2);
+
+        screen
+            .draw_batch(&[DrawCmd::Frame(
+                &frame,
+                mirror.clone(),
+                Coordinate::default(),
+            )])
+            .unwrap();
+        assert!(frame.flipped[0].get().is_some());
+        assert!(frame.flipped[1].get().is_none());
+        assert_eq!(&screen.buffer()[12..16], &[255, 0, 0, 255]);
+        assert_eq!(&screen.buffer()[16..20], &[0, 255, 0, 255]);
+
+        frame.pixels[0].change_color(0, Color::RGB(0, 0, 255));
+        frame.invalidate_cache();
+        assert!(frame.flipped[0].get().is_none());
+        screen
+            .draw_batch(&[DrawCmd::Frame(&frame, mirror, Coordinate::default())])
+            .unwrap();
+        assert_eq!(&screen.buffer()[12..16], &[0, 0, 255, 255]);
     }
 }
@@ -0,0 +1,343 @@
+//! The settings menu picking how the game's window is shown and played.
+//!
+//! A `SettingsMenu` is a `Menu` of buttons, each stepping one of the
+//! `WindowSettings` to its next preset: the scale, windowed or fullscreen mode
+//! and the monitor to show the window on. Every press hands back the settings
+//! to apply right away with `GameWindow::apply`, so the window changes while
+//! the menu is still open.
+//!
+//! Below those, a button steps the game's `FrameRate` cap through presets and
+//! one per `GameInput` rebinds it: pressing it waits for the next key pressed,
+//! which `bind` hands back to bind the input to.
+//!
+//! The event loop opens the menu of a window registered with
+//! `EventHandler::settings_menu` when `MenuBack` is pressed, feeds it input
+//! instead of the game while it's open and draws it over the game's screen.
+//!
+//! # Key Structures
+//! - **`SettingsMenu`**: The window settings and the menu changing them.
+//!
+//! # Example Usage
+//! ```ignore
+//! let mut settings = SettingsMenu::new(font, (8, 8), 1, WindowSettings::default())
+//!     .monitors(window.monitors());
+//!
+//! settings.open();
+//! if let Some(changed) = settings.handle(UiInput::Confirm) {
+//!     window.apply(&changed)?;
+//! }
+//!
+//! // Once a binding's button was pressed
+//! if let Some((input, key)) = settings.bind(pressed_key) {
+//!     input_handler.update_binding(&input, key);
+//! }
+//! ```
+use std::sync::Arc;
+
+use winit::keyboard::PhysicalKey;
+
+use crate::{
+    font::BitmapFont,
+    game::FrameRate,
+    input::{GameInput, GameInputHandler},
+    renderer::DrawCmd,
+    ui::{Menu, UiEvent, UiInput},
+    window::{Renderable, WindowSettings},
+};
+
+const SCALE: usize = 0;
+const MODE: usize = 1;
+const MONITOR: usize = 2;
+const FPS: usize = 3;
+/// The first of the buttons rebinding `REBINDABLE` in order.
+const BINDINGS: usize = 4;
+const BACK: usize = BINDINGS + REBINDABLE.len();
+
+/// The frame rate caps stepped through, `None` being uncapped.
+const FRAME_RATES: [Option<u64>; 5] = [Some(30), Some(60), Some(120), Some(144), None];
+
+const REBINDABLE: [(GameInput, &str); 8] = [
+    (GameInput::PlayerMoveUp, "Up"),
+    (GameInput::PlayerMoveLeft, "Left"),
+    (GameInput::PlayerMoveRight, "Right"),
+    (GameInput::PlayerMoveDown, "Down"),
+    (GameInput::PlayerSprint, "Sprint"),
+    (GameInput::PlayerDash, "Dash"),
+    (GameInput::MenuConfirm, "Confirm"),
+    (GameInput::MenuBack, "Back"),
+];
+
+/// The window settings and the menu changing them.
+pub struct SettingsMenu {
+    menu: Menu,
+    settings: WindowSettings,
+    /// How many monitors the window can be moved between
+    monitors: usize,
+    frame_rate: Option<FrameRate>,
+    /// The key bound to each of `REBINDABLE`
+    keys: Vec<PhysicalKey>,
+    /// The input waiting for a key to be bound to
+    rebinding: Option<GameInput>,
+    open: bool,
+}
+impl SettingsMenu {
+    /// A closed menu with its top left corner at `position`, starting from
+    /// `settings`.
+    pub fn new(
+        font: Arc<BitmapFont>,
+        position: (i32, i32),
+        scale: u32,
+        settings: WindowSettings,
+    ) -> Self {
+        let menu = (0..BACK)
+            .fold(Menu::new(font, position, scale), |menu, _| menu.button(""))
+            .button("Back");
+        let defaults = GameInputHandler::default();
+        let mut settings = Self {
+            menu,
+            settings,
+            monitors: 1,
+            frame_rate: None,
+            keys: REBINDABLE
+                .iter()
+                .map(|(input, _)| *defaults.get_binding(input))
+                .collect(),
+            rebinding: None,
+            open: false,
+        };
+        settings.relabel();
+        settings
+    }
+    /// Lets the window move between `monitors` monitors.
+    pub fn monitors(mut self, monitors: usize) -> Self {
+        self.monitors = monitors;
+        self
+    }
+    /// Caps the game at the frame rate picked, through the `FrameRate` its
+    /// `GameState` hands out.
+    pub fn frame_rate(mut self, frame_rate: FrameRate) -> Self {
+        self.frame_rate = Some(frame_rate);
+        self.relabel();
+        self
+    }
+    /// Shows the keys `handler` binds instead of the defaults.
+    pub(crate) fn show_bindings(&mut self, handler: &GameInputHandler) {
+        for (key, (input, _)) in self.keys.iter_mut().zip(REBINDABLE) {
+            *key = *handler.get_binding(&input);
+        }
+        self.relabel();
+    }
+    pub fn settings(&self) -> WindowSettings {
+        self.settings
+    }
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+    pub fn close(&mut self) {
+        self.open = false;
+        self.rebinding = None;
+        self.relabel();
+    }
+    /// Whether a binding's button was pressed and waits for a key.
+    pub fn is_rebinding(&self) -> bool {
+        self.rebinding.is_some()
+    }
+    /// Takes `key` as the new key of the input waiting for one.
+    ///
+    /// Returns the input and key to bind, if one was waiting
+    pub fn bind(&mut self, key: PhysicalKey) -> Option<(GameInput, PhysicalKey)> {
+        let input = self.rebinding.take()?;
+        if let Some(i) = REBINDABLE.iter().position(|(other, _)| *other == input) {
+            self.keys[i] = key;
+        }
+        self.relabel();
+        Some((input, key))
+    }
+    /// Applies a single input while the menu is open.
+    ///
+    /// Returns the settings to show the window with if a button changed them
+    pub fn handle(&mut self, input: UiInput) -> Option<WindowSettings> {
+        if !self.open {
+            return None;
+        }
+        match self.menu.handle(input)? {
+            UiEvent::Pressed(SCALE) => self.settings.next_scale(),
+            UiEvent::Pressed(MODE) => self.settings.next_mode(),
+            UiEvent::Pressed(MONITOR) => self.settings.next_monitor(self.monitors),
+            UiEvent::Pressed(FPS) => {
+                self.next_frame_rate();
+                return None;
+            }
+            UiEvent::Pressed(BACK) | UiEvent::Back => {
+                self.close();
+                return None;
+            }
+            UiEvent::Pressed(i) if (BINDINGS..BACK).contains(&i) => {
+                self.rebinding = Some(REBINDABLE[i - BINDINGS].0);
+                self.relabel();
+                return None;
+            }
+            _ => return None,
+        }
+        self.relabel();
+        Some(self.settings)
+    }
+    /// Shows the current settings on their buttons.
+    fn relabel(&mut self) {
+        let settings = self.settings;
+        self.menu
+            .set_label(SCALE, format!("Scale {}x", settings.scale));
+        self.menu.set_label(MODE, settings.mode.to_string());
+        let monitor = settings
+            .monitor
+            .map_or("Current".to_string(), |m| (m + 1).to_string());
+        self.menu.set_label(MONITOR, format!("Monitor {monitor}"));
+        let fps = match self.frame_rate.as_ref().map(FrameRate::target) {
+            Some(Some(fps)) => format!("FPS {fps}"),
+            Some(None) => "FPS Uncapped".to_string(),
+            None => "FPS Default".to_string(),
+        };
+        self.menu.set_label(FPS, fps);
+        for (i, ((input, name), key)) in REBINDABLE.iter().zip(&self.keys).enumerate() {
+            let key = if self.rebinding == Some(*input) {
+                "...".to_string()
+            } else {
+                key_name(*key)
+            };
+            self.menu.set_label(BINDINGS + i, format!("{name} {key}"));
+        }
+    }
+    /// Caps the frame rate at the preset after the current cap.
+    fn next_frame_rate(&mut self) {
+        let Some(frame_rate) = &self.frame_rate else {
+            return;
+        };
+        let current = FRAME_RATES
+            .iter()
+            .position(|fps| *fps == frame_rate.target());
+        let next = current.map_or(0, |i| (i + 1) % FRAME_RATES.len());
+        frame_rate.set_target(FRAME_RATES[next]);
+        self.relabel();
+    }
+}
+
+/// A short name for `key`, like `W` or `Up`.
+fn key_name(key: PhysicalKey) -> String {
+    let PhysicalKey::Code(code) = key else {
+        return "?".to_string();
+    };
+    let name = format!("{code:?}");
+    ["Key", "Arrow", "Digit"]
+        .iter()
+        .find_map(|prefix| name.strip_prefix(prefix))
+        .unwrap_or(&name)
+        .to_string()
+}
+impl Renderable for SettingsMenu {
+    fn commands<'a>(&'a self, batch: &mut Vec<DrawCmd<'a>>) {
+        if self.open {
+            self.menu.commands(batch);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use winit::keyboard::KeyCode;
+
+    use crate::{
+        layout::Coordinate,
+        ui::Widget,
+        window::{WindowMode, MIN_SCALE},
+    };
+
+    fn settings() -> SettingsMenu {
+        let font = "STARTFONT 2.1\nFONT_ASCENT 1\nFONT_DESCENT 0\nENDFONT\n";
+        let font = Arc::new(BitmapFont::from_bdf(font).unwrap());
+        SettingsMenu::new(font, (0, 0), 1, WindowSettings::default()).monitors(2)
+    }
+
+    fn label(settings: &SettingsMenu, index: usize) -> &str {
+        match &settings.menu.widgets()[index] {
+            Widget::Button { label } => label,
+            _ => unreachable!("the settings are all buttons"),
+        }
+    }
+
+    #[test]
+    fn test_buttons_step_through_presets() {
+        let mut settings = settings();
+        assert_eq!(settings.handle(UiInput::Confirm), None);
+        settings.open();
+
+        let down = UiInput::Navigate(Coordinate { x: 0.0, y: 1.0 });
+        let mut changed = settings.handle(UiInput::Confirm);
+        settings.handle(UiInput::Confirm);
+        assert_eq!(changed.map(|s| s.scale), Some(5));
+        assert_eq!(label(&settings, SCALE), "Scale 6x");
+        changed = settings.handle(UiInput::Confirm);
+        assert_eq!(changed.map(|s| s.scale), Some(MIN_SCALE));
+
+        settings.handle(down);
+        changed = settings.handle(UiInput::Confirm);
+        assert_eq!(changed.map(|s| s.mode), Some(WindowMode::Borderless));
+        assert_eq!(label(&settings, MODE), "Borderless");
+
+        settings.handle(down);
+        assert_eq!(label(&settings, MONITOR), "Monitor Current");
+        settings.handle(UiInput::Confirm);
+        changed = settings.handle(UiInput::Confirm);
+        assert_eq!(changed.and_then(|s| s.monitor), Some(1));
+        assert_eq!(label(&settings, MONITOR), "Monitor 2");
+
+        // Leaving keeps what was picked
+        assert_eq!(settings.handle(UiInput::Back), None);
+        assert!(!settings.is_open());
+        assert_eq!(settings.settings().mode, WindowMode::Borderless);
+    }
+
+    #[test]
+    fn test_binding_waits_for_a_key() {
+        let mut settings = settings();
+        let key = PhysicalKey::Code(KeyCode::KeyW);
+        assert_eq!(settings.bind(key), None);
+        settings.open();
+
+        let up = UiInput::Navigate(Coordinate { x: 0.0, y: -1.0 });
+        for _ in 0..=REBINDABLE.len() {
+            settings.handle(up);
+        }
+        assert_eq!(label(&settings, BINDINGS), "Up Up");
+        settings.handle(UiInput::Confirm);
+        assert!(settings.is_rebinding());
+        assert_eq!(label(&settings, BINDINGS), "Up ...");
+
+        assert_eq!(settings.bind(key), Some((GameInput::PlayerMoveUp, key)));
+        assert!(!settings.is_rebinding());
+        assert_eq!(label(&settings, BINDINGS), "Up W");
+    }
+
+    #[test]
+    fn test_frame_rate_steps_through_caps() {
+        let frame_rate = FrameRate::new(Some(60));
+        let mut settings = settings().frame_rate(frame_rate.clone());
+        settings.open();
+        let down = UiInput::Navigate(Coordinate { x: 0.0, y: 1.0 });
+        for _ in 0..FPS {
+            settings.handle(down);
+        }
+
+        assert_eq!(settings.handle(UiInput::Confirm), None);
+        assert_eq!(frame_rate.target(), Some(120));
+        settings.handle(UiInput::Confirm);
+        settings.handle(UiInput::Confirm);
+        assert_eq!(frame_rate.target(), None);
+        assert_eq!(label(&settings, FPS), "FPS Uncapped");
+        settings.handle(UiInput::Confirm);
+        assert_eq!(frame_rate.target(), Some(30));
+    }
+}
@@ -0,0 +1,266 @@
+//! A shop overlay where the player buys items for currency.
+//!
+//! A `Shop` lists its wares as buttons of a `Menu`, each labelled with its
+//! price and how many are left, and sells the focused one when confirmed if
+//! the player can afford it. Bought items are handed back to the caller to put
+//! wherever the player keeps them. What's left in stock can be written to and
+//! restored from a plain text save, so a shop doesn't restock by reloading.
+//!
+//! # Key Structures
+//! - **`Listing`**: An item for sale, its price and stock.
+//! - **`Shop`**: The listings and the menu they're picked from.
+//! - **`ShopEvent`**: An item bought, a purchase refused or the shop left.
+//!
+//! Errors:
+//! - `ShopError`: Buying an item or restoring stock from a save failed.
+//!
+//! # Example Usage
+//! ```ignore
+//! let mut shop = Shop::new(font, (16, 16), 1)
+//!     .listing("potion", 10, None)
+//!     .listing("sword", 120, Some(1));
+//! shop.load(&fs::read_to_string("save/shop.txt")?)?;
+//!
+//! match shop.handle(UiInput::Confirm, &mut gold) {
+//!     Some(ShopEvent::Bought(item)) => inventory.push(item),
+//!     Some(ShopEvent::Refused(error)) => hud.flash(&error.to_string()),
+//!     Some(ShopEvent::Closed) => shop_open = false,
+//!     None => (),
+//! }
+//! fs::write("save/shop.txt", shop.save())?;
+//! ```
+use std::{fmt::Write, sync::Arc};
+
+use thiserror::Error;
+
+use crate::{
+    font::BitmapFont,
+    renderer::DrawCmd,
+    ui::{Menu, UiEvent, UiInput},
+    window::Renderable,
+};
+
+/// An item for sale.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Listing {
+    pub item: String,
+    pub price: u32,
+    /// How many are left, or `None` for as many as the player wants.
+    pub stock: Option<u32>,
+}
+impl Listing {
+    fn label(&self) -> String {
+        match self.stock {
+            Some(stock) => format!("{} {}G x{stock}", self.item, self.price),
+            None => format!("{} {}G", self.item, self.price),
+        }
+    }
+}
+
+/// What happened in the shop.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShopEvent {
+    /// The player paid for an item.
+    Bought(String),
+    Refused(ShopError),
+    Closed,
+}
+
+/// Listings picked from a menu.
+pub struct Shop {
+    listings: Vec<Listing>,
+    menu: Menu,
+}
+impl Shop {
+    pub fn new(font: Arc<BitmapFont>, position: (i32, i32), scale: u32) -> Self {
+        Self {
+            listings: Vec::new(),
+            menu: Menu::new(font, position, scale),
+        }
+    }
+    /// Puts `stock` of `item` up for sale at `price`, or as many as the player
+    /// wants with `None`.
+    pub fn listing(mut self, item: &str, price: u32, stock: Option<u32>) -> Self {
+        let listing = Listing {
+            item: item.to_string(),
+            price,
+            stock,
+        };
+        self.menu = self.menu.button(listing.label());
+        self.listings.push(listing);
+        self
+    }
+    pub fn listings(&self) -> &[Listing] {
+        &self.listings
+    }
+    /// Applies a single menu input, buying the focused item with `wallet` when
+    /// confirmed.
+    pub fn handle(&mut self, input: UiInput, wallet: &mut u32) -> Option<ShopEvent> {
+        match self.menu.handle(input)? {
+            UiEvent::Pressed(index) => Some(match self.buy(index, wallet) {
+                Ok(item) => ShopEvent::Bought(item),
+                Err(error) => ShopEvent::Refused(error),
+            }),
+            UiEvent::Back => Some(ShopEvent::Closed),
+            _ => None,
+        }
+    }
+    /// Sells the listing at `index` if `wallet` can pay for it.
+    ///
+    /// Returns the item bought
+    pub fn buy(&mut self, index: usize, wallet: &mut u32) -> Result<String, ShopError> {
+        let listing = self
+            .listings
+            .get_mut(index)
+            .ok_or(ShopError::NoListing(index))?;
+        if listing.stock == Some(0) {
+            return Err(ShopError::SoldOut(listing.item.clone()));
+        }
+        if *wallet < listing.price {
+            return Err(ShopError::Unaffordable {
+                price: listing.price,
+                wallet: *wallet,
+            });
+        }
+        *wallet -= listing.price;
+        if let Some(stock) = &mut listing.stock {
+            *stock -= 1;
+        }
+        self.menu.set_label(index, listing.label());
+        Ok(listing.item.clone())
+    }
+    /// Writes what's left of every limited listing, one per line.
+    pub fn save(&self) -> String {
+        let mut save = String::new();
+        for listing in &self.listings {
+            if let Some(stock) = listing.stock {
+                let _ = writeln!(save, "stock {} {stock}", listing.item);
+            }
+        }
+        save
+    }
+    /// Restores the stock of the listings in a save.
+    ///
+    /// Items no longer for sale are skipped
+    pub fn load(&mut self, save: &str) -> Result<(), ShopError> {
+        let mut restored = Vec::new();
+        for (number, line) in save.lines().enumerate() {
+            let fields: Vec<_> = line.split_whitespace().collect();
+            match fields[..] {
+                [] => (),
+                ["stock", item, stock] => {
+                    let stock = stock.parse().map_err(|_| ShopError::Syntax(number + 1))?;
+                    restored.push((item, stock));
+                }
+                _ => return Err(ShopError::Syntax(number + 1)),
+            }
+        }
+        for (item, stock) in restored {
+            if let Some(index) = self.listings.iter().position(|l| l.item == item) {
+                self.listings[index].stock = Some(stock);
+                self.menu.set_label(index, self.listings[index].label());
+            }
+        }
+        Ok(())
+    }
+    /// Queues the listings onto a batch of draw commands.
+    pub fn commands<'a>(&'a self, batch: &mut Vec<DrawCmd<'a>>) {
+        self.menu.commands(batch)
+    }
+}
+impl Renderable for Shop {
+    fn commands<'a>(&'a self, batch: &mut Vec<DrawCmd<'a>>) {
+        Shop::commands(self, batch)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ShopError {
+    #[error("no listing {0} in the shop")]
+    NoListing(usize),
+    #[error("{0} is sold out")]
+    SoldOut(String),
+    #[error("costs {price} but only {wallet} to spend")]
+    Unaffordable { price: u32, wallet: u32 },
+    #[error("malformed shop save on line {0}")]
+    Syntax(usize),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{layout::Coordinate, ui::Widget};
+
+    const FONT: &str = "\
+STARTFONT 2.1
+FONT_ASCENT 6
+FONT_DESCENT 0
+ENDFONT
+";
+
+    fn shop() -> Shop {
+        let font = Arc::new(BitmapFont::from_bdf(FONT).unwrap());
+        Shop::new(font, (0, 0), 1)
+            .listing("potion", 10, None)
+            .listing("sword", 30, Some(1))
+    }
+
+    fn label(shop: &Shop, index: usize) -> &str {
+        match &shop.menu.widgets()[index] {
+            Widget::Button { label } => label,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_buying() {
+        let mut shop = shop();
+        let mut gold = 35;
+        let down = UiInput::Navigate(Coordinate { x: 0.0, y: 1.0 });
+
+        assert_eq!(
+            shop.handle(UiInput::Confirm, &mut gold),
+            Some(ShopEvent::Bought("potion".into()))
+        );
+        assert_eq!(gold, 25);
+        shop.handle(down, &mut gold);
+        assert_eq!(
+            shop.handle(UiInput::Confirm, &mut gold),
+            Some(ShopEvent::Refused(ShopError::Unaffordable {
+                price: 30,
+                wallet: 25
+            }))
+        );
+
+        gold = 100;
+        assert_eq!(shop.buy(1, &mut gold), Ok("sword".into()));
+        assert_eq!(label(&shop, 1), "sword 30G x0");
+        assert_eq!(
+            shop.buy(1, &mut gold),
+            Err(ShopError::SoldOut("sword".into()))
+        );
+        assert_eq!(gold, 70);
+        assert_eq!(
+            shop.handle(UiInput::Back, &mut gold),
+            Some(ShopEvent::Closed)
+        );
+    }
+
+    #[test]
+    fn test_stock_round_trip() {
+        let mut shop = shop();
+        let mut gold = 100;
+        shop.buy(1, &mut gold).unwrap();
+        assert_eq!(shop.save(), "stock sword 0\n");
+
+        let mut restored = self::shop();
+        restored.load("stock sword 0\nstock axe 2\n").unwrap();
+        assert_eq!(restored.listings()[1].stock, Some(0));
+        assert_eq!(label(&restored, 1), "sword 30G x0");
+        assert!(matches!(
+            restored.load("stock sword many"),
+            Err(ShopError::Syntax(1))
+        ));
+        assert_eq!(restored.listings()[1].stock, Some(0));
+    }
+}
@@ -0,0 +1,345 @@
+//! The simulation state of a single tick, for rollback and desync detection.
+//!
+//! A `TickState` is everything that decides how the game plays on from a tick:
+//! where the players are and how fast they move, how they move and which frame
+//! of their animation they're on, since frames decide where players collide,
+//! the stamina, dash and hit-stop timers, how far a cutscene has played and the
+//! state of the random number generator. What's only there to look at, like
+//! the camera, screen fades or weather particles, is left out. `GameState`
+//! takes a snapshot of it with `snapshot` and goes back to one with `restore`,
+//! which is what rollback netcode builds on to replay ticks with late input.
+//!
+//! States are written as plain text, one field per line, and `checksum`
+//! hashes that text so two games can compare a tick cheaply to find out when
+//! they stopped agreeing.
+//!
+//! # Key Structures
+//! - **`TickState`**: The simulation state of a tick.
+//! - **`Movement`**: How a player moves and which frame their animation shows.
+//! - **`CoPlayerTick`**: Where the co-op player is and how they move.
+//!
+//! Errors:
+//! - `SnapshotError`: A saved state couldn't be read.
+//!
+//! # Example Usage
+//! ```ignore
+//! let saved = state.snapshot();
+//! if saved.checksum() != checksum_from_other_game {
+//!     warn!(tick = saved.tick, "games desynced");
+//! }
+//!
+//! // Input for an earlier tick arrived late
+//! state.restore(&saved);
+//! ```
+use std::fmt::{Debug, Write};
+
+use thiserror::Error;
+
+use crate::{
+    controller::{Facing, MovementMode, Pose},
+    cutscene::Playback,
+    layout::Coordinate,
+};
+
+const MODES: [MovementMode; 3] = [
+    MovementMode::Normal,
+    MovementMode::Climbing,
+    MovementMode::Swimming,
+];
+const FACINGS: [Facing; 4] = [Facing::Left, Facing::Right, Facing::Up, Facing::Down];
+const POSES: [Pose; 6] = [
+    Pose::Idle,
+    Pose::SideIdle,
+    Pose::BackIdle,
+    Pose::SideWalk,
+    Pose::FrontWalk,
+    Pose::BackWalk,
+];
+
+/// The simulation state of a tick.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TickState {
+    /// Frames simulated since the game started.
+    pub tick: u64,
+    pub position: Coordinate,
+    pub velocity: Coordinate,
+    pub knockback: Coordinate,
+    /// The way a dash would burst.
+    pub heading: Coordinate,
+    pub sprinting: bool,
+    pub stamina: f32,
+    /// Seconds left of the dash and of its cooldown.
+    pub dash: (f32, f32),
+    /// Seconds left for a buffered dash press.
+    pub dash_buffer: f32,
+    pub movement: Movement,
+    /// Ticks left frozen by a hit.
+    pub hit_stop: u32,
+    /// How far the playing cutscene has played, if any.
+    pub cutscene: Option<Playback>,
+    /// The co-op player, if one joined.
+    pub co_player: Option<CoPlayerTick>,
+    /// The state of the weather's random number generator, if any.
+    pub rng: Option<u32>,
+}
+
+/// How a player moves and which frame their animation shows in a tick.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Movement {
+    pub mode: MovementMode,
+    pub facing: Facing,
+    pub pose: Pose,
+    /// The frame of the animation playing and seconds it's been shown for.
+    pub frame: (usize, f32),
+}
+
+/// Where the co-op player is and how they move in a tick.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CoPlayerTick {
+    pub position: Coordinate,
+    pub velocity: Coordinate,
+    pub knockback: Coordinate,
+    pub movement: Movement,
+}
+impl TickState {
+    /// Writes the state as text, one field per line.
+    pub fn save(&self) -> String {
+        let xy = |c: Coordinate| [c.x, c.y];
+        let mut save = format!("tick {}\n", self.tick);
+        field(&mut save, "position", &xy(self.position));
+        field(&mut save, "velocity", &xy(self.velocity));
+        field(&mut save, "knockback", &xy(self.knockback));
+        field(&mut save, "heading", &xy(self.heading));
+        let _ = writeln!(save, "sprinting {}", self.sprinting);
+        field(&mut save, "stamina", &[self.stamina]);
+        field(&mut save, "dash", &[self.dash.0, self.dash.1]);
+        field(&mut save, "dash_buffer", &[self.dash_buffer]);
+        movement(&mut save, "movement", &self.movement);
+        let _ = writeln!(save, "hit_stop {}", self.hit_stop);
+        if let Some(cutscene) = self.cutscene {
+            let _ = writeln!(
+                save,
+                "cutscene {} {} {} {}",
+                cutscene.step, cutscene.elapsed, cutscene.confirmed, cutscene.started
+            );
+        }
+        if let Some(co_player) = self.co_player {
+            let (position, velocity, knockback) =
+                (co_player.position, co_player.velocity, co_player.knockback);
+            let values = [
+                position.x,
+                position.y,
+                velocity.x,
+                velocity.y,
+                knockback.x,
+                knockback.y,
+            ];
+            field(&mut save, "co_player", &values);
+            movement(&mut save, "co_movement", &co_player.movement);
+        }
+        if let Some(rng) = self.rng {
+            let _ = writeln!(save, "rng {rng}");
+        }
+        save
+    }
+    /// Reads a state written by `save`.
+    pub fn load(save: &str) -> Result<Self, SnapshotError> {
+        let mut state = TickState::default();
+        let mut seen = Vec::new();
+        for (number, line) in save.lines().enumerate() {
+            let syntax = || SnapshotError::Syntax(number + 1);
+            let fields: Vec<_> = line.split_whitespace().collect();
+            let Some((&name, values)) = fields.split_first() else {
+                continue;
+            };
+            let floats = || {
+                values
+                    .iter()
+                    .map(|v| v.parse::<f32>().map_err(|_| syntax()))
+                    .collect::<Result<Vec<_>, _>>()
+            };
+            let xy = || match floats()?[..] {
+                [x, y] => Ok(Coordinate { x, y }),
+                _ => Err(syntax()),
+            };
+            let word = || match values {
+                [value] => Ok(*value),
+                _ => Err(syntax()),
+            };
+            let single = || match floats()?[..] {
+                [value] => Ok(value),
+                _ => Err(syntax()),
+            };
+            let movement = || match values {
+                [mode, facing, pose, frame, timer] => Ok(Movement {
+                    mode: variant(mode, &MODES).ok_or_else(syntax)?,
+                    facing: variant(facing, &FACINGS).ok_or_else(syntax)?,
+                    pose: variant(pose, &POSES).ok_or_else(syntax)?,
+                    frame: (
+                        frame.parse().map_err(|_| syntax())?,
+                        timer.parse().map_err(|_| syntax())?,
+                    ),
+                }),
+                _ => Err(syntax()),
+            };
+            match name {
+                "tick" => state.tick = word()?.parse().map_err(|_| syntax())?,
+                "position" => state.position = xy()?,
+                "velocity" => state.velocity = xy()?,
+                "knockback" => state.knockback = xy()?,
+                "heading" => state.heading = xy()?,
+                "sprinting" => state.sprinting = word()?.parse().map_err(|_| syntax())?,
+                "stamina" => state.stamina = single()?,
+                "dash" => {
+                    state.dash = match floats()?[..] {
+                        [active, recharge] => (active, recharge),
+                        _ => return Err(syntax()),
+                    }
+                }
+                "dash_buffer" => state.dash_buffer = single()?,
+                "movement" => state.movement = movement()?,
+                "hit_stop" => state.hit_stop = word()?.parse().map_err(|_| syntax())?,
+                "cutscene" => {
+                    let parse = |step: &str, elapsed: &str, confirmed: &str, started: &str| {
+                        Some(Playback {
+                            step: step.parse().ok()?,
+                            elapsed: elapsed.parse().ok()?,
+                            confirmed: confirmed.parse().ok()?,
+                            started: started.parse().ok()?,
+                        })
+                    };
+                    state.cutscene = match values {
+                        [step, elapsed, confirmed, started] => {
+                            Some(parse(step, elapsed, confirmed, started).ok_or_else(syntax)?)
+                        }
+                        _ => return Err(syntax()),
+                    }
+                }
+                "co_player" => {
+                    state.co_player = match floats()?[..] {
+                        [x, y, vx, vy, kx, ky] => Some(CoPlayerTick {
+                            position: Coordinate { x, y },
+                            velocity: Coordinate { x: vx, y: vy },
+                            knockback: Coordinate { x: kx, y: ky },
+                            movement: Movement::default(),
+                        }),
+                        _ => return Err(syntax()),
+                    }
+                }
+                "co_movement" => {
+                    let co_player = state.co_player.as_mut().ok_or_else(syntax)?;
+                    co_player.movement = movement()?;
+                }
+                "rng" => state.rng = Some(word()?.parse().map_err(|_| syntax())?),
+                _ => return Err(syntax()),
+            }
+            seen.push(name);
+        }
+        for required in ["tick", "position", "velocity", "stamina", "dash"] {
+            if !seen.contains(&required) {
+                return Err(SnapshotError::Missing(required));
+            }
+        }
+        Ok(state)
+    }
+    /// A hash of the state that's the same on every machine, to compare ticks
+    /// between games without sending whole states.
+    pub fn checksum(&self) -> u64 {
+        // FNV-1a, which unlike the standard hasher is guaranteed to stay the same
+        self.save()
+            .bytes()
+            .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+            })
+    }
+}
+
+/// Writes a line of `name` followed by a player's `movement`.
+fn movement(save: &mut String, name: &str, movement: &Movement) {
+    let (frame, timer) = movement.frame;
+    let _ = writeln!(
+        save,
+        "{name} {:?} {:?} {:?} {frame} {timer}",
+        movement.mode, movement.facing, movement.pose
+    );
+}
+
+/// The one of `variants` written as `word`.
+fn variant<T: Debug + Copy>(word: &str, variants: &[T]) -> Option<T> {
+    variants.iter().copied().find(|v| format!("{v:?}") == word)
+}
+
+/// Writes a line of `name` followed by `values`.
+fn field(save: &mut String, name: &str, values: &[f32]) {
+    let _ = write!(save, "{name}");
+    for value in values {
+        let _ = write!(save, " {value}");
+    }
+    save.push('\n');
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SnapshotError {
+    #[error("malformed tick state on line {0}")]
+    Syntax(usize),
+    #[error("tick state has no {0}")]
+    Missing(&'static str),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> TickState {
+        TickState {
+            tick: 42,
+            position: Coordinate { x: 1.5, y: -2.0 },
+            velocity: Coordinate { x: 0.1, y: 0.0 },
+            stamina: 70.25,
+            dash: (0.05, 0.9),
+            movement: Movement {
+                mode: MovementMode::Climbing,
+                facing: Facing::Up,
+                pose: Pose::BackWalk,
+                frame: (1, 0.125),
+            },
+            hit_stop: 2,
+            cutscene: Some(Playback {
+                step: 3,
+                elapsed: 0.5,
+                confirmed: true,
+                started: false,
+            }),
+            co_player: Some(CoPlayerTick {
+                position: Coordinate { x: 8.0, y: 0.0 },
+                knockback: Coordinate { x: -3.0, y: 1.0 },
+                ..Default::default()
+            }),
+            rng: Some(123_456),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_save_round_trip() {
+        let state = state();
+        assert_eq!(TickState::load(&state.save()), Ok(state));
+        assert_eq!(
+            TickState::load("tick 1\nposition 0 zero\n"),
+            Err(SnapshotError::Syntax(2))
+        );
+        assert_eq!(
+            TickState::load("tick 1\n"),
+            Err(SnapshotError::Missing("position"))
+        );
+    }
+
+    #[test]
+    fn test_checksum_tells_states_apart() {
+        let state = state();
+        assert_eq!(state.checksum(), state.clone().checksum());
+        let mut nudged = state.clone();
+        nudged.position.x += f32::EPSILON * 2.0;
+        assert_ne!(state.checksum(), nudged.checksum());
+    }
+}
@@ -0,0 +1,197 @@
+//! Spatial partitioning for collision and proximity queries.
+//!
+//! Testing every collider against every other grows with the square of their
+//! count. A `SpatialHash` buckets rectangles into a uniform grid of cells so a
+//! query only looks at what shares a cell with the queried area, which keeps
+//! aggro range and trigger checks cheap no matter how big the level is. Ids are
+//! whatever the caller tracks its things by, e.g. an index into a list of NPCs.
+//!
+//! # Key Structures
+//! - **`SpatialHash`**: Rectangles by id, bucketed into cells for fast queries.
+//!
+//! # Example Usage
+//! ```ignore
+//! let mut grid = SpatialHash::new(32.0);
+//! for (id, npc) in npcs.iter().enumerate() {
+//!     grid.insert(id, npc.bounds());
+//! }
+//!
+//! for id in grid.query_radius(player_pos, AGGRO_RANGE) {
+//!     npcs[id].chase(player_pos);
+//! }
+//! ```
+use std::{collections::HashMap, hash::Hash};
+
+use crate::{hitbox::Rect, layout::Coordinate};
+
+/// The range of cells a rectangle covers, inclusive on both ends.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Cells {
+    x: (i32, i32),
+    y: (i32, i32),
+}
+impl Cells {
+    fn iter(self) -> impl Iterator<Item = (i32, i32)> {
+        (self.x.0..=self.x.1).flat_map(move |x| (self.y.0..=self.y.1).map(move |y| (x, y)))
+    }
+}
+
+/// Rectangles by id, bucketed into a uniform grid of square cells.
+///
+/// Cells should be around the size of the things stored: much smaller and
+/// every rectangle lands in many cells, much bigger and queries look at too
+/// much at once
+#[derive(Debug, Clone)]
+pub struct SpatialHash<Id> {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<Id>>,
+    rects: HashMap<Id, Rect>,
+}
+impl<Id: Copy + Eq + Hash> SpatialHash<Id> {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+            rects: HashMap::new(),
+        }
+    }
+    /// Places `id` at `rect`, moving it if it was already placed.
+    pub fn insert(&mut self, id: Id, rect: Rect) {
+        self.remove(id);
+        for cell in self.cells(&rect).iter() {
+            self.cells.entry(cell).or_default().push(id);
+        }
+        self.rects.insert(id, rect);
+    }
+    /// Takes `id` out, returning where it was.
+    pub fn remove(&mut self, id: Id) -> Option<Rect> {
+        let rect = self.rects.remove(&id)?;
+        for cell in self.cells(&rect).iter() {
+            if let Some(ids) = self.cells.get_mut(&cell) {
+                ids.retain(|i| *i != id);
+                if ids.is_empty() {
+                    self.cells.remove(&cell);
+                }
+            }
+        }
+        Some(rect)
+    }
+    pub fn get(&self, id: Id) -> Option<Rect> {
+        self.rects.get(&id).copied()
+    }
+    pub fn len(&self) -> usize {
+        self.rects.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.rects.clear();
+    }
+    /// Every id whose rectangle overlaps `rect`, each once.
+    pub fn query_aabb(&self, rect: Rect) -> impl Iterator<Item = Id> + '_ {
+        let query = self.cells(&rect);
+        query.iter().flat_map(move |cell| {
+            self.cells
+                .get(&cell)
+                .into_iter()
+                .flatten()
+                .copied()
+                .filter(move |id| {
+                    let other = self.rects[id];
+                    // Something covering several of the queried cells is only
+                    // reported from the first of them
+                    let cells = self.cells(&other);
+                    cell == (cells.x.0.max(query.x.0), cells.y.0.max(query.y.0))
+                        && other.overlaps(&rect)
+                })
+        })
+    }
+    /// Every id whose rectangle comes within `radius` of `center`, each once.
+    pub fn query_radius(&self, center: Coordinate, radius: f32) -> impl Iterator<Item = Id> + '_ {
+        let bounds = Rect {
+            x: center.x - radius,
+            y: center.y - radius,
+            width: radius * 2.0,
+            height: radius * 2.0,
+        };
+        self.query_aabb(bounds).filter(move |id| {
+            let rect = self.rects[id];
+            let closest = Coordinate {
+                x: center.x.clamp(rect.x, rect.x + rect.width),
+                y: center.y.clamp(rect.y, rect.y + rect.height),
+            };
+            (closest - center).length() < radius
+        })
+    }
+    fn cells(&self, rect: &Rect) -> Cells {
+        let cell = |value: f32| (value / self.cell_size).floor() as i32;
+        Cells {
+            x: (cell(rect.x), cell(rect.x + rect.width)),
+            y: (cell(rect.y), cell(rect.y + rect.height)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: f32, y: f32, width: f32, height: f32) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    fn sorted(ids: impl Iterator<Item = u32>) -> Vec<u32> {
+        let mut ids: Vec<_> = ids.collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn test_query_aabb() {
+        let mut grid = SpatialHash::new(10.0);
+        grid.insert(1, rect(2.0, 2.0, 4.0, 4.0));
+        // Spans four cells but is reported once
+        grid.insert(2, rect(8.0, 8.0, 6.0, 6.0));
+        grid.insert(3, rect(50.0, 50.0, 2.0, 2.0));
+
+        assert_eq!(sorted(grid.query_aabb(rect(0.0, 0.0, 20.0, 20.0))), [1, 2]);
+        assert_eq!(sorted(grid.query_aabb(rect(12.0, 12.0, 1.0, 1.0))), [2]);
+        // Shares a cell with 1 without overlapping it
+        assert!(sorted(grid.query_aabb(rect(0.0, 0.0, 1.0, 1.0))).is_empty());
+        assert_eq!(sorted(grid.query_aabb(rect(-5.0, 45.0, 100.0, 10.0))), [3]);
+    }
+
+    #[test]
+    fn test_move_and_remove() {
+        let mut grid = SpatialHash::new(10.0);
+        grid.insert(1, rect(0.0, 0.0, 2.0, 2.0));
+        grid.insert(1, rect(30.0, 0.0, 2.0, 2.0));
+        assert_eq!(grid.len(), 1);
+        assert!(sorted(grid.query_aabb(rect(0.0, 0.0, 5.0, 5.0))).is_empty());
+        assert_eq!(sorted(grid.query_aabb(rect(29.0, 0.0, 5.0, 5.0))), [1]);
+
+        assert_eq!(grid.remove(1), Some(rect(30.0, 0.0, 2.0, 2.0)));
+        assert_eq!(grid.remove(1), None);
+        assert!(grid.is_empty());
+        assert!(grid.cells.is_empty());
+    }
+
+    #[test]
+    fn test_query_radius() {
+        let mut grid = SpatialHash::new(8.0);
+        grid.insert(1, rect(10.0, 0.0, 2.0, 2.0));
+        // Inside the bounding square but past the radius in the corner
+        grid.insert(2, rect(8.0, 8.0, 2.0, 2.0));
+        grid.insert(3, rect(-1.0, -1.0, 2.0, 2.0));
+
+        let found = sorted(grid.query_radius(Coordinate::default(), 11.0));
+        assert_eq!(found, [1, 3]);
+    }
+}
@@ -0,0 +1,243 @@
+//! Two players sharing one screen, each with a view of their own.
+//!
+//! A `SplitScreen` cuts the screen in two views divided by a line, either side
+//! by side or stacked. Every view has its own `Camera` following its player and
+//! is drawn from its own batch, so the same world can be drawn twice through
+//! different cameras. Views are drawn off-screen first and then copied into
+//! place, which keeps anything drawn past a view's edge out of the other one.
+//!
+//! # Key Structures
+//! - **`Split`**: How the screen is cut in two.
+//! - **`SplitScreen`**: Both views, their cameras and the line between them.
+//!
+//! # Example Usage
+//! ```ignore
+//! let mut split = SplitScreen::new(Split::SideBySide, 192, 108);
+//!
+//! // Every frame
+//! split.update(delta, [one_pos, two_pos]);
+//! let mut batches = [vec![DrawCmd::Clear], vec![DrawCmd::Clear]];
+//! for (player, batch) in [Player::One, Player::Two].into_iter().zip(&mut batches) {
+//!     let camera = split.camera(player);
+//!     one.commands(mirror, camera.to_screen(one_pos), batch);
+//!     two.commands(mirror, camera.to_screen(two_pos), batch);
+//! }
+//! split.draw(&mut *screen, [&batches[0], &batches[1]])?;
+//! screen.render()?;
+//! ```
+use crate::{
+    camera::Camera,
+    input::Player,
+    layout::Coordinate,
+    palette::{Color, MIDNIGHT},
+    renderer::DrawCmd,
+    window::{HeadlessScreen, Screen, WindowError},
+};
+
+/// How the screen is cut in two.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Split {
+    /// Player one on the left, player two on the right.
+    SideBySide,
+    /// Player one on top, player two below.
+    Stacked,
+}
+
+/// A part of the screen drawn through its own camera.
+struct View {
+    screen: HeadlessScreen,
+    camera: Camera,
+    /// Where the view's top-left corner is on the screen
+    origin: (u32, u32),
+}
+impl View {
+    fn new(width: u32, height: u32, origin: (u32, u32)) -> Self {
+        let mut camera = Camera::new(width, height);
+        camera.lock_follow();
+        Self {
+            screen: HeadlessScreen::new(width, height),
+            camera,
+            origin,
+        }
+    }
+    /// Copies what was drawn into the view onto its part of `target`.
+    fn blit(&mut self, target: &mut [u8], target_width: u32, target_height: u32) {
+        let (x, y) = self.origin;
+        let width = self.screen.width().min(target_width.saturating_sub(x)) as usize;
+        let height = self.screen.height().min(target_height.saturating_sub(y));
+        let stride = self.screen.width() as usize * 4;
+        for row in 0..height {
+            let src = row as usize * stride;
+            let dst = ((y + row) * target_width + x) as usize * 4;
+            target[dst..dst + width * 4]
+                .copy_from_slice(&self.screen.buffer()[src..src + width * 4]);
+        }
+    }
+}
+
+/// A screen cut in two views, one per player.
+pub struct SplitScreen {
+    split: Split,
+    views: [View; 2],
+    divider: Color,
+}
+impl SplitScreen {
+    /// Cuts a screen of `width` by `height` pixels in two views with a line
+    /// of a single pixel between them.
+    ///
+    /// Both cameras follow the targets given to `update`
+    pub fn new(split: Split, width: u32, height: u32) -> Self {
+        let views = match split {
+            Split::SideBySide => {
+                let first = width.saturating_sub(1) / 2;
+                let second = width.saturating_sub(first + 1);
+                [
+                    View::new(first, height, (0, 0)),
+                    View::new(second, height, (first + 1, 0)),
+                ]
+            }
+            Split::Stacked => {
+                let first = height.saturating_sub(1) / 2;
+                let second = height.saturating_sub(first + 1);
+                [
+                    View::new(width, first, (0, 0)),
+                    View::new(width, second, (0, first + 1)),
+                ]
+            }
+        };
+        Self {
+            split,
+            views,
+            divider: MIDNIGHT,
+        }
+    }
+    /// The color of the line between the views.
+    pub fn divider(mut self, color: Color) -> Self {
+        self.divider = color;
+        self
+    }
+    pub fn split(&self) -> Split {
+        self.split
+    }
+    /// The camera of `player`'s view, e.g. to pan it during a cutscene.
+    pub fn camera(&mut self, player: Player) -> &mut Camera {
+        &mut self.views[player as usize].camera
+    }
+    /// The size of `player`'s view in pixels.
+    pub fn view_size(&self, player: Player) -> (u32, u32) {
+        let screen = &self.views[player as usize].screen;
+        (screen.width(), screen.height())
+    }
+    /// Moves both cameras along for `delta` seconds, following each player's
+    /// target.
+    pub fn update(&mut self, delta: f32, targets: [Coordinate; 2]) {
+        for (view, target) in self.views.iter_mut().zip(targets) {
+            view.camera.update(delta, target);
+        }
+    }
+    /// Draws each player's batch into their view and both views onto
+    /// `screen`, leaving rendering to the caller.
+    pub fn draw<S: Screen + ?Sized>(
+        &mut self,
+        screen: &mut S,
+        batches: [&[DrawCmd]; 2],
+    ) -> Result<(), WindowError> {
+        let (width, height) = (screen.width(), screen.height());
+        screen.clear()?;
+        for (view, batch) in self.views.iter_mut().zip(batches) {
+            view.screen.draw_commands(batch)?;
+            view.blit(screen.frame_buffer(), width, height);
+        }
+        self.draw_divider(screen)
+    }
+    fn draw_divider<S: Screen + ?Sized>(&self, screen: &mut S) -> Result<(), WindowError> {
+        let (width, height) = (screen.width(), screen.height());
+        let (x, y) = self.views[1].origin;
+        let divider = match self.split {
+            Split::SideBySide => (0..height)
+                .map(|row| DrawCmd::Span {
+                    start: (x as i32 - 1, row as i32),
+                    len: 1,
+                    color: self.divider,
+                })
+                .collect::<Vec<_>>(),
+            Split::Stacked => vec![DrawCmd::Span {
+                start: (0, y as i32 - 1),
+                len: width,
+                color: self.divider,
+            }],
+        };
+        for cmd in &divider {
+            cmd.draw(screen)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::palette::{GREEN, RED};
+
+    fn marker<'a>(camera: &Camera, world: Coordinate, color: Color) -> DrawCmd<'a> {
+        let Coordinate { x, y } = camera.to_screen(world);
+        DrawCmd::Span {
+            start: (x as i32, y as i32),
+            len: 1,
+            color,
+        }
+    }
+
+    fn rgba(color: Color) -> Option<Color> {
+        let [r, g, b, a] = color.to_rgba();
+        Some(Color::RGBA(r, g, b, a))
+    }
+
+    #[test]
+    fn test_views_follow_their_players() {
+        let mut screen = HeadlessScreen::new(21, 10);
+        let mut split = SplitScreen::new(Split::SideBySide, 21, 10).divider(GREEN);
+        assert_eq!(split.view_size(Player::One), (10, 10));
+        assert_eq!(split.view_size(Player::Two), (10, 10));
+
+        let (one, two) = (Coordinate { x: 100.0, y: 100.0 }, Coordinate::default());
+        split.update(0.1, [one, two]);
+        let batches = [Player::One, Player::Two].map(|player| {
+            let camera = split.camera(player);
+            vec![
+                DrawCmd::Clear,
+                marker(camera, one, RED),
+                marker(camera, two, RED),
+            ]
+        });
+        split.draw(&mut screen, [&batches[0], &batches[1]]).unwrap();
+
+        // Each player is in the middle of their own view
+        assert_eq!(screen.pixel(5, 5), rgba(RED));
+        assert_eq!(screen.pixel(16, 5), rgba(RED));
+        assert!((0..10).all(|y| screen.pixel(10, y) == rgba(GREEN)));
+        let reds = (0..21)
+            .flat_map(|x| (0..10).map(move |y| (x, y)))
+            .filter(|&(x, y)| screen.pixel(x, y) == rgba(RED))
+            .count();
+        assert_eq!(reds, 2);
+    }
+
+    #[test]
+    fn test_stacked_views_stay_apart() {
+        let mut screen = HeadlessScreen::new(8, 9);
+        let mut split = SplitScreen::new(Split::Stacked, 8, 9).divider(GREEN);
+        assert_eq!(split.view_size(Player::Two), (8, 4));
+
+        // Running past the bottom of the top view doesn't reach the other one
+        let spill = [DrawCmd::Span {
+            start: (0, 3),
+            len: 8,
+            color: RED,
+        }];
+        split.draw(&mut screen, [&spill, &[]]).unwrap();
+        assert_eq!(screen.pixel(0, 3), rgba(RED));
+        assert_eq!(screen.pixel(0, 4), rgba(GREEN));
+        assert_ne!(screen.pixel(0, 5), rgba(RED));
+    }
+}
@@ -10,4 +10,24 @@ pub trait Character<S: Screen>: Send + 'static {
     fn front_walk(&mut self) -> &mut dyn Animation<S>;
     /// The character's back walk animation.
     fn back_walk(&mut self) -> &mut dyn Animation<S>;
+    /// The character's idle animation while facing sideways, drawn mirrored
+    /// when facing left.
+    ///
+    /// Defaults to the idle animation
+    fn side_idle(&mut self) -> &mut dyn Animation<S> {
+        self.idle()
+    }
+    /// The character's idle animation while facing away.
+    ///
+    /// Defaults to the idle animation
+    fn back_idle(&mut self) -> &mut dyn Animation<S> {
+        self.idle()
+    }
+    /// Any other animation the character has by name, e.g. `"attack"`,
+    /// `"hurt"` or `"emote"`.
+    ///
+    /// Defaults to the character having none
+    fn action(&mut self, _name: &str) -> Option<&mut dyn Animation<S>> {
+        None
+    }
 }
\ No newline at end of file
@@ -26,5 +26,6 @@
 ///
 /// ## Example Implementations
 /// See `Knight` or other concrete structs that embed `Sprite`-based animations.
+#[allow(clippy::module_inception)]
 pub(crate) mod character;
 pub(crate) mod knight;
@@ -0,0 +1,182 @@
+//! Frames read from images, trimmed down to what's visible in them.
+//!
+//! Sprites drawn in an image editor usually come with empty space around
+//! them, every cell of which would otherwise become a `Pixel` drawn without
+//! showing anything. Loading keeps only the cells that aren't fully
+//! transparent, cuts the frame down to the box around them and records where
+//! that box sat in the image as a `Trim`, so the frame is still drawn where the
+//! whole image would have been, mirrored or not.
+//!
+//! PNG images are read with the `png` feature, other sources are converted from
+//! raw RGBA with `frame_from_rgba`.
+//!
+//! Errors:
+//! - `ImageError`: Reading or decoding an image failed.
+//!
+//! # Example Usage
+//! ```ignore
+//! let step = load_frame("assets/knight/walk_0.png")?
+//!     .duration_ms(120)
+//!     .build();
+//! ```
+#[cfg(feature = "png")]
+use std::{fs::File, path::Path};
+
+use thiserror::Error;
+
+use crate::{
+    palette::{Color, ColorScheme},
+    renderer::{FrameBuilder, Pixel, Trim},
+};
+
+/// Reads the PNG image at `path` into a trimmed frame.
+#[cfg(feature = "png")]
+pub fn load_frame(path: impl AsRef<Path>) -> Result<FrameBuilder, ImageError> {
+    let mut decoder = png::Decoder::new(File::open(path)?);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
+    let mut data = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut data)?;
+    let data = &data[..info.buffer_size()];
+    let rgba: Vec<u8> = match info.color_type {
+        png::ColorType::Rgba => data.to_vec(),
+        png::ColorType::Rgb => data
+            .chunks_exact(3)
+            .flat_map(|c| [c[0], c[1], c[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => data
+            .chunks_exact(2)
+            .flat_map(|c| [c[0], c[0], c[0], c[1]])
+            .collect(),
+        png::ColorType::Grayscale => data.iter().flat_map(|&v| [v, v, v, 255]).collect(),
+        color => return Err(ImageError::Unsupported(format!("{color:?}"))),
+    };
+    frame_from_rgba(info.width, info.height, &rgba)
+}
+
+/// Turns a `width` by `height` image of RGBA bytes into a frame of its
+/// visible cells, trimmed to the box around them.
+pub fn frame_from_rgba(width: u32, height: u32, rgba: &[u8]) -> Result<FrameBuilder, ImageError> {
+    let too_big = || ImageError::TooBig(width, height);
+    let (image_width, image_height) = (
+        u16::try_from(width).map_err(|_| too_big())?,
+        u16::try_from(height).map_err(|_| too_big())?,
+    );
+    if rgba.len() != width as usize * height as usize * 4 {
+        return Err(ImageError::Size(rgba.len(), width, height));
+    }
+    let visible: Vec<_> = rgba
+        .chunks_exact(4)
+        .enumerate()
+        .filter(|(_, rgba)| rgba[3] > 0)
+        .map(|(i, rgba)| {
+            let (x, y) = (i as u32 % width, i as u32 / width);
+            (
+                x as u16,
+                y as u16,
+                Color::RGBA(rgba[0], rgba[1], rgba[2], rgba[3]),
+            )
+        })
+        .collect();
+    let left = visible.iter().map(|&(x, _, _)| x).min().unwrap_or(0);
+    let top = visible.iter().map(|&(_, y, _)| y).min().unwrap_or(0);
+    let pixels = visible
+        .into_iter()
+        .map(|(x, y, color)| Pixel::new(ColorScheme::Standard(color), x - left, y - top));
+    Ok(FrameBuilder::new().pixels(pixels).trim(Trim {
+        x: left,
+        y: top,
+        width: image_width,
+        height: image_height,
+    }))
+}
+
+#[derive(Debug, Error)]
+pub enum ImageError {
+    #[error("failed to read image: {0}")]
+    ReadError(#[from] std::io::Error),
+    #[cfg(feature = "png")]
+    #[error("failed to decode image: {0}")]
+    Decode(#[from] png::DecodingError),
+    #[error("unsupported image color type {0}")]
+    Unsupported(String),
+    #[error("image of {0}x{1} is too big for a frame")]
+    TooBig(u32, u32),
+    #[error("{0} bytes aren't a {1}x{2} RGBA image")]
+    Size(usize, u32, u32),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        layout::{Coordinate, MirrorDirection},
+        palette::{GREEN, RED},
+        renderer::DrawCmd,
+        window::{HeadlessScreen, Screen},
+    };
+
+    fn rgba(color: Color) -> Option<Color> {
+        let [r, g, b, a] = color.to_rgba();
+        Some(Color::RGBA(r, g, b, a))
+    }
+
+    /// A 5x4 image with two visible cells in it.
+    fn image() -> Vec<u8> {
+        let mut image = vec![0; 5 * 4 * 4];
+        image[(5 + 1) * 4..(5 + 1) * 4 + 4].copy_from_slice(&RED.to_rgba());
+        image[(2 * 5 + 2) * 4..(2 * 5 + 2) * 4 + 4].copy_from_slice(&GREEN.to_rgba());
+        image
+    }
+
+    #[test]
+    fn test_trimmed_frame_draws_in_place() {
+        let frame = frame_from_rgba(5, 4, &image()).unwrap().build();
+        assert_eq!(frame.pixels.len(), 2);
+        assert_eq!((frame.width, frame.height), (1, 1));
+        assert_eq!(
+            frame.trim(),
+            Some(Trim {
+                x: 1,
+                y: 1,
+                width: 5,
+                height: 4
+            })
+        );
+
+        let offset = Coordinate { x: 10.0, y: 10.0 };
+        // Facing left mirrors across the whole image
+        for (mirror, red) in [
+            (MirrorDirection::None, 11),
+            (MirrorDirection::FlipVertical, 13),
+        ] {
+            let mirror = frame.mirror_value(mirror);
+            let offset = offset + frame.anchor(&mirror);
+            let mut screen = HeadlessScreen::new(20, 20);
+            screen
+                .draw_batch(&[DrawCmd::Frame(&frame, mirror, offset)])
+                .unwrap();
+            assert_eq!(screen.pixel(red, 11), rgba(RED));
+            assert_eq!(screen.pixel(12, 12), rgba(GREEN));
+        }
+
+        assert!(matches!(
+            frame_from_rgba(5, 5, &image()),
+            Err(ImageError::Size(80, 5, 5))
+        ));
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn test_load_png_frame() {
+        let mut screen = HeadlessScreen::new(5, 4);
+        screen.frame_buffer().copy_from_slice(&image());
+        let path = std::env::temp_dir().join("thegame_trimmed_frame.png");
+        screen.save_png(&path).unwrap();
+
+        let frame = load_frame(&path).unwrap().build();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(frame.pixels.len(), 2);
+        assert_eq!(frame.trim().map(|t| (t.x, t.y)), Some((1, 1)));
+    }
+}
@@ -0,0 +1,237 @@
+//! Characters drawn as a stack of separately authored layers.
+//!
+//! A `LayeredSprite` puts a body `Layer` and any number of equipment layers,
+//! e.g. a helmet and a weapon, on top of each other. Every layer has its own
+//! palette and either a single frame shown throughout or frames of its own
+//! following the body's animation, so equipment can be swapped at runtime
+//! without authoring every combination by hand. The layers are merged into
+//! plain `Frame`s whenever the equipment changes, so drawing a layered sprite
+//! costs the same as drawing any other.
+//!
+//! # Key Structures
+//! - **`Layer`**: The frames of one part of a character in its own palette.
+//! - **`LayeredSprite`**: A body with equipment layers drawn over it in order.
+//!
+//! # Example Usage
+//! ```ignore
+//! let mut knight = LayeredSprite::new(Layer::new(body_frames))
+//!     .layer("helmet", Layer::new(vec![helmet]).palette(&[(LIGHT_GRAY, RED)]))
+//!     .layer("weapon", Layer::new(sword_frames));
+//!
+//! // Picking up an axe
+//! knight.equip("weapon", Layer::new(axe_frames));
+//! ```
+use crate::{palette::Color, renderer::Frame, sprite::sprite::Sprite};
+
+/// The frames of one part of a character in its own palette.
+#[derive(Clone, Debug)]
+pub struct Layer {
+    frames: Vec<Frame>,
+}
+impl Layer {
+    /// A single frame is shown with every frame of the body, while more are
+    /// cycled through along with the body's frames.
+    pub fn new(frames: Vec<Frame>) -> Self {
+        Self { frames }
+    }
+    /// Recolors the layer, replacing every `from` color with its `to`.
+    pub fn palette(mut self, swaps: &[(Color, Color)]) -> Self {
+        for frame in &mut self.frames {
+            for pixel in &mut frame.pixels {
+                for (color, _) in &mut pixel.pixels {
+                    if let Some((_, to)) = swaps.iter().find(|(from, _)| from == color) {
+                        *color = *to;
+                    }
+                }
+            }
+            frame.invalidate_cache();
+        }
+        self
+    }
+    /// The frame shown over the body's frame at `frame_pos`.
+    fn frame(&self, frame_pos: usize) -> Option<&Frame> {
+        if self.frames.is_empty() {
+            return None;
+        }
+        self.frames.get(frame_pos % self.frames.len())
+    }
+}
+
+/// A body with equipment layers drawn over it in the order they were added.
+///
+/// Frame durations and collision boxes are those of the body
+pub struct LayeredSprite {
+    body: Layer,
+    /// Equipment by slot, from bottom to top
+    layers: Vec<(String, Layer)>,
+    /// The body and its layers merged
+    frames: Vec<Frame>,
+    timer: f32,
+    frame_pos: usize,
+    paused: bool,
+    frame_rate: Option<u32>,
+}
+impl LayeredSprite {
+    pub fn new(body: Layer) -> Self {
+        let mut sprite = Self {
+            body,
+            layers: Vec::new(),
+            frames: Vec::new(),
+            timer: 0.0,
+            frame_pos: 0,
+            paused: false,
+            frame_rate: None,
+        };
+        sprite.compose();
+        sprite
+    }
+    /// Adds a layer on top of the others in `slot`.
+    pub fn layer(mut self, slot: &str, layer: Layer) -> Self {
+        self.equip(slot, layer);
+        self
+    }
+    /// Plays the frames without a duration of their own at `fps`.
+    pub fn frame_rate(mut self, fps: u32) -> Self {
+        self.frame_rate = Some(fps);
+        self
+    }
+    /// Puts `layer` in `slot`, replacing whatever was equipped there and keeping
+    /// its place in the stack, or on top if the slot was empty.
+    pub fn equip(&mut self, slot: &str, layer: Layer) {
+        match self.layers.iter_mut().find(|(s, _)| s == slot) {
+            Some((_, equipped)) => *equipped = layer,
+            None => self.layers.push((slot.to_string(), layer)),
+        }
+        self.compose();
+    }
+    /// Takes off whatever is equipped in `slot`.
+    pub fn unequip(&mut self, slot: &str) -> Option<Layer> {
+        let index = self.layers.iter().position(|(s, _)| s == slot)?;
+        let (_, layer) = self.layers.remove(index);
+        self.compose();
+        Some(layer)
+    }
+    /// Merges every frame of the body with the layers over it.
+    fn compose(&mut self) {
+        self.frames = self
+            .body
+            .frames
+            .iter()
+            .enumerate()
+            .map(|(i, body)| {
+                let mut pixels = body.pixels.clone();
+                for (_, layer) in &self.layers {
+                    if let Some(frame) = layer.frame(i) {
+                        pixels.extend(frame.pixels.iter().cloned());
+                    }
+                }
+                let mut frame = Frame::new(pixels, body.duration);
+                frame.boxes = body.boxes.clone();
+                frame
+            })
+            .collect();
+        self.frame_pos = self.frame_pos.min(self.frames.len().saturating_sub(1));
+    }
+}
+impl Sprite for LayeredSprite {
+    fn frames(&self) -> &Vec<Frame> {
+        &self.frames
+    }
+    fn frame_pos(&self) -> usize {
+        self.frame_pos
+    }
+    fn timer(&self) -> f32 {
+        self.timer
+    }
+    fn frame_pos_mut(&mut self) -> &mut usize {
+        &mut self.frame_pos
+    }
+    fn timer_mut(&mut self) -> &mut f32 {
+        &mut self.timer
+    }
+    fn paused(&self) -> bool {
+        self.paused
+    }
+    fn paused_mut(&mut self) -> &mut bool {
+        &mut self.paused
+    }
+    fn frame_rate(&self) -> Option<u32> {
+        self.frame_rate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        palette::{BLACK, GREEN, RED},
+        prelude::*,
+        renderer::{DrawCmd, Pixel},
+    };
+
+    fn frame(color: Color, x: u16, y: u16) -> Frame {
+        Frame::new(vec![Pixel::new(ColorScheme::Standard(color), x, y)], None)
+    }
+
+    fn draw(sprite: &LayeredSprite) -> HeadlessScreen {
+        let mut screen = HeadlessScreen::new(4, 2);
+        let mut batch = vec![DrawCmd::Clear];
+        Animation::<HeadlessScreen>::commands(
+            sprite,
+            MirrorDirection::None,
+            Coordinate::default(),
+            &mut batch,
+        );
+        screen.draw_batch(&batch).unwrap();
+        screen
+    }
+
+    fn rgba(color: Color) -> Option<Color> {
+        let [r, g, b, a] = color.to_rgba();
+        Some(Color::RGBA(r, g, b, a))
+    }
+
+    #[test]
+    fn test_layers_follow_body_frames() {
+        let body = Layer::new(vec![frame(GREEN, 0, 1), frame(GREEN, 1, 1)]);
+        let mut sprite = LayeredSprite::new(body)
+            .layer("helmet", Layer::new(vec![frame(RED, 0, 0)]))
+            .layer(
+                "weapon",
+                Layer::new(vec![frame(RED, 2, 0), frame(RED, 3, 0)]),
+            );
+
+        let screen = draw(&sprite);
+        assert_eq!(screen.pixel(0, 1), rgba(GREEN));
+        assert_eq!(screen.pixel(0, 0), rgba(RED));
+        assert_eq!(screen.pixel(2, 0), rgba(RED));
+
+        Animation::<HeadlessScreen>::advance(&mut sprite, 0.5);
+        let screen = draw(&sprite);
+        assert_eq!(screen.pixel(1, 1), rgba(GREEN));
+        // The single frame helmet stays while the weapon moves along
+        assert_eq!(screen.pixel(0, 0), rgba(RED));
+        assert_eq!(screen.pixel(3, 0), rgba(RED));
+        assert_eq!(screen.pixel(2, 0), rgba(BLACK));
+    }
+
+    #[test]
+    fn test_equipment_swaps_and_palettes() {
+        let mut sprite = LayeredSprite::new(Layer::new(vec![frame(GREEN, 0, 0)]))
+            .layer("helmet", Layer::new(vec![frame(GREEN, 1, 0)]))
+            .layer("weapon", Layer::new(vec![frame(GREEN, 1, 0)]));
+
+        // Replacing keeps the slot's place below the weapon
+        sprite.equip(
+            "helmet",
+            Layer::new(vec![frame(GREEN, 1, 0)]).palette(&[(GREEN, RED)]),
+        );
+        assert_eq!(draw(&sprite).pixel(1, 0), rgba(GREEN));
+
+        assert!(sprite.unequip("weapon").is_some());
+        assert!(sprite.unequip("weapon").is_none());
+        let screen = draw(&sprite);
+        assert_eq!(screen.pixel(0, 0), rgba(GREEN));
+        assert_eq!(screen.pixel(1, 0), rgba(RED));
+    }
+}
@@ -18,4 +18,15 @@
 //! that return a concrete type implementing this trait, allowing those behaviors
 //! to be animated or drawn using the [`Animation::play`] method.
 pub(crate) mod character;
+pub(crate) mod image;
+pub(crate) mod layers;
+#[allow(clippy::module_inception)]
 pub(crate) mod sprite;
+pub(crate) mod validation;
+
+#[cfg(feature = "png")]
+pub use image::load_frame;
+pub use image::{frame_from_rgba, ImageError};
+pub use layers::{Layer, LayeredSprite};
+pub use sprite::{AnimationState, SpriteData, SpriteInstance};
+pub use validation::{validate, FrameIssue, SIZE_TOLERANCE};
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use crate::renderer::Frame;
 
 /// A `Sprite` consists of two or more `Frame`s, where each `Frame` represents a
@@ -8,4 +10,110 @@ pub trait Sprite {
     fn timer(&self) -> f32;
     fn frame_pos_mut(&mut self) -> &mut usize;
     fn timer_mut(&mut self) -> &mut f32;
+    fn paused(&self) -> bool;
+    fn paused_mut(&mut self) -> &mut bool;
+    /// Frames per second used for any `Frame` without an explicit duration.
+    ///
+    /// Defaults to `None` which spreads one second evenly across all frames.
+    fn frame_rate(&self) -> Option<u32> {
+        None
+    }
+    /// Freezes the animation on its current frame until resumed.
+    fn pause(&mut self) {
+        *self.paused_mut() = true;
+    }
+    fn resume(&mut self) {
+        *self.paused_mut() = false;
+    }
+    /// Starts over from the first frame.
+    fn reset(&mut self) {
+        self.set_frame(0);
+    }
+    /// Jumps to the `n`th frame, or the last one past the end, with its whole
+    /// view time ahead of it.
+    fn set_frame(&mut self, n: usize) {
+        *self.frame_pos_mut() = n.min(self.frames().len().saturating_sub(1));
+        *self.timer_mut() = 0.0;
+    }
+}
+
+/// Frames and timing of a sprite shared by every entity playing it.
+#[derive(Debug)]
+pub struct SpriteData {
+    frames: Vec<Frame>,
+    frame_rate: Option<u32>,
+}
+impl SpriteData {
+    pub fn new(frames: Vec<Frame>) -> Self {
+        Self {
+            frames,
+            frame_rate: None,
+        }
+    }
+    /// Plays the frames without a duration of their own at `fps`.
+    pub fn frame_rate(mut self, fps: u32) -> Self {
+        self.frame_rate = Some(fps);
+        self
+    }
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+}
+
+/// How far a single entity is in playing a sprite.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AnimationState {
+    pub timer: f32,
+    pub frame_pos: usize,
+    pub paused: bool,
+}
+
+/// An entity's own playback of shared `SpriteData`.
+///
+/// Clones share the frames and only copy the `AnimationState`, so any number of
+/// entities can play the same sprite without duplicating it
+#[derive(Debug, Clone)]
+pub struct SpriteInstance {
+    data: Arc<SpriteData>,
+    state: AnimationState,
+}
+impl SpriteInstance {
+    pub fn new(data: Arc<SpriteData>) -> Self {
+        Self {
+            data,
+            state: AnimationState::default(),
+        }
+    }
+    pub fn data(&self) -> &Arc<SpriteData> {
+        &self.data
+    }
+    pub fn state(&self) -> &AnimationState {
+        &self.state
+    }
+}
+impl Sprite for SpriteInstance {
+    fn frames(&self) -> &Vec<Frame> {
+        &self.data.frames
+    }
+    fn frame_pos(&self) -> usize {
+        self.state.frame_pos
+    }
+    fn timer(&self) -> f32 {
+        self.state.timer
+    }
+    fn frame_pos_mut(&mut self) -> &mut usize {
+        &mut self.state.frame_pos
+    }
+    fn timer_mut(&mut self) -> &mut f32 {
+        &mut self.state.timer
+    }
+    fn paused(&self) -> bool {
+        self.state.paused
+    }
+    fn paused_mut(&mut self) -> &mut bool {
+        &mut self.state.paused
+    }
+    fn frame_rate(&self) -> Option<u32> {
+        self.data.frame_rate
+    }
 }
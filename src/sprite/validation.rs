@@ -0,0 +1,152 @@
+//! Checks the frames of a sprite for authoring mistakes.
+//!
+//! Hand-authored frames break in ways that only show while the game runs: a
+//! frame much bigger or smaller than the rest making the sprite jump, a pixel moved with
+//! `Pixel::move_pos` past the frame without it being resized, or frames that
+//! are empty or copies of each other. `validate` reports all of them at once so
+//! they can be fixed before they're noticed in game. `GameState` runs it on the
+//! player's animations in debug builds.
+//!
+//! # Key Structures
+//! - **`FrameIssue`**: A problem found with a single frame.
+//!
+//! # Example Usage
+//! ```ignore
+//! for issue in validate(knight.idle().frames()) {
+//!     eprintln!("idle: {issue}");
+//! }
+//! ```
+use thiserror::Error;
+
+use crate::renderer::Frame;
+
+/// How many pixels a frame may be wider or taller than the first one, e.g. for
+/// limbs stretched out mid-stride, before it's considered mismatched.
+pub const SIZE_TOLERANCE: u16 = 2;
+
+/// A problem found with a frame of a sprite, by index into its frames.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum FrameIssue {
+    #[error(
+        "frame {frame} is {width}x{height} while frame 0 is {expected_width}x{expected_height}"
+    )]
+    MismatchedSize {
+        frame: usize,
+        width: u16,
+        height: u16,
+        expected_width: u16,
+        expected_height: u16,
+    },
+    #[error("frame {frame} has a pixel at ({x}, {y}) outside of it")]
+    OutOfBounds { frame: usize, x: u16, y: u16 },
+    #[error("frame {frame} draws nothing")]
+    Empty { frame: usize },
+    #[error("frame {frame} draws the same as frame {of}")]
+    Duplicate { frame: usize, of: usize },
+}
+
+/// Checks every frame against the first and each other.
+///
+/// Returns every issue found in the order of the frames
+pub fn validate(frames: &[Frame]) -> Vec<FrameIssue> {
+    let mut issues = Vec::new();
+    let Some(first) = frames.first() else {
+        return issues;
+    };
+    for (i, frame) in frames.iter().enumerate() {
+        if frame.width.abs_diff(first.width) > SIZE_TOLERANCE
+            || frame.height.abs_diff(first.height) > SIZE_TOLERANCE
+        {
+            issues.push(FrameIssue::MismatchedSize {
+                frame: i,
+                width: frame.width,
+                height: frame.height,
+                expected_width: first.width,
+                expected_height: first.height,
+            });
+        }
+        // The stored size only follows moved pixels once the frame is resized
+        let outside = frame
+            .pixels
+            .iter()
+            .flat_map(|p| &p.pixels)
+            .map(|(_, c)| (c.x as u16, c.y as u16))
+            .find(|&(x, y)| x > frame.width || y > frame.height);
+        if let Some((x, y)) = outside {
+            issues.push(FrameIssue::OutOfBounds { frame: i, x, y });
+        }
+        if frame.raster.is_empty() {
+            issues.push(FrameIssue::Empty { frame: i });
+        } else if let Some(of) = frames[..i]
+            .iter()
+            .position(|other| other.raster == frame.raster)
+        {
+            issues.push(FrameIssue::Duplicate { frame: i, of });
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        layout::Direction,
+        palette::{ColorScheme, RED},
+        prelude::*,
+        renderer::Pixel,
+    };
+
+    fn frame(cells: &[(u16, u16)]) -> Frame {
+        let pixels = cells
+            .iter()
+            .map(|&(x, y)| Pixel::new(ColorScheme::Standard(RED), x, y))
+            .collect();
+        Frame::new(pixels, None)
+    }
+
+    #[test]
+    fn test_validate_reports_issues() {
+        let mut moved = frame(&[(0, 0), (2, 2)]);
+        moved.pixels[0].move_pos(0, Direction::Horizontal(5));
+        let frames = [
+            frame(&[(0, 0), (2, 2)]),
+            frame(&[(0, 0), (6, 2)]),
+            frame(&[]),
+            frame(&[(2, 2), (0, 0)]),
+            moved,
+        ];
+
+        assert_eq!(
+            validate(&frames),
+            [
+                FrameIssue::MismatchedSize {
+                    frame: 1,
+                    width: 6,
+                    height: 2,
+                    expected_width: 2,
+                    expected_height: 2,
+                },
+                FrameIssue::Empty { frame: 2 },
+                FrameIssue::Duplicate { frame: 3, of: 0 },
+                FrameIssue::OutOfBounds {
+                    frame: 4,
+                    x: 5,
+                    y: 0
+                },
+                // Still drawn like before until the cache is invalidated
+                FrameIssue::Duplicate { frame: 4, of: 0 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_knight_frames_are_valid() {
+        let mut knight = Knight::new();
+        let character: &mut dyn Character<GameWindowScreen> = &mut knight;
+        assert_eq!(validate(character.idle().frames()), []);
+        assert_eq!(validate(character.side_walk().frames()), []);
+        assert_eq!(validate(character.front_walk().frames()), []);
+        assert_eq!(validate(character.back_walk().frames()), []);
+    }
+}
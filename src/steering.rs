@@ -0,0 +1,297 @@
+//! Steering behaviors for moving NPCs and enemies.
+//!
+//! Each behavior turns an agent's position (and the positions of agents close
+//! to it) into a desired velocity no longer than `1.0`. Behaviors are combined by
+//! weight in a `Steering` and snapped with `to_input` into the same directional
+//! `Coordinate`s player input produces, so non-player characters reuse the
+//! existing animation selection.
+//!
+//! # Key Structures
+//! - **`Behavior`**: Produces a desired velocity for an agent.
+//! - **`Seek`**, **`Flee`**, **`Arrive`**, **`Wander`** and **`Separation`**: The provided behaviors.
+//! - **`Steering`**: A weighted blend of behaviors.
+//!
+//! # Example Usage
+//! ```ignore
+//! let mut steering = Steering::new()
+//!     .with(Arrive::new(player_pos, 24.0), 1.0)
+//!     .with(Separation::new(12.0), 1.5);
+//!
+//! let velocity = steering.steer(enemy_pos, &other_enemies);
+//! if let Some(direction) = to_input(velocity) {
+//!     enemy_pos += direction * speed * velocity.length() * delta;
+//! }
+//! ```
+use std::f32::consts::{FRAC_PI_4, TAU};
+
+use crate::layout::Coordinate;
+
+/// A source of desired velocity for an agent.
+pub trait Behavior {
+    /// The velocity the agent at `position` should move with, at most `1.0` long.
+    ///
+    /// `neighbours` are the positions of other nearby agents
+    fn steer(&mut self, position: Coordinate, neighbours: &[Coordinate]) -> Coordinate;
+}
+
+/// Heads straight for a target at full speed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Seek {
+    pub target: Coordinate,
+}
+impl Seek {
+    pub fn new(target: Coordinate) -> Self {
+        Self { target }
+    }
+}
+impl Behavior for Seek {
+    fn steer(&mut self, position: Coordinate, _: &[Coordinate]) -> Coordinate {
+        normalized(self.target - position)
+    }
+}
+
+/// Runs directly away from a threat once it comes within `radius`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Flee {
+    pub threat: Coordinate,
+    pub radius: f32,
+}
+impl Flee {
+    pub fn new(threat: Coordinate, radius: f32) -> Self {
+        Self { threat, radius }
+    }
+}
+impl Behavior for Flee {
+    fn steer(&mut self, position: Coordinate, _: &[Coordinate]) -> Coordinate {
+        let away = position - self.threat;
+        if away.length() >= self.radius {
+            return Coordinate::default();
+        }
+        normalized(away)
+    }
+}
+
+/// Heads for a target, slowing down within `slowing_radius` to stop on it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Arrive {
+    pub target: Coordinate,
+    pub slowing_radius: f32,
+}
+impl Arrive {
+    pub fn new(target: Coordinate, slowing_radius: f32) -> Self {
+        Self {
+            target,
+            slowing_radius,
+        }
+    }
+}
+impl Behavior for Arrive {
+    fn steer(&mut self, position: Coordinate, _: &[Coordinate]) -> Coordinate {
+        let offset = self.target - position;
+        let distance = offset.length();
+        if distance <= self.slowing_radius {
+            return offset * (1.0 / self.slowing_radius.max(f32::EPSILON));
+        }
+        normalized(offset)
+    }
+}
+
+/// Ambles in a slowly changing random direction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Wander {
+    /// Current heading in radians.
+    angle: f32,
+    /// Largest change of heading in radians per step.
+    pub jitter: f32,
+    state: u32,
+}
+impl Wander {
+    /// Wanders from a fixed `seed` so the same seed always walks the same way.
+    pub fn new(jitter: f32, seed: u32) -> Self {
+        let mut wander = Self {
+            angle: 0.0,
+            jitter,
+            // Xorshift never leaves zero
+            state: seed.max(1),
+        };
+        wander.angle = wander.random() * TAU;
+        wander
+    }
+    /// A pseudo random number from `0.0` to `1.0`.
+    fn random(&mut self) -> f32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        self.state as f32 / u32::MAX as f32
+    }
+}
+impl Behavior for Wander {
+    fn steer(&mut self, _: Coordinate, _: &[Coordinate]) -> Coordinate {
+        self.angle += (self.random() * 2.0 - 1.0) * self.jitter;
+        Coordinate {
+            x: self.angle.cos(),
+            y: self.angle.sin(),
+        }
+    }
+}
+
+/// Keeps away from neighbours closer than `radius`, harder the closer they are.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Separation {
+    pub radius: f32,
+}
+impl Separation {
+    pub fn new(radius: f32) -> Self {
+        Self { radius }
+    }
+}
+impl Behavior for Separation {
+    fn steer(&mut self, position: Coordinate, neighbours: &[Coordinate]) -> Coordinate {
+        let mut push = Coordinate::default();
+        for &neighbour in neighbours {
+            let away = position - neighbour;
+            let distance = away.length();
+            // Agents sharing the exact same spot can't tell which way is away
+            if distance > 0.0 && distance < self.radius {
+                push += normalized(away) * (1.0 - distance / self.radius);
+            }
+        }
+        clamp_length(push)
+    }
+}
+
+/// A weighted blend of behaviors.
+#[derive(Default)]
+pub struct Steering {
+    behaviors: Vec<(Box<dyn Behavior>, f32)>,
+}
+impl Steering {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn with(mut self, behavior: impl Behavior + 'static, weight: f32) -> Self {
+        self.behaviors.push((Box::new(behavior), weight));
+        self
+    }
+    /// The weighted sum of every behavior's velocity, at most `1.0` long.
+    pub fn steer(&mut self, position: Coordinate, neighbours: &[Coordinate]) -> Coordinate {
+        let mut velocity = Coordinate::default();
+        for (behavior, weight) in &mut self.behaviors {
+            velocity += behavior.steer(position, neighbours) * *weight;
+        }
+        clamp_length(velocity)
+    }
+}
+
+/// Snaps a velocity to the closest of the eight input directions.
+///
+/// Each axis is `-1.0`, `0.0` or `1.0` like movement input. Returns `None`
+/// for velocities too small to walk in any direction
+pub fn to_input(velocity: Coordinate) -> Option<Coordinate> {
+    if velocity.length() < 0.05 {
+        return None;
+    }
+    let angle = (velocity.y.atan2(velocity.x) / FRAC_PI_4).round() * FRAC_PI_4;
+    Some(Coordinate {
+        x: angle.cos().round(),
+        y: angle.sin().round(),
+    })
+}
+
+fn normalized(v: Coordinate) -> Coordinate {
+    let length = v.length();
+    if length == 0.0 {
+        return v;
+    }
+    v * (1.0 / length)
+}
+
+fn clamp_length(v: Coordinate) -> Coordinate {
+    if v.length() > 1.0 {
+        normalized(v)
+    } else {
+        v
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Coordinate, b: Coordinate) -> bool {
+        (a - b).length() < 1e-4
+    }
+
+    #[test]
+    fn test_seek_and_flee() {
+        let origin = Coordinate::default();
+        let target = Coordinate { x: 3.0, y: 4.0 };
+
+        let seek = Seek::new(target).steer(origin, &[]);
+        assert!(close(seek, Coordinate { x: 0.6, y: 0.8 }));
+
+        let mut flee = Flee::new(target, 10.0);
+        assert!(close(
+            flee.steer(origin, &[]),
+            Coordinate { x: -0.6, y: -0.8 }
+        ));
+        flee.radius = 5.0;
+        assert_eq!(flee.steer(origin, &[]), Coordinate::default());
+    }
+
+    #[test]
+    fn test_arrive_slows_down() {
+        let mut arrive = Arrive::new(Coordinate { x: 10.0, y: 0.0 }, 4.0);
+
+        assert_eq!(arrive.steer(Coordinate::default(), &[]).length(), 1.0);
+        let near = arrive.steer(Coordinate { x: 8.0, y: 0.0 }, &[]);
+        assert!(close(near, Coordinate { x: 0.5, y: 0.0 }));
+        assert_eq!(
+            arrive.steer(Coordinate { x: 10.0, y: 0.0 }, &[]).length(),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_separation() {
+        let mut separation = Separation::new(4.0);
+        let position = Coordinate::default();
+        let neighbours = [
+            Coordinate { x: 2.0, y: 0.0 },
+            Coordinate { x: 0.0, y: 10.0 },
+        ];
+
+        // Only the close neighbour pushes
+        let push = separation.steer(position, &neighbours);
+        assert!(close(push, Coordinate { x: -0.5, y: 0.0 }));
+    }
+
+    #[test]
+    fn test_wander_is_deterministic() {
+        let mut a = Wander::new(0.5, 7);
+        let mut b = Wander::new(0.5, 7);
+
+        for _ in 0..10 {
+            let step = a.steer(Coordinate::default(), &[]);
+            assert!(close(step, b.steer(Coordinate::default(), &[])));
+            assert!((step.length() - 1.0).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_steering_blend_to_input() {
+        let mut steering = Steering::new()
+            .with(Seek::new(Coordinate { x: 10.0, y: 0.0 }), 1.0)
+            .with(Seek::new(Coordinate { x: 0.0, y: 10.0 }), 1.0);
+
+        let velocity = steering.steer(Coordinate::default(), &[]);
+        assert!((velocity.length() - 1.0).abs() < 1e-4);
+        assert_eq!(to_input(velocity), Some(Coordinate { x: 1.0, y: 1.0 }));
+
+        assert_eq!(
+            to_input(Coordinate { x: -0.9, y: 0.1 }),
+            Some(Coordinate { x: -1.0, y: 0.0 })
+        );
+        assert_eq!(to_input(Coordinate { x: 0.0, y: 0.01 }), None);
+    }
+}
@@ -2,27 +2,604 @@
 //!
 //! This module contains traits and structures designed for managing event-driven
 //! communication between different parts of the system using channels. The primary
-//! trait, `Subscriber`, allows for the subscription to a `Receiver<T>`, enabling
+//! trait, `Subscriber`, allows for the subscription to an `Inbox<T>`, enabling
 //! the handling of asynchronous events or messages of type `T` in a decoupled manner.
 //!
 //! # Key Features
-//! - **Subscriber Trait**: Allows types to subscribe to a `Receiver<T>` and handle messages.
+//! - **Subscriber Trait**: Allows types to subscribe to an `Inbox<T>` and handle messages.
+//! - **Inbox**: A subscriber's end of its channel, unsubscribing it once dropped.
 //! - **Crossbeam Channel**: Leverages `crossbeam::channel::Receiver` for efficient message passing.
+//! - **EventBus**: Typed topics, one per event type, shared by any number of
+//!   publishers and bounded subscribers that can later unsubscribe.
+//! - **Health**: How many events every subscriber received, lost to a full
+//!   channel or still has pending, to spot the ones falling behind.
 //!
 //! # Example Usage
 //! A typical implementation of the `Subscriber` trait would look like this:
-//! ```rust
+//! ```ignore
 //! struct MySubscriber;
 //!
 //! impl Subscriber<String> for MySubscriber {
-//!     fn subscribe(&mut self, rx: Receiver<String>) {
+//!     fn subscribe(&mut self, rx: Inbox<String>) {
 //!         // Logic for handling received messages
 //!     }
 //! }
 //! ```
-use crossbeam::channel::Receiver;
+//!
+//! Systems can also be wired together through the bus without going through
+//! `EventHandler`:
+//! ```ignore
+//! let bus = EventBus::new();
+//! let id = bus.subscribe::<String>(&mut my_subscriber, 16, Backpressure::DropOldest);
+//!
+//! let publisher = bus.publisher::<String>();
+//! publisher.publish("hello".into());
+//!
+//! if bus.health(id).is_some_and(|h| h.dropped > 0) {
+//!     warn!("my_subscriber can't keep up");
+//! }
+//! bus.unsubscribe(id);
+//! ```
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    ops::Deref,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, Weak,
+    },
+};
+
+use crossbeam::channel::{bounded, Receiver, Sender, TrySendError};
 
 /// A generic event subscriber that listens for incoming messages of type `T`
 pub trait Subscriber<T> {
-    fn subscribe(&mut self, rx: Receiver<T>);
+    fn subscribe(&mut self, rx: Inbox<T>);
+}
+
+/// The receiving end of a subscription.
+///
+/// Dropping every clone of the inbox removes the subscription the next time
+/// its topic is published to. The `Receiver` it derefs to doesn't count, so
+/// keep the inbox itself for as long as events are wanted
+pub struct Inbox<T> {
+    rx: Receiver<T>,
+    alive: Arc<()>,
+}
+impl<T> Deref for Inbox<T> {
+    type Target = Receiver<T>;
+    fn deref(&self) -> &Self::Target {
+        &self.rx
+    }
+}
+impl<T> Clone for Inbox<T> {
+    fn clone(&self) -> Self {
+        Self {
+            rx: self.rx.clone(),
+            alive: self.alive.clone(),
+        }
+    }
+}
+impl<T> From<Receiver<T>> for Inbox<T> {
+    /// Wraps a receiver of a channel that isn't a topic's, e.g. one fed
+    /// directly in tests.
+    fn from(rx: Receiver<T>) -> Self {
+        Self {
+            rx,
+            alive: Arc::new(()),
+        }
+    }
+}
+
+/// What a publisher does when a subscriber's channel is full.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Backpressure {
+    /// Wait until the subscriber makes room.
+    Block,
+    /// Discard the event being published.
+    DropNewest,
+    /// Discard the subscriber's oldest pending event to make room.
+    DropOldest,
+    /// Replace whatever the subscriber hasn't received yet, so it only ever
+    /// has the latest event pending, e.g. the direction currently held.
+    ///
+    /// The channel holds a single event whatever its capacity
+    Coalesce,
+}
+
+/// How well a subscriber keeps up with the events published to it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Health {
+    /// Events handed to the subscriber, including ones dropped later on
+    pub delivered: u64,
+    /// Events discarded because the subscriber's channel was full, whether
+    /// the ones published or the oldest pending ones
+    pub dropped: u64,
+    /// Events not received yet
+    pub pending: usize,
+    /// How many events the channel holds
+    pub capacity: usize,
+}
+
+/// Identifies a subscription so it can be removed from the `EventBus`, or
+/// whoever else it was made with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionId {
+    topic: TypeId,
+    id: u64,
+}
+
+/// The id of the next subscription made, unique across every topic.
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// A typed publish/subscribe hub with one topic per event type.
+///
+/// The bus is cheap to clone and every clone shares the same topics.
+#[derive(Clone, Default)]
+pub struct EventBus {
+    topics: Arc<Mutex<HashMap<TypeId, Arc<dyn AnyTopic>>>>,
+}
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Registers a subscriber to every `T` published from now on.
+    ///
+    /// The subscriber's channel holds at most `capacity` pending events,
+    /// past which `policy` decides what happens to new ones.
+    pub fn subscribe<T: Send + 'static>(
+        &self,
+        subscriber: &mut dyn Subscriber<T>,
+        capacity: usize,
+        policy: Backpressure,
+    ) -> SubscriptionId {
+        let (id, rx) = self.topic::<T>().lock().unwrap().add(capacity, policy);
+        subscriber.subscribe(rx);
+        id
+    }
+    /// Stops delivering events to a subscription.
+    ///
+    /// Returns whether the subscription was still registered
+    pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        let topic = self.topics.lock().unwrap().get(&id.topic).cloned();
+        topic.is_some_and(|t| t.unsubscribe(id.id))
+    }
+    /// How well a subscription keeps up, or `None` once it's removed.
+    pub fn health(&self, id: SubscriptionId) -> Option<Health> {
+        let topic = self.topics.lock().unwrap().get(&id.topic).cloned();
+        topic.and_then(|t| t.health(id.id))
+    }
+    /// Gets a handle for publishing `T` to every subscriber of its topic.
+    pub fn publisher<T: Send + 'static>(&self) -> Publisher<T> {
+        Publisher {
+            topic: self.topic::<T>(),
+        }
+    }
+    /// Publishes a single event without holding on to a `Publisher`.
+    ///
+    /// Returns the number of subscribers the event was delivered to
+    pub fn publish<T: Clone + Send + 'static>(&self, event: T) -> usize {
+        self.publisher::<T>().publish(event)
+    }
+    fn topic<T: Send + 'static>(&self) -> Arc<Mutex<Topic<T>>> {
+        let topic = self
+            .topics
+            .lock()
+            .unwrap()
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Arc::new(Mutex::new(Topic::<T>::default())))
+            .clone();
+        topic
+            .into_any()
+            .downcast::<Mutex<Topic<T>>>()
+            .expect("topics are keyed by their event type")
+    }
+}
+
+/// A cloneable handle publishing `T` to every subscriber of its topic.
+pub struct Publisher<T> {
+    topic: Arc<Mutex<Topic<T>>>,
+}
+impl<T: Clone + Send + 'static> Publisher<T> {
+    /// Sends `event` to every subscriber according to their backpressure policy.
+    ///
+    /// Subscribers whose inboxes were dropped are removed. Waiting on a full `Backpressure::Block` subscriber
+    /// doesn't hold up anyone else using the topic meanwhile. Returns the
+    /// number of subscribers the event was delivered to
+    pub fn publish(&self, event: T) -> usize {
+        let (mut delivered, waiting) = self.topic.lock().unwrap().offer(event);
+        for (id, tx, event) in waiting {
+            let sent = tx.send(event).is_ok();
+            delivered += usize::from(sent);
+            self.topic.lock().unwrap().settle(id, sent);
+        }
+        delivered
+    }
+}
+impl<T> Clone for Publisher<T> {
+    fn clone(&self) -> Self {
+        Self {
+            topic: self.topic.clone(),
+        }
+    }
+}
+
+/// Every subscription to events of type `T`.
+pub(crate) struct Topic<T> {
+    subscriptions: Vec<Subscription<T>>,
+}
+impl<T> Default for Topic<T> {
+    fn default() -> Self {
+        Self {
+            subscriptions: Vec::new(),
+        }
+    }
+}
+impl<T: 'static> Topic<T> {
+    /// Opens a channel of `capacity` events for a new subscription.
+    ///
+    /// Channels hold at least one event, without room evicting the oldest
+    /// would never make any
+    pub(crate) fn add(
+        &mut self,
+        capacity: usize,
+        policy: Backpressure,
+    ) -> (SubscriptionId, Inbox<T>) {
+        let capacity = match policy {
+            Backpressure::Coalesce => 1,
+            _ => capacity.max(1),
+        };
+        let (tx, rx) = bounded::<T>(capacity);
+        let inbox = Inbox::from(rx);
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let evicts = matches!(policy, Backpressure::DropOldest | Backpressure::Coalesce);
+        self.subscriptions.push(Subscription {
+            id,
+            tx,
+            rx: evicts.then(|| inbox.rx.clone()),
+            alive: Arc::downgrade(&inbox.alive),
+            policy,
+            health: Health {
+                capacity,
+                ..Health::default()
+            },
+        });
+        let id = SubscriptionId {
+            topic: TypeId::of::<T>(),
+            id,
+        };
+        (id, inbox)
+    }
+    /// Removes the subscription `id` if it's one of this topic's.
+    ///
+    /// Returns whether it was
+    pub(crate) fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        id.topic == TypeId::of::<T>() && self.remove(id.id)
+    }
+    fn remove(&mut self, id: u64) -> bool {
+        let before = self.subscriptions.len();
+        self.subscriptions.retain(|sub| sub.id != id);
+        self.subscriptions.len() != before
+    }
+    /// Sends `event` to every subscriber according to their backpressure
+    /// policy, removing those whose inboxes were dropped.
+    ///
+    /// Returns the number of subscribers the event was delivered to
+    pub(crate) fn publish(&mut self, event: T) -> usize
+    where
+        T: Clone,
+    {
+        let (mut delivered, waiting) = self.offer(event);
+        for (id, tx, event) in waiting {
+            let sent = tx.send(event).is_ok();
+            delivered += usize::from(sent);
+            self.settle(id, sent);
+        }
+        delivered
+    }
+    /// Sends `event` to every subscriber that doesn't have to be waited on.
+    ///
+    /// Returns the number it was delivered to and the `Backpressure::Block`
+    /// subscribers that are full, to be sent to and settled afterwards
+    fn offer(&mut self, event: T) -> (usize, Vec<Waiting<T>>)
+    where
+        T: Clone,
+    {
+        let mut delivered = 0;
+        let mut waiting = Vec::new();
+        self.subscriptions
+            .retain_mut(|sub| match sub.deliver(event.clone()) {
+                Delivery::Sent => {
+                    delivered += 1;
+                    true
+                }
+                Delivery::Dropped => true,
+                Delivery::Disconnected => false,
+                Delivery::Full(event) => {
+                    waiting.push((sub.id, sub.tx.clone(), event));
+                    true
+                }
+            });
+        (delivered, waiting)
+    }
+    /// Records whether an event a subscriber was waited on for got `sent`,
+    /// removing the subscriber if it didn't.
+    fn settle(&mut self, id: u64, sent: bool) {
+        if !sent {
+            self.subscriptions.retain(|sub| sub.id != id);
+        } else if let Some(sub) = self.subscriptions.iter_mut().find(|sub| sub.id == id) {
+            sub.health.delivered += 1;
+        }
+    }
+    /// How well the subscription `id` keeps up, or `None` if it isn't one
+    /// of this topic's.
+    pub(crate) fn health(&self, id: SubscriptionId) -> Option<Health> {
+        if id.topic != TypeId::of::<T>() {
+            return None;
+        }
+        self.find(id.id)
+    }
+    fn find(&self, id: u64) -> Option<Health> {
+        let sub = self.subscriptions.iter().find(|sub| sub.id == id)?;
+        Some(sub.health())
+    }
+}
+
+/// Type-erased access to a `Topic` for operations not needing its event type.
+trait AnyTopic: Send + Sync {
+    fn unsubscribe(&self, id: u64) -> bool;
+    fn health(&self, id: u64) -> Option<Health>;
+    fn into_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync>;
+}
+impl<T: Send + 'static> AnyTopic for Mutex<Topic<T>> {
+    fn unsubscribe(&self, id: u64) -> bool {
+        self.lock().unwrap().remove(id)
+    }
+    fn health(&self, id: u64) -> Option<Health> {
+        self.lock().unwrap().find(id)
+    }
+    fn into_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> {
+        self
+    }
+}
+
+struct Subscription<T> {
+    id: u64,
+    tx: Sender<T>,
+    /// Kept to evict the oldest pending event under `Backpressure::DropOldest`
+    /// and `Backpressure::Coalesce`.
+    rx: Option<Receiver<T>>,
+    /// Gone once the subscriber dropped its inbox, which the receiver kept
+    /// for evicting would otherwise hide
+    alive: Weak<()>,
+    policy: Backpressure,
+    health: Health,
+}
+impl<T> Subscription<T> {
+    /// Sends `event` without waiting, handing it back if the subscriber
+    /// would have to be waited on.
+    fn deliver(&mut self, event: T) -> Delivery<T> {
+        if self.alive.strong_count() == 0 {
+            return Delivery::Disconnected;
+        }
+        let delivery = match self.policy {
+            Backpressure::Block => match self.tx.try_send(event) {
+                Ok(_) => Delivery::Sent,
+                Err(TrySendError::Full(e)) => Delivery::Full(e),
+                Err(TrySendError::Disconnected(_)) => Delivery::Disconnected,
+            },
+            Backpressure::DropNewest => match self.tx.try_send(event) {
+                Ok(_) => Delivery::Sent,
+                Err(TrySendError::Full(_)) => Delivery::Dropped,
+                Err(TrySendError::Disconnected(_)) => Delivery::Disconnected,
+            },
+            Backpressure::DropOldest | Backpressure::Coalesce => {
+                let mut event = event;
+                loop {
+                    match self.tx.try_send(event) {
+                        Ok(_) => break Delivery::Sent,
+                        Err(TrySendError::Full(e)) => {
+                            // The subscriber may have made room in the meantime
+                            if let Some(Ok(_)) = self.rx.as_ref().map(Receiver::try_recv) {
+                                self.health.dropped += 1;
+                            }
+                            event = e;
+                        }
+                        Err(TrySendError::Disconnected(_)) => break Delivery::Disconnected,
+                    }
+                }
+            }
+        };
+        match delivery {
+            Delivery::Sent => self.health.delivered += 1,
+            Delivery::Dropped => self.health.dropped += 1,
+            Delivery::Disconnected | Delivery::Full(_) => (),
+        }
+        delivery
+    }
+    fn health(&self) -> Health {
+        Health {
+            pending: self.tx.len(),
+            ..self.health
+        }
+    }
+}
+
+enum Delivery<T> {
+    Sent,
+    Dropped,
+    Disconnected,
+    /// A `Backpressure::Block` subscriber has no room for the event yet
+    Full(T),
+}
+
+/// A full subscriber's id and sender with the event it's waited on for.
+type Waiting<T> = (u64, Sender<T>, T);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Listener<T> {
+        rx: Option<Inbox<T>>,
+    }
+    impl<T> Subscriber<T> for Listener<T> {
+        fn subscribe(&mut self, rx: Inbox<T>) {
+            self.rx = Some(rx);
+        }
+    }
+    impl<T> Listener<T> {
+        fn drain(&self) -> Vec<T> {
+            self.rx.as_ref().unwrap().try_iter().collect()
+        }
+    }
+
+    #[test]
+    fn test_topics_are_keyed_by_type() {
+        let bus = EventBus::new();
+        let mut numbers = Listener::<u32>::default();
+        let mut words = Listener::<&str>::default();
+        bus.subscribe(&mut numbers, 4, Backpressure::Block);
+        bus.subscribe(&mut words, 4, Backpressure::Block);
+
+        // Every publisher shares the same topic
+        let first = bus.publisher::<u32>();
+        let second = first.clone();
+        assert_eq!(first.publish(1), 1);
+        assert_eq!(second.publish(2), 1);
+        assert_eq!(bus.publish("hi"), 1);
+
+        assert_eq!(numbers.drain(), vec![1, 2]);
+        assert_eq!(words.drain(), vec!["hi"]);
+    }
+
+    #[test]
+    fn test_backpressure_policies() {
+        let bus = EventBus::new();
+        let mut newest = Listener::<u32>::default();
+        let mut oldest = Listener::<u32>::default();
+        bus.subscribe(&mut newest, 2, Backpressure::DropNewest);
+        bus.subscribe(&mut oldest, 2, Backpressure::DropOldest);
+
+        for i in 0..4u32 {
+            bus.publish(i);
+        }
+
+        assert_eq!(newest.drain(), vec![0, 1]);
+        assert_eq!(oldest.drain(), vec![2, 3]);
+
+        // Without any room there'd be nothing to evict
+        let mut none = Listener::<&str>::default();
+        bus.subscribe(&mut none, 0, Backpressure::DropOldest);
+        bus.publish("first");
+        bus.publish("second");
+        assert_eq!(none.drain(), vec!["second"]);
+    }
+
+    #[test]
+    fn test_blocked_publisher_leaves_topic_usable() {
+        let bus = EventBus::new();
+        let mut slow = Listener::<u32>::default();
+        let mut other = Listener::<u32>::default();
+        let slow_id = bus.subscribe(&mut slow, 1, Backpressure::Block);
+        let other_id = bus.subscribe(&mut other, 4, Backpressure::Block);
+        bus.publish(1u32);
+
+        let publisher = bus.publisher::<u32>();
+        let blocked = std::thread::spawn(move || publisher.publish(2));
+        // Waits for the slow subscriber, not for the topic to be unlocked
+        while other.rx.as_ref().unwrap().len() < 2 {
+            std::thread::yield_now();
+        }
+        assert!(bus.health(slow_id).is_some());
+        assert!(bus.unsubscribe(other_id));
+
+        assert_eq!(slow.rx.as_ref().unwrap().recv(), Ok(1));
+        assert_eq!(blocked.join().unwrap(), 2);
+        assert_eq!(slow.drain(), vec![2]);
+        assert_eq!(bus.health(slow_id).map(|h| h.delivered), Some(2));
+    }
+
+    #[test]
+    fn test_coalesce_reports_health() {
+        let bus = EventBus::new();
+        let mut latest = Listener::<u32>::default();
+        let mut slow = Listener::<u32>::default();
+        let latest_id = bus.subscribe(&mut latest, 8, Backpressure::Coalesce);
+        let slow_id = bus.subscribe(&mut slow, 2, Backpressure::DropNewest);
+
+        for i in 0..3u32 {
+            bus.publish(i);
+        }
+
+        let health = |delivered, dropped, pending, capacity| Health {
+            delivered,
+            dropped,
+            pending,
+            capacity,
+        };
+        assert_eq!(bus.health(latest_id), Some(health(3, 2, 1, 1)));
+        assert_eq!(bus.health(slow_id), Some(health(2, 1, 2, 2)));
+        assert_eq!(latest.drain(), vec![2]);
+        assert_eq!(bus.health(latest_id).map(|h| h.pending), Some(0));
+
+        bus.unsubscribe(slow_id);
+        assert_eq!(bus.health(slow_id), None);
+    }
+
+    #[test]
+    fn test_unsubscribe() {
+        let bus = EventBus::new();
+        let mut kept = Listener::<u32>::default();
+        let mut removed = Listener::<u32>::default();
+        bus.subscribe(&mut kept, 4, Backpressure::Block);
+        let id = bus.subscribe(&mut removed, 4, Backpressure::Block);
+
+        assert!(bus.unsubscribe(id));
+        assert!(!bus.unsubscribe(id));
+        assert_eq!(bus.publish(7u32), 1);
+        assert_eq!(kept.drain(), vec![7]);
+        assert!(removed.drain().is_empty());
+
+        // Dropping the receiving side unsubscribes as well
+        drop(kept);
+        assert_eq!(bus.publish(8u32), 0);
+    }
+
+    #[test]
+    fn test_dropped_evicting_subscribers_are_removed() {
+        let bus = EventBus::new();
+        let mut oldest = Listener::<u32>::default();
+        let mut latest = Listener::<u32>::default();
+        let oldest_id = bus.subscribe(&mut oldest, 2, Backpressure::DropOldest);
+        let latest_id = bus.subscribe(&mut latest, 2, Backpressure::Coalesce);
+        assert_eq!(bus.publish(1u32), 2);
+
+        // Keeping the receiver for evicting doesn't keep them subscribed
+        drop(oldest);
+        assert_eq!(bus.publish(2u32), 1);
+        assert_eq!(bus.health(oldest_id), None);
+        drop(latest);
+        assert_eq!(bus.publish(3u32), 0);
+        assert_eq!(bus.health(latest_id), None);
+    }
+
+    #[test]
+    fn test_topics_only_remove_their_own() {
+        let mut numbers = Topic::<u32>::default();
+        let mut words = Topic::<&str>::default();
+        let (number, _rx) = numbers.add(4, Backpressure::DropOldest);
+        let (word, _words_rx) = words.add(4, Backpressure::DropOldest);
+        let (other, _other_rx) = Topic::<u32>::default().add(4, Backpressure::DropOldest);
+
+        // Ids are unique across topics, even of the same type
+        assert!(!numbers.unsubscribe(word));
+        assert!(!numbers.unsubscribe(other));
+        assert_eq!(numbers.health(word), None);
+        assert!(numbers.health(number).is_some());
+        assert!(numbers.unsubscribe(number));
+        assert_eq!(numbers.publish(1), 0);
+        assert_eq!(words.publish("kept"), 1);
+    }
 }
@@ -0,0 +1,381 @@
+//! On-screen touch controls for devices without a keyboard.
+//!
+//! `TouchControls` turn touches (in screen pixels) into the same movement
+//! `Coordinate`s and `Action`s the keyboard produces: a `VirtualJoystick` steers
+//! the player while a finger drags its knob and every `TouchButton` presses its
+//! `GameInput` while touched. Shared with `EventHandler`, which feeds it the
+//! window's touch events, the controls are drawn on top of the game by
+//! `GameState`.
+//!
+//! # Key Structures
+//! - **`TouchPoint`**: A finger starting, moving or lifting at a position.
+//! - **`VirtualJoystick`**: A knob dragged from its center to walk in that direction.
+//! - **`TouchButton`**: A round button pressing an input while touched.
+//! - **`TouchControls`**: The joystick and buttons placed on screen.
+//!
+//! # Example Usage
+//! ```ignore
+//! let controls = Arc::new(Mutex::new(
+//!     TouchControls::new()
+//!         .joystick(VirtualJoystick::new(Coordinate { x: 24.0, y: 100.0 }, 16.0))
+//!         .button(TouchButton::new(Coordinate { x: 170.0, y: 100.0 }, 10.0, GameInput::PlayerDash)),
+//! ));
+//! event_handler.touch_controls(controls.clone());
+//! state.touch_overlay(controls);
+//! ```
+use winit::event::{ElementState, TouchPhase};
+
+use crate::{
+    input::{Action, GameInput},
+    layout::Coordinate,
+    palette::{Color, GREEN, LIGHT_GRAY, MIDNIGHT},
+    renderer::DrawCmd,
+    steering::to_input,
+};
+
+/// A finger starting, moving or lifting at a position in screen pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TouchPoint {
+    /// Stays the same for a finger from the moment it touches until it lifts.
+    pub id: u64,
+    pub phase: TouchPhase,
+    pub position: Coordinate,
+}
+
+/// A knob dragged away from its center to walk in that direction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VirtualJoystick {
+    pub center: Coordinate,
+    /// How far the knob can be dragged and how close to the center a touch
+    /// must start to grab it.
+    pub radius: f32,
+    /// Fraction of the radius the knob must leave the center by to walk.
+    pub dead_zone: f32,
+    finger: Option<u64>,
+    /// Offset of the knob from the center
+    knob: Coordinate,
+}
+impl VirtualJoystick {
+    pub fn new(center: Coordinate, radius: f32) -> Self {
+        Self {
+            center,
+            radius,
+            dead_zone: 0.25,
+            finger: None,
+            knob: Coordinate::default(),
+        }
+    }
+    pub fn dead_zone(mut self, dead_zone: f32) -> Self {
+        self.dead_zone = dead_zone;
+        self
+    }
+    /// The direction walked in, snapped like movement input.
+    pub fn direction(&self) -> Option<Coordinate> {
+        if self.finger.is_none() || self.knob.length() <= self.radius * self.dead_zone {
+            return None;
+        }
+        to_input(self.knob)
+    }
+    fn drag(&mut self, position: Coordinate) {
+        let offset = position - self.center;
+        let length = offset.length();
+        self.knob = if length > self.radius {
+            offset * (self.radius / length)
+        } else {
+            offset
+        };
+    }
+}
+
+/// A round button pressing `input` while touched.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TouchButton {
+    pub center: Coordinate,
+    pub radius: f32,
+    pub input: GameInput,
+    finger: Option<u64>,
+}
+impl TouchButton {
+    pub fn new(center: Coordinate, radius: f32, input: GameInput) -> Self {
+        Self {
+            center,
+            radius,
+            input,
+            finger: None,
+        }
+    }
+    pub fn is_pressed(&self) -> bool {
+        self.finger.is_some()
+    }
+}
+
+/// A virtual joystick and buttons placed on screen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TouchControls {
+    joystick: Option<VirtualJoystick>,
+    buttons: Vec<TouchButton>,
+    /// Size of the screen laid out for relative to the one placed on
+    scale: f32,
+}
+impl TouchControls {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn joystick(mut self, joystick: VirtualJoystick) -> Self {
+        self.joystick = Some(joystick);
+        self
+    }
+    pub fn button(mut self, button: TouchButton) -> Self {
+        self.buttons.push(button);
+        self
+    }
+    /// Lays the controls out again for a screen `scale` times the size they
+    /// were placed on, e.g. `0.5` for a screen zoomed in twice as far.
+    pub fn relayout(&mut self, scale: f32) {
+        let ratio = scale / self.scale;
+        if let Some(joystick) = &mut self.joystick {
+            joystick.center = joystick.center * ratio;
+            joystick.radius *= ratio;
+            joystick.knob = joystick.knob * ratio;
+        }
+        for button in &mut self.buttons {
+            button.center = button.center * ratio;
+            button.radius *= ratio;
+        }
+        self.scale = scale;
+    }
+    /// Updates the controls with a touch.
+    ///
+    /// Returns the actions of every button pressed or released by it
+    pub fn handle(&mut self, touch: TouchPoint) -> Vec<Action> {
+        let mut actions = Vec::new();
+        match touch.phase {
+            TouchPhase::Started => {
+                if let Some(joystick) = self.joystick.as_mut().filter(|j| {
+                    j.finger.is_none() && (touch.position - j.center).length() <= j.radius
+                }) {
+                    joystick.finger = Some(touch.id);
+                    joystick.drag(touch.position);
+                    return actions;
+                }
+                if let Some(button) = self
+                    .buttons
+                    .iter_mut()
+                    .find(|b| !b.is_pressed() && (touch.position - b.center).length() <= b.radius)
+                {
+                    button.finger = Some(touch.id);
+                    actions.push(Action {
+                        input: button.input,
+                        state: ElementState::Pressed,
+                    });
+                }
+            }
+            TouchPhase::Moved => {
+                if let Some(joystick) = self
+                    .joystick
+                    .as_mut()
+                    .filter(|j| j.finger == Some(touch.id))
+                {
+                    joystick.drag(touch.position);
+                }
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                if let Some(joystick) = self
+                    .joystick
+                    .as_mut()
+                    .filter(|j| j.finger == Some(touch.id))
+                {
+                    joystick.finger = None;
+                    joystick.knob = Coordinate::default();
+                }
+                for button in &mut self.buttons {
+                    if button.finger == Some(touch.id) {
+                        button.finger = None;
+                        actions.push(Action {
+                            input: button.input,
+                            state: ElementState::Released,
+                        });
+                    }
+                }
+            }
+        }
+        actions
+    }
+    /// The direction the joystick walks in, if it's held outside its dead zone.
+    pub fn direction(&self) -> Option<Coordinate> {
+        self.joystick.as_ref().and_then(VirtualJoystick::direction)
+    }
+    /// Whether the joystick is held, even inside its dead zone.
+    pub fn is_steering(&self) -> bool {
+        self.joystick.as_ref().is_some_and(|j| j.finger.is_some())
+    }
+    /// Lifts every finger, e.g. when the window loses focus.
+    ///
+    /// Returns the release of every button that was pressed
+    pub fn reset(&mut self) -> Vec<Action> {
+        if let Some(joystick) = &mut self.joystick {
+            joystick.finger = None;
+            joystick.knob = Coordinate::default();
+        }
+        self.buttons
+            .iter_mut()
+            .filter_map(|b| {
+                b.finger.take().map(|_| Action {
+                    input: b.input,
+                    state: ElementState::Released,
+                })
+            })
+            .collect()
+    }
+    /// Queues the joystick and buttons onto a batch of draw commands.
+    pub fn commands(&self, batch: &mut Vec<DrawCmd<'_>>) {
+        if let Some(joystick) = &self.joystick {
+            disc(joystick.center, joystick.radius, MIDNIGHT, batch);
+            disc(
+                joystick.center + joystick.knob,
+                joystick.radius / 2.0,
+                LIGHT_GRAY,
+                batch,
+            );
+        }
+        for button in &self.buttons {
+            let color = if button.is_pressed() {
+                LIGHT_GRAY
+            } else {
+                GREEN
+            };
+            disc(button.center, button.radius, color, batch);
+        }
+    }
+}
+
+impl Default for TouchControls {
+    fn default() -> Self {
+        Self {
+            joystick: None,
+            buttons: Vec::new(),
+            scale: 1.0,
+        }
+    }
+}
+
+/// Queues a filled circle as one span per row.
+fn disc(center: Coordinate, radius: f32, color: Color, batch: &mut Vec<DrawCmd<'_>>) {
+    let rows = radius.floor() as i32;
+    for dy in -rows..=rows {
+        let half = (radius * radius - (dy * dy) as f32).sqrt().floor() as i32;
+        batch.push(DrawCmd::Span {
+            start: (center.x.round() as i32 - half, center.y.round() as i32 + dy),
+            len: (half * 2 + 1) as u32,
+            color,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{palette::BLACK, prelude::*};
+
+    fn touch(id: u64, phase: TouchPhase, x: f32, y: f32) -> TouchPoint {
+        TouchPoint {
+            id,
+            phase,
+            position: Coordinate { x, y },
+        }
+    }
+
+    fn controls() -> TouchControls {
+        TouchControls::new()
+            .joystick(VirtualJoystick::new(Coordinate { x: 10.0, y: 10.0 }, 8.0))
+            .button(TouchButton::new(
+                Coordinate { x: 40.0, y: 10.0 },
+                4.0,
+                GameInput::PlayerDash,
+            ))
+    }
+
+    #[test]
+    fn test_joystick_steers() {
+        let mut controls = controls();
+
+        controls.handle(touch(1, TouchPhase::Started, 11.0, 10.0));
+        assert!(controls.is_steering());
+        assert_eq!(controls.direction(), None);
+
+        controls.handle(touch(1, TouchPhase::Moved, 30.0, 10.0));
+        assert_eq!(controls.direction(), Some(Coordinate { x: 1.0, y: 0.0 }));
+        // Another finger doesn't move the knob
+        controls.handle(touch(2, TouchPhase::Moved, 10.0, 30.0));
+        assert_eq!(controls.direction(), Some(Coordinate { x: 1.0, y: 0.0 }));
+
+        controls.handle(touch(1, TouchPhase::Ended, 30.0, 10.0));
+        assert_eq!(controls.direction(), None);
+        assert!(!controls.is_steering());
+    }
+
+    #[test]
+    fn test_buttons_press_and_release() {
+        let mut controls = controls();
+        let dash = |state| Action {
+            input: GameInput::PlayerDash,
+            state,
+        };
+
+        assert_eq!(
+            controls.handle(touch(3, TouchPhase::Started, 41.0, 11.0)),
+            [dash(ElementState::Pressed)]
+        );
+        assert_eq!(controls.handle(touch(3, TouchPhase::Moved, 60.0, 11.0)), []);
+        assert_eq!(
+            controls.handle(touch(3, TouchPhase::Ended, 60.0, 11.0)),
+            [dash(ElementState::Released)]
+        );
+        assert_eq!(
+            controls.handle(touch(4, TouchPhase::Started, 60.0, 60.0)),
+            []
+        );
+
+        controls.handle(touch(5, TouchPhase::Started, 40.0, 10.0));
+        assert_eq!(controls.reset(), [dash(ElementState::Released)]);
+    }
+
+    #[test]
+    fn test_relayout_keeps_controls_in_place() {
+        let mut controls = controls();
+        controls.relayout(0.5);
+        controls.relayout(0.5);
+
+        // The button now sits at half the distance in a screen of half the size
+        assert_eq!(
+            controls.handle(touch(1, TouchPhase::Started, 20.0, 5.0)),
+            [Action {
+                input: GameInput::PlayerDash,
+                state: ElementState::Pressed
+            }]
+        );
+        controls.relayout(1.0);
+        assert_eq!(controls, {
+            let mut pressed = self::controls();
+            pressed.handle(touch(1, TouchPhase::Started, 40.0, 10.0));
+            pressed
+        });
+    }
+
+    #[test]
+    fn test_controls_overlay() {
+        let mut screen = HeadlessScreen::new(50, 20);
+        let mut batch = vec![DrawCmd::Clear];
+        controls().commands(&mut batch);
+        screen.draw_batch(&batch).unwrap();
+        let rgba = |color: Color| {
+            let [r, g, b, a] = color.to_rgba();
+            Some(Color::RGBA(r, g, b, a))
+        };
+
+        assert_eq!(screen.pixel(10, 10), rgba(LIGHT_GRAY));
+        assert_eq!(screen.pixel(10, 16), rgba(MIDNIGHT));
+        assert_eq!(screen.pixel(40, 14), rgba(GREEN));
+        assert_eq!(screen.pixel(44, 14), rgba(BLACK));
+    }
+}
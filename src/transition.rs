@@ -0,0 +1,170 @@
+//! Screen transitions covering level changes and deaths instead of hard cuts.
+//!
+//! A `Transition` first covers the screen, reports the moment it's fully covered
+//! so whatever is shown underneath can be swapped out unseen, then reveals the
+//! screen again. Its draw commands go at the end of the frame's batch.
+//!
+//! # Key Structures
+//! - **`Transition`**: A running transition and its progress.
+//! - **`TransitionKind`**: How the screen is covered: fade, wipe or pixel dissolve.
+//!
+//! # Example Usage
+//! ```ignore
+//! let mut transition = Transition::new(TransitionKind::Dissolve(BLACK), 0.8);
+//! // Every frame
+//! if transition.advance(delta) {
+//!     level = next_level();
+//! }
+//! transition.commands(width, height, &mut batch);
+//! ```
+use crate::{grading::Grade, palette::Color, renderer::DrawCmd};
+
+/// How a transition covers the screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransitionKind {
+    /// Fades everything towards a color.
+    Fade(Color),
+    /// Sweeps a color across the screen from left to right.
+    Wipe(Color),
+    /// Covers the screen with a color pixel by pixel in a scattered order.
+    Dissolve(Color),
+}
+
+/// A transition running for `duration` seconds, half covering and half revealing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transition {
+    pub kind: TransitionKind,
+    pub duration: f32,
+    elapsed: f32,
+}
+impl Transition {
+    pub fn new(kind: TransitionKind, duration: f32) -> Self {
+        Self {
+            kind,
+            duration,
+            elapsed: 0.0,
+        }
+    }
+    /// Progresses the transition by `delta` seconds.
+    ///
+    /// Returns `true` on the one update the screen became fully covered
+    pub fn advance(&mut self, delta: f32) -> bool {
+        let half = self.duration / 2.0;
+        let was_covered = self.elapsed >= half;
+        self.elapsed = (self.elapsed + delta).min(self.duration);
+        !was_covered && self.elapsed >= half
+    }
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+    /// How much of the screen is covered from `0.0` to `1.0`.
+    pub fn coverage(&self) -> f32 {
+        if self.duration <= 0.0 {
+            return 0.0;
+        }
+        let progress = self.elapsed / self.duration;
+        1.0 - (progress * 2.0 - 1.0).abs()
+    }
+    /// Queues the cover over a screen of the given size onto a batch of draw commands.
+    pub fn commands(&self, width: u32, height: u32, batch: &mut Vec<DrawCmd<'_>>) {
+        let coverage = self.coverage();
+        if coverage <= 0.0 {
+            return;
+        }
+        match self.kind {
+            TransitionKind::Fade(color) => batch.push(DrawCmd::Grade(Grade::Fade {
+                color,
+                amount: coverage,
+            })),
+            TransitionKind::Wipe(color) => {
+                let len = (width as f32 * coverage).round() as u32;
+                if len > 0 {
+                    batch.extend((0..height).map(|y| DrawCmd::Span {
+                        start: (0, y as i32),
+                        len,
+                        color,
+                    }));
+                }
+            }
+            TransitionKind::Dissolve(color) => {
+                for y in 0..height {
+                    for x in 0..width {
+                        if threshold(x, y) < coverage {
+                            batch.push(DrawCmd::Span {
+                                start: (x as i32, y as i32),
+                                len: 1,
+                                color,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A scattered but fixed value from `0.0` to `1.0` for every pixel so the
+/// dissolve covers each one once and uncovers it in reverse.
+fn threshold(x: u32, y: u32) -> f32 {
+    let mut hash = x.wrapping_mul(0x9E37_79B9) ^ y.wrapping_mul(0x85EB_CA6B);
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x2C1B_3C6D);
+    hash ^= hash >> 12;
+    // Strictly below 1.0 so a fully covered screen covers every pixel
+    (hash >> 8) as f32 / (1 << 24) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{palette::BLACK, prelude::*};
+
+    fn covered(screen: &HeadlessScreen) -> usize {
+        screen
+            .buffer()
+            .chunks_exact(4)
+            .filter(|p| p[..3] == [0, 0, 0])
+            .count()
+    }
+
+    #[test]
+    fn test_transition_covers_then_reveals() {
+        let mut transition = Transition::new(TransitionKind::Wipe(BLACK), 1.0);
+
+        assert!(!transition.advance(0.25));
+        assert_eq!(transition.coverage(), 0.5);
+        assert!(transition.advance(0.25));
+        assert!(!transition.advance(0.25));
+        assert_eq!(transition.coverage(), 0.5);
+        transition.advance(1.0);
+        assert!(transition.is_finished());
+        assert_eq!(transition.coverage(), 0.0);
+    }
+
+    #[test]
+    fn test_transition_kinds_cover_screen() {
+        for kind in [
+            TransitionKind::Fade(BLACK),
+            TransitionKind::Wipe(BLACK),
+            TransitionKind::Dissolve(BLACK),
+        ] {
+            let mut screen = HeadlessScreen::new(8, 8);
+            let mut transition = Transition::new(kind, 1.0);
+            let mut draw = |transition: &Transition| {
+                let mut batch = vec![DrawCmd::Clear];
+                batch.extend((0..8).map(|y| DrawCmd::Span {
+                    start: (0, y),
+                    len: 8,
+                    color: Color::RGB(200, 200, 200),
+                }));
+                transition.commands(8, 8, &mut batch);
+                screen.draw_batch(&batch).unwrap();
+                covered(&screen)
+            };
+
+            assert_eq!(draw(&transition), 0, "{kind:?}");
+            transition.advance(0.5);
+            assert_eq!(draw(&transition), 64, "{kind:?}");
+        }
+    }
+}
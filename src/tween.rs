@@ -0,0 +1,231 @@
+//! Easing and interpolation of values over time.
+//!
+//! A `Tween` moves any `Lerp` value (scalars, `Coordinate`s and `Color`s) from
+//! one value to another over a duration, shaped by an `Easing` curve. It's
+//! progressed with the game's `delta` like animations, which makes it a fit for
+//! UI slides, camera pans or bobbing pickups.
+//!
+//! # Key Structures
+//! - **`Easing`**: The curve progress follows from start to end.
+//! - **`Repeat`**: What a tween does once it reached its end.
+//! - **`Lerp`**: Values that can be interpolated.
+//! - **`Tween`**: A value being interpolated over time.
+//!
+//! # Example Usage
+//! ```ignore
+//! let mut bob = Tween::new(0.0, 2.0, 0.6)
+//!     .easing(Easing::EaseInOut)
+//!     .repeat(Repeat::PingPong);
+//!
+//! // Every frame
+//! let lift = bob.advance(delta);
+//! pickup.commands(pickup_pos + Coordinate { x: 0.0, y: -lift }, &mut batch);
+//! ```
+use crate::{layout::Coordinate, palette::Color};
+
+/// The curve progress follows from start to end.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Easing {
+    /// Constant speed.
+    #[default]
+    Linear,
+    /// Starts slow and speeds up.
+    EaseIn,
+    /// Starts fast and slows down.
+    EaseOut,
+    /// Slow at both ends.
+    EaseInOut,
+    /// Hits the end early and bounces off it a few times like a dropped ball.
+    Bounce,
+}
+impl Easing {
+    /// The eased progress for a linear progress `t` from `0.0` to `1.0`.
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => t * (2.0 - t),
+            Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+            Easing::Bounce => bounce(t),
+        }
+    }
+}
+
+/// Falls onto the end and bounces off it with shrinking heights.
+fn bounce(t: f32) -> f32 {
+    const N: f32 = 7.5625;
+    const D: f32 = 2.75;
+    if t < 1.0 / D {
+        N * t * t
+    } else if t < 2.0 / D {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984375
+    }
+}
+
+/// What a tween does once it reached its end.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Repeat {
+    /// Stays at the end.
+    #[default]
+    Once,
+    /// Jumps back to the start and plays again.
+    Loop,
+    /// Plays back to the start and then forwards again.
+    PingPong,
+}
+
+/// Values that can be interpolated.
+pub trait Lerp: Clone {
+    /// The value `t` of the way from `self` to `to`, where `t` may leave `0.0`
+    /// to `1.0` for easings overshooting their ends.
+    fn lerp(&self, to: &Self, t: f32) -> Self;
+}
+impl Lerp for f32 {
+    fn lerp(&self, to: &Self, t: f32) -> Self {
+        self + (to - self) * t
+    }
+}
+impl Lerp for Coordinate {
+    fn lerp(&self, to: &Self, t: f32) -> Self {
+        Coordinate {
+            x: self.x.lerp(&to.x, t),
+            y: self.y.lerp(&to.y, t),
+        }
+    }
+}
+impl Lerp for Color {
+    /// Stays `RGB` when both colors are opaque `RGB`s.
+    fn lerp(&self, to: &Self, t: f32) -> Self {
+        let (from, end) = (self.to_rgba(), to.to_rgba());
+        let channel = |i: usize| {
+            (from[i] as f32)
+                .lerp(&(end[i] as f32), t)
+                .round()
+                .clamp(0.0, 255.0) as u8
+        };
+        match (self, to) {
+            (Color::RGB(..), Color::RGB(..)) => Color::RGB(channel(0), channel(1), channel(2)),
+            _ => Color::RGBA(channel(0), channel(1), channel(2), channel(3)),
+        }
+    }
+}
+
+/// A value interpolated from `from` to `to` over `duration` seconds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tween<T: Lerp> {
+    pub from: T,
+    pub to: T,
+    pub duration: f32,
+    pub easing: Easing,
+    pub repeat: Repeat,
+    elapsed: f32,
+}
+impl<T: Lerp> Tween<T> {
+    pub fn new(from: T, to: T, duration: f32) -> Self {
+        Self {
+            from,
+            to,
+            duration,
+            easing: Easing::default(),
+            repeat: Repeat::default(),
+            elapsed: 0.0,
+        }
+    }
+    pub fn easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+    pub fn repeat(mut self, repeat: Repeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+    /// Progresses the tween by `delta` seconds and returns the new value.
+    pub fn advance(&mut self, delta: f32) -> T {
+        self.elapsed += delta;
+        if self.repeat == Repeat::Once {
+            self.elapsed = self.elapsed.min(self.duration);
+        }
+        self.value()
+    }
+    /// The value at the current point in time.
+    pub fn value(&self) -> T {
+        self.from.lerp(&self.to, self.easing.apply(self.progress()))
+    }
+    /// Linear progress from `0.0` at the start to `1.0` at the end.
+    pub fn progress(&self) -> f32 {
+        if self.duration <= 0.0 {
+            return 1.0;
+        }
+        let t = self.elapsed / self.duration;
+        match self.repeat {
+            Repeat::Once => t.min(1.0),
+            Repeat::Loop => t.fract(),
+            Repeat::PingPong => 1.0 - ((t % 2.0) - 1.0).abs(),
+        }
+    }
+    /// Whether a tween playing `Once` reached its end. Repeating tweens never finish.
+    pub fn is_finished(&self) -> bool {
+        self.repeat == Repeat::Once && self.elapsed >= self.duration
+    }
+    /// Starts over from the beginning.
+    pub fn reset(&mut self) {
+        self.elapsed = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_easings_keep_ends() {
+        for easing in [
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+            Easing::Bounce,
+        ] {
+            assert_eq!(easing.apply(0.0), 0.0, "{easing:?}");
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-6, "{easing:?}");
+        }
+        assert_eq!(Easing::EaseIn.apply(0.5), 0.25);
+        assert_eq!(Easing::EaseOut.apply(0.5), 0.75);
+        assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+    }
+
+    #[test]
+    fn test_tween_values() {
+        let mut slide = Tween::new(Coordinate::default(), Coordinate { x: 10.0, y: -4.0 }, 2.0);
+        assert_eq!(slide.advance(1.0), Coordinate { x: 5.0, y: -2.0 });
+        assert_eq!(slide.advance(5.0), Coordinate { x: 10.0, y: -4.0 });
+        assert!(slide.is_finished());
+
+        let fade = Tween::new(Color::RGB(0, 0, 0), Color::RGB(255, 100, 10), 1.0);
+        assert_eq!(fade.clone().advance(0.5), Color::RGB(128, 50, 5));
+        let fade = Tween::new(Color::RGB(0, 0, 0), Color::RGBA(0, 0, 0, 0), 1.0);
+        assert_eq!(fade.clone().advance(0.5), Color::RGBA(0, 0, 0, 128));
+    }
+
+    #[test]
+    fn test_tween_repeats() {
+        let mut bob = Tween::new(0.0, 2.0, 1.0).repeat(Repeat::PingPong);
+        assert_eq!(bob.advance(0.5), 1.0);
+        assert_eq!(bob.advance(0.75), 1.5);
+        assert_eq!(bob.advance(1.0), 0.5);
+        assert!(!bob.is_finished());
+
+        let mut spin = Tween::new(0.0, 1.0, 1.0).repeat(Repeat::Loop);
+        assert_eq!(spin.advance(1.25), 0.25);
+        spin.reset();
+        assert_eq!(spin.value(), 0.0);
+    }
+}
@@ -0,0 +1,643 @@
+//! A small retained widget toolkit for menus.
+//!
+//! A `Menu` lays out its widgets as a vertical list drawn with a `BitmapFont`
+//! and keeps track of which one is focused. It is driven by `UiInput`, built
+//! from the same movement `Coordinate`s and `Action`s gameplay receives, or from
+//! pointer positions in screen pixels, and reports what changed as `UiEvent`s.
+//!
+//! # Key Structures
+//! - **`Menu`**: A focusable vertical list of widgets, drawable as a `Renderable`.
+//! - **`Widget`**: A button, checkbox toggle, slider or text field inside a `Menu`.
+//! - **`UiInput`**: Navigation, confirmation and pointer input fed to a `Menu`.
+//! - **`UiEvent`**: What the user did to a widget, identified by its index.
+//!
+//! # Example Usage
+//! ```ignore
+//! let mut menu = Menu::new(font, (16, 16), 1)
+//!     .button("Play")
+//!     .checkbox("Fullscreen", false)
+//!     .slider("Volume", 0.8, 0.0..=1.0, 0.1)
+//!     .text_field("Name", "", 12);
+//!
+//! match menu.handle(UiInput::Confirm) {
+//!     Some(UiEvent::Pressed(0)) => start_game(),
+//!     Some(UiEvent::Changed(2, volume)) => set_volume(volume),
+//!     _ => (),
+//! }
+//!
+//! // Typed text only reaches the focused text field
+//! text_mode.enable(menu.wants_text());
+//! menu.type_text(&text_input);
+//! ```
+use std::{ops::RangeInclusive, sync::Arc};
+
+use winit::event::ElementState;
+
+use crate::{
+    font::BitmapFont,
+    input::{Action, GameInput, TextInput},
+    layout::Coordinate,
+    palette::{Color, GREEN, LIGHT_GRAY, MIDNIGHT},
+    renderer::DrawCmd,
+    window::Renderable,
+};
+
+/// Width of a slider's track in font pixels.
+const SLIDER_WIDTH: u32 = 32;
+
+/// A single interactive element of a `Menu`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Widget {
+    Button {
+        label: String,
+    },
+    Checkbox {
+        label: String,
+        checked: bool,
+    },
+    Slider {
+        label: String,
+        value: f32,
+        range: RangeInclusive<f32>,
+        step: f32,
+    },
+    TextField {
+        label: String,
+        text: String,
+        /// Characters the text is limited to.
+        max_len: usize,
+        /// Text an IME is still composing, shown after `text`.
+        composing: String,
+    },
+}
+impl Widget {
+    fn label(&self) -> &str {
+        match self {
+            Widget::Button { label }
+            | Widget::Checkbox { label, .. }
+            | Widget::Slider { label, .. }
+            | Widget::TextField { label, .. } => label,
+        }
+    }
+    fn label_mut(&mut self) -> &mut String {
+        match self {
+            Widget::Button { label }
+            | Widget::Checkbox { label, .. }
+            | Widget::Slider { label, .. }
+            | Widget::TextField { label, .. } => label,
+        }
+    }
+}
+
+/// Input understood by a `Menu`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UiInput {
+    /// Moves focus vertically or adjusts the focused slider horizontally.
+    Navigate(Coordinate),
+    /// Activates the focused widget.
+    Confirm,
+    /// Leaves the menu.
+    Back,
+    /// Focuses the widget under a pointer at the given screen pixel.
+    Point(i32, i32),
+    /// Focuses and activates the widget under a pointer at the given screen pixel.
+    Click(i32, i32),
+}
+impl UiInput {
+    /// The menu input for a pressed `MenuConfirm` or `MenuBack` action.
+    pub fn from_action(action: &Action) -> Option<Self> {
+        match (action.input, action.state) {
+            (GameInput::MenuConfirm, ElementState::Pressed) => Some(UiInput::Confirm),
+            (GameInput::MenuBack, ElementState::Pressed) => Some(UiInput::Back),
+            _ => None,
+        }
+    }
+}
+impl From<Coordinate> for UiInput {
+    fn from(value: Coordinate) -> Self {
+        UiInput::Navigate(value)
+    }
+}
+
+/// A change made to a `Menu`'s widgets, identified by the widget's index.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UiEvent {
+    Pressed(usize),
+    Toggled(usize, bool),
+    Changed(usize, f32),
+    /// The text of a text field changed.
+    Edited(usize),
+    Back,
+}
+
+/// A vertical list of widgets with a single focused entry.
+pub struct Menu {
+    font: Arc<BitmapFont>,
+    position: (i32, i32),
+    scale: u32,
+    /// Pixels between two rows at scale `1`.
+    spacing: u32,
+    widgets: Vec<Widget>,
+    focused: usize,
+}
+impl Menu {
+    /// Creates an empty menu with its top left corner at `position`.
+    pub fn new(font: Arc<BitmapFont>, position: (i32, i32), scale: u32) -> Self {
+        Self {
+            font,
+            position,
+            scale,
+            spacing: 2,
+            widgets: Vec::new(),
+            focused: 0,
+        }
+    }
+    pub fn spacing(mut self, spacing: u32) -> Self {
+        self.spacing = spacing;
+        self
+    }
+    pub fn button(mut self, label: impl Into<String>) -> Self {
+        self.widgets.push(Widget::Button {
+            label: label.into(),
+        });
+        self
+    }
+    pub fn checkbox(mut self, label: impl Into<String>, checked: bool) -> Self {
+        self.widgets.push(Widget::Checkbox {
+            label: label.into(),
+            checked,
+        });
+        self
+    }
+    /// Adds a slider moving by `step` within `range`, which may be given
+    /// either way round.
+    pub fn slider(
+        mut self,
+        label: impl Into<String>,
+        value: f32,
+        range: RangeInclusive<f32>,
+        step: f32,
+    ) -> Self {
+        let (start, end) = range.into_inner();
+        let (start, end) = (start.min(end), start.max(end));
+        self.widgets.push(Widget::Slider {
+            label: label.into(),
+            value: value.max(start).min(end),
+            range: start..=end,
+            step,
+        });
+        self
+    }
+    /// Adds a text field holding at most `max_len` characters.
+    pub fn text_field(
+        mut self,
+        label: impl Into<String>,
+        text: impl Into<String>,
+        max_len: usize,
+    ) -> Self {
+        self.widgets.push(Widget::TextField {
+            label: label.into(),
+            text: text.into().chars().take(max_len).collect(),
+            max_len,
+            composing: String::new(),
+        });
+        self
+    }
+    pub fn widgets(&self) -> &[Widget] {
+        &self.widgets
+    }
+    pub fn focused(&self) -> usize {
+        self.focused
+    }
+    /// Relabels the widget at `index`, e.g. a button showing a changing count.
+    pub fn set_label(&mut self, index: usize, label: impl Into<String>) {
+        if let Some(widget) = self.widgets.get_mut(index) {
+            *widget.label_mut() = label.into();
+        }
+    }
+    /// Applies a single input, returning what changed if anything.
+    pub fn handle(&mut self, input: UiInput) -> Option<UiEvent> {
+        if self.widgets.is_empty() {
+            return (input == UiInput::Back).then_some(UiEvent::Back);
+        }
+        match input {
+            UiInput::Navigate(Coordinate { y, .. }) if y != 0.0 => {
+                let len = self.widgets.len();
+                self.focused = if y < 0.0 {
+                    (self.focused + len - 1) % len
+                } else {
+                    (self.focused + 1) % len
+                };
+                None
+            }
+            UiInput::Navigate(Coordinate { x, .. }) if x != 0.0 => {
+                let index = self.focused;
+                match &mut self.widgets[index] {
+                    Widget::Slider {
+                        value, range, step, ..
+                    } => {
+                        let moved = (*value + step.copysign(x)).clamp(*range.start(), *range.end());
+                        set_value(value, moved).then_some(UiEvent::Changed(index, *value))
+                    }
+                    _ => None,
+                }
+            }
+            UiInput::Navigate(_) => None,
+            UiInput::Confirm => self.activate(self.focused),
+            UiInput::Back => Some(UiEvent::Back),
+            UiInput::Point(x, y) => {
+                if let Some(index) = self.row_at(x, y) {
+                    self.focused = index;
+                }
+                None
+            }
+            UiInput::Click(x, y) => {
+                let index = self.row_at(x, y)?;
+                self.focused = index;
+                let control_x = self.control_x();
+                let track = (SLIDER_WIDTH * self.scale) as f32;
+                match &mut self.widgets[index] {
+                    // Jump straight to the clicked spot of the track
+                    Widget::Slider {
+                        value, range, step, ..
+                    } => {
+                        let t = ((x - control_x) as f32 / track).clamp(0.0, 1.0);
+                        let raw = range.start() + t * (range.end() - range.start());
+                        let snapped =
+                            range.start() + ((raw - range.start()) / *step).round() * *step;
+                        let snapped = snapped.clamp(*range.start(), *range.end());
+                        set_value(value, snapped).then_some(UiEvent::Changed(index, *value))
+                    }
+                    _ => self.activate(index),
+                }
+            }
+        }
+    }
+    /// Whether a text field is focused, i.e. typed keys should be delivered
+    /// as text rather than gameplay input.
+    pub fn wants_text(&self) -> bool {
+        matches!(
+            self.widgets.get(self.focused),
+            Some(Widget::TextField { .. })
+        )
+    }
+    /// Types into the focused text field, returning what changed if anything.
+    pub fn type_text(&mut self, input: &TextInput) -> Option<UiEvent> {
+        let index = self.focused;
+        let Some(Widget::TextField {
+            text,
+            max_len,
+            composing,
+            ..
+        }) = self.widgets.get_mut(index)
+        else {
+            return None;
+        };
+        let before = text.clone();
+        match input {
+            TextInput::Insert(typed) => {
+                composing.clear();
+                let room = max_len.saturating_sub(text.chars().count());
+                text.extend(typed.chars().take(room));
+            }
+            TextInput::Compose(typed) => *composing = typed.clone(),
+            TextInput::Backspace => {
+                text.pop();
+            }
+        }
+        (*text != before).then_some(UiEvent::Edited(index))
+    }
+    fn activate(&mut self, index: usize) -> Option<UiEvent> {
+        match &mut self.widgets[index] {
+            Widget::Button { .. } => Some(UiEvent::Pressed(index)),
+            Widget::Checkbox { checked, .. } => {
+                *checked = !*checked;
+                Some(UiEvent::Toggled(index, *checked))
+            }
+            Widget::Slider { .. } => None,
+            // Confirming a text field submits it
+            Widget::TextField { .. } => Some(UiEvent::Pressed(index)),
+        }
+    }
+    fn row_height(&self) -> i32 {
+        (self.font.line_height(self.scale) + self.spacing * self.scale) as i32
+    }
+    /// Where the checkbox and slider controls start, right of the widest label.
+    fn control_x(&self) -> i32 {
+        let labels = self
+            .widgets
+            .iter()
+            .map(|w| self.font.measure(w.label(), self.scale).0)
+            .max()
+            .unwrap_or_default();
+        self.position.0 + (labels + 4 * self.scale) as i32
+    }
+    /// The widget whose row contains the given screen pixel.
+    fn row_at(&self, x: i32, y: i32) -> Option<usize> {
+        let right = self.control_x() + (SLIDER_WIDTH * self.scale) as i32;
+        let height = self.row_height();
+        if x < self.position.0 || x >= right || y < self.position.1 || height <= 0 {
+            return None;
+        }
+        let row = ((y - self.position.1) / height) as usize;
+        let inside = (y - self.position.1) % height < self.font.line_height(self.scale) as i32;
+        (row < self.widgets.len() && inside).then_some(row)
+    }
+    /// Queues every widget onto a batch of draw commands.
+    pub fn commands<'a>(&'a self, batch: &mut Vec<DrawCmd<'a>>) {
+        let control_x = self.control_x();
+        let line = self.font.line_height(self.scale);
+        for (i, widget) in self.widgets.iter().enumerate() {
+            let color = if i == self.focused { GREEN } else { LIGHT_GRAY };
+            let top = self.position.1 + i as i32 * self.row_height();
+            self.font.commands(
+                widget.label(),
+                (self.position.0, top),
+                color,
+                self.scale,
+                batch,
+            );
+            match widget {
+                Widget::Button { .. } => (),
+                Widget::Checkbox { checked, .. } => {
+                    let side = line.saturating_sub(2 * self.scale).max(3);
+                    let top = top + (line.saturating_sub(side) / 2) as i32;
+                    rect(batch, (control_x, top), side, side, self.scale, color);
+                    if *checked {
+                        let inset = self.scale;
+                        let inner = side.saturating_sub(2 * inset);
+                        batch.push(DrawCmd::Rect {
+                            start: (control_x + inset as i32, top + inset as i32),
+                            width: inner,
+                            height: inner,
+                            color,
+                        });
+                    }
+                }
+                Widget::Slider { value, range, .. } => {
+                    let width = SLIDER_WIDTH * self.scale;
+                    let span = range.end() - range.start();
+                    let ratio = if span > 0.0 {
+                        (value - range.start()) / span
+                    } else {
+                        0.0
+                    };
+                    let filled = ((width as f32 * ratio).round() as u32).min(width);
+                    let y = top + ((line - self.scale) / 2) as i32;
+                    for row in 0..self.scale as i32 {
+                        if filled > 0 {
+                            batch.push(DrawCmd::Span {
+                                start: (control_x, y + row),
+                                len: filled,
+                                color,
+                            });
+                        }
+                        if filled < width {
+                            batch.push(DrawCmd::Span {
+                                start: (control_x + filled as i32, y + row),
+                                len: width - filled,
+                                color: MIDNIGHT,
+                            });
+                        }
+                    }
+                }
+                Widget::TextField {
+                    text, composing, ..
+                } => {
+                    // A cursor marks the field being typed into
+                    let cursor = if i == self.focused { "_" } else { "" };
+                    self.font.commands(
+                        &format!("{text}{composing}{cursor}"),
+                        (control_x, top),
+                        color,
+                        self.scale,
+                        batch,
+                    );
+                }
+            }
+        }
+    }
+}
+impl Renderable for Menu {
+    fn commands<'a>(&'a self, batch: &mut Vec<DrawCmd<'a>>) {
+        Menu::commands(self, batch)
+    }
+}
+
+/// Stores `new` in `value`, returning whether it changed.
+fn set_value(value: &mut f32, new: f32) -> bool {
+    let changed = *value != new;
+    *value = new;
+    changed
+}
+
+/// Queues a `thickness` wide outline of a `width` by `height` rectangle.
+fn rect(
+    batch: &mut Vec<DrawCmd>,
+    (x, y): (i32, i32),
+    width: u32,
+    height: u32,
+    thickness: u32,
+    color: Color,
+) {
+    let thickness = thickness.min(width).min(height);
+    for row in 0..height {
+        if row < thickness || row >= height - thickness {
+            batch.push(DrawCmd::Span {
+                start: (x, y + row as i32),
+                len: width,
+                color,
+            });
+        } else {
+            batch.push(DrawCmd::Span {
+                start: (x, y + row as i32),
+                len: thickness,
+                color,
+            });
+            batch.push(DrawCmd::Span {
+                start: (x + (width - thickness) as i32, y + row as i32),
+                len: thickness,
+                color,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{prelude::*, window::HeadlessScreen};
+
+    // Every glyph is a solid 4x6 block advancing by 5
+    const FONT: &str = "\
+STARTFONT 2.1
+FONT_ASCENT 6
+FONT_DESCENT 0
+STARTCHAR A
+ENCODING 65
+DWIDTH 5 0
+BBX 4 6 0 0
+BITMAP
+F0
+F0
+F0
+F0
+F0
+F0
+ENDCHAR
+ENDFONT
+";
+
+    fn menu() -> Menu {
+        let font = Arc::new(BitmapFont::from_bdf(FONT).unwrap());
+        Menu::new(font, (0, 0), 1)
+            .button("A")
+            .checkbox("AA", false)
+            .slider("A", 0.5, 0.0..=1.0, 0.25)
+    }
+
+    fn down() -> UiInput {
+        Coordinate { x: 0.0, y: 1.0 }.into()
+    }
+
+    #[test]
+    fn test_keyboard_navigation() {
+        let mut menu = menu();
+
+        assert_eq!(menu.handle(UiInput::Confirm), Some(UiEvent::Pressed(0)));
+        menu.handle(down());
+        assert_eq!(
+            menu.handle(UiInput::Confirm),
+            Some(UiEvent::Toggled(1, true))
+        );
+        menu.handle(down());
+
+        // Sliders move by their step and stop at the ends
+        let right = Coordinate { x: 1.0, y: 0.0 }.into();
+        assert_eq!(menu.handle(right), Some(UiEvent::Changed(2, 0.75)));
+        assert_eq!(menu.handle(right), Some(UiEvent::Changed(2, 1.0)));
+        assert_eq!(menu.handle(right), None);
+
+        // Focus wraps around
+        menu.handle(down());
+        assert_eq!(menu.focused(), 0);
+        menu.handle(Coordinate { x: 0.0, y: -1.0 }.into());
+        assert_eq!(menu.focused(), 2);
+        assert_eq!(menu.handle(UiInput::Back), Some(UiEvent::Back));
+    }
+
+    #[test]
+    fn test_actions_map_to_ui_input() {
+        let press = |input| Action {
+            input,
+            state: ElementState::Pressed,
+        };
+        assert_eq!(
+            UiInput::from_action(&press(GameInput::MenuConfirm)),
+            Some(UiInput::Confirm)
+        );
+        assert_eq!(
+            UiInput::from_action(&press(GameInput::MenuBack)),
+            Some(UiInput::Back)
+        );
+        assert_eq!(UiInput::from_action(&press(GameInput::PlayerDash)), None);
+    }
+
+    #[test]
+    fn test_pointer_input() {
+        let mut menu = menu();
+
+        // Rows are 8 pixels apart with controls right of the 10 pixel wide labels
+        menu.handle(UiInput::Point(1, 9));
+        assert_eq!(menu.focused(), 1);
+        assert_eq!(menu.handle(UiInput::Point(1, 7)), None);
+        assert_eq!(menu.focused(), 1);
+
+        assert_eq!(
+            menu.handle(UiInput::Click(15, 17)),
+            Some(UiEvent::Changed(2, 0.0))
+        );
+        assert_eq!(
+            menu.handle(UiInput::Click(38, 17)),
+            Some(UiEvent::Changed(2, 0.75))
+        );
+        assert_eq!(menu.handle(UiInput::Click(1, 1)), Some(UiEvent::Pressed(0)));
+        assert_eq!(menu.handle(UiInput::Click(100, 1)), None);
+    }
+
+    #[test]
+    fn test_degenerate_menus_dont_panic() {
+        let font = Arc::new(BitmapFont::from_bdf(FONT).unwrap());
+        // Nothing is tall enough to point at, nor to fit a whole checkbox
+        let mut menu =
+            Menu::new(font, (0, 0), 0)
+                .checkbox("A", true)
+                .slider("A", 5.0, 1.0..=0.0, 0.25);
+        assert_eq!(menu.handle(UiInput::Click(0, 0)), None);
+        menu.commands(&mut Vec::new());
+
+        menu.handle(down());
+        assert_eq!(
+            menu.handle(Coordinate { x: -1.0, y: 0.0 }.into()),
+            Some(UiEvent::Changed(1, 0.75))
+        );
+    }
+
+    #[test]
+    fn test_draw_menu() {
+        let mut menu = menu();
+        menu.handle(down());
+        menu.handle(UiInput::Confirm);
+
+        let mut screen = HeadlessScreen::new(64, 24);
+        let mut batch = vec![DrawCmd::Clear];
+        menu.commands(&mut batch);
+        screen.draw_batch(&batch).unwrap();
+
+        let rgba = |x, y| screen.pixel(x, y).map(|c| c.to_rgba());
+        assert_eq!(rgba(0, 0), Some(LIGHT_GRAY.to_rgba()));
+        // Focused label and its filled checkbox
+        assert_eq!(rgba(0, 8), Some(GREEN.to_rgba()));
+        assert_eq!(rgba(15, 9), Some(GREEN.to_rgba()));
+        assert_eq!(rgba(17, 11), Some(GREEN.to_rgba()));
+        // Half filled slider track
+        assert_eq!(rgba(15, 18), Some(LIGHT_GRAY.to_rgba()));
+        assert_eq!(rgba(40, 18), Some(MIDNIGHT.to_rgba()));
+    }
+
+    #[test]
+    fn test_text_field() {
+        let mut menu = menu().text_field("A", "AAAA", 3);
+        assert!(!menu.wants_text());
+        menu.handle(Coordinate { x: 0.0, y: -1.0 }.into());
+        assert!(menu.wants_text());
+        assert_eq!(menu.widgets()[3], text_field("AAA", ""));
+
+        assert_eq!(
+            menu.type_text(&TextInput::Backspace),
+            Some(UiEvent::Edited(3))
+        );
+        assert_eq!(menu.type_text(&TextInput::Compose("A".into())), None);
+        assert_eq!(menu.widgets()[3], text_field("AA", "A"));
+        // Committing ends the composition and stops at the limit
+        assert_eq!(
+            menu.type_text(&TextInput::Insert("AAA".into())),
+            Some(UiEvent::Edited(3))
+        );
+        assert_eq!(menu.widgets()[3], text_field("AAA", ""));
+        assert_eq!(menu.handle(UiInput::Confirm), Some(UiEvent::Pressed(3)));
+
+        // Other widgets ignore text
+        menu.handle(down());
+        assert_eq!(menu.type_text(&TextInput::Backspace), None);
+    }
+
+    fn text_field(text: &str, composing: &str) -> Widget {
+        Widget::TextField {
+            label: "A".into(),
+            text: text.into(),
+            max_len: 3,
+            composing: composing.into(),
+        }
+    }
+}
@@ -0,0 +1,533 @@
+//! How a screen's pixels are fit into the window presenting them.
+//!
+//! A screen has as many pixels as the game is drawn at, which rarely divides
+//! evenly into the window's physical pixels, especially while the camera zooms
+//! in with fewer of them. A `Viewport` decides where on the window's surface
+//! the screen is shown and how big: scaled by whole numbers only so every
+//! pixel stays the same size, stretched over the whole surface, or scaled as
+//! far as it fits, with black bars around whatever the screen doesn't cover.
+//!
+//! The same mapping is undone to find the screen pixel under the cursor.
+//!
+//! Windows keep their `Pixels` buffer at the screen's size and leave the
+//! scaling to the GPU with a `FitRenderer`, while `Viewport::present` scales
+//! on the CPU for surfaces that are only memory.
+//!
+//! # Key Structures
+//! - **`Fit`**: How the screen is scaled into the surface.
+//! - **`Viewport`**: Where the screen is shown on the surface.
+//! - **`FitRenderer`**: Draws a `Pixels` buffer where its viewport shows it.
+//!
+//! # Example Usage
+//! ```ignore
+//! let mut viewport = Viewport::new(320, 180).fit(Fit::Letterbox);
+//! viewport.set_surface(1920, 1200);
+//!
+//! let renderer = FitRenderer::new(&pixels, &viewport);
+//! pixels.render_with(|encoder, target, _| {
+//!     renderer.render(encoder, target);
+//!     Ok(())
+//! })?;
+//! let pixel = viewport.to_screen(cursor.x, cursor.y);
+//! ```
+use pixels::{
+    wgpu::{self, util::DeviceExt},
+    Pixels,
+};
+
+use crate::{layout::Coordinate, palette::BLACK, renderer::fill};
+
+/// How the screen is scaled into the surface.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Fit {
+    /// Scales by the largest whole number that fits, keeping every pixel the
+    /// same size, with bars around it.
+    #[default]
+    Integer,
+    /// Covers the whole surface, whatever it does to the screen's proportions.
+    Stretch,
+    /// Scales as far as it fits while keeping the screen's proportions, with
+    /// bars on the sides it doesn't reach.
+    Letterbox,
+}
+
+/// Where on the surface the screen is shown, in physical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Area {
+    /// Negative when the screen is bigger than the surface
+    pub x: i64,
+    pub y: i64,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Where the screen is shown on the surface and how it's scaled.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Viewport {
+    size: (u32, u32),
+    surface: (u32, u32),
+    fit: Fit,
+    /// The screen column every surface column shows, if any
+    columns: Vec<Option<usize>>,
+}
+impl Viewport {
+    /// Shows a `width` by `height` screen on a surface of the same size.
+    pub fn new(width: u32, height: u32) -> Self {
+        let mut viewport = Self {
+            size: (width, height),
+            surface: (width, height),
+            fit: Fit::default(),
+            columns: Vec::new(),
+        };
+        viewport.map_columns();
+        viewport
+    }
+    pub fn fit(mut self, fit: Fit) -> Self {
+        self.set_fit(fit);
+        self
+    }
+    pub fn set_fit(&mut self, fit: Fit) {
+        self.fit = fit;
+        self.map_columns();
+    }
+    /// Shows a screen of another size, e.g. after its resolution changed.
+    pub fn set_size(&mut self, width: u32, height: u32) {
+        self.size = (width, height);
+        self.map_columns();
+    }
+    /// Fits the screen into a surface of another size.
+    pub fn set_surface(&mut self, width: u32, height: u32) {
+        self.surface = (width, height);
+        self.map_columns();
+    }
+    pub fn surface(&self) -> (u32, u32) {
+        self.surface
+    }
+    /// Where on the surface the screen is shown.
+    pub fn area(&self) -> Area {
+        let (width, height) = (self.size.0.max(1) as f64, self.size.1.max(1) as f64);
+        let (surface_width, surface_height) = (self.surface.0 as f64, self.surface.1 as f64);
+        let fitting = (surface_width / width).min(surface_height / height);
+        let (scale_x, scale_y) = match self.fit {
+            Fit::Stretch => (surface_width / width, surface_height / height),
+            Fit::Letterbox => (fitting, fitting),
+            // Too small a surface crops the screen rather than shrinking it
+            Fit::Integer => {
+                let scale = fitting.floor().max(1.0);
+                (scale, scale)
+            }
+        };
+        let (area_width, area_height) = ((width * scale_x).round(), (height * scale_y).round());
+        Area {
+            x: ((surface_width - area_width) / 2.0).floor() as i64,
+            y: ((surface_height - area_height) / 2.0).floor() as i64,
+            width: area_width as u32,
+            height: area_height as u32,
+        }
+    }
+    /// The screen pixel under the surface's physical pixel at `x` and `y`.
+    ///
+    /// Positions on the bars land outside of the screen
+    pub fn to_screen(&self, x: f64, y: f64) -> Coordinate {
+        let area = self.area();
+        Coordinate {
+            x: ((x - area.x as f64) * self.size.0 as f64 / area.width.max(1) as f64) as f32,
+            y: ((y - area.y as f64) * self.size.1 as f64 / area.height.max(1) as f64) as f32,
+        }
+    }
+    /// Scales the screen's RGBA `frame` onto the RGBA `surface`, filling
+    /// around it with black.
+    ///
+    /// Frames not the size of the screen leave the surface black
+    pub fn present(&self, frame: &[u8], surface: &mut [u8]) {
+        let area = self.area();
+        let (width, height) = (self.size.0 as usize, self.size.1 as usize);
+        let surface_width = self.surface.0 as usize;
+        if frame.len() != width * height * 4
+            || surface_width == 0
+            || area.width == 0
+            || area.height == 0
+        {
+            return fill(surface, BLACK.to_rgba());
+        }
+        if self.surface == self.size && surface.len() == frame.len() {
+            return surface.copy_from_slice(frame);
+        }
+        let line = surface_width * 4;
+        let mut previous: Option<(usize, usize)> = None;
+        for row in 0..surface.len() / line {
+            let start = row * line;
+            let inside = row as i64 - area.y;
+            if !(0..area.height as i64).contains(&inside) {
+                fill(&mut surface[start..start + line], BLACK.to_rgba());
+                continue;
+            }
+            let source = inside as usize * height / area.height as usize;
+            // Rows scaled up from the same screen row are copied whole
+            if let Some((shown, _)) = previous.filter(|&(_, shown)| shown == source) {
+                surface.copy_within(shown * line..(shown + 1) * line, start);
+                continue;
+            }
+            previous = Some((row, source));
+            let source = &frame[source * width * 4..(source + 1) * width * 4];
+            let pixels = surface[start..start + line].chunks_exact_mut(4);
+            for (dst, column) in pixels.zip(&self.columns) {
+                match column {
+                    Some(column) => dst.copy_from_slice(&source[column * 4..column * 4 + 4]),
+                    None => dst.copy_from_slice(&BLACK.to_rgba()),
+                }
+            }
+        }
+    }
+    /// Works out which screen column every surface column shows, once per
+    /// change rather than every frame.
+    fn map_columns(&mut self) {
+        let area = self.area();
+        let width = self.size.0 as usize;
+        self.columns = (0..self.surface.0 as i64)
+            .map(|column| {
+                let inside = column - area.x;
+                (0..area.width as i64)
+                    .contains(&inside)
+                    .then(|| inside as usize * width / area.width as usize)
+            })
+            .collect();
+    }
+    /// How the GPU moves and scales a quad covering the surface onto the
+    /// area, and the part of the surface it's clipped to, if any is left.
+    fn transform(&self) -> ([f32; 16], Option<Area>) {
+        let area = self.area();
+        let (surface_width, surface_height) =
+            (self.surface.0.max(1) as f32, self.surface.1.max(1) as f32);
+        let (x, y) = (area.x as f32, area.y as f32);
+        let (width, height) = (area.width as f32, area.height as f32);
+        let (scale_x, scale_y) = (width / surface_width, height / surface_height);
+        // The surface's y axis points down while the GPU's points up
+        let shift_x = (2.0 * x + width) / surface_width - 1.0;
+        let shift_y = 1.0 - (2.0 * y + height) / surface_height;
+        #[rustfmt::skip]
+        let transform = [
+            scale_x, 0.0,     0.0, 0.0,
+            0.0,     scale_y, 0.0, 0.0,
+            0.0,     0.0,     1.0, 0.0,
+            shift_x, shift_y, 0.0, 1.0,
+        ];
+        let (left, top) = (area.x.max(0), area.y.max(0));
+        let right = (area.x + area.width as i64).min(self.surface.0 as i64);
+        let bottom = (area.y + area.height as i64).min(self.surface.1 as i64);
+        let clip = (right > left && bottom > top).then(|| Area {
+            x: left,
+            y: top,
+            width: (right - left) as u32,
+            height: (bottom - top) as u32,
+        });
+        (transform, clip)
+    }
+}
+
+/// Draws a `Pixels` buffer the size of the screen onto its surface where a
+/// `Viewport` shows it, scaled on the GPU, clearing around it to black.
+///
+/// Stands in for `Pixels`' own renderer, which only scales by whole numbers.
+/// Made again whenever the buffer is resized since it samples its texture
+pub struct FitRenderer {
+    vertices: wgpu::Buffer,
+    uniforms: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    pipeline: wgpu::RenderPipeline,
+    clip: Option<Area>,
+}
+impl FitRenderer {
+    pub fn new(pixels: &Pixels, viewport: &Viewport) -> Self {
+        let device = &pixels.context().device;
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("fit_renderer_shader"),
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+        // Nearest neighbour keeps every pixel sharp however far it's scaled
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("fit_renderer_sampler"),
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+        // One triangle covering the whole surface
+        let corners: [f32; 6] = [-1.0, -1.0, 3.0, -1.0, -1.0, 3.0];
+        let vertices = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("fit_renderer_vertices"),
+            contents: &to_bytes(&corners),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let (transform, clip) = viewport.transform();
+        let uniforms = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("fit_renderer_transform"),
+            contents: &to_bytes(&transform),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("fit_renderer_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(16 * 4),
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let texture = pixels
+            .texture()
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("fit_renderer_bind_group"),
+            layout: &layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&texture),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: uniforms.as_entire_binding(),
+                },
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("fit_renderer_pipeline_layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("fit_renderer_pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: "vs_main",
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: 2 * 4,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &[wgpu::VertexAttribute {
+                        format: wgpu::VertexFormat::Float32x2,
+                        offset: 0,
+                        shader_location: 0,
+                    }],
+                }],
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: pixels.render_texture_format(),
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        });
+        Self {
+            vertices,
+            uniforms,
+            bind_group,
+            pipeline,
+            clip,
+        }
+    }
+    /// Moves the screen to where `viewport` shows it now, e.g. after the
+    /// surface was resized or the fit changed.
+    pub fn update(&mut self, pixels: &Pixels, viewport: &Viewport) {
+        let (transform, clip) = viewport.transform();
+        let queue = &pixels.context().queue;
+        queue.write_buffer(&self.uniforms, 0, &to_bytes(&transform));
+        self.clip = clip;
+    }
+    /// Draws the buffer's texture onto `target`, e.g. from `Pixels::render_with`.
+    pub fn render(&self, encoder: &mut wgpu::CommandEncoder, target: &wgpu::TextureView) {
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("fit_renderer_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        // Nothing but bars when the screen is entirely off the surface
+        let Some(clip) = self.clip else {
+            return;
+        };
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        pass.set_vertex_buffer(0, self.vertices.slice(..));
+        pass.set_scissor_rect(clip.x as u32, clip.y as u32, clip.width, clip.height);
+        pass.draw(0..3, 0..1);
+    }
+}
+
+/// Stretches the texture over the triangle moved onto the viewport's area.
+const SHADER: &str = "
+struct Locals {
+    transform: mat4x4<f32>,
+}
+@group(0) @binding(2) var<uniform> locals: Locals;
+@group(0) @binding(0) var color: texture_2d<f32>;
+@group(0) @binding(1) var color_sampler: sampler;
+
+struct VertexOutput {
+    @location(0) tex_coord: vec2<f32>,
+    @builtin(position) position: vec4<f32>,
+}
+
+@vertex
+fn vs_main(@location(0) position: vec2<f32>) -> VertexOutput {
+    var out: VertexOutput;
+    out.tex_coord = fma(position, vec2<f32>(0.5, -0.5), vec2<f32>(0.5, 0.5));
+    out.position = locals.transform * vec4<f32>(position, 0.0, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(@location(0) tex_coord: vec2<f32>) -> @location(0) vec4<f32> {
+    return textureSample(color, color_sampler, tex_coord);
+}
+";
+
+fn to_bytes(floats: &[f32]) -> Vec<u8> {
+    floats.iter().flat_map(|f| f.to_ne_bytes()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit_modes() {
+        let mut viewport = Viewport::new(160, 90);
+        viewport.set_surface(500, 300);
+        assert_eq!(
+            viewport.area(),
+            Area {
+                x: 10,
+                y: 15,
+                width: 480,
+                height: 270
+            }
+        );
+        viewport.set_fit(Fit::Letterbox);
+        assert_eq!(
+            viewport.area(),
+            Area {
+                x: 0,
+                y: 9,
+                width: 500,
+                height: 281
+            }
+        );
+        viewport.set_fit(Fit::Stretch);
+        assert_eq!(
+            viewport.area(),
+            Area {
+                x: 0,
+                y: 0,
+                width: 500,
+                height: 300
+            }
+        );
+
+        // The cursor maps back to the pixel it's over
+        viewport.set_fit(Fit::Integer);
+        let corner = viewport.to_screen(10.0 + 3.0 * 4.5, 15.0 + 3.0 * 2.0);
+        assert_eq!((corner.x.floor(), corner.y.floor()), (4.0, 2.0));
+        assert!(viewport.to_screen(5.0, 5.0).x < 0.0);
+    }
+
+    #[test]
+    fn test_present_scales_with_bars() {
+        // A red pixel next to a green one, shown three times as big in a
+        // surface too tall for it
+        let frame = [255, 0, 0, 255, 0, 255, 0, 255];
+        let mut viewport = Viewport::new(2, 1);
+        viewport.set_surface(6, 5);
+        let mut surface = vec![7; 6 * 5 * 4];
+        viewport.present(&frame, &mut surface);
+
+        let rows: Vec<Vec<u8>> = surface
+            .chunks_exact(6 * 4)
+            .map(|row| {
+                row.chunks_exact(4)
+                    .map(|p| p[0] / 255 + p[1] / 255 * 2)
+                    .collect()
+            })
+            .collect();
+        let shown = [1, 1, 1, 2, 2, 2];
+        assert_eq!(rows, [[0; 6], shown, shown, shown, [0; 6]]);
+        assert!(surface.chunks_exact(4).all(|p| p[3] == 255));
+
+        // A frame of the wrong size is only shown as black
+        viewport.present(&frame[..4], &mut surface);
+        assert!(surface.chunks_exact(4).all(|p| p == [0, 0, 0, 255]));
+        Viewport::new(2, 1).present(&frame, &mut [7; 4]);
+    }
+
+    #[test]
+    fn test_transform_moves_quad_onto_area() {
+        let mut viewport = Viewport::new(2, 1);
+        viewport.set_surface(10, 8);
+        // Scaled 5 times across the whole width, from y 1 to 6
+        let (transform, clip) = viewport.transform();
+        assert_eq!(
+            (transform[0], transform[5], transform[12], transform[13]),
+            (1.0, 0.625, 0.0, 0.125)
+        );
+        assert_eq!(
+            clip,
+            Some(Area {
+                x: 0,
+                y: 1,
+                width: 10,
+                height: 5
+            })
+        );
+
+        // Cropped to the surface when it's too small to show the whole screen
+        viewport.set_surface(1, 1);
+        let cropped = viewport.transform().1;
+        assert_eq!(
+            cropped.map(|c| (c.x, c.y, c.width, c.height)),
+            Some((0, 0, 1, 1))
+        );
+        viewport.set_surface(0, 0);
+        assert_eq!(viewport.transform().1, None);
+    }
+}
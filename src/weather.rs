@@ -0,0 +1,207 @@
+//! Rain and snow falling in front of everything else.
+//!
+//! `Weather` is a screen-space layer of particles drawn over the world but
+//! under the HUD. Particles fall at their own speed, drift with the wind and
+//! come back in at the top once they leave the screen, so a fixed number of
+//! them keeps the whole screen covered. A level turns its weather on or off
+//! through `GameState::weather`.
+//!
+//! # Key Structures
+//! - **`Precipitation`**: What falls and how it looks.
+//! - **`Weather`**: The particles falling across the screen.
+//!
+//! # Example Usage
+//! ```ignore
+//! state.weather(Some(Weather::new(Precipitation::Rain, 192, 108, 80).wind(-30.0)));
+//!
+//! // Leaving the storm behind
+//! state.weather(None);
+//! ```
+use std::f32::consts::TAU;
+
+use crate::{
+    layout::Coordinate,
+    palette::{Color, LIGHT_GRAY},
+    renderer::DrawCmd,
+};
+
+const RAIN: Color = Color::RGB(110, 130, 190);
+
+/// What falls and how it looks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Precipitation {
+    /// Fast streaks slanted by the wind.
+    Rain,
+    /// Slow flakes swaying from side to side.
+    Snow,
+}
+impl Precipitation {
+    /// The slowest and fastest a particle falls in pixels per second.
+    fn speeds(&self) -> (f32, f32) {
+        match self {
+            Precipitation::Rain => (120.0, 180.0),
+            Precipitation::Snow => (12.0, 24.0),
+        }
+    }
+}
+
+/// A single raindrop or snowflake.
+#[derive(Debug, Clone, PartialEq)]
+struct Particle {
+    position: Coordinate,
+    speed: f32,
+    /// Where in its sway a snowflake starts
+    phase: f32,
+}
+
+/// Particles falling across a screen of `width` by `height` pixels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Weather {
+    precipitation: Precipitation,
+    width: f32,
+    height: f32,
+    /// Sideways drift in pixels per second.
+    wind: f32,
+    particles: Vec<Particle>,
+    /// Seconds since the weather started, for swaying
+    time: f32,
+    state: u32,
+}
+impl Weather {
+    /// Scatters `count` particles across the screen.
+    pub fn new(precipitation: Precipitation, width: u32, height: u32, count: usize) -> Self {
+        let mut weather = Self {
+            precipitation,
+            width: width as f32,
+            height: height as f32,
+            wind: 0.0,
+            particles: Vec::with_capacity(count),
+            time: 0.0,
+            state: 1,
+        };
+        for _ in 0..count {
+            let y = weather.random() * weather.height;
+            let particle = weather.spawn(y);
+            weather.particles.push(particle);
+        }
+        weather
+    }
+    pub fn wind(mut self, wind: f32) -> Self {
+        self.wind = wind;
+        self
+    }
+    pub fn precipitation(&self) -> Precipitation {
+        self.precipitation
+    }
+    /// The state of the random number generator particles are spawned with.
+    pub fn rng(&self) -> u32 {
+        self.state
+    }
+    pub fn set_rng(&mut self, state: u32) {
+        // Xorshift never leaves zero
+        self.state = state.max(1);
+    }
+    /// Lets every particle fall for `delta` seconds.
+    pub fn update(&mut self, delta: f32) {
+        self.time += delta;
+        for i in 0..self.particles.len() {
+            let particle = &mut self.particles[i];
+            particle.position.y += particle.speed * delta;
+            // Drifting off one side comes back in on the other
+            particle.position.x = (particle.position.x + self.wind * delta).rem_euclid(self.width);
+            if particle.position.y >= self.height {
+                self.particles[i] = self.spawn(0.0);
+            }
+        }
+    }
+    /// Queues every particle onto a batch of draw commands.
+    pub fn commands(&self, batch: &mut Vec<DrawCmd<'_>>) {
+        for particle in &self.particles {
+            let Coordinate { x, y } = particle.position;
+            match self.precipitation {
+                Precipitation::Rain => {
+                    // Streaks trail back along the way they fall
+                    let slant = self.wind / particle.speed;
+                    for i in 0..3 {
+                        batch.push(DrawCmd::Span {
+                            start: ((x - slant * i as f32) as i32, y as i32 - i),
+                            len: 1,
+                            color: RAIN,
+                        });
+                    }
+                }
+                Precipitation::Snow => {
+                    let sway = (self.time * 2.0 + particle.phase).sin() * 2.0;
+                    batch.push(DrawCmd::Span {
+                        start: ((x + sway) as i32, y as i32),
+                        len: 1,
+                        color: LIGHT_GRAY,
+                    });
+                }
+            }
+        }
+    }
+    fn spawn(&mut self, y: f32) -> Particle {
+        let (slowest, fastest) = self.precipitation.speeds();
+        Particle {
+            position: Coordinate {
+                x: self.random() * self.width,
+                y,
+            },
+            speed: slowest + self.random() * (fastest - slowest),
+            phase: self.random() * TAU,
+        }
+    }
+    /// A pseudo random number from `0.0` to `1.0`.
+    fn random(&mut self) -> f32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        self.state as f32 / u32::MAX as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_particles_fall_and_wrap() {
+        let mut weather = Weather::new(Precipitation::Rain, 40, 30, 20).wind(-500.0);
+        assert!(weather
+            .particles
+            .iter()
+            .all(|p| (0.0..30.0).contains(&p.position.y)));
+
+        let before = weather.particles[0].position.y;
+        weather.update(0.01);
+        assert!(weather.particles[0].position.y > before);
+
+        for _ in 0..100 {
+            weather.update(0.05);
+            assert!(weather.particles.iter().all(|p| {
+                (0.0..40.0).contains(&p.position.x) && (0.0..30.0).contains(&p.position.y)
+            }));
+        }
+        assert_eq!(weather.particles.len(), 20);
+    }
+
+    #[test]
+    fn test_weather_overlay() {
+        let mut screen = HeadlessScreen::new(40, 30);
+        let weather = Weather::new(Precipitation::Snow, 40, 30, 50);
+        let mut batch = vec![DrawCmd::Clear];
+        weather.commands(&mut batch);
+        screen.draw_batch(&batch).unwrap();
+
+        let [r, g, b, a] = LIGHT_GRAY.to_rgba();
+        let flake = Some(Color::RGBA(r, g, b, a));
+        let flakes = (0..40)
+            .flat_map(|x| (0..30).map(move |y| (x, y)))
+            .filter(|&(x, y)| screen.pixel(x, y) == flake)
+            .count();
+        assert!(flakes > 0);
+        assert_eq!(batch.len(), 51);
+    }
+}
@@ -7,26 +7,130 @@
 //!
 //! Key Types:
 //! - `GameWindow`: A window that integrates pixel rendering, supporting fixed sizes and pixel-perfect
-//! rendering, ideal for games or applications with low-resolution graphics.
+//!   rendering, ideal for games or applications with low-resolution graphics.
 //! - `Window`: A trait that abstracts common window operations, allowing different window types to
-//! conform to a unified API for interaction.
+//!   conform to a unified API for interaction.
 //! - `Screen`: A trait that allows manipulation of the screen's framebuffer, enabling pixel drawing
-//! and access to the window's dimensions.
+//!   and access to the window's dimensions.
+//! - `Renderable`: Content the event loop draws into a window's own screen on every redraw.
+//! - `RedrawRequest`: Hands a frame drawn on another thread to the event loop, which presents
+//!   it once the window is redrawn.
+//! - `WindowSettings`: The scale, windowed or fullscreen mode and monitor a `GameWindow` is shown
+//!   with, picked in the settings menu and applied live with `GameWindow::apply`.
+//! - `HeadlessScreen`: A `Screen` rendering into memory so the engine can run without a window,
+//!   with an inspectable buffer and an optional PNG dump (`png` feature).
 //!
 //! Errors:
 //! - `WindowError`: A custom error type that captures potential errors that can occur during window
-//! creation or pixel surface setup.
+//!   creation or pixel surface setup.
 
-use std::sync::{Arc, Mutex};
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+#[cfg(feature = "png")]
+use std::{fs::File, io::BufWriter, path::Path};
 
 use pixels::{Pixels, PixelsBuilder, SurfaceTexture};
 use thiserror::Error;
+use tracing::trace_span;
 use winit::{
     dpi::LogicalSize,
-    window::{WindowBuilder, WindowId},
+    window::{Fullscreen, WindowBuilder, WindowId},
+};
+
+use crate::{
+    event::EventHandler,
+    layout::Coordinate,
+    palette::{Color, BLACK},
+    renderer::{draw_all, Compositor, DrawCmd},
+    viewport::{Fit, FitRenderer, Viewport},
 };
 
-use crate::EventHandler;
+/// Size in logical window pixels of a single pixel of a `GameWindow`'s screen.
+pub(crate) const PIXEL_SCALE: f64 = 4.0;
+/// The smallest scale a window can be shown at.
+pub const MIN_SCALE: u32 = 1;
+/// The largest scale a window can be shown at.
+pub const MAX_SCALE: u32 = 6;
+
+/// The logical size of a window showing a screen `width` by `height` pixels,
+/// each `scale` logical pixels big.
+///
+/// The system scales it to the monitor's DPI, so the window looks the same
+/// size everywhere
+pub(crate) fn window_size(width: u32, height: u32, scale: f64) -> LogicalSize<f64> {
+    LogicalSize::new(width as f64 * scale, height as f64 * scale)
+}
+
+/// How a window sits on its monitor.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WindowMode {
+    /// A decorated window sized by its scale.
+    #[default]
+    Windowed,
+    /// A window without decorations covering the whole monitor.
+    Borderless,
+    /// Takes over the monitor at its largest video mode.
+    Fullscreen,
+}
+impl fmt::Display for WindowMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            WindowMode::Windowed => "Windowed",
+            WindowMode::Borderless => "Borderless",
+            WindowMode::Fullscreen => "Fullscreen",
+        })
+    }
+}
+
+/// How a `GameWindow` is shown, as picked in the settings menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowSettings {
+    /// Logical pixels each pixel of the screen is shown at while windowed,
+    /// kept between `MIN_SCALE` and `MAX_SCALE`.
+    pub scale: u32,
+    pub mode: WindowMode,
+    /// Index among the available monitors, the window's own when `None`.
+    pub monitor: Option<usize>,
+}
+impl Default for WindowSettings {
+    fn default() -> Self {
+        Self {
+            scale: PIXEL_SCALE as u32,
+            mode: WindowMode::default(),
+            monitor: None,
+        }
+    }
+}
+impl WindowSettings {
+    /// Steps to the next scale, wrapping around, e.g. for a menu button.
+    pub fn next_scale(&mut self) {
+        self.scale = self.scale.clamp(MIN_SCALE, MAX_SCALE) % MAX_SCALE + MIN_SCALE;
+    }
+    /// Steps to the next mode, wrapping around.
+    pub fn next_mode(&mut self) {
+        self.mode = match self.mode {
+            WindowMode::Windowed => WindowMode::Borderless,
+            WindowMode::Borderless => WindowMode::Fullscreen,
+            WindowMode::Fullscreen => WindowMode::Windowed,
+        };
+    }
+    /// Steps to the next of `monitors` available monitors, wrapping around.
+    pub fn next_monitor(&mut self, monitors: usize) {
+        self.monitor = Some(self.monitor.map_or(0, |m| m + 1) % monitors.max(1));
+    }
+}
+
+/// A window that can be shown as `WindowSettings` say, e.g. as picked in a
+/// `SettingsMenu`.
+pub trait Configurable {
+    /// Shows the window as `settings` say right away.
+    fn apply(&self, settings: &WindowSettings) -> Result<(), WindowError>;
+}
 
 /// Represents a generic abstraction over a window.
 ///
@@ -34,6 +138,12 @@ use crate::EventHandler;
 /// to expose a common interface for identification and interaction.
 pub trait Window {
     fn id(&self) -> WindowId;
+    /// Asks the system to send the window a redraw event.
+    fn request_redraw(&self);
+    /// Lets the system's input method compose text into the window.
+    fn set_ime_allowed(&self, allowed: bool);
+    /// Physical pixels per logical pixel of the monitor the window is on.
+    fn scale_factor(&self) -> f64;
 }
 
 /// A concrete implementation of the `Screen` trait backed by a pixel buffer.
@@ -42,10 +152,31 @@ pub trait Window {
 /// such as a game window or off-screen framebuffer. It maintains the dimensions
 /// of the render area and the actual `Pixels` surface used for rendering.
 ///
+/// All drawing goes into an off-screen `Compositor` which is only copied to
+/// the `Pixels` buffer on `render`. The buffer stays as big as the screen and
+/// is scaled to the window's physical pixels on the GPU where its `Viewport`
+/// says.
 pub struct GameWindowScreen {
     width: u32,
     height: u32,
+    /// The resolution the window was built for
+    layout: (u32, u32),
     surface: Pixels,
+    compositor: Compositor,
+    viewport: Viewport,
+    renderer: FitRenderer,
+    /// Logical window pixels each pixel of the layout is shown at
+    pixel_scale: f64,
+}
+impl GameWindowScreen {
+    /// Changes how the screen is scaled into its window.
+    pub fn set_fit(&mut self, fit: Fit) {
+        self.viewport.set_fit(fit);
+        self.renderer.update(&self.surface, &self.viewport);
+    }
+    pub fn viewport(&self) -> &Viewport {
+        &self.viewport
+    }
 }
 impl Screen for GameWindowScreen {
     fn width(&self) -> u32 {
@@ -55,21 +186,152 @@ impl Screen for GameWindowScreen {
         self.height
     }
     fn frame_buffer(&mut self) -> &mut [u8] {
-        self.surface.frame_mut()
+        self.compositor.buffer_mut()
     }
     fn clear(&mut self) -> Result<(), WindowError> {
-        let frame = self.surface.frame_mut();
-        // Clear the frame by setting all pixels to black (may flicker)
-        for pixel in frame.chunks_exact_mut(4) {
-            pixel.copy_from_slice(&[0, 0, 0, 255]); // RGBA black
+        // Only the off-screen frame is cleared so nothing is presented yet
+        self.compositor.clear(BLACK);
+        Ok(())
+    }
+    fn render(&mut self) -> Result<(), WindowError> {
+        self.compositor.present(self.surface.frame_mut());
+        let renderer = &self.renderer;
+        self.surface.render_with(|encoder, target, _| {
+            renderer.render(encoder, target);
+            Ok(())
+        })?;
+        Ok(())
+    }
+    fn resize_surface(&mut self, width: u32, height: u32) -> Result<(), WindowError> {
+        // Only the surface follows the window, the buffer keeps the screen's size
+        self.surface.resize_surface(width, height)?;
+        self.viewport.set_surface(width, height);
+        self.renderer.update(&self.surface, &self.viewport);
+        Ok(())
+    }
+    fn set_resolution(&mut self, width: u32, height: u32) -> Result<(), WindowError> {
+        if (width, height) == (self.width, self.height) {
+            return Ok(());
+        }
+        self.compositor = Compositor::new(width, height);
+        self.surface.resize_buffer(width, height)?;
+        self.viewport.set_size(width, height);
+        // The buffer's texture was replaced along with it
+        self.renderer = FitRenderer::new(&self.surface, &self.viewport);
+        self.width = width;
+        self.height = height;
+        Ok(())
+    }
+    fn layout_size(&self) -> (u32, u32) {
+        self.layout
+    }
+    fn to_screen(&self, x: f64, y: f64) -> Option<Coordinate> {
+        Some(self.viewport.to_screen(x, y))
+    }
+    fn pixel_scale(&self) -> f64 {
+        self.pixel_scale
+    }
+}
+
+/// A `Screen` that renders into memory instead of a window.
+///
+/// Runs the engine where no display is available such as tests or benchmarks.
+/// The last drawn frame stays inspectable and can be dumped as a PNG image.
+pub struct HeadlessScreen {
+    width: u32,
+    height: u32,
+    layout: (u32, u32),
+    buffer: Vec<u8>,
+    renders: u64,
+    /// How the screen would be fit into its window, once it was given a
+    /// surface to fit into
+    viewport: Option<Viewport>,
+}
+impl HeadlessScreen {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            layout: (width, height),
+            buffer: vec![0; (width * height * 4) as usize], // RGBA buffer
+            renders: 0,
+            viewport: None,
+        }
+    }
+    /// The size of the surface the screen was last resized to fit into.
+    pub fn surface(&self) -> Option<(u32, u32)> {
+        self.viewport.as_ref().map(Viewport::surface)
+    }
+    /// The raw RGBA framebuffer in row-major order.
+    pub fn buffer(&self) -> &[u8] {
+        &self.buffer
+    }
+    /// The color at the given screen position, if within bounds.
+    pub fn pixel(&self, x: u32, y: u32) -> Option<Color> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let idx = ((y * self.width) + x) as usize * 4;
+        let p = &self.buffer[idx..idx + 4];
+        Some(Color::RGBA(p[0], p[1], p[2], p[3]))
+    }
+    /// The number of times the screen has been rendered.
+    pub fn renders(&self) -> u64 {
+        self.renders
+    }
+    /// Writes the framebuffer to `path` as an RGBA PNG image.
+    #[cfg(feature = "png")]
+    pub fn save_png(&self, path: impl AsRef<Path>) -> Result<(), WindowError> {
+        let file = File::create(path).map_err(png::EncodingError::from)?;
+        let mut encoder = png::Encoder::new(BufWriter::new(file), self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header()?.write_image_data(&self.buffer)?;
+        Ok(())
+    }
+}
+impl Screen for HeadlessScreen {
+    fn width(&self) -> u32 {
+        self.width
+    }
+    fn height(&self) -> u32 {
+        self.height
+    }
+    fn frame_buffer(&mut self) -> &mut [u8] {
+        &mut self.buffer
+    }
+    fn clear(&mut self) -> Result<(), WindowError> {
+        let black = BLACK.to_rgba();
+        for pixel in self.buffer.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&black);
         }
-        self.surface.render()?;
         Ok(())
     }
     fn render(&mut self) -> Result<(), WindowError> {
-        self.surface.render()?;
+        self.renders += 1;
+        Ok(())
+    }
+    fn resize_surface(&mut self, width: u32, height: u32) -> Result<(), WindowError> {
+        self.viewport
+            .get_or_insert_with(|| Viewport::new(self.width, self.height))
+            .set_surface(width, height);
         Ok(())
     }
+    fn set_resolution(&mut self, width: u32, height: u32) -> Result<(), WindowError> {
+        self.width = width;
+        self.height = height;
+        self.buffer = vec![0; (width * height * 4) as usize];
+        if let Some(viewport) = &mut self.viewport {
+            viewport.set_size(width, height);
+        }
+        Ok(())
+    }
+    fn layout_size(&self) -> (u32, u32) {
+        self.layout
+    }
+    fn to_screen(&self, x: f64, y: f64) -> Option<Coordinate> {
+        Some(self.viewport.as_ref()?.to_screen(x, y))
+    }
 }
 
 /// Configures and builds a fixed-size window for a game with pixel rendering.
@@ -77,79 +339,361 @@ impl Screen for GameWindowScreen {
 /// The `GameWindow` is for creating a window that's suitable
 /// for retro-style or low-resolution games, where fixed dimensions and pixel-perfect
 /// rendering are important.
-pub(crate) struct GameWindow<'a> {
+pub struct GameWindow {
     inner: Arc<Mutex<winit::window::Window>>,
     screen: Arc<Mutex<GameWindowScreen>>,
-    title: String,
-    evt: &'a EventHandler,
 }
-impl<'a> GameWindow<'a> {
+impl GameWindow {
     /// Creates a new configuration for a `GameWindow`.
     ///
     /// Constructs the actual OS window and sets up the pixel rendering surface.
     ///
-    /// Scaling happens by a `2.0` factor
-    pub(crate) fn new(
+    /// Scaling happens by a `PIXEL_SCALE` factor
+    pub fn new(
         width: u32,
         height: u32,
         title: String,
-        evt: &'a EventHandler,
+        evt: &EventHandler,
+    ) -> Result<Self, WindowError> {
+        Self::with_vsync(width, height, title, evt, true)
+    }
+    /// Creates a `GameWindow` presenting in sync with the display's refresh when
+    /// `vsync` is set, or as soon as a frame is ready otherwise (e.g. to measure
+    /// an uncapped frame rate).
+    pub fn with_vsync(
+        width: u32,
+        height: u32,
+        title: String,
+        evt: &EventHandler,
+        vsync: bool,
     ) -> Result<Self, WindowError> {
         let pixel_size = LogicalSize::new(width, height);
-        let window_size = pixel_size.to_physical(4.0);
         // Base cross-platform windowing for game view
         let window = WindowBuilder::new()
-            .with_title(title.clone())
-            .with_inner_size(window_size)
+            .with_title(title)
+            .with_inner_size(window_size(width, height, PIXEL_SCALE))
             .with_resizable(false)
             .with_min_inner_size(pixel_size)
             .build(evt.event_loop())?;
 
-        // Logical texture to render pixels
-        let surface_texture = SurfaceTexture::new(window_size.width, window_size.height, &window);
-        let surface =
-            PixelsBuilder::new(pixel_size.width, pixel_size.height, surface_texture).build()?;
+        // A buffer of the screen's size scaled to the window's physical
+        // pixels on the GPU where the viewport shows it
+        let surface_size = window.inner_size();
+        let surface_texture = SurfaceTexture::new(surface_size.width, surface_size.height, &window);
+        let surface = PixelsBuilder::new(width, height, surface_texture)
+            .enable_vsync(vsync)
+            .build()?;
+        let mut viewport = Viewport::new(width, height);
+        viewport.set_surface(surface_size.width, surface_size.height);
+        let renderer = FitRenderer::new(&surface, &viewport);
 
         Ok(Self {
             screen: Arc::new(Mutex::new(GameWindowScreen {
                 width,
                 height,
+                layout: (width, height),
                 surface,
+                compositor: Compositor::new(width, height),
+                viewport,
+                renderer,
+                pixel_scale: PIXEL_SCALE,
             })),
             inner: Arc::new(Mutex::new(window)),
-            title: title.into(),
-            evt,
         })
     }
-    pub(crate) fn screen(&self) -> Arc<Mutex<GameWindowScreen>> {
+    pub fn id(&self) -> WindowId {
+        self.inner.lock().unwrap().id()
+    }
+    /// How many monitors the window can be moved between.
+    pub fn monitors(&self) -> usize {
+        self.inner.lock().unwrap().available_monitors().count()
+    }
+    pub fn screen(&self) -> Arc<Mutex<GameWindowScreen>> {
         self.screen.clone()
     }
-    pub(crate) fn window(&mut self) -> Arc<Mutex<winit::window::Window>> {
+    pub fn window(&mut self) -> Arc<Mutex<winit::window::Window>> {
         self.inner.clone()
     }
 }
+impl Configurable for GameWindow {
+    /// The window's screen is fit into whatever size it ends up as, and keeps
+    /// the scale when moved to another monitor's DPI
+    fn apply(&self, settings: &WindowSettings) -> Result<(), WindowError> {
+        let window = self
+            .inner
+            .lock()
+            .map_err(|e| WindowError::ScreenLockError(e.to_string()))?;
+        let mut screen = self
+            .screen
+            .lock()
+            .map_err(|e| WindowError::ScreenLockError(e.to_string()))?;
+        let monitor = settings
+            .monitor
+            .and_then(|i| window.available_monitors().nth(i))
+            .or_else(|| window.current_monitor());
+        screen.pixel_scale = settings.scale.clamp(MIN_SCALE, MAX_SCALE) as f64;
+        match settings.mode {
+            WindowMode::Windowed => {
+                window.set_fullscreen(None);
+                if let Some(monitor) = &monitor {
+                    window.set_outer_position(monitor.position());
+                }
+                let (width, height) = screen.layout;
+                let _ = window.request_inner_size(window_size(width, height, screen.pixel_scale));
+            }
+            WindowMode::Borderless => window.set_fullscreen(Some(Fullscreen::Borderless(monitor))),
+            WindowMode::Fullscreen => {
+                let mode = monitor.as_ref().and_then(|monitor| {
+                    monitor.video_modes().max_by_key(|mode| {
+                        let size = mode.size();
+                        (size.width * size.height, mode.refresh_rate_millihertz())
+                    })
+                });
+                // Monitors without video modes to pick from are covered instead
+                let fullscreen = match mode {
+                    Some(mode) => Fullscreen::Exclusive(mode),
+                    None => Fullscreen::Borderless(monitor),
+                };
+                window.set_fullscreen(Some(fullscreen));
+            }
+        }
+        Ok(())
+    }
+}
 impl Window for winit::window::Window {
     fn id(&self) -> WindowId {
         self.id()
     }
+    fn request_redraw(&self) {
+        self.request_redraw()
+    }
+    fn set_ime_allowed(&self, allowed: bool) {
+        self.set_ime_allowed(allowed)
+    }
+    fn scale_factor(&self) -> f64 {
+        self.scale_factor()
+    }
 }
 
 /// The `Screen` trait defines the essential methods required for interacting with a screen or framebuffer.
 /// Implementing this trait allows a type to expose properties which can be used for rendering graphics or manipulating pixel data.
 pub trait Screen: Send + 'static {
+    /// Erases the frame being drawn to black without presenting it, so a
+    /// cleared screen is never shown before it's drawn again.
     fn clear(&mut self) -> Result<(), WindowError>;
     fn width(&self) -> u32;
     fn height(&self) -> u32;
     fn frame_buffer(&mut self) -> &mut [u8];
+    /// Presents the frame drawn since the last one, the only place a frame
+    /// reaches the display.
     fn render(&mut self) -> Result<(), WindowError>;
+    /// Fits what's presented into a window of `width` by `height` physical
+    /// pixels, e.g. after it moved to a monitor of another DPI.
+    ///
+    /// Screens not presented into a window have nothing to fit
+    fn resize_surface(&mut self, _width: u32, _height: u32) -> Result<(), WindowError> {
+        Ok(())
+    }
+    /// Changes how many pixels the screen is made of while its window keeps
+    /// its size, e.g. fewer to zoom in on the world.
+    ///
+    /// What was drawn before is lost
+    fn set_resolution(&mut self, width: u32, height: u32) -> Result<(), WindowError>;
+    /// The resolution the screen's window is laid out for, which stays the
+    /// same whatever `set_resolution` changed the screen to.
+    fn layout_size(&self) -> (u32, u32) {
+        (self.width(), self.height())
+    }
+    /// Logical window pixels each pixel of the screen's layout is shown at.
+    fn pixel_scale(&self) -> f64 {
+        PIXEL_SCALE
+    }
+    /// The pixel of the screen under a position in its window's physical
+    /// pixels, for screens that know how they're fit into their window.
+    fn to_screen(&self, _x: f64, _y: f64) -> Option<Coordinate> {
+        None
+    }
+    /// Draws every command in order and renders the result.
+    ///
+    /// Callers hold the screen's lock once for the whole frame rather than
+    /// once per drawn element.
+    fn draw_batch(&mut self, batch: &[DrawCmd]) -> Result<(), WindowError>
+    where
+        Self: Sized,
+    {
+        self.draw_commands(batch)?;
+        self.render()
+    }
+    /// Draws every command in order without presenting the result, leaving
+    /// that to whoever calls `render`.
+    fn draw_commands(&mut self, batch: &[DrawCmd]) -> Result<(), WindowError>
+    where
+        Self: Sized,
+    {
+        let _span = trace_span!("draw_batch", commands = batch.len()).entered();
+        draw_all(self, batch)
+    }
 }
 
+/// Hands frames drawn off the event loop's thread over to it for presenting.
+///
+/// The drawing side marks the screen dirty and asks for its window to be
+/// redrawn, while the event loop only presents it on `RedrawRequested` like
+/// winit expects. Clones refer to the same screen
+#[derive(Clone)]
+pub struct RedrawRequest {
+    window: Arc<Mutex<dyn Window + Send>>,
+    dirty: Arc<AtomicBool>,
+}
+impl RedrawRequest {
+    pub fn new(window: Arc<Mutex<dyn Window + Send>>) -> Self {
+        Self {
+            window,
+            dirty: Arc::new(AtomicBool::new(false)),
+        }
+    }
+    /// Marks the screen dirty and asks for the window to be redrawn.
+    pub fn request(&self) {
+        self.dirty.store(true, Ordering::Release);
+        self.window.lock().unwrap().request_redraw();
+    }
+    pub(crate) fn id(&self) -> WindowId {
+        self.window.lock().unwrap().id()
+    }
+    /// Whether a frame is waiting to be presented, which it no longer is afterwards.
+    pub(crate) fn take(&self) -> bool {
+        self.dirty.swap(false, Ordering::Acquire)
+    }
+}
+
+/// Content owned by a window which the event loop redraws on its behalf.
+///
+/// Windows not driven by the game thread (e.g. the designer) register a
+/// `Renderable` together with their screen in `EventHandler`, so their screen
+/// is cleared, drawn and presented whenever the window asks to be redrawn.
+pub trait Renderable {
+    /// Queues everything to be drawn onto the window's screen this frame.
+    fn commands<'a>(&'a self, batch: &mut Vec<DrawCmd<'a>>);
+}
+
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug, Error)]
 pub enum WindowError {
     #[error("window creation failed: {0}")]
     WindowCreationError(#[from] winit::error::OsError),
     #[error("pixels surface texture setup failed: {0}")]
     PixelsCreationError(#[from] pixels::Error),
+    #[error("pixels surface resize failed: {0}")]
+    SurfaceResizeError(#[from] pixels::TextureError),
     #[error("failed to lock screen: {0}")]
     ScreenLockError(String),
+    #[cfg(feature = "png")]
+    #[error("failed to dump screen: {0}")]
+    ScreenDumpError(#[from] png::EncodingError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockWindow;
+
+    #[test]
+    fn test_headless_screen_inspection() {
+        let mut screen = HeadlessScreen::new(4, 2);
+        screen.clear().unwrap();
+        // Clearing alone presents nothing
+        assert_eq!(screen.renders(), 0);
+        screen.frame_buffer()[20..24].copy_from_slice(&[255, 0, 0, 255]);
+        screen.render().unwrap();
+
+        assert_eq!(screen.pixel(1, 1), Some(Color::RGBA(255, 0, 0, 255)));
+        assert_eq!(screen.pixel(0, 0), Some(Color::RGBA(0, 0, 0, 255)));
+        assert_eq!(screen.pixel(4, 0), None);
+        assert_eq!(screen.renders(), 1);
+    }
+
+    #[test]
+    fn test_headless_resolution_change() {
+        let mut screen = HeadlessScreen::new(8, 4);
+        screen.set_resolution(4, 2).unwrap();
+
+        assert_eq!((screen.width(), screen.height()), (4, 2));
+        assert_eq!(screen.buffer().len(), 4 * 2 * 4);
+        assert_eq!(screen.layout_size(), (8, 4));
+        assert_eq!(screen.pixel(4, 0), None);
+    }
+
+    #[test]
+    fn test_window_size_follows_dpi() {
+        use winit::dpi::PhysicalSize;
+
+        assert_eq!(
+            window_size(320, 180, PIXEL_SCALE).to_physical::<u32>(1.0),
+            PhysicalSize::new(1280, 720)
+        );
+        assert_eq!(
+            window_size(320, 180, PIXEL_SCALE).to_physical::<u32>(1.5),
+            PhysicalSize::new(1920, 1080)
+        );
+    }
+
+    #[test]
+    fn test_window_settings_cycle() {
+        let mut settings = WindowSettings {
+            scale: 9,
+            ..Default::default()
+        };
+        settings.next_scale();
+        assert_eq!(settings.scale, MIN_SCALE);
+        settings.next_scale();
+        assert_eq!(settings.scale, 2);
+
+        for mode in [
+            WindowMode::Borderless,
+            WindowMode::Fullscreen,
+            WindowMode::Windowed,
+        ] {
+            settings.next_mode();
+            assert_eq!(settings.mode, mode);
+        }
+
+        assert_eq!(settings.monitor, None);
+        settings.next_monitor(2);
+        settings.next_monitor(2);
+        assert_eq!(settings.monitor, Some(1));
+        settings.next_monitor(2);
+        assert_eq!(settings.monitor, Some(0));
+    }
+
+    #[test]
+    fn test_redraw_request_marks_dirty() {
+        let redraw = RedrawRequest::new(Arc::new(Mutex::new(MockWindow::default())));
+        assert!(!redraw.take());
+
+        redraw.clone().request();
+        redraw.request();
+        assert!(redraw.take());
+        // Presenting once covers every request made since
+        assert!(!redraw.take());
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn test_headless_screen_png_dump() {
+        let mut screen = HeadlessScreen::new(3, 2);
+        screen.clear().unwrap();
+        screen.frame_buffer()[0..4].copy_from_slice(&[0, 255, 0, 255]);
+
+        let path = std::env::temp_dir().join("thegame_headless_dump.png");
+        screen.save_png(&path).unwrap();
+
+        let decoder = png::Decoder::new(File::open(&path).unwrap());
+        let mut reader = decoder.read_info().unwrap();
+        let mut image = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut image).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!((info.width, info.height), (3, 2));
+        assert_eq!(&image[..info.buffer_size()], screen.buffer());
+    }
 }